@@ -37,7 +37,7 @@ fn main() {
         .key_switch(0)
         .extract_at(0);
     let zero_enc = cks[1]
-        .encrypt(vec![0].as_slice())
+        .encrypt(vec![0u8].as_slice())
         .unseed::<Vec<Vec<u64>>>()
         .key_switch(1)
         .extract_at(0);
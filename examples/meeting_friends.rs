@@ -17,26 +17,20 @@ impl<T> Location<T> {
     }
 }
 
-fn should_meet(a: &Location<u8>, b: &Location<u8>, b_threshold: &u8) -> bool {
-    let diff_x = a.x() - b.x();
-    let diff_y = a.y() - b.y();
-    let d_sq = &(&diff_x * &diff_x) + &(&diff_y * &diff_y);
-
-    d_sq.le(b_threshold)
-}
-
 /// Calculates distance square between a's and b's location. Returns a boolean
 /// indicating whether diatance sqaure is <= `b_threshold`.
-fn should_meet_fhe(
-    a: &Location<FheUint8>,
-    b: &Location<FheUint8>,
-    b_threshold: &FheUint8,
-) -> FheBool {
-    let diff_x = a.x() - b.x();
-    let diff_y = a.y() - b.y();
-    let d_sq = &(&diff_x * &diff_x) + &(&diff_y * &diff_y);
-
-    d_sq.le(b_threshold)
+///
+/// The function is generic over plaintext (`u8`) and encrypted (`FheUint8`)
+/// integers. Hence the same logic is tested in plaintext and run in the
+/// encrypted domain.
+fn should_meet<T: FheIntegerOps>(a: &Location<T>, b: &Location<T>, b_threshold: &T) -> T::Bool {
+    let diff_x = a.x().wrapping_sub(b.x());
+    let diff_y = a.y().wrapping_sub(b.y());
+    let d_sq = diff_x
+        .wrapping_mul(&diff_x)
+        .wrapping_add(&diff_y.wrapping_mul(&diff_y));
+
+    d_sq.fhe_le(b_threshold)
 }
 
 // Ever wondered who are the long distance friends (friends of friends or
@@ -118,7 +112,7 @@ fn main() {
     };
 
     // run the circuit
-    let out_c = should_meet_fhe(
+    let out_c = should_meet(
         &user_a_location_enc,
         &user_b_location_enc,
         &user_b_threshold_enc,
@@ -145,7 +145,11 @@ impl<T> ArithmeticOps for ModularOpsU64<T> {
     }
 
     fn neg(&self, a: &Self::Element) -> Self::Element {
-        self.q - *a
+        if *a == 0 {
+            0
+        } else {
+            self.q - *a
+        }
     }
 
     // fn modulus(&self) -> Self::Element {
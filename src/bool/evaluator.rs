@@ -19,21 +19,25 @@ use crate::{
         public_key_share,
     },
     ntt::{Ntt, NttInit},
-    pbs::{pbs, PbsInfo, PbsKey, WithShoupRepr},
+    pbs::{
+        circuit_bootstrap, cmux_tree_lookup, pbs, pbs_many, sample_extract, PbsInfo, PbsKey,
+        WithShoupRepr,
+    },
     random::{
         DefaultSecureRng, NewWithSeed, RandomFill, RandomFillGaussianInModulus,
         RandomFillUniformInModulus,
     },
     rgsw::{
-        generate_auto_map, public_key_encrypt_rgsw, rgsw_by_rgsw_inplace, rgsw_x_rgsw_scratch_rows,
-        rlwe_auto_scratch_rows, rlwe_x_rgsw_scratch_rows, secret_key_encrypt_rgsw,
-        seeded_auto_key_gen, RgswCiphertextMutRef, RgswCiphertextRef, RuntimeScratchMutRef,
+        generate_auto_map, rgsw_by_rgsw_inplace, rgsw_x_rgsw_scratch_rows, rlwe_auto_scratch_rows,
+        rlwe_x_rgsw_scratch_rows, seeded_auto_key_gen, RgswCiphertextMutRef, RgswCiphertextRef,
+        RuntimeScratchMutRef,
     },
+    stats,
     utils::{
         encode_x_pow_si_with_emebedding_factor, mod_exponent, puncture_p_rng, TryConvertFrom1,
         WithLocal,
     },
-    BooleanGates, Encoder, Matrix, MatrixEntity, MatrixMut, RowEntity, RowMut,
+    BooleanGates, Encoder, Error, LookupTables, Matrix, MatrixEntity, MatrixMut, RowEntity, RowMut,
 };
 
 use super::{
@@ -43,11 +47,18 @@ use super::{
         CommonReferenceSeededNonInteractiveMultiPartyServerKeyShare,
         InteractiveMultiPartyClientKey, NonInteractiveMultiPartyClientKey,
         SeededInteractiveMultiPartyServerKey, SeededNonInteractiveMultiPartyServerKey,
-        SeededSinglePartyServerKey, SinglePartyClientKey,
+        SinglePartyClientKey,
     },
     parameters::{BoolParameters, CiphertextModulus, DecompositionCount, DoubleDecomposerParams},
 };
 
+#[cfg(test)]
+use super::keys::SeededSinglePartyServerKey;
+#[cfg(feature = "interactive_mp")]
+use crate::rgsw::{public_key_encrypt_rgsw, public_key_encrypt_scheme_switch_key_share};
+#[cfg(test)]
+use crate::rgsw::{secret_key_encrypt_rgsw, seeded_scheme_switch_key_gen};
+
 /// Common reference seed used for Interactive multi-party,
 ///
 /// Seeds for public key shares and differents parts of server key shares are
@@ -269,6 +280,67 @@ where
     }
 }
 
+/// Encoding of messages of `bits` bits with a padding bit, that is m -> m *
+/// Q/2^{bits+1}. Messages occupy the first half of Z_Q, which is where test
+/// vector of a lookup table is defined.
+pub(super) trait MessageEncoding {
+    type Element;
+    /// m * Q/2^{bits+1}
+    fn message_el(&self, m: u64, bits: usize) -> Self::Element;
+    /// Decodes message of `bits` bits. Padding bit is discarded.
+    fn decode_message(&self, m: Self::Element, bits: usize) -> u64;
+}
+
+impl<T> MessageEncoding for CiphertextModulus<T>
+where
+    CiphertextModulus<T>: Modulus<Element = T>,
+    T: PrimInt + NumInfo + FromPrimitive,
+{
+    type Element = T;
+
+    fn message_el(&self, m: u64, bits: usize) -> Self::Element {
+        let delta = if self.is_native() {
+            T::one() << ((T::BITS as usize) - bits - 1)
+        } else {
+            self.q().unwrap() >> (bits + 1)
+        };
+        delta * T::from_u64(m).unwrap()
+    }
+
+    fn decode_message(&self, m: Self::Element, bits: usize) -> u64 {
+        let m = ((m.to_f64().unwrap() * (1u64 << (bits + 1)) as f64) / self.q_as_f64().unwrap())
+            .round() as u64;
+        m % (1u64 << bits)
+    }
+}
+
+/// Returns test vector v(X^{-g}) given test vector v(X) and auto map of -g
+fn automorph_test_vec<R: RowMut + RowEntity, ModOp: ArithmeticOps<Element = R::Element>>(
+    test_vec: &R,
+    auto_map: &(Vec<usize>, Vec<bool>),
+    modop: &ModOp,
+) -> R
+where
+    R::Element: Copy,
+{
+    let (auto_map_index, auto_map_sign) = auto_map;
+    let mut test_vec_autog = R::zeros(test_vec.as_ref().len());
+    izip!(
+        test_vec.as_ref().iter(),
+        auto_map_index.iter(),
+        auto_map_sign.iter()
+    )
+    .for_each(|(v, to_index, to_sign)| {
+        if !to_sign {
+            // negate
+            test_vec_autog.as_mut()[*to_index] = modop.neg(v);
+        } else {
+            test_vec_autog.as_mut()[*to_index] = *v;
+        }
+    });
+    test_vec_autog
+}
+
 pub(super) struct BoolPbsInfo<M: Matrix, Ntt, RlweModOp, LweModOp> {
     auto_decomposer: DefaultDecomposer<M::MatElement>,
     rlwe_rgsw_decomposer: (
@@ -276,6 +348,7 @@ pub(super) struct BoolPbsInfo<M: Matrix, Ntt, RlweModOp, LweModOp> {
         DefaultDecomposer<M::MatElement>,
     ),
     lwe_decomposer: DefaultDecomposer<M::MatElement>,
+    scheme_switch_decomposer: Option<DefaultDecomposer<M::MatElement>>,
     g_k_dlog_map: Vec<usize>,
     rlwe_nttop: Ntt,
     rlwe_modop: RlweModOp,
@@ -321,6 +394,9 @@ where
     fn auto_decomposer(&self) -> &Self::D {
         &self.auto_decomposer
     }
+    fn scheme_switch_decomposer(&self) -> Option<&Self::D> {
+        self.scheme_switch_decomposer.as_ref()
+    }
     fn embedding_factor(&self) -> usize {
         self.embedding_factor
     }
@@ -368,6 +444,18 @@ where
     nor_test_vec: M::R,
     xor_test_vec: M::R,
     xnor_test_vec: M::R,
+    /// Test vector of count of true inputs among 2 inputs, which majority gate
+    /// bootstraps before adding the third input
+    count_test_vec: M::R,
+    /// Test vector of majority gate, that is sign of sum of the count of 2
+    /// inputs and the third input
+    majority_test_vec: M::R,
+    /// Factors of `count_test_vec` that map sum of 2 inputs to their count and
+    /// their XOR with a single multi-value bootstrap. Refer to `pbs_many`.
+    count_and_xor_test_vec_factors: [Vec<(usize, bool)>; 2],
+    /// Auto map of v(X) -> v(X^{-g}) in ring X^{q/2}+1 (q: blind rotation
+    /// modulus) applied to test vectors
+    test_vec_auto_map: (Vec<usize>, Vec<bool>),
     /// Non-interactive u_i -> s key switch decomposer
     ni_ui_to_s_ks_decomposer: Option<DefaultDecomposer<M::MatElement>>,
     _phantom: PhantomData<SKey>,
@@ -625,6 +713,119 @@ pub(super) fn multi_party_user_id_lwe_segment(
     )
 }
 
+impl<M, NttOp, RlweModOp, LweModOp, Skey> BoolEvaluator<M, NttOp, RlweModOp, LweModOp, Skey>
+where
+    M: MatrixMut + MatrixEntity,
+    M::R: RowMut + RowEntity,
+    M::MatElement: PrimInt
+        + FromPrimitive
+        + One
+        + Copy
+        + Zero
+        + Display
+        + WrappingSub
+        + NumInfo
+        + From<bool>
+        + WrappingAdd
+        + Debug,
+    RlweModOp: VectorOps<Element = M::MatElement>
+        + ArithmeticOps<Element = M::MatElement>
+        + ShoupMatrixFMA<M::R>,
+    LweModOp: VectorOps<Element = M::MatElement> + ArithmeticOps<Element = M::MatElement>,
+    NttOp: Ntt<Element = M::MatElement>,
+    Skey: PbsKey<AutoKey = <Skey as PbsKey>::RgswCt, LweKskKey = M>,
+    <Skey as PbsKey>::RgswCt: WithShoupRepr<M = M>,
+{
+    /// Returns test vector that maps message m of `bits` bits to `f(m)`.
+    ///
+    /// Message m, shifted by half of the message interval, decrypts to
+    /// [m*q/2^{bits+1}, (m+1)*q/2^{bits+1}) (q: blind rotation modulus) in the
+    /// first half of Z_q. Hence test vector is set to `f(m)` in the interval.
+    fn lut_test_vec<F: Fn(u64) -> M::MatElement>(&self, bits: usize, f: F) -> M::R {
+        let br_q = self.pbs_info.br_q();
+        let log_interval = br_q.trailing_zeros() as usize - bits - 1;
+        let mut test_vec = M::R::zeros(br_q >> 1);
+        test_vec
+            .as_mut()
+            .iter_mut()
+            .enumerate()
+            .for_each(|(i, v)| *v = f((i >> log_interval) as u64));
+
+        automorph_test_vec(
+            &test_vec,
+            &self.test_vec_auto_map,
+            &self.pbs_info.rlwe_modop,
+        )
+    }
+
+    /// Sets `c0` to `c0 ^ c1` and returns count of true inputs among `c0` and
+    /// `c1`, encoded as -Q/4, 0, or Q/4, with a single multi-value bootstrap of
+    /// `c0 + c1 + Q/4`. Each output is extracted from a single coefficient of
+    /// the blind rotated test vector, hence both have noise of a bootstrapped
+    /// ciphertext.
+    fn _count_and_xor<C: FnOnce(&mut stats::GateCounts)>(
+        &mut self,
+        c0: &mut M::R,
+        c1: &M::R,
+        count: C,
+        server_key: &Skey,
+    ) -> M::R {
+        self._add_and_shift_lwe_cts(c0, c1);
+
+        // Multi-value PBS
+        let mut outs = stats::record_bootstrap(count, || {
+            pbs_many(
+                &self.pbs_info,
+                &self.count_test_vec,
+                &self.count_and_xor_test_vec_factors,
+                c0,
+                server_key,
+                &mut self.scratch_memory.lwe_vector,
+                &mut self.scratch_memory.decomposition_matrix,
+            )
+        });
+
+        // XOR is either 0 or Q/4. Subtract Q/8.
+        *c0 = outs.pop().unwrap();
+        let modop = &self.pbs_info.rlwe_modop;
+        c0.as_mut()[0] = modop.sub(&c0.as_ref()[0], &self.pbs_info.rlwe_q().true_el());
+        outs.pop().unwrap()
+    }
+
+    /// Bootstraps `c0`, encryption of message m of `bits` bits, to encryption
+    /// of encoded element `f(m)`
+    fn _apply_lut<F: Fn(u64) -> M::MatElement>(
+        &mut self,
+        c0: &mut M::R,
+        bits: usize,
+        f: F,
+        server_key: &Skey,
+    ) {
+        let test_vec = self.lut_test_vec(bits, f);
+
+        // Shift by half of the message interval, so that noise in either
+        // direction does not move m to the neighbouring interval
+        let modop = &self.pbs_info.rlwe_modop;
+        let half_interval = self.pbs_info.rlwe_q().message_el(1, bits + 1);
+        c0.as_mut()[0] = modop.add(&c0.as_ref()[0], &half_interval);
+
+        // PBS
+        stats::record_bootstrap(
+            |c| c.lut += 1,
+            || {
+                pbs(
+                    &self.pbs_info,
+                    &test_vec,
+                    c0,
+                    server_key,
+                    &mut self.scratch_memory.lwe_vector,
+                    &mut self.scratch_memory.decomposition_matrix,
+                )
+            },
+        );
+    }
+}
+
 impl<M: Matrix, NttOp, RlweModOp, LweModOp, SKey> BoolEvaluator<M, NttOp, RlweModOp, LweModOp, SKey>
 where
     M: MatrixEntity + MatrixMut,
@@ -685,7 +886,8 @@ where
         let true_m_el = parameters.rlwe_q().true_el();
         // -Q/8
         let false_m_el = parameters.rlwe_q().false_el();
-        let (auto_map_index, auto_map_sign) = generate_auto_map(qby2, -(g as isize));
+
+        let test_vec_auto_map = generate_auto_map(qby2, -(g as isize));
 
         let init_test_vec = |partition_el: usize,
                              before_partition_el: M::MatElement,
@@ -699,23 +901,7 @@ where
                 }
             }
 
-            // v(X) -> v(X^{-g})
-            let mut test_vec_autog = M::R::zeros(qby2);
-            izip!(
-                test_vec.as_ref().iter(),
-                auto_map_index.iter(),
-                auto_map_sign.iter()
-            )
-            .for_each(|(v, to_index, to_sign)| {
-                if !to_sign {
-                    // negate
-                    test_vec_autog.as_mut()[*to_index] = rlwe_modop.neg(v);
-                } else {
-                    test_vec_autog.as_mut()[*to_index] = *v;
-                }
-            });
-
-            return test_vec_autog;
+            automorph_test_vec(&test_vec, &test_vec_auto_map, &rlwe_modop)
         };
 
         let nand_test_vec = init_test_vec(3 * qby8, true_m_el, false_m_el);
@@ -724,6 +910,28 @@ where
         let nor_test_vec = init_test_vec(qby8, true_m_el, false_m_el);
         let xor_test_vec = init_test_vec(qby8, false_m_el, true_m_el);
         let xnor_test_vec = init_test_vec(qby8, true_m_el, false_m_el);
+        let majority_test_vec = init_test_vec(0, false_m_el, true_m_el);
+
+        // Input c0 + c1 + Q/4 is 0, Q/4, or Q/2 if none, one, or both of the
+        // inputs are true and is mapped to -Q/4, 0, or Q/4. Q/2 maps to Q/4 since
+        // the second half of Z_q maps to the negation of the first half.
+        let count_test_vec = {
+            let qby4_el = parameters.rlwe_q().qby4();
+            let mut test_vec = M::R::zeros(qby2);
+            for i in 0..qby2 {
+                if i < qby8 {
+                    test_vec.as_mut()[i] = rlwe_modop.neg(&qby4_el);
+                } else if i >= 3 * qby8 {
+                    test_vec.as_mut()[i] = qby4_el;
+                }
+            }
+            automorph_test_vec(&test_vec, &test_vec_auto_map, &rlwe_modop)
+        };
+
+        // Test vector X^{k} * v(X) maps phase i to v(i - k). Hence -X^{q/4} *
+        // count_test_vec maps 0, Q/4, and Q/2 to 0, Q/4, and 0, that is Q/4 if
+        // exactly one of the inputs is true.
+        let count_and_xor_test_vec_factors = [vec![(0, true)], vec![(q >> 2, false)]];
 
         // auto map indices and sign
         // Auto maps are stored as [-g, g^{1}, g^{2}, ..., g^{w}]
@@ -759,6 +967,7 @@ where
             auto_decomposer: parameters.auto_decomposer(),
             lwe_decomposer: parameters.lwe_decomposer(),
             rlwe_rgsw_decomposer: parameters.rlwe_rgsw_decomposer(),
+            scheme_switch_decomposer: parameters.scheme_switch_decomposer(),
             g_k_dlog_map,
             embedding_factor,
             lwe_modop,
@@ -778,6 +987,10 @@ where
             nor_test_vec,
             xnor_test_vec,
             xor_test_vec,
+            count_test_vec,
+            majority_test_vec,
+            count_and_xor_test_vec_factors,
+            test_vec_auto_map,
             ni_ui_to_s_ks_decomposer,
             _phantom: PhantomData,
         }
@@ -789,6 +1002,7 @@ where
         ClientKey::new(self.parameters().clone())
     }
 
+    #[cfg(test)]
     pub(super) fn single_party_server_key<K: SinglePartyClientKey<Element = i32>>(
         &self,
         client_key: &K,
@@ -886,16 +1100,34 @@ where
                 rng,
             );
 
+            // Scheme switching key RLWE'(s^2) if parameters support circuit
+            // bootstrapping
+            let scheme_switch_key = self.pbs_info.scheme_switch_decomposer.as_ref().map(|d| {
+                let mut key = M::zeros(d.decomposition_count().0, rlwe_n);
+                seeded_scheme_switch_key_gen(
+                    &mut key,
+                    &sk_rlwe,
+                    &d.gadget_vector(),
+                    &self.pbs_info.rlwe_modop,
+                    &self.pbs_info.rlwe_nttop,
+                    &mut main_prng,
+                    rng,
+                );
+                key
+            });
+
             SeededSinglePartyServerKey::from_raw(
                 auto_keys,
                 rgsw_cts,
                 lwe_ksk,
+                scheme_switch_key,
                 self.pbs_info.parameters.clone(),
                 main_seed,
             )
         })
     }
 
+    #[cfg(feature = "interactive_mp")]
     pub(super) fn gen_interactive_multi_party_server_key_share<
         K: InteractiveMultiPartyClientKey<Element = i32>,
     >(
@@ -1036,11 +1268,30 @@ where
             &sk_lwe,
         );
 
+        // Share of scheme switching key RLWE'(s^2) if parameters support
+        // circuit bootstrapping
+        let scheme_switch_key = DefaultSecureRng::with_local_mut(|rng| {
+            self.pbs_info.scheme_switch_decomposer.as_ref().map(|d| {
+                let mut key = M::zeros(d.decomposition_count().0 * 2, ring_size);
+                public_key_encrypt_scheme_switch_key_share(
+                    &mut key,
+                    &sk_rlwe,
+                    collective_pk,
+                    &d.gadget_vector(),
+                    rlweq_modop,
+                    rlweq_nttop,
+                    rng,
+                );
+                key
+            })
+        });
+
         CommonReferenceSeededInteractiveMultiPartyServerKeyShare::new(
             self_leader_rgsws,
             not_self_leader_rgsws,
             auto_keys,
             lwe_ksk,
+            scheme_switch_key,
             cr_seed.clone(),
             self.pbs_info.parameters.clone(),
             user_id,
@@ -1049,15 +1300,19 @@ where
 
     pub(super) fn aggregate_interactive_multi_party_server_key_shares<S>(
         &self,
+        cr_seed: &InteractiveMultiPartyCrs<S>,
         shares: &[CommonReferenceSeededInteractiveMultiPartyServerKeyShare<
             M,
             BoolParameters<M::MatElement>,
             InteractiveMultiPartyCrs<S>,
         >],
-    ) -> SeededInteractiveMultiPartyServerKey<
-        M,
-        InteractiveMultiPartyCrs<S>,
-        BoolParameters<M::MatElement>,
+    ) -> Result<
+        SeededInteractiveMultiPartyServerKey<
+            M,
+            InteractiveMultiPartyCrs<S>,
+            BoolParameters<M::MatElement>,
+        >,
+        Error,
     >
     where
         S: PartialEq + Clone,
@@ -1067,21 +1322,51 @@ where
             self.parameters().variant(),
             &ParameterVariant::InteractiveMultiParty
         );
-        assert!(shares.len() > 0);
+        if shares.is_empty() {
+            return Err(Error::NoShares);
+        }
 
         let total_users = shares.len();
 
-        let parameters = shares[0].parameters().clone();
-        let cr_seed = shares[0].cr_seed();
+        // every user must have exactly one share generated with parameters and
+        // common reference seed of the protocol instance for `total_users`
+        // users
+        let mut has_share = vec![false; total_users];
+        for s in shares {
+            let user_id = s.user_id();
+            if user_id >= total_users {
+                return Err(Error::InvalidUserId {
+                    user_id,
+                    total_users,
+                });
+            }
+            if std::mem::replace(&mut has_share[user_id], true) {
+                return Err(Error::DuplicateShare { user_id });
+            }
+            if s.parameters() != self.parameters() || s.cr_seed() != cr_seed {
+                return Err(Error::ShareParameterMismatch { user_id });
+            }
+            let lwe_n = self.parameters().lwe_n().0;
+            let (start, end) = multi_party_user_id_lwe_segment(user_id, total_users, lwe_n);
+            if s.self_leader_rgsws().len() != end - start
+                || s.not_self_leader_rgsws().len() != lwe_n - (end - start)
+            {
+                return Err(Error::InvalidShare { user_id });
+            }
+            let scheme_switch_key_dimension = self
+                .pbs_info
+                .scheme_switch_decomposer
+                .as_ref()
+                .map(|d| (d.decomposition_count().0 * 2, self.parameters().rlwe_n().0));
+            if s.scheme_switch_key().map(|k| k.dimension()) != scheme_switch_key_dimension {
+                return Err(Error::InvalidShare { user_id });
+            }
+        }
+
+        let parameters = self.parameters().clone();
 
         let rlwe_n = parameters.rlwe_n().0;
 
-        // sanity checks
-        shares.iter().skip(1).for_each(|s| {
-            assert!(s.parameters() == &parameters);
-            assert!(s.cr_seed() == cr_seed);
-        });
-
         let rlweq_modop = &self.pbs_info.rlwe_modop;
         let rlweq_nttop = &self.pbs_info.rlwe_nttop;
 
@@ -1210,13 +1495,29 @@ where
             lweq_modop.elwise_add_mut(lwe_ksk.as_mut(), si.lwe_ksk().as_ref())
         });
 
-        SeededInteractiveMultiPartyServerKey::new(
+        // Scheme switching key RLWE'(s^2) = \sum RLWE'(s_i s)
+        let scheme_switch_key = self.pbs_info.scheme_switch_decomposer.as_ref().map(|d| {
+            let mut key = M::zeros(d.decomposition_count().0 * 2, rlwe_n);
+            shares.iter().for_each(|s| {
+                izip!(
+                    key.iter_rows_mut(),
+                    s.scheme_switch_key().unwrap().iter_rows()
+                )
+                .for_each(|(out, share)| {
+                    rlweq_modop.elwise_add_mut(out.as_mut(), share.as_ref());
+                });
+            });
+            key
+        });
+
+        Ok(SeededInteractiveMultiPartyServerKey::new(
             rgsw_cts,
             auto_keys,
             lwe_ksk,
+            scheme_switch_key,
             cr_seed.clone(),
             parameters,
-        )
+        ))
     }
 
     pub(super) fn aggregate_non_interactive_multi_party_server_key_shares(
@@ -1227,10 +1528,13 @@ where
             BoolParameters<M::MatElement>,
             NonInteractiveMultiPartyCrs<[u8; 32]>,
         >],
-    ) -> SeededNonInteractiveMultiPartyServerKey<
-        M,
-        NonInteractiveMultiPartyCrs<[u8; 32]>,
-        BoolParameters<M::MatElement>,
+    ) -> Result<
+        SeededNonInteractiveMultiPartyServerKey<
+            M,
+            NonInteractiveMultiPartyCrs<[u8; 32]>,
+            BoolParameters<M::MatElement>,
+        >,
+        Error,
     >
     where
         M: Clone + Debug,
@@ -1240,26 +1544,45 @@ where
             self.parameters().variant(),
             &ParameterVariant::NonInteractiveMultiParty
         );
+        if key_shares.is_empty() {
+            return Err(Error::NoShares);
+        }
 
         let total_users = key_shares.len();
+
+        // every user must have exactly one share generated with parameters and
+        // common reference seed of the protocol instance for `total_users`
+        // users
+        let mut has_share = vec![false; total_users];
+        for k in key_shares {
+            let user_id = k.user_index();
+            if user_id >= total_users {
+                return Err(Error::InvalidUserId {
+                    user_id,
+                    total_users,
+                });
+            }
+            if std::mem::replace(&mut has_share[user_id], true) {
+                return Err(Error::DuplicateShare { user_id });
+            }
+            if k.parameters() != self.parameters() || k.cr_seed() != cr_seed {
+                return Err(Error::ShareParameterMismatch { user_id });
+            }
+            if k.total_users() != total_users {
+                return Err(Error::InvalidShare { user_id });
+            }
+        }
+
         let key_shares = (0..total_users)
             .map(|user_id| {
                 // find share of user_id
                 key_shares
                     .iter()
                     .find(|share| share.user_index() == user_id)
-                    .expect(&format!("Key Share for user_id={user_id} missing"))
+                    .unwrap()
             })
             .collect_vec();
 
-        // check parameters and cr seed are equal
-        {
-            key_shares.iter().for_each(|k| {
-                assert!(k.parameters() == self.parameters());
-                assert!(k.cr_seed() == cr_seed);
-            });
-        }
-
         let rlwe_modop = &self.pbs_info().rlwe_modop;
         let nttop = &self.pbs_info().rlwe_nttop;
         let ring_size = self.parameters().rlwe_n().0;
@@ -1611,14 +1934,14 @@ where
             lwe_ksk
         };
 
-        SeededNonInteractiveMultiPartyServerKey::new(
+        Ok(SeededNonInteractiveMultiPartyServerKey::new(
             uj_to_s_ksks,
             rgsw_cts,
             auto_keys,
             lwe_ksk,
             cr_seed.clone(),
             self.parameters().clone(),
-        )
+        ))
     }
 
     pub(super) fn gen_non_interactive_multi_party_key_share<
@@ -2122,6 +2445,14 @@ where
         c0.as_mut()[0] = modop.add(&c0.as_ref()[0], &self.pbs_info.rlwe_qby4);
     }
 
+    /// Returns c0 - c1 + Q/4
+    fn _subtract_and_shift_lwe_cts(&self, c0: &mut M::R, c1: &M::R) {
+        let modop = &self.pbs_info.rlwe_modop;
+        modop.elwise_sub_mut(c0.as_mut(), c1.as_ref());
+        // +Q/4
+        c0.as_mut()[0] = modop.add(&c0.as_ref()[0], &self.pbs_info.rlwe_qby4);
+    }
+
     /// Returns 2(c0 - c1) + Q/4
     fn _subtract_double_lwe_cts(&self, c0: &mut M::R, c1: &M::R) {
         let modop = &self.pbs_info.rlwe_modop;
@@ -2164,13 +2495,18 @@ where
         self._add_and_shift_lwe_cts(c0, c1);
 
         // PBS
-        pbs(
-            &self.pbs_info,
-            &self.nand_test_vec,
-            c0,
-            server_key,
-            &mut self.scratch_memory.lwe_vector,
-            &mut self.scratch_memory.decomposition_matrix,
+        stats::record_bootstrap(
+            |c| c.nand += 1,
+            || {
+                pbs(
+                    &self.pbs_info,
+                    &self.nand_test_vec,
+                    c0,
+                    server_key,
+                    &mut self.scratch_memory.lwe_vector,
+                    &mut self.scratch_memory.decomposition_matrix,
+                )
+            },
         );
     }
 
@@ -2178,13 +2514,18 @@ where
         self._add_and_shift_lwe_cts(c0, c1);
 
         // PBS
-        pbs(
-            &self.pbs_info,
-            &self.and_test_vec,
-            c0,
-            server_key,
-            &mut self.scratch_memory.lwe_vector,
-            &mut self.scratch_memory.decomposition_matrix,
+        stats::record_bootstrap(
+            |c| c.and += 1,
+            || {
+                pbs(
+                    &self.pbs_info,
+                    &self.and_test_vec,
+                    c0,
+                    server_key,
+                    &mut self.scratch_memory.lwe_vector,
+                    &mut self.scratch_memory.decomposition_matrix,
+                )
+            },
         );
     }
 
@@ -2192,13 +2533,18 @@ where
         self._add_and_shift_lwe_cts(c0, c1);
 
         // PBS
-        pbs(
-            &self.pbs_info,
-            &self.or_test_vec,
-            c0,
-            server_key,
-            &mut self.scratch_memory.lwe_vector,
-            &mut self.scratch_memory.decomposition_matrix,
+        stats::record_bootstrap(
+            |c| c.or += 1,
+            || {
+                pbs(
+                    &self.pbs_info,
+                    &self.or_test_vec,
+                    c0,
+                    server_key,
+                    &mut self.scratch_memory.lwe_vector,
+                    &mut self.scratch_memory.decomposition_matrix,
+                )
+            },
         );
     }
 
@@ -2206,27 +2552,37 @@ where
         self._add_and_shift_lwe_cts(c0, c1);
 
         // PBS
-        pbs(
-            &self.pbs_info,
-            &self.nor_test_vec,
-            c0,
-            server_key,
-            &mut self.scratch_memory.lwe_vector,
-            &mut self.scratch_memory.decomposition_matrix,
-        )
+        stats::record_bootstrap(
+            |c| c.nor += 1,
+            || {
+                pbs(
+                    &self.pbs_info,
+                    &self.nor_test_vec,
+                    c0,
+                    server_key,
+                    &mut self.scratch_memory.lwe_vector,
+                    &mut self.scratch_memory.decomposition_matrix,
+                )
+            },
+        );
     }
 
     fn xor_inplace(&mut self, c0: &mut M::R, c1: &M::R, server_key: &Self::Key) {
         self._subtract_double_lwe_cts(c0, c1);
 
         // PBS
-        pbs(
-            &self.pbs_info,
-            &self.xor_test_vec,
-            c0,
-            server_key,
-            &mut self.scratch_memory.lwe_vector,
-            &mut self.scratch_memory.decomposition_matrix,
+        stats::record_bootstrap(
+            |c| c.xor += 1,
+            || {
+                pbs(
+                    &self.pbs_info,
+                    &self.xor_test_vec,
+                    c0,
+                    server_key,
+                    &mut self.scratch_memory.lwe_vector,
+                    &mut self.scratch_memory.decomposition_matrix,
+                )
+            },
         );
     }
 
@@ -2234,21 +2590,115 @@ where
         self._subtract_double_lwe_cts(c0, c1);
 
         // PBS
-        pbs(
-            &self.pbs_info,
-            &self.xnor_test_vec,
-            c0,
-            server_key,
-            &mut self.scratch_memory.lwe_vector,
-            &mut self.scratch_memory.decomposition_matrix,
+        stats::record_bootstrap(
+            |c| c.xnor += 1,
+            || {
+                pbs(
+                    &self.pbs_info,
+                    &self.xnor_test_vec,
+                    c0,
+                    server_key,
+                    &mut self.scratch_memory.lwe_vector,
+                    &mut self.scratch_memory.decomposition_matrix,
+                )
+            },
+        );
+    }
+
+    fn andny_inplace(&mut self, c0: &mut M::R, c1: &M::R, server_key: &Self::Key) {
+        // !c0 + c1 + Q/4
+        self.pbs_info.rlwe_modop.elwise_neg_mut(c0.as_mut());
+        self._add_and_shift_lwe_cts(c0, c1);
+
+        // PBS
+        stats::record_bootstrap(
+            |c| c.andny += 1,
+            || {
+                pbs(
+                    &self.pbs_info,
+                    &self.and_test_vec,
+                    c0,
+                    server_key,
+                    &mut self.scratch_memory.lwe_vector,
+                    &mut self.scratch_memory.decomposition_matrix,
+                )
+            },
+        );
+    }
+
+    fn andyn_inplace(&mut self, c0: &mut M::R, c1: &M::R, server_key: &Self::Key) {
+        // c0 + !c1 + Q/4
+        self._subtract_and_shift_lwe_cts(c0, c1);
+
+        // PBS
+        stats::record_bootstrap(
+            |c| c.andyn += 1,
+            || {
+                pbs(
+                    &self.pbs_info,
+                    &self.and_test_vec,
+                    c0,
+                    server_key,
+                    &mut self.scratch_memory.lwe_vector,
+                    &mut self.scratch_memory.decomposition_matrix,
+                )
+            },
+        );
+    }
+
+    fn orny_inplace(&mut self, c0: &mut M::R, c1: &M::R, server_key: &Self::Key) {
+        // !c0 + c1 + Q/4
+        self.pbs_info.rlwe_modop.elwise_neg_mut(c0.as_mut());
+        self._add_and_shift_lwe_cts(c0, c1);
+
+        // PBS
+        stats::record_bootstrap(
+            |c| c.orny += 1,
+            || {
+                pbs(
+                    &self.pbs_info,
+                    &self.or_test_vec,
+                    c0,
+                    server_key,
+                    &mut self.scratch_memory.lwe_vector,
+                    &mut self.scratch_memory.decomposition_matrix,
+                )
+            },
+        );
+    }
+
+    fn oryn_inplace(&mut self, c0: &mut M::R, c1: &M::R, server_key: &Self::Key) {
+        // c0 + !c1 + Q/4
+        self._subtract_and_shift_lwe_cts(c0, c1);
+
+        // PBS
+        stats::record_bootstrap(
+            |c| c.oryn += 1,
+            || {
+                pbs(
+                    &self.pbs_info,
+                    &self.or_test_vec,
+                    c0,
+                    server_key,
+                    &mut self.scratch_memory.lwe_vector,
+                    &mut self.scratch_memory.decomposition_matrix,
+                )
+            },
         );
     }
 
     fn not_inplace(&self, c0: &mut M::R) {
+        stats::record_gate(|c| c.not += 1);
         let modop = &self.pbs_info.rlwe_modop;
         c0.as_mut().iter_mut().for_each(|v| *v = modop.neg(v));
     }
 
+    fn trivial(&self, m: bool) -> M::R {
+        let mut c = M::R::zeros(self.pbs_info.rlwe_n() + 1);
+        c.as_mut()[0] = self.pbs_info.rlwe_q().encode(m);
+        c
+    }
+
     fn and(
         &mut self,
         c0: &Self::Ciphertext,
@@ -2315,9 +2765,396 @@ where
         out
     }
 
+    fn andny(
+        &mut self,
+        c0: &Self::Ciphertext,
+        c1: &Self::Ciphertext,
+        key: &Self::Key,
+    ) -> Self::Ciphertext {
+        let mut out = c0.clone();
+        self.andny_inplace(&mut out, c1, key);
+        out
+    }
+
+    fn andyn(
+        &mut self,
+        c0: &Self::Ciphertext,
+        c1: &Self::Ciphertext,
+        key: &Self::Key,
+    ) -> Self::Ciphertext {
+        let mut out = c0.clone();
+        self.andyn_inplace(&mut out, c1, key);
+        out
+    }
+
+    fn orny(
+        &mut self,
+        c0: &Self::Ciphertext,
+        c1: &Self::Ciphertext,
+        key: &Self::Key,
+    ) -> Self::Ciphertext {
+        let mut out = c0.clone();
+        self.orny_inplace(&mut out, c1, key);
+        out
+    }
+
+    fn oryn(
+        &mut self,
+        c0: &Self::Ciphertext,
+        c1: &Self::Ciphertext,
+        key: &Self::Key,
+    ) -> Self::Ciphertext {
+        let mut out = c0.clone();
+        self.oryn_inplace(&mut out, c1, key);
+        out
+    }
+
     fn not(&self, c: &Self::Ciphertext) -> Self::Ciphertext {
         let mut out = c.clone();
         self.not_inplace(&mut out);
         out
     }
+
+    fn majority(
+        &mut self,
+        c0: &Self::Ciphertext,
+        c1: &Self::Ciphertext,
+        c2: &Self::Ciphertext,
+        key: &Self::Key,
+    ) -> Self::Ciphertext {
+        // Sum of the 3 inputs has thrice the noise of a bootstrapped ciphertext,
+        // which exceeds the noise a bootstrap can tolerate. Hence count of true
+        // inputs among `c0` and `c1`, encoded as -Q/4, 0, or Q/4, is bootstrapped
+        // first.
+        let mut out = c0.clone();
+        self._add_and_shift_lwe_cts(&mut out, c1);
+        stats::record_bootstrap(
+            |c| c.majority += 1,
+            || {
+                pbs(
+                    &self.pbs_info,
+                    &self.count_test_vec,
+                    &mut out,
+                    key,
+                    &mut self.scratch_memory.lwe_vector,
+                    &mut self.scratch_memory.decomposition_matrix,
+                )
+            },
+        );
+
+        // Sum of the count and `c2` is 3Q/8 or Q/8 if majority of the inputs
+        // are true and -Q/8 or -3Q/8 otherwise. Hence the output is sign of the
+        // sum.
+        let modop = &self.pbs_info.rlwe_modop;
+        modop.elwise_add_mut(out.as_mut(), c2.as_ref());
+        stats::record_bootstrap(
+            |_| {},
+            || {
+                pbs(
+                    &self.pbs_info,
+                    &self.majority_test_vec,
+                    &mut out,
+                    key,
+                    &mut self.scratch_memory.lwe_vector,
+                    &mut self.scratch_memory.decomposition_matrix,
+                )
+            },
+        );
+        out
+    }
+
+    fn mux(
+        &mut self,
+        selector: &Self::Ciphertext,
+        if_true: &Self::Ciphertext,
+        if_false: &Self::Ciphertext,
+        key: &Self::Key,
+    ) -> Self::Ciphertext {
+        // s & a
+        let mut out = selector.clone();
+        self._add_and_shift_lwe_cts(&mut out, if_true);
+        // b & !s
+        let mut b_and_not_s = if_false.clone();
+        self._subtract_and_shift_lwe_cts(&mut b_and_not_s, selector);
+
+        // PBS
+        stats::record_bootstrap(
+            |c| c.mux += 1,
+            || {
+                pbs(
+                    &self.pbs_info,
+                    &self.and_test_vec,
+                    &mut out,
+                    key,
+                    &mut self.scratch_memory.lwe_vector,
+                    &mut self.scratch_memory.decomposition_matrix,
+                );
+            },
+        );
+        stats::record_bootstrap(
+            |_| {},
+            || {
+                pbs(
+                    &self.pbs_info,
+                    &self.and_test_vec,
+                    &mut b_and_not_s,
+                    key,
+                    &mut self.scratch_memory.lwe_vector,
+                    &mut self.scratch_memory.decomposition_matrix,
+                );
+            },
+        );
+
+        // At most one of (s & a) and (b & !s) is true, hence their sum plus Q/8
+        // encodes their OR. But the sum has twice the noise of a bootstrapped
+        // ciphertext, which bootstraps consuming the output cannot tolerate.
+        // Hence OR is bootstrapped.
+        self._add_and_shift_lwe_cts(&mut out, &b_and_not_s);
+        stats::record_bootstrap(
+            |_| {},
+            || {
+                pbs(
+                    &self.pbs_info,
+                    &self.or_test_vec,
+                    &mut out,
+                    key,
+                    &mut self.scratch_memory.lwe_vector,
+                    &mut self.scratch_memory.decomposition_matrix,
+                );
+            },
+        );
+        out
+    }
+
+    fn half_adder(&mut self, c0: &mut M::R, c1: &M::R, key: &Self::Key) -> M::R {
+        let mut carry = self._count_and_xor(c0, c1, |c| c.half_adder += 1, key);
+
+        // Count minus Q/8 is Q/8 iff both inputs are true, and is -Q/8 or -3Q/8
+        // otherwise. Hence carry is sign of the sum.
+        let modop = &self.pbs_info.rlwe_modop;
+        carry.as_mut()[0] = modop.add(&carry.as_ref()[0], &self.pbs_info.rlwe_q().false_el());
+        stats::record_bootstrap(
+            |_| {},
+            || {
+                pbs(
+                    &self.pbs_info,
+                    &self.majority_test_vec,
+                    &mut carry,
+                    key,
+                    &mut self.scratch_memory.lwe_vector,
+                    &mut self.scratch_memory.decomposition_matrix,
+                )
+            },
+        );
+        carry
+    }
+
+    fn full_adder(&mut self, c0: &mut M::R, c1: &M::R, c2: &M::R, key: &Self::Key) -> M::R {
+        let mut carry = self._count_and_xor(c0, c1, |c| c.full_adder += 1, key);
+
+        // Carry is majority of the inputs, that is sign of sum of the count and
+        // `c2` (refer to `majority`)
+        let modop = &self.pbs_info.rlwe_modop;
+        modop.elwise_add_mut(carry.as_mut(), c2.as_ref());
+        stats::record_bootstrap(
+            |_| {},
+            || {
+                pbs(
+                    &self.pbs_info,
+                    &self.majority_test_vec,
+                    &mut carry,
+                    key,
+                    &mut self.scratch_memory.lwe_vector,
+                    &mut self.scratch_memory.decomposition_matrix,
+                )
+            },
+        );
+
+        // Sum is XOR of `c0 ^ c1` and `c2`
+        self._subtract_double_lwe_cts(c0, c2);
+        stats::record_bootstrap(
+            |_| {},
+            || {
+                pbs(
+                    &self.pbs_info,
+                    &self.xor_test_vec,
+                    c0,
+                    key,
+                    &mut self.scratch_memory.lwe_vector,
+                    &mut self.scratch_memory.decomposition_matrix,
+                )
+            },
+        );
+        carry
+    }
+
+    fn cmux_tree_lookup(
+        &mut self,
+        index: &[M::R],
+        table: &[Vec<bool>],
+        out_bits: usize,
+        key: &Skey,
+    ) -> Option<Vec<M::R>> {
+        let ring_size = self.pbs_info.rlwe_n();
+        key.scheme_switch_key()?;
+        if out_bits == 0 || out_bits > ring_size {
+            return None;
+        }
+
+        // Each polynomial packs entries of 2^{log_entries} consecutive indices,
+        // with `out_bits` coefficients per entry
+        let rlwe_q = *self.pbs_info.rlwe_q();
+        let log_entries = std::cmp::min(index.len(), (ring_size / out_bits).ilog2() as usize);
+        let polys = (0..1usize << (index.len() - log_entries))
+            .map(|h| {
+                let mut p = M::R::zeros(ring_size);
+                for l in 0..1usize << log_entries {
+                    let entry = table.get((h << log_entries) + l);
+                    for o in 0..out_bits {
+                        p.as_mut()[l * out_bits + o] =
+                            rlwe_q.encode(entry.is_some_and(|entry| entry[o]));
+                    }
+                }
+                p
+            })
+            .collect_vec();
+
+        let (rlrg_d_a, rlrg_d_b) = self.pbs_info.parameters.rlwe_rgsw_decomposition_count();
+        let rgsw_cts = index
+            .iter()
+            .map(|c| {
+                stats::record_gate(|c| {
+                    c.circuit_bootstrap += 1;
+                    c.circuit_bootstrap_blind_rotations += rlrg_d_a.0 + rlrg_d_b.0;
+                });
+                circuit_bootstrap(
+                    &self.pbs_info,
+                    c,
+                    key,
+                    &mut self.scratch_memory.lwe_vector,
+                    &mut self.scratch_memory.decomposition_matrix,
+                )
+            })
+            .collect_vec();
+
+        let rlwe = cmux_tree_lookup(
+            &self.pbs_info,
+            &polys,
+            &rgsw_cts,
+            out_bits,
+            &mut self.scratch_memory.decomposition_matrix,
+        );
+        Some(
+            (0..out_bits)
+                .map(|o| {
+                    let mut out = M::R::zeros(ring_size + 1);
+                    sample_extract(&mut out, &rlwe, &self.pbs_info.rlwe_modop, o);
+                    out
+                })
+                .collect(),
+        )
+    }
+}
+
+impl<M, NttOp, RlweModOp, LweModOp, Skey> LookupTables
+    for BoolEvaluator<M, NttOp, RlweModOp, LweModOp, Skey>
+where
+    M: MatrixMut + MatrixEntity,
+    M::R: RowMut + RowEntity + Clone,
+    M::MatElement: PrimInt
+        + FromPrimitive
+        + One
+        + Copy
+        + Zero
+        + Display
+        + WrappingSub
+        + NumInfo
+        + From<bool>
+        + WrappingAdd
+        + Debug,
+    RlweModOp: VectorOps<Element = M::MatElement>
+        + ArithmeticOps<Element = M::MatElement>
+        + ShoupMatrixFMA<M::R>,
+    LweModOp: VectorOps<Element = M::MatElement> + ArithmeticOps<Element = M::MatElement>,
+    NttOp: Ntt<Element = M::MatElement>,
+    Skey: PbsKey<AutoKey = <Skey as PbsKey>::RgswCt, LweKskKey = M>,
+    <Skey as PbsKey>::RgswCt: WithShoupRepr<M = M>,
+{
+    type Message = M::R;
+
+    fn max_message_bits(&self) -> usize {
+        self.parameters().max_lut_message_bits()
+    }
+
+    fn trivial_message(&self, m: u64, bits: usize) -> M::R {
+        let mut c = M::R::zeros(self.pbs_info.rlwe_n() + 1);
+        c.as_mut()[0] = self.pbs_info.rlwe_q().message_el(m, bits);
+        c
+    }
+
+    fn add_message_inplace(&mut self, c0: &mut M::R, c1: &M::R, _bits: usize, _key: &Skey) {
+        self.pbs_info
+            .rlwe_modop
+            .elwise_add_mut(c0.as_mut(), c1.as_ref());
+    }
+
+    fn apply_lut<F: Fn(u64) -> u64>(
+        &mut self,
+        c: &M::R,
+        bits: usize,
+        f: F,
+        server_key: &Skey,
+    ) -> M::R {
+        let rlwe_q = *self.pbs_info.rlwe_q();
+        let mask = (1u64 << bits) - 1;
+        let mut out = c.clone();
+        self._apply_lut(
+            &mut out,
+            bits,
+            |m| rlwe_q.message_el(f(m) & mask, bits),
+            server_key,
+        );
+        out
+    }
+
+    fn apply_lut_to_bool<F: Fn(u64) -> bool>(
+        &mut self,
+        c: &M::R,
+        bits: usize,
+        f: F,
+        server_key: &Skey,
+    ) -> M::R {
+        let rlwe_q = *self.pbs_info.rlwe_q();
+        let mut out = c.clone();
+        self._apply_lut(&mut out, bits, |m| rlwe_q.encode(f(m)), server_key);
+        out
+    }
+
+    fn bool_to_message(&mut self, c: &M::R, bits: usize, m: u64, server_key: &Skey) -> M::R {
+        // true is in the first half of Z_Q and false is in the second half.
+        // Hence constant test vector m/2 maps true to m/2 and false to -m/2,
+        // and adding m/2 afterwards maps them to m and 0.
+        let half_m = self.pbs_info.rlwe_q().message_el(m, bits + 1);
+        let test_vec = self.lut_test_vec(0, |_| half_m);
+
+        // PBS
+        let mut out = c.clone();
+        stats::record_bootstrap(
+            |c| c.lut += 1,
+            || {
+                pbs(
+                    &self.pbs_info,
+                    &test_vec,
+                    &mut out,
+                    server_key,
+                    &mut self.scratch_memory.lwe_vector,
+                    &mut self.scratch_memory.decomposition_matrix,
+                )
+            },
+        );
+
+        let modop = &self.pbs_info.rlwe_modop;
+        out.as_mut()[0] = modop.add(&out.as_ref()[0], &half_m);
+        out
+    }
 }
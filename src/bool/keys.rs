@@ -8,6 +8,9 @@ use crate::{
     Matrix, MatrixEntity, MatrixMut, RowEntity, RowMut,
 };
 
+#[cfg(any(feature = "interactive_mp", feature = "non_interactive_mp"))]
+use crate::ledger::{DecryptionShareLedger, DecryptionShareStore, RunLedger, RunStore};
+
 use super::parameters::{BoolParameters, CiphertextModulus};
 
 pub(crate) trait SinglePartyClientKey {
@@ -20,6 +23,10 @@ pub(crate) trait InteractiveMultiPartyClientKey {
     type Element;
     fn sk_rlwe(&self) -> Vec<Self::Element>;
     fn sk_lwe(&self) -> Vec<Self::Element>;
+    #[cfg(any(feature = "interactive_mp", feature = "non_interactive_mp"))]
+    fn decryption_shares(&self) -> &DecryptionShareLedger;
+    #[cfg(any(feature = "interactive_mp", feature = "non_interactive_mp"))]
+    fn runs(&self) -> &RunLedger;
 }
 
 pub(crate) trait NonInteractiveMultiPartyClientKey {
@@ -27,6 +34,10 @@ pub(crate) trait NonInteractiveMultiPartyClientKey {
     fn sk_rlwe(&self) -> Vec<Self::Element>;
     fn sk_u_rlwe(&self) -> Vec<Self::Element>;
     fn sk_lwe(&self) -> Vec<Self::Element>;
+    #[cfg(any(feature = "interactive_mp", feature = "non_interactive_mp"))]
+    fn decryption_shares(&self) -> &DecryptionShareLedger;
+    #[cfg(any(feature = "interactive_mp", feature = "non_interactive_mp"))]
+    fn runs(&self) -> &RunLedger;
 }
 
 /// Client key
@@ -46,10 +57,17 @@ pub(crate) trait NonInteractiveMultiPartyClientKey {
 ///
 ///     Puncture 3 -> Seed of RLWE secret used as `u` in
 ///                   non-interactive multi-party.
+///
+/// Key records decryption shares it generates to never generate two different
+/// shares of the same ciphertext. Clones of the key share the record.
 #[derive(Clone)]
 pub struct ClientKey<S, E> {
     seed: S,
     parameters: BoolParameters<E>,
+    #[cfg(any(feature = "interactive_mp", feature = "non_interactive_mp"))]
+    decryption_shares: DecryptionShareLedger,
+    #[cfg(any(feature = "interactive_mp", feature = "non_interactive_mp"))]
+    runs: RunLedger,
 }
 
 mod impl_ck {
@@ -66,7 +84,63 @@ mod impl_ck {
             let mut rng = DefaultSecureRng::new();
             let mut seed = [0u8; 32];
             rng.fill_bytes(&mut seed);
-            Self { seed, parameters }
+            Self {
+                seed,
+                parameters,
+                #[cfg(any(feature = "interactive_mp", feature = "non_interactive_mp"))]
+                decryption_shares: DecryptionShareLedger::default(),
+                #[cfg(any(feature = "interactive_mp", feature = "non_interactive_mp"))]
+                runs: RunLedger::default(),
+            }
+        }
+
+        pub(in super::super) fn parameters(&self) -> &BoolParameters<E> {
+            &self.parameters
+        }
+
+        /// Returns the key with decryption shares recorded in `store` instead
+        /// of in memory
+        ///
+        /// Shares already in `store` are returned by `gen_decryption_share`
+        /// as is. Hence a client that restarts with the same key must attach
+        /// the same persistent store (for ex, `FileDecryptionShareStore`)
+        /// before generating any decryption share. Different keys must not
+        /// share a store.
+        #[cfg(any(feature = "interactive_mp", feature = "non_interactive_mp"))]
+        pub fn with_decryption_share_store<St: DecryptionShareStore + Send + 'static>(
+            mut self,
+            store: St,
+        ) -> Self {
+            self.decryption_shares = DecryptionShareLedger::new(store);
+            self
+        }
+
+        /// Returns the key with runs of the protocol recorded in `store`
+        /// instead of in memory
+        ///
+        /// Like `with_decryption_share_store`, a client that restarts with the
+        /// same key must attach the same persistent store (for ex,
+        /// `FileRunStore`) before generating any key share.
+        #[cfg(any(feature = "interactive_mp", feature = "non_interactive_mp"))]
+        pub fn with_run_store<St: RunStore + Send + 'static>(mut self, store: St) -> Self {
+            let allow_conflicts = self.runs.allows_conflicts();
+            self.runs = RunLedger::new(store);
+            if allow_conflicts {
+                self.runs.allow_conflicts();
+            }
+            self
+        }
+
+        /// Returns the key that participates in more than one run of the
+        /// protocol with the same common reference seed
+        ///
+        /// Only meant for tests. Running the protocol twice with the same
+        /// common reference seed and producing different outputs leaks the
+        /// secret.
+        #[cfg(any(feature = "interactive_mp", feature = "non_interactive_mp"))]
+        pub fn allow_conflicting_runs(mut self) -> Self {
+            self.runs.allow_conflicts();
+            self
         }
     }
 
@@ -123,6 +197,12 @@ mod impl_ck {
         fn sk_rlwe(&self) -> Vec<Self::Element> {
             <Self as SinglePartyClientKey>::sk_rlwe(&self)
         }
+        fn decryption_shares(&self) -> &DecryptionShareLedger {
+            &self.decryption_shares
+        }
+        fn runs(&self) -> &RunLedger {
+            &self.runs
+        }
     }
 
     #[cfg(feature = "non_interactive_mp")]
@@ -152,6 +232,12 @@ mod impl_ck {
             );
             out
         }
+        fn decryption_shares(&self) -> &DecryptionShareLedger {
+            &self.decryption_shares
+        }
+        fn runs(&self) -> &RunLedger {
+            &self.runs
+        }
     }
 }
 
@@ -350,6 +436,11 @@ pub struct CommonReferenceSeededInteractiveMultiPartyServerKeyShare<M: Matrix, P
     /// LWE_{q, z}(m) where q is LWE ciphertext modulus, `s` is the ideal RLWE
     /// secret with dimension N, and `z` is the ideal LWE secret of dimension n.
     lwe_ksk: M::R,
+    /// Public key encrypted share RLWE'(s_i s) of scheme switching key
+    /// RLWE'(s^2) where `s_i` is the user's RLWE secret and `s` is the ideal
+    /// RLWE secret. Part `a` of RLWE ciphertexts is followed by part `b`.
+    /// Only present if parameters support circuit bootstrapping.
+    scheme_switch_key: Option<M>,
     /// Common reference seed
     cr_seed: S,
     parameters: P,
@@ -365,6 +456,7 @@ impl<M: Matrix, P, S> CommonReferenceSeededInteractiveMultiPartyServerKeyShare<M
         not_self_leader_rgsws: Vec<M>,
         auto_keys: HashMap<usize, M>,
         lwe_ksk: M::R,
+        scheme_switch_key: Option<M>,
         cr_seed: S,
         parameters: P,
         user_id: usize,
@@ -374,6 +466,7 @@ impl<M: Matrix, P, S> CommonReferenceSeededInteractiveMultiPartyServerKeyShare<M
             not_self_leader_rgsws,
             auto_keys,
             lwe_ksk,
+            scheme_switch_key,
             cr_seed,
             parameters,
             user_id,
@@ -404,6 +497,10 @@ impl<M: Matrix, P, S> CommonReferenceSeededInteractiveMultiPartyServerKeyShare<M
         &self.lwe_ksk
     }
 
+    pub(super) fn scheme_switch_key(&self) -> Option<&M> {
+        self.scheme_switch_key.as_ref()
+    }
+
     pub(super) fn user_id(&self) -> usize {
         self.user_id
     }
@@ -423,6 +520,10 @@ pub struct SeededInteractiveMultiPartyServerKey<M: Matrix, S, P> {
     /// s}(m) to LWE_{q, z}(m) where s is ideal RLWE secret and z is ideal LWE
     /// secret.
     lwe_ksk: M::R,
+    /// Scheme switching key RLWE'(s^2) under ideal RLWE secret, which is the
+    /// sum of users' shares. Part `a` of RLWE ciphertexts is followed by part
+    /// `b`. Only present if parameters support circuit bootstrapping.
+    scheme_switch_key: Option<M>,
     /// Common reference seed
     cr_seed: S,
     parameters: P,
@@ -433,6 +534,7 @@ impl<M: Matrix, S, P> SeededInteractiveMultiPartyServerKey<M, S, P> {
         rgsw_cts: Vec<M>,
         auto_keys: HashMap<usize, M>,
         lwe_ksk: M::R,
+        scheme_switch_key: Option<M>,
         cr_seed: S,
         parameters: P,
     ) -> Self {
@@ -440,6 +542,7 @@ impl<M: Matrix, S, P> SeededInteractiveMultiPartyServerKey<M, S, P> {
             rgsw_cts,
             auto_keys,
             lwe_ksk,
+            scheme_switch_key,
             cr_seed,
             parameters,
         }
@@ -448,6 +551,16 @@ impl<M: Matrix, S, P> SeededInteractiveMultiPartyServerKey<M, S, P> {
     pub(super) fn rgsw_cts(&self) -> &[M] {
         &self.rgsw_cts
     }
+
+    #[cfg(feature = "interactive_mp")]
+    pub(super) fn cr_seed(&self) -> &S {
+        &self.cr_seed
+    }
+
+    #[cfg(feature = "interactive_mp")]
+    pub(super) fn parameters(&self) -> &P {
+        &self.parameters
+    }
 }
 
 /// Seeded single party server key
@@ -459,16 +572,21 @@ pub struct SeededSinglePartyServerKey<M: Matrix, P, S> {
     pub(crate) auto_keys: HashMap<usize, M>,
     /// LWE ksk to key switching LWE ciphertext from RLWE secret to LWE secret
     pub(crate) lwe_ksk: M::R,
+    /// Scheme switching key RLWE'(s^2) for circuit bootstrapping. Only present
+    /// if parameters support circuit bootstrapping
+    pub(crate) scheme_switch_key: Option<M>,
     /// Parameters
     pub(crate) parameters: P,
     /// Main seed
     pub(crate) seed: S,
 }
+#[cfg(test)]
 impl<M: Matrix, S> SeededSinglePartyServerKey<M, BoolParameters<M::MatElement>, S> {
     pub(super) fn from_raw(
         auto_keys: HashMap<usize, M>,
         rgsw_cts: Vec<M>,
         lwe_ksk: M::R,
+        scheme_switch_key: Option<M>,
         parameters: BoolParameters<M::MatElement>,
         seed: S,
     ) -> Self {
@@ -491,11 +609,21 @@ impl<M: Matrix, S> SeededSinglePartyServerKey<M, BoolParameters<M::MatElement>,
             lwe_ksk.as_ref().len()
                 == (parameters.lwe_decomposition_count().0 * parameters.rlwe_n().0)
         );
+        if let Some(key) = &scheme_switch_key {
+            assert!(
+                key.dimension()
+                    == (
+                        parameters.scheme_switch_decomposition_count().0,
+                        parameters.rlwe_n().0
+                    )
+            );
+        }
 
         SeededSinglePartyServerKey {
             rgsw_cts,
             auto_keys,
             lwe_ksk,
+            scheme_switch_key,
             parameters,
             seed,
         }
@@ -511,6 +639,9 @@ pub(crate) struct ServerKeyEvaluationDomain<M, P, R, N> {
     galois_keys: HashMap<usize, M>,
     /// LWE key switching key to key switch LWE_{q, s}(m) to LWE_{q, z}(m)
     lwe_ksk: M,
+    /// Scheme switching key RLWE'(s^2) in evaluation domain, if server key
+    /// supports circuit bootstrapping
+    scheme_switch_key: Option<M>,
     parameters: P,
     _phanton: PhantomData<(R, N)>,
 }
@@ -533,6 +664,7 @@ pub(super) mod impl_server_key_eval_domain {
         }
     }
 
+    #[cfg(test)]
     impl<
             M: MatrixMut + MatrixEntity,
             R: RandomFillUniformInModulus<[M::MatElement], CiphertextModulus<M::MatElement>>
@@ -655,10 +787,34 @@ pub(super) mod impl_server_key_eval_domain {
                 data
             };
 
+            // scheme switching key
+            let scheme_switch_key = value.scheme_switch_key.as_ref().map(|seeded_key| {
+                let d = parameters.scheme_switch_decomposition_count().0;
+                assert!(seeded_key.dimension() == (d, ring_size));
+
+                let mut data = M::zeros(d * 2, ring_size);
+
+                // sample RLWE'_A(s^2)
+                data.iter_rows_mut().take(d).for_each(|ri| {
+                    RandomFillUniformInModulus::random_fill(&mut main_prng, &rlwe_q, ri.as_mut())
+                });
+
+                // copy over RLWE'_B(s^2)
+                izip!(data.iter_rows_mut().skip(d), seeded_key.iter_rows())
+                    .for_each(|(to_ri, from_ri)| to_ri.as_mut().copy_from_slice(from_ri.as_ref()));
+
+                // send to evaluation domain
+                data.iter_rows_mut()
+                    .for_each(|ri| nttop.forward(ri.as_mut()));
+
+                data
+            });
+
             ServerKeyEvaluationDomain {
                 rgsw_cts,
                 galois_keys: auto_keys,
                 lwe_ksk,
+                scheme_switch_key,
                 parameters: parameters.clone(),
                 _phanton: PhantomData,
             }
@@ -771,10 +927,21 @@ pub(super) mod impl_server_key_eval_domain {
                 },
             );
 
+            // scheme switching key
+            let scheme_switch_key = value.scheme_switch_key.as_ref().map(|key| {
+                let mut eval_key = M::zeros(key.dimension().0, rlwe_n);
+                izip!(eval_key.iter_rows_mut(), key.iter_rows()).for_each(|(to_ri, from_ri)| {
+                    to_ri.as_mut().copy_from_slice(from_ri.as_ref());
+                    rlwe_nttop.forward(to_ri.as_mut());
+                });
+                eval_key
+            });
+
             ServerKeyEvaluationDomain {
                 rgsw_cts,
                 galois_keys: auto_keys,
                 lwe_ksk,
+                scheme_switch_key,
                 parameters: value.parameters.clone(),
                 _phanton: PhantomData,
             }
@@ -796,6 +963,10 @@ pub(super) mod impl_server_key_eval_domain {
         fn lwe_ksk(&self) -> &Self::LweKskKey {
             &self.lwe_ksk
         }
+
+        fn scheme_switch_key(&self) -> Option<&Self::AutoKey> {
+            self.scheme_switch_key.as_ref()
+        }
     }
 
     #[cfg(test)]
@@ -1060,6 +1231,16 @@ impl<M: Matrix, S, P> SeededNonInteractiveMultiPartyServerKey<M, S, P> {
             parameters,
         }
     }
+
+    #[cfg(feature = "non_interactive_mp")]
+    pub(super) fn cr_seed(&self) -> &S {
+        &self.cr_seed
+    }
+
+    #[cfg(feature = "non_interactive_mp")]
+    pub(super) fn parameters(&self) -> &P {
+        &self.parameters
+    }
 }
 
 /// This key is equivalent to NonInteractiveServerKeyEvaluationDomain with the
@@ -1079,10 +1260,16 @@ mod impl_shoup_non_interactive_server_key_eval_domain {
     use super::*;
     use crate::{backend::Modulus, decomposer::NumInfo, pbs::PbsKey};
 
+    #[cfg(feature = "non_interactive_mp")]
     impl<M> ShoupNonInteractiveServerKeyEvaluationDomain<M> {
         pub(in super::super) fn ui_to_s_ksk(&self, user_id: usize) -> &NormalAndShoup<M> {
             &self.ui_to_s_ksks[user_id]
         }
+
+        /// Returns no. of users of the server key
+        pub(in super::super) fn total_users(&self) -> usize {
+            self.ui_to_s_ksks.len()
+        }
     }
 
     impl<M: Matrix + ToShoup<Modulus = M::MatElement>, R, N>
@@ -1180,6 +1367,7 @@ pub(crate) struct ShoupServerKeyEvaluationDomain<M> {
     rgsw_cts: Vec<NormalAndShoup<M>>,
     galois_keys: HashMap<usize, NormalAndShoup<M>>,
     lwe_ksk: M,
+    scheme_switch_key: Option<NormalAndShoup<M>>,
 }
 
 mod shoup_server_key_eval_domain {
@@ -1211,10 +1399,15 @@ mod shoup_server_key_eval_domain {
                 auto_keys.insert(index, NormalAndShoup::new_with_modulus(key, q));
             });
 
+            let scheme_switch_key = value
+                .scheme_switch_key
+                .map(|key| NormalAndShoup::new_with_modulus(key, q));
+
             Self {
                 rgsw_cts,
                 galois_keys: auto_keys,
                 lwe_ksk: value.lwe_ksk,
+                scheme_switch_key,
             }
         }
     }
@@ -1234,6 +1427,10 @@ mod shoup_server_key_eval_domain {
         fn lwe_ksk(&self) -> &Self::LweKskKey {
             &self.lwe_ksk
         }
+
+        fn scheme_switch_key(&self) -> Option<&Self::AutoKey> {
+            self.scheme_switch_key.as_ref()
+        }
     }
 }
 
@@ -1366,6 +1563,10 @@ mod impl_common_ref_non_interactive_multi_party_server_share {
             self.user_id
         }
 
+        pub(in super::super) fn total_users(&self) -> usize {
+            self.total_users
+        }
+
         pub(in super::super) fn auto_keys_share(&self) -> &HashMap<usize, M> {
             &self.auto_keys_share
         }
@@ -1420,6 +1621,473 @@ impl<M> WithShoupRepr for NormalAndShoup<M> {
     }
 }
 
+#[cfg(any(feature = "interactive_mp", feature = "non_interactive_mp"))]
+mod impl_serialization {
+    use itertools::Itertools;
+
+    use crate::{
+        backend::Modulus,
+        bool::evaluator::NonInteractiveMultiPartyCrs,
+        serialization::{
+            bits_of_largest, impl_binary_encoding, tag, Decode, DecodeError, Encode, Reader, Writer,
+        },
+    };
+    #[cfg(feature = "interactive_mp")]
+    use crate::{
+        bool::evaluator::InteractiveMultiPartyCrs,
+        decomposer::{Decomposer, DefaultDecomposer},
+    };
+
+    use super::*;
+
+    type Parameters = BoolParameters<u64>;
+
+    fn encode_seed(w: &mut Writer, seed: &[u8; 32]) {
+        w.bytes(seed);
+    }
+
+    fn decode_seed(r: &mut Reader) -> Result<[u8; 32], DecodeError> {
+        Ok(r.take(32)?.try_into().unwrap())
+    }
+
+    /// Encodes RLWE ciphertexts (RGSW ciphertexts, auto keys, ...) with
+    /// coefficients mod Q
+    fn encode_rlwe_matrices(w: &mut Writer, ms: &[Vec<Vec<u64>>], parameters: &Parameters) {
+        let log_q = parameters.rlwe_q().log_q();
+        w.seq(ms, |w, m| w.packed_matrix(m, log_q));
+    }
+
+    fn decode_rlwe_matrices(
+        r: &mut Reader,
+        parameters: &Parameters,
+    ) -> Result<Vec<Vec<Vec<u64>>>, DecodeError> {
+        r.seq(|r| r.packed_matrix_mod(parameters.rlwe_n().0, parameters.rlwe_q()))
+    }
+
+    fn encode_auto_keys(
+        w: &mut Writer,
+        auto_keys: &HashMap<usize, Vec<Vec<u64>>>,
+        parameters: &Parameters,
+    ) {
+        let log_q = parameters.rlwe_q().log_q();
+        w.map(auto_keys, |w, m| w.packed_matrix(m, log_q));
+    }
+
+    fn decode_auto_keys(
+        r: &mut Reader,
+        parameters: &Parameters,
+    ) -> Result<HashMap<usize, Vec<Vec<u64>>>, DecodeError> {
+        let auto_keys =
+            r.map(|r| r.packed_matrix_mod(parameters.rlwe_n().0, parameters.rlwe_q()))?;
+        let mut dlogs = auto_keys.keys().copied().collect_vec();
+        dlogs.sort_unstable();
+        if dlogs != parameters.auto_element_dlogs() {
+            return Err(DecodeError::Invalid(
+                "auto keys do not match parameters".to_string(),
+            ));
+        }
+        Ok(auto_keys)
+    }
+
+    /// Encodes LWE key switching key with coefficients mod q_ks
+    fn encode_lwe_ksk(w: &mut Writer, lwe_ksk: &Vec<u64>, parameters: &Parameters) {
+        w.packed(lwe_ksk, parameters.lwe_q().log_q());
+    }
+
+    fn decode_lwe_ksk(r: &mut Reader, parameters: &Parameters) -> Result<Vec<u64>, DecodeError> {
+        r.packed_mod(
+            parameters.rlwe_n().0 * parameters.lwe_decomposition_count().0,
+            parameters.lwe_q(),
+        )
+    }
+
+    #[cfg(feature = "interactive_mp")]
+    /// Encodes scheme switching key (share) of interactive multi-party server
+    /// key with coefficients mod Q
+    fn encode_scheme_switch_key(
+        w: &mut Writer,
+        key: &Option<Vec<Vec<u64>>>,
+        parameters: &Parameters,
+    ) {
+        w.bool(key.is_some());
+        if let Some(key) = key {
+            w.packed_matrix(key, parameters.rlwe_q().log_q());
+        }
+    }
+
+    #[cfg(feature = "interactive_mp")]
+    fn decode_scheme_switch_key(
+        r: &mut Reader,
+        parameters: &Parameters,
+    ) -> Result<Option<Vec<Vec<u64>>>, DecodeError> {
+        let key = if r.bool()? {
+            Some(r.packed_matrix_mod(parameters.rlwe_n().0, parameters.rlwe_q())?)
+        } else {
+            None
+        };
+        // key has part `a` and part `b` of a RLWE ciphertext per element of
+        // the scheme switching gadget vector
+        let rows = parameters
+            .scheme_switch_decomposer::<DefaultDecomposer<u64>>()
+            .map(|d| d.decomposition_count().0 * 2);
+        if key.as_ref().map(|k| k.len()) != rows {
+            return Err(DecodeError::Invalid(
+                "scheme switching key does not match parameters".to_string(),
+            ));
+        }
+        Ok(key)
+    }
+
+    impl Encode for ClientKey<[u8; 32], u64> {
+        fn encode(&self, w: &mut Writer) {
+            self.parameters.encode(w);
+            encode_seed(w, &self.seed);
+        }
+    }
+
+    impl Decode for ClientKey<[u8; 32], u64> {
+        fn decode(r: &mut Reader) -> Result<Self, DecodeError> {
+            Ok(ClientKey {
+                parameters: Parameters::decode(r)?,
+                seed: decode_seed(r)?,
+                decryption_shares: DecryptionShareLedger::default(),
+                runs: RunLedger::default(),
+            })
+        }
+    }
+
+    impl_binary_encoding!(tag::CLIENT_KEY, impl[] ClientKey<[u8; 32], u64>);
+
+    impl Encode for CommonReferenceSeededCollectivePublicKeyShare<Vec<u64>, [u8; 32], Parameters> {
+        fn encode(&self, w: &mut Writer) {
+            self.parameters.encode(w);
+            encode_seed(w, &self.cr_seed);
+            w.packed(&self.share, self.parameters.rlwe_q().log_q());
+        }
+    }
+
+    impl Decode for CommonReferenceSeededCollectivePublicKeyShare<Vec<u64>, [u8; 32], Parameters> {
+        fn decode(r: &mut Reader) -> Result<Self, DecodeError> {
+            let parameters = Parameters::decode(r)?;
+            let cr_seed = decode_seed(r)?;
+            let share = r.packed_mod(parameters.rlwe_n().0, parameters.rlwe_q())?;
+            Ok(CommonReferenceSeededCollectivePublicKeyShare {
+                share,
+                cr_seed,
+                parameters,
+            })
+        }
+    }
+
+    impl_binary_encoding!(
+        tag::COLLECTIVE_PUBLIC_KEY_SHARE,
+        impl[] CommonReferenceSeededCollectivePublicKeyShare<Vec<u64>, [u8; 32], Parameters>
+    );
+
+    impl<R, Mo> Encode for PublicKey<Vec<Vec<u64>>, R, Mo> {
+        fn encode(&self, w: &mut Writer) {
+            let log_q = self
+                .key
+                .iter()
+                .map(|r| bits_of_largest(r))
+                .max()
+                .unwrap_or(0);
+            w.packed_matrix(&self.key, log_q);
+        }
+    }
+
+    impl<R, Mo> Decode for PublicKey<Vec<Vec<u64>>, R, Mo> {
+        fn decode(r: &mut Reader) -> Result<Self, DecodeError> {
+            let rows = r.len(9)?;
+            let key = (0..rows)
+                .map(|_| r.packed())
+                .collect::<Result<Vec<_>, _>>()?;
+            // public key is RLWE ciphertext [a, b]
+            if key.len() != 2 || key[0].len() != key[1].len() || !key[0].len().is_power_of_two() {
+                return Err(DecodeError::Invalid("invalid public key".to_string()));
+            }
+            Ok(PublicKey {
+                key,
+                _phantom: PhantomData,
+            })
+        }
+    }
+
+    impl_binary_encoding!(tag::PUBLIC_KEY, impl[R, Mo] PublicKey<Vec<Vec<u64>>, R, Mo>);
+
+    #[cfg(feature = "interactive_mp")]
+    impl Encode
+        for CommonReferenceSeededInteractiveMultiPartyServerKeyShare<
+            Vec<Vec<u64>>,
+            Parameters,
+            InteractiveMultiPartyCrs<[u8; 32]>,
+        >
+    {
+        fn encode(&self, w: &mut Writer) {
+            self.parameters.encode(w);
+            encode_seed(w, &self.cr_seed.seed);
+            w.usize(self.user_id);
+            encode_rlwe_matrices(w, &self.self_leader_rgsws, &self.parameters);
+            encode_rlwe_matrices(w, &self.not_self_leader_rgsws, &self.parameters);
+            encode_auto_keys(w, &self.auto_keys, &self.parameters);
+            encode_lwe_ksk(w, &self.lwe_ksk, &self.parameters);
+            encode_scheme_switch_key(w, &self.scheme_switch_key, &self.parameters);
+        }
+    }
+
+    #[cfg(feature = "interactive_mp")]
+    impl Decode
+        for CommonReferenceSeededInteractiveMultiPartyServerKeyShare<
+            Vec<Vec<u64>>,
+            Parameters,
+            InteractiveMultiPartyCrs<[u8; 32]>,
+        >
+    {
+        fn decode(r: &mut Reader) -> Result<Self, DecodeError> {
+            let parameters = Parameters::decode(r)?;
+            let cr_seed = InteractiveMultiPartyCrs {
+                seed: decode_seed(r)?,
+            };
+            let user_id = r.usize()?;
+            let self_leader_rgsws = decode_rlwe_matrices(r, &parameters)?;
+            let not_self_leader_rgsws = decode_rlwe_matrices(r, &parameters)?;
+            if self_leader_rgsws.len() + not_self_leader_rgsws.len() != parameters.lwe_n().0 {
+                return Err(DecodeError::Invalid(
+                    "expected a RGSW ciphertext per LWE secret index".to_string(),
+                ));
+            }
+            let auto_keys = decode_auto_keys(r, &parameters)?;
+            let lwe_ksk = decode_lwe_ksk(r, &parameters)?;
+            let scheme_switch_key = decode_scheme_switch_key(r, &parameters)?;
+            Ok(CommonReferenceSeededInteractiveMultiPartyServerKeyShare {
+                self_leader_rgsws,
+                not_self_leader_rgsws,
+                auto_keys,
+                lwe_ksk,
+                scheme_switch_key,
+                cr_seed,
+                parameters,
+                user_id,
+            })
+        }
+    }
+
+    #[cfg(feature = "interactive_mp")]
+    impl_binary_encoding!(
+        tag::INTERACTIVE_SERVER_KEY_SHARE,
+        impl[] CommonReferenceSeededInteractiveMultiPartyServerKeyShare<
+            Vec<Vec<u64>>,
+            Parameters,
+            InteractiveMultiPartyCrs<[u8; 32]>,
+        >
+    );
+
+    #[cfg(feature = "interactive_mp")]
+    impl Encode
+        for SeededInteractiveMultiPartyServerKey<
+            Vec<Vec<u64>>,
+            InteractiveMultiPartyCrs<[u8; 32]>,
+            Parameters,
+        >
+    {
+        fn encode(&self, w: &mut Writer) {
+            self.parameters.encode(w);
+            encode_seed(w, &self.cr_seed.seed);
+            encode_rlwe_matrices(w, &self.rgsw_cts, &self.parameters);
+            encode_auto_keys(w, &self.auto_keys, &self.parameters);
+            encode_lwe_ksk(w, &self.lwe_ksk, &self.parameters);
+            encode_scheme_switch_key(w, &self.scheme_switch_key, &self.parameters);
+        }
+    }
+
+    #[cfg(feature = "interactive_mp")]
+    impl Decode
+        for SeededInteractiveMultiPartyServerKey<
+            Vec<Vec<u64>>,
+            InteractiveMultiPartyCrs<[u8; 32]>,
+            Parameters,
+        >
+    {
+        fn decode(r: &mut Reader) -> Result<Self, DecodeError> {
+            let parameters = Parameters::decode(r)?;
+            let cr_seed = InteractiveMultiPartyCrs {
+                seed: decode_seed(r)?,
+            };
+            let rgsw_cts = decode_rlwe_matrices(r, &parameters)?;
+            if rgsw_cts.len() != parameters.lwe_n().0 {
+                return Err(DecodeError::Invalid(
+                    "expected a RGSW ciphertext per LWE secret index".to_string(),
+                ));
+            }
+            let auto_keys = decode_auto_keys(r, &parameters)?;
+            let lwe_ksk = decode_lwe_ksk(r, &parameters)?;
+            let scheme_switch_key = decode_scheme_switch_key(r, &parameters)?;
+            Ok(SeededInteractiveMultiPartyServerKey {
+                rgsw_cts,
+                auto_keys,
+                lwe_ksk,
+                scheme_switch_key,
+                cr_seed,
+                parameters,
+            })
+        }
+    }
+
+    #[cfg(feature = "interactive_mp")]
+    impl_binary_encoding!(
+        tag::INTERACTIVE_SERVER_KEY,
+        impl[] SeededInteractiveMultiPartyServerKey<
+            Vec<Vec<u64>>,
+            InteractiveMultiPartyCrs<[u8; 32]>,
+            Parameters,
+        >
+    );
+
+    impl Encode
+        for CommonReferenceSeededNonInteractiveMultiPartyServerKeyShare<
+            Vec<Vec<u64>>,
+            Parameters,
+            NonInteractiveMultiPartyCrs<[u8; 32]>,
+        >
+    {
+        fn encode(&self, w: &mut Writer) {
+            self.parameters.encode(w);
+            encode_seed(w, &self.cr_seed.seed);
+            w.usize(self.user_id);
+            w.usize(self.total_users);
+            encode_rlwe_matrices(w, &self.self_leader_ni_rgsw_cts, &self.parameters);
+            encode_rlwe_matrices(w, &self.not_self_leader_ni_rgsw_cts, &self.parameters);
+            encode_rlwe_matrices(w, &self.ni_rgsw_zero_encs, &self.parameters);
+            encode_rlwe_matrices(w, std::slice::from_ref(&self.ui_to_s_ksk), &self.parameters);
+            encode_rlwe_matrices(w, &self.ksk_zero_encs_for_others, &self.parameters);
+            encode_auto_keys(w, &self.auto_keys_share, &self.parameters);
+            encode_lwe_ksk(w, &self.lwe_ksk_share, &self.parameters);
+        }
+    }
+
+    impl Decode
+        for CommonReferenceSeededNonInteractiveMultiPartyServerKeyShare<
+            Vec<Vec<u64>>,
+            Parameters,
+            NonInteractiveMultiPartyCrs<[u8; 32]>,
+        >
+    {
+        fn decode(r: &mut Reader) -> Result<Self, DecodeError> {
+            let parameters = Parameters::decode(r)?;
+            let cr_seed = NonInteractiveMultiPartyCrs {
+                seed: decode_seed(r)?,
+            };
+            let user_id = r.usize()?;
+            let total_users = r.usize()?;
+            if user_id >= total_users {
+                return Err(DecodeError::Invalid(format!(
+                    "user id {user_id} is not less than total users {total_users}"
+                )));
+            }
+            let lwe_n = parameters.lwe_n().0;
+            let self_leader_ni_rgsw_cts = decode_rlwe_matrices(r, &parameters)?;
+            let not_self_leader_ni_rgsw_cts = decode_rlwe_matrices(r, &parameters)?;
+            let ni_rgsw_zero_encs = decode_rlwe_matrices(r, &parameters)?;
+            let mut ui_to_s_ksk = decode_rlwe_matrices(r, &parameters)?;
+            let ksk_zero_encs_for_others = decode_rlwe_matrices(r, &parameters)?;
+            if self_leader_ni_rgsw_cts.len() + not_self_leader_ni_rgsw_cts.len() != lwe_n
+                || ni_rgsw_zero_encs.len() != lwe_n
+                || ui_to_s_ksk.len() != 1
+                || ksk_zero_encs_for_others.len() != total_users - 1
+            {
+                return Err(DecodeError::Invalid(
+                    "no. of ciphertexts does not match parameters and total users".to_string(),
+                ));
+            }
+            let auto_keys_share = decode_auto_keys(r, &parameters)?;
+            let lwe_ksk_share = decode_lwe_ksk(r, &parameters)?;
+            Ok(
+                CommonReferenceSeededNonInteractiveMultiPartyServerKeyShare {
+                    self_leader_ni_rgsw_cts,
+                    not_self_leader_ni_rgsw_cts,
+                    ni_rgsw_zero_encs,
+                    ui_to_s_ksk: ui_to_s_ksk.pop().unwrap(),
+                    ksk_zero_encs_for_others,
+                    auto_keys_share,
+                    lwe_ksk_share,
+                    user_id,
+                    total_users,
+                    lwe_n,
+                    cr_seed,
+                    parameters,
+                },
+            )
+        }
+    }
+
+    impl_binary_encoding!(
+        tag::NON_INTERACTIVE_SERVER_KEY_SHARE,
+        impl[] CommonReferenceSeededNonInteractiveMultiPartyServerKeyShare<
+            Vec<Vec<u64>>,
+            Parameters,
+            NonInteractiveMultiPartyCrs<[u8; 32]>,
+        >
+    );
+
+    impl Encode
+        for SeededNonInteractiveMultiPartyServerKey<
+            Vec<Vec<u64>>,
+            NonInteractiveMultiPartyCrs<[u8; 32]>,
+            Parameters,
+        >
+    {
+        fn encode(&self, w: &mut Writer) {
+            self.parameters.encode(w);
+            encode_seed(w, &self.cr_seed.seed);
+            encode_rlwe_matrices(w, &self.ui_to_s_ksks, &self.parameters);
+            encode_rlwe_matrices(w, &self.rgsw_cts, &self.parameters);
+            encode_auto_keys(w, &self.auto_keys, &self.parameters);
+            encode_lwe_ksk(w, &self.lwe_ksk, &self.parameters);
+        }
+    }
+
+    impl Decode
+        for SeededNonInteractiveMultiPartyServerKey<
+            Vec<Vec<u64>>,
+            NonInteractiveMultiPartyCrs<[u8; 32]>,
+            Parameters,
+        >
+    {
+        fn decode(r: &mut Reader) -> Result<Self, DecodeError> {
+            let parameters = Parameters::decode(r)?;
+            let cr_seed = NonInteractiveMultiPartyCrs {
+                seed: decode_seed(r)?,
+            };
+            let ui_to_s_ksks = decode_rlwe_matrices(r, &parameters)?;
+            let rgsw_cts = decode_rlwe_matrices(r, &parameters)?;
+            if rgsw_cts.len() != parameters.lwe_n().0 {
+                return Err(DecodeError::Invalid(
+                    "expected a RGSW ciphertext per LWE secret index".to_string(),
+                ));
+            }
+            let auto_keys = decode_auto_keys(r, &parameters)?;
+            let lwe_ksk = decode_lwe_ksk(r, &parameters)?;
+            Ok(SeededNonInteractiveMultiPartyServerKey {
+                ui_to_s_ksks,
+                rgsw_cts,
+                auto_keys,
+                lwe_ksk,
+                cr_seed,
+                parameters,
+            })
+        }
+    }
+
+    impl_binary_encoding!(
+        tag::NON_INTERACTIVE_SERVER_KEY,
+        impl[] SeededNonInteractiveMultiPartyServerKey<
+            Vec<Vec<u64>>,
+            NonInteractiveMultiPartyCrs<[u8; 32]>,
+            Parameters,
+        >
+    );
+}
+
 #[cfg(test)]
 pub(crate) mod key_size {
     use num_traits::{FromPrimitive, PrimInt};
@@ -1455,6 +2123,10 @@ pub(crate) mod key_size {
                 .values()
                 .for_each(|v| total += v.size(log_rlweq));
 
+            if let Some(key) = &self.scheme_switch_key {
+                total += key.size(log_rlweq);
+            }
+
             let log_lweq = self.parameters().lwe_q().log_q();
             total += self.lwe_ksk.size(log_lweq);
             total
@@ -0,0 +1,197 @@
+//! Ciphertexts of messages of upto 3 bits evaluated with programmable
+//! bootstraps of arbitrary lookup tables
+
+use std::ops::Add;
+
+use itertools::Itertools;
+
+use crate::{
+    backend::ModularOpsU64,
+    multi_party::{
+        check_decryption_shares, multi_party_aggregate_decryption_shares_and_decrypt,
+        DecryptionShare,
+    },
+    pbs::PbsInfo,
+    utils::WithLocal,
+    Decryptor, Encryptor, Error, MultiPartyDecryptor,
+};
+
+use super::{
+    evaluator::MessageEncoding, impl_bool_frontend::FheBool, mock::MockClientKey,
+    parameters::CiphertextModulus, try_with_evaluator, Backend, BoolEvaluator, LookupTables,
+};
+
+/// Max. message bits of lookup tables supported by any parameter set.
+///
+/// Mock and Lazy types support the same, since no parameter set supports
+/// lookup tables over 4 bit messages with failure probability at most 2^-40.
+pub(crate) const MAX_MESSAGE_BITS: usize = 3;
+
+type Message<C> = <<C as Backend>::Evaluator as LookupTables>::Message;
+
+/// Fhe ciphertext of unsigned message of `BITS` bits.
+///
+/// Unlike `FheUint8`, that is a ciphertext per bit, `FheShortUint` encrypts
+/// the message in a single LWE ciphertext and any function of the message
+/// is evaluated with a single bootstrap (refer to `FheShortUint::apply_lut`).
+///
+/// Since every additional message bit halves the noise margin of the
+/// ciphertext, `BITS` must not exceed max. message bits supported by the
+/// parameter set (refer to `ParameterSelector`), which is at most 3 bits.
+#[derive(Clone)]
+pub struct FheShortUint<C: Backend, const BITS: usize> {
+    pub(crate) data: Message<C>,
+}
+
+impl<C: Backend, const BITS: usize> FheShortUint<C, BITS> {
+    fn assert_supported() {
+        let max_bits = C::Evaluator::with_local(|e| e.max_message_bits());
+        assert!(
+            BITS >= 1 && BITS <= max_bits,
+            "FheShortUint<{BITS}> is not supported by the parameter set. Max. supported message \
+             bits: {max_bits}"
+        );
+    }
+
+    /// Returns trivial encryption of `m mod 2^BITS`. Note that trivial
+    /// encryption does not hide `m`.
+    pub fn trivial(m: u8) -> FheShortUint<C, BITS> {
+        Self::assert_supported();
+        C::Evaluator::with_local(|e| FheShortUint {
+            data: e.trivial_message(m as u64 % (1 << BITS), BITS),
+        })
+    }
+
+    /// Returns message of bits `bits` in little endian order. Missing bits
+    /// are set to 0. Requires a bootstrap per bit.
+    ///
+    /// Panics if more than `BITS` bits are supplied
+    pub fn from_bits(bits: &[FheBool<C>]) -> FheShortUint<C, BITS> {
+        Self::assert_supported();
+        assert!(
+            bits.len() <= BITS,
+            "Expected at most {BITS} bits, got {}",
+            bits.len()
+        );
+        C::with_evaluator(|e, key| {
+            let mut data = e.trivial_message(0, BITS);
+            bits.iter().enumerate().for_each(|(i, bit)| {
+                let m = e.bool_to_message(bit.data(), BITS, 1 << i, key);
+                e.add_message_inplace(&mut data, &m, BITS, key);
+            });
+            FheShortUint { data }
+        })
+    }
+
+    /// Returns bits of the message in little endian order. Requires a
+    /// bootstrap per bit.
+    pub fn to_bits(&self) -> Vec<FheBool<C>> {
+        (0..BITS)
+            .map(|i| self.apply_lut_to_bool(|m| (m >> i) & 1 == 1))
+            .collect()
+    }
+
+    /// Returns encryption of `f(m) mod 2^BITS` where `m` is the message of
+    /// `self`. Requires a single bootstrap.
+    pub fn apply_lut<F: Fn(u8) -> u8>(&self, f: F) -> FheShortUint<C, BITS> {
+        C::with_evaluator(|e, key| FheShortUint {
+            data: e.apply_lut(&self.data, BITS, |m| f(m as u8) as u64, key),
+        })
+    }
+
+    /// Returns encryption of `f(m)` where `m` is the message of `self`.
+    /// Requires a single bootstrap.
+    pub fn apply_lut_to_bool<F: Fn(u8) -> bool>(&self, f: F) -> FheBool<C> {
+        C::with_evaluator(|e, key| FheBool {
+            data: e.apply_lut_to_bool(&self.data, BITS, |m| f(m as u8), key),
+        })
+    }
+}
+
+/// Returns encryption of sum of the messages. Addition does not require a
+/// bootstrap but the sum must be less than 2^BITS. Otherwise the sum
+/// overflows into the padding bit and lookup tables applied to the output
+/// return incorrect results.
+///
+/// Parameters bound failure probability of lookup tables applied to sums of
+/// at most 4 outputs of lookup tables (or of `FheShortUint::from_bits`).
+/// Lookup tables applied to larger sums fail with higher probability.
+impl<C: Backend, const BITS: usize> Add<&FheShortUint<C, BITS>> for &FheShortUint<C, BITS> {
+    type Output = FheShortUint<C, BITS>;
+
+    fn add(self, rhs: &FheShortUint<C, BITS>) -> Self::Output {
+        C::with_evaluator(|e, key| {
+            let mut data = self.data.clone();
+            e.add_message_inplace(&mut data, &rhs.data, BITS, key);
+            FheShortUint { data }
+        })
+    }
+}
+
+impl<K, const BITS: usize> MultiPartyDecryptor<u8, FheShortUint<Vec<u64>, BITS>> for K
+where
+    K: MultiPartyDecryptor<bool, Vec<u64>, DecryptionShare = DecryptionShare>,
+{
+    type DecryptionShare = DecryptionShare;
+
+    fn gen_decryption_share(&self, c: &FheShortUint<Vec<u64>, BITS>) -> Self::DecryptionShare {
+        // Decryption share is independent of the encoding
+        MultiPartyDecryptor::<bool, Vec<u64>>::gen_decryption_share(self, &c.data)
+    }
+
+    fn try_gen_decryption_share(
+        &self,
+        c: &FheShortUint<Vec<u64>, BITS>,
+    ) -> Result<Self::DecryptionShare, Error> {
+        MultiPartyDecryptor::<bool, Vec<u64>>::try_gen_decryption_share(self, &c.data)
+    }
+
+    fn aggregate_decryption_shares(
+        &self,
+        c: &FheShortUint<Vec<u64>, BITS>,
+        shares: &[Self::DecryptionShare],
+    ) -> u8 {
+        let shares = shares.iter().map(|s| s.0).collect_vec();
+        BoolEvaluator::with_local(|e| {
+            let modop: &ModularOpsU64<CiphertextModulus<u64>> = e.pbs_info().modop_rlweq();
+            let noisy_m =
+                multi_party_aggregate_decryption_shares_and_decrypt(&c.data, &shares, modop);
+            e.pbs_info().rlwe_q().decode_message(noisy_m, BITS) as u8
+        })
+    }
+
+    fn try_aggregate_decryption_shares(
+        &self,
+        c: &FheShortUint<Vec<u64>, BITS>,
+        shares: &[Self::DecryptionShare],
+    ) -> Result<u8, Error> {
+        let values = shares.iter().map(|s| s.0).collect_vec();
+        try_with_evaluator(|e| {
+            check_decryption_shares(
+                &c.data,
+                &values,
+                e.parameters().rlwe_n().0,
+                e.pbs_info().rlwe_q(),
+            )
+        })??;
+        Ok(
+            MultiPartyDecryptor::<u8, FheShortUint<Vec<u64>, BITS>>::aggregate_decryption_shares(
+                self, c, shares,
+            ),
+        )
+    }
+}
+
+impl<const BITS: usize> Encryptor<u8, FheShortUint<bool, BITS>> for MockClientKey {
+    fn encrypt(&self, m: &u8) -> FheShortUint<bool, BITS> {
+        FheShortUint {
+            data: *m as u64 % (1 << BITS),
+        }
+    }
+}
+
+impl<const BITS: usize> Decryptor<u8, FheShortUint<bool, BITS>> for MockClientKey {
+    fn decrypt(&self, c: &FheShortUint<bool, BITS>) -> u8 {
+        c.data as u8
+    }
+}
@@ -0,0 +1,292 @@
+//! Plaintext simulation of boolean gates
+//!
+//! `MockBoolEvaluator` evaluates boolean gates on plaintext bools instead of
+//! ciphertexts. It is bit-exact with `BoolEvaluator` but does not require
+//! keys or bootstrapping. Hence circuits written with Fhe types can be
+//! debugged in milliseconds by switching to Mock types (for example,
+//! `MockFheUint8` in place of `FheUint8`). Gates evaluated by Mock evaluator
+//! are recorded in `stats`, which can be used to estimate cost of the circuit
+//! before running it for real.
+
+use std::cell::RefCell;
+
+use crate::{stats, utils::WithLocal, Decryptor, Encryptor};
+
+use super::{lut::MAX_MESSAGE_BITS, Backend, BooleanGates, LookupTables};
+
+thread_local! {
+    static MOCK_BOOL_EVALUATOR: RefCell<MockBoolEvaluator> = const { RefCell::new(MockBoolEvaluator) };
+}
+
+/// Evaluates boolean gates on plaintext bools
+#[derive(Default)]
+pub struct MockBoolEvaluator;
+
+/// Server key of `MockBoolEvaluator`. Mock evaluator does not require any key.
+pub struct MockServerKey;
+
+/// Client key for Mock types. Ciphertexts of Mock types are plaintext, hence
+/// encryption and decryption are identity.
+pub struct MockClientKey;
+
+impl WithLocal for MockBoolEvaluator {
+    fn with_local<F, R>(func: F) -> R
+    where
+        F: Fn(&Self) -> R,
+    {
+        MOCK_BOOL_EVALUATOR.with_borrow(|e| func(e))
+    }
+
+    fn with_local_mut<F, R>(func: F) -> R
+    where
+        F: Fn(&mut Self) -> R,
+    {
+        MOCK_BOOL_EVALUATOR.with_borrow_mut(|e| func(e))
+    }
+
+    fn with_local_mut_mut<F, R>(func: &mut F) -> R
+    where
+        F: FnMut(&mut Self) -> R,
+    {
+        MOCK_BOOL_EVALUATOR.with_borrow_mut(|e| func(e))
+    }
+}
+
+impl Backend for bool {
+    type Evaluator = MockBoolEvaluator;
+    type Key = MockServerKey;
+
+    fn with_evaluator<F, R>(func: F) -> R
+    where
+        F: FnOnce(&mut MockBoolEvaluator, &MockServerKey) -> R,
+    {
+        MOCK_BOOL_EVALUATOR.with_borrow_mut(|e| func(e, &MockServerKey))
+    }
+}
+
+impl BooleanGates for MockBoolEvaluator {
+    type Ciphertext = bool;
+    type Key = MockServerKey;
+
+    fn and_inplace(&mut self, c0: &mut bool, c1: &bool, _key: &MockServerKey) {
+        stats::record_gate(|c| c.and += 1);
+        *c0 &= *c1;
+    }
+
+    fn nand_inplace(&mut self, c0: &mut bool, c1: &bool, _key: &MockServerKey) {
+        stats::record_gate(|c| c.nand += 1);
+        *c0 = !(*c0 & *c1);
+    }
+
+    fn or_inplace(&mut self, c0: &mut bool, c1: &bool, _key: &MockServerKey) {
+        stats::record_gate(|c| c.or += 1);
+        *c0 |= *c1;
+    }
+
+    fn nor_inplace(&mut self, c0: &mut bool, c1: &bool, _key: &MockServerKey) {
+        stats::record_gate(|c| c.nor += 1);
+        *c0 = !(*c0 | *c1);
+    }
+
+    fn xor_inplace(&mut self, c0: &mut bool, c1: &bool, _key: &MockServerKey) {
+        stats::record_gate(|c| c.xor += 1);
+        *c0 ^= *c1;
+    }
+
+    fn xnor_inplace(&mut self, c0: &mut bool, c1: &bool, _key: &MockServerKey) {
+        stats::record_gate(|c| c.xnor += 1);
+        *c0 = !(*c0 ^ *c1);
+    }
+
+    fn andny_inplace(&mut self, c0: &mut bool, c1: &bool, _key: &MockServerKey) {
+        stats::record_gate(|c| c.andny += 1);
+        *c0 = !*c0 & *c1;
+    }
+
+    fn andyn_inplace(&mut self, c0: &mut bool, c1: &bool, _key: &MockServerKey) {
+        stats::record_gate(|c| c.andyn += 1);
+        *c0 &= !*c1;
+    }
+
+    fn orny_inplace(&mut self, c0: &mut bool, c1: &bool, _key: &MockServerKey) {
+        stats::record_gate(|c| c.orny += 1);
+        *c0 = !*c0 | *c1;
+    }
+
+    fn oryn_inplace(&mut self, c0: &mut bool, c1: &bool, _key: &MockServerKey) {
+        stats::record_gate(|c| c.oryn += 1);
+        *c0 |= !*c1;
+    }
+
+    fn not_inplace(&self, c: &mut bool) {
+        stats::record_gate(|c| c.not += 1);
+        *c = !*c;
+    }
+
+    fn and(&mut self, c0: &bool, c1: &bool, key: &MockServerKey) -> bool {
+        let mut out = *c0;
+        self.and_inplace(&mut out, c1, key);
+        out
+    }
+
+    fn nand(&mut self, c0: &bool, c1: &bool, key: &MockServerKey) -> bool {
+        let mut out = *c0;
+        self.nand_inplace(&mut out, c1, key);
+        out
+    }
+
+    fn or(&mut self, c0: &bool, c1: &bool, key: &MockServerKey) -> bool {
+        let mut out = *c0;
+        self.or_inplace(&mut out, c1, key);
+        out
+    }
+
+    fn nor(&mut self, c0: &bool, c1: &bool, key: &MockServerKey) -> bool {
+        let mut out = *c0;
+        self.nor_inplace(&mut out, c1, key);
+        out
+    }
+
+    fn xor(&mut self, c0: &bool, c1: &bool, key: &MockServerKey) -> bool {
+        let mut out = *c0;
+        self.xor_inplace(&mut out, c1, key);
+        out
+    }
+
+    fn xnor(&mut self, c0: &bool, c1: &bool, key: &MockServerKey) -> bool {
+        let mut out = *c0;
+        self.xnor_inplace(&mut out, c1, key);
+        out
+    }
+
+    fn andny(&mut self, c0: &bool, c1: &bool, key: &MockServerKey) -> bool {
+        let mut out = *c0;
+        self.andny_inplace(&mut out, c1, key);
+        out
+    }
+
+    fn andyn(&mut self, c0: &bool, c1: &bool, key: &MockServerKey) -> bool {
+        let mut out = *c0;
+        self.andyn_inplace(&mut out, c1, key);
+        out
+    }
+
+    fn orny(&mut self, c0: &bool, c1: &bool, key: &MockServerKey) -> bool {
+        let mut out = *c0;
+        self.orny_inplace(&mut out, c1, key);
+        out
+    }
+
+    fn oryn(&mut self, c0: &bool, c1: &bool, key: &MockServerKey) -> bool {
+        let mut out = *c0;
+        self.oryn_inplace(&mut out, c1, key);
+        out
+    }
+
+    fn not(&self, c: &bool) -> bool {
+        let mut out = *c;
+        self.not_inplace(&mut out);
+        out
+    }
+
+    fn majority(&mut self, c0: &bool, c1: &bool, c2: &bool, _key: &MockServerKey) -> bool {
+        stats::record_gate(|c| c.majority += 1);
+        (*c0 & *c1) | (*c2 & (*c0 | *c1))
+    }
+
+    fn mux(
+        &mut self,
+        selector: &bool,
+        if_true: &bool,
+        if_false: &bool,
+        _key: &MockServerKey,
+    ) -> bool {
+        stats::record_gate(|c| c.mux += 1);
+        if *selector {
+            *if_true
+        } else {
+            *if_false
+        }
+    }
+
+    fn half_adder(&mut self, c0: &mut bool, c1: &bool, _key: &MockServerKey) -> bool {
+        stats::record_gate(|c| c.half_adder += 1);
+        let carry = *c0 & *c1;
+        *c0 ^= *c1;
+        carry
+    }
+
+    fn full_adder(&mut self, c0: &mut bool, c1: &bool, c2: &bool, _key: &MockServerKey) -> bool {
+        stats::record_gate(|c| c.full_adder += 1);
+        let carry = (*c0 & *c1) | (*c2 & (*c0 ^ *c1));
+        *c0 ^= *c1 ^ *c2;
+        carry
+    }
+
+    fn trivial(&self, m: bool) -> bool {
+        m
+    }
+}
+
+impl LookupTables for MockBoolEvaluator {
+    type Message = u64;
+
+    fn max_message_bits(&self) -> usize {
+        // Mock evaluator does not depend on parameters
+        MAX_MESSAGE_BITS
+    }
+
+    fn trivial_message(&self, m: u64, bits: usize) -> u64 {
+        m % (1 << bits)
+    }
+
+    fn add_message_inplace(&mut self, c0: &mut u64, c1: &u64, bits: usize, _key: &MockServerKey) {
+        // Sum that overflows into the padding bit is not a valid message of
+        // `BoolEvaluator`, hence unlike gates it cannot be simulated
+        *c0 += *c1;
+        assert!(*c0 < 1 << bits, "Sum {c0} overflows {bits} bits");
+    }
+
+    fn apply_lut<F: Fn(u64) -> u64>(
+        &mut self,
+        c: &u64,
+        bits: usize,
+        f: F,
+        _key: &MockServerKey,
+    ) -> u64 {
+        stats::record_gate(|c| c.lut += 1);
+        f(*c) % (1 << bits)
+    }
+
+    fn apply_lut_to_bool<F: Fn(u64) -> bool>(
+        &mut self,
+        c: &u64,
+        _bits: usize,
+        f: F,
+        _key: &MockServerKey,
+    ) -> bool {
+        stats::record_gate(|c| c.lut += 1);
+        f(*c)
+    }
+
+    fn bool_to_message(&mut self, c: &bool, bits: usize, m: u64, _key: &MockServerKey) -> u64 {
+        stats::record_gate(|c| c.lut += 1);
+        if *c {
+            m % (1 << bits)
+        } else {
+            0
+        }
+    }
+}
+
+impl Encryptor<bool, bool> for MockClientKey {
+    fn encrypt(&self, m: &bool) -> bool {
+        *m
+    }
+}
+
+impl Decryptor<bool, bool> for MockClientKey {
+    fn decrypt(&self, c: &bool) -> bool {
+        *c
+    }
+}
@@ -1,11 +1,19 @@
 mod evaluator;
 mod keys;
+#[cfg(any(feature = "interactive_mp", feature = "non_interactive_mp"))]
+pub(crate) mod lut;
+#[cfg(any(feature = "interactive_mp", feature = "non_interactive_mp"))]
+mod mock;
 pub(crate) mod parameters;
+#[cfg(any(feature = "interactive_mp", feature = "non_interactive_mp"))]
+mod radix;
 
 #[cfg(feature = "interactive_mp")]
 mod mp_api;
 #[cfg(feature = "non_interactive_mp")]
 mod ni_mp_api;
+#[cfg(any(feature = "interactive_mp", feature = "non_interactive_mp"))]
+mod thread_pool;
 
 #[cfg(feature = "non_interactive_mp")]
 pub use ni_mp_api::*;
@@ -13,14 +21,48 @@ pub use ni_mp_api::*;
 #[cfg(feature = "interactive_mp")]
 pub use mp_api::*;
 
-use crate::RowEntity;
+#[cfg(any(feature = "interactive_mp", feature = "non_interactive_mp"))]
+pub use mock::{MockBoolEvaluator, MockClientKey, MockServerKey};
+
+#[cfg(any(feature = "interactive_mp", feature = "non_interactive_mp"))]
+pub use thread_pool::ThreadPool;
 
 pub type ClientKey = keys::ClientKey<[u8; 32], u64>;
 #[cfg(any(feature = "interactive_mp", feature = "non_interactive_mp"))]
 pub type FheBool = impl_bool_frontend::FheBool<Vec<u64>>;
+/// Plaintext simulation of `FheBool` evaluated with `MockBoolEvaluator`
+#[cfg(any(feature = "interactive_mp", feature = "non_interactive_mp"))]
+pub type MockFheBool = impl_bool_frontend::FheBool<bool>;
+/// `FheBool` recorded in a circuit instead of evaluated. Refer to
+/// `Circuit::record`.
+#[cfg(any(feature = "interactive_mp", feature = "non_interactive_mp"))]
+pub type LazyFheBool = impl_bool_frontend::FheBool<crate::circuit::Wire>;
+
+/// Fhe unsigned message of `BITS` (upto 3) bits in a single ciphertext
+#[cfg(any(feature = "interactive_mp", feature = "non_interactive_mp"))]
+pub type FheShortUint<const BITS: usize> = lut::FheShortUint<Vec<u64>, BITS>;
+/// Plaintext simulation of `FheShortUint<BITS>` evaluated with
+/// `MockBoolEvaluator`
+#[cfg(any(feature = "interactive_mp", feature = "non_interactive_mp"))]
+pub type MockFheShortUint<const BITS: usize> = lut::FheShortUint<bool, BITS>;
+/// `FheShortUint<BITS>` recorded in a circuit instead of evaluated. Refer to
+/// `Circuit::record`.
+#[cfg(any(feature = "interactive_mp", feature = "non_interactive_mp"))]
+pub type LazyFheShortUint<const BITS: usize> = lut::FheShortUint<crate::circuit::Wire, BITS>;
+
+/// Fhe unsigned 8 bit integer of 4 digits of 2 bits
+#[cfg(any(feature = "interactive_mp", feature = "non_interactive_mp"))]
+pub type FheRadixUint8 = radix::FheRadixUint8<Vec<u64>>;
+/// Plaintext simulation of `FheRadixUint8` evaluated with `MockBoolEvaluator`
+#[cfg(any(feature = "interactive_mp", feature = "non_interactive_mp"))]
+pub type MockFheRadixUint8 = radix::FheRadixUint8<bool>;
+/// `FheRadixUint8` recorded in a circuit instead of evaluated. Refer to
+/// `Circuit::record`.
+#[cfg(any(feature = "interactive_mp", feature = "non_interactive_mp"))]
+pub type LazyFheRadixUint8 = radix::FheRadixUint8<crate::circuit::Wire>;
 
 pub(crate) trait BooleanGates {
-    type Ciphertext: RowEntity;
+    type Ciphertext;
     type Key;
 
     fn and_inplace(&mut self, c0: &mut Self::Ciphertext, c1: &Self::Ciphertext, key: &Self::Key);
@@ -29,6 +71,14 @@ pub(crate) trait BooleanGates {
     fn nor_inplace(&mut self, c0: &mut Self::Ciphertext, c1: &Self::Ciphertext, key: &Self::Key);
     fn xor_inplace(&mut self, c0: &mut Self::Ciphertext, c1: &Self::Ciphertext, key: &Self::Key);
     fn xnor_inplace(&mut self, c0: &mut Self::Ciphertext, c1: &Self::Ciphertext, key: &Self::Key);
+    /// Sets `c0` to `!c0 & c1`
+    fn andny_inplace(&mut self, c0: &mut Self::Ciphertext, c1: &Self::Ciphertext, key: &Self::Key);
+    /// Sets `c0` to `c0 & !c1`
+    fn andyn_inplace(&mut self, c0: &mut Self::Ciphertext, c1: &Self::Ciphertext, key: &Self::Key);
+    /// Sets `c0` to `!c0 | c1`
+    fn orny_inplace(&mut self, c0: &mut Self::Ciphertext, c1: &Self::Ciphertext, key: &Self::Key);
+    /// Sets `c0` to `c0 | !c1`
+    fn oryn_inplace(&mut self, c0: &mut Self::Ciphertext, c1: &Self::Ciphertext, key: &Self::Key);
     fn not_inplace(&self, c: &mut Self::Ciphertext);
 
     fn and(
@@ -67,12 +117,204 @@ pub(crate) trait BooleanGates {
         c1: &Self::Ciphertext,
         key: &Self::Key,
     ) -> Self::Ciphertext;
+    fn andny(
+        &mut self,
+        c0: &Self::Ciphertext,
+        c1: &Self::Ciphertext,
+        key: &Self::Key,
+    ) -> Self::Ciphertext;
+    fn andyn(
+        &mut self,
+        c0: &Self::Ciphertext,
+        c1: &Self::Ciphertext,
+        key: &Self::Key,
+    ) -> Self::Ciphertext;
+    fn orny(
+        &mut self,
+        c0: &Self::Ciphertext,
+        c1: &Self::Ciphertext,
+        key: &Self::Key,
+    ) -> Self::Ciphertext;
+    fn oryn(
+        &mut self,
+        c0: &Self::Ciphertext,
+        c1: &Self::Ciphertext,
+        key: &Self::Key,
+    ) -> Self::Ciphertext;
     fn not(&self, c: &Self::Ciphertext) -> Self::Ciphertext;
+
+    /// Returns majority of `c0`, `c1`, and `c2`, that is true iff at least 2
+    /// of the inputs are true
+    fn majority(
+        &mut self,
+        c0: &Self::Ciphertext,
+        c1: &Self::Ciphertext,
+        c2: &Self::Ciphertext,
+        key: &Self::Key,
+    ) -> Self::Ciphertext;
+
+    /// Returns `if_true` if `selector` is true, otherwise returns `if_false`
+    ///
+    /// Requires 3 bootstraps, one for each of `selector & if_true` and
+    /// `!selector & if_false` and one for their OR. Hence MUX is no cheaper
+    /// than composing it from AND and OR gates.
+    fn mux(
+        &mut self,
+        selector: &Self::Ciphertext,
+        if_true: &Self::Ciphertext,
+        if_false: &Self::Ciphertext,
+        key: &Self::Key,
+    ) -> Self::Ciphertext;
+
+    /// Sets `c0` to `c0 ^ c1` and returns `c0 & c1`, that is sum and carry of
+    /// half adder.
+    ///
+    /// `BoolEvaluator` evaluates sum and count of true inputs with a single
+    /// multi-value bootstrap, and carry from the count with another.
+    fn half_adder(
+        &mut self,
+        c0: &mut Self::Ciphertext,
+        c1: &Self::Ciphertext,
+        key: &Self::Key,
+    ) -> Self::Ciphertext {
+        let carry = self.and(c0, c1, key);
+        self.xor_inplace(c0, c1, key);
+        carry
+    }
+
+    /// Sets `c0` to `c0 ^ c1 ^ c2` and returns `majority(c0, c1, c2)`, that is
+    /// sum and carry of full adder.
+    ///
+    /// `BoolEvaluator` evaluates `c0 ^ c1` and count of true inputs among
+    /// `c0` and `c1` with a single multi-value bootstrap, carry from the count
+    /// and `c2`, and sum as XOR of `c0 ^ c1` and `c2`. Hence full adder
+    /// requires 3 bootstraps instead of 4.
+    fn full_adder(
+        &mut self,
+        c0: &mut Self::Ciphertext,
+        c1: &Self::Ciphertext,
+        c2: &Self::Ciphertext,
+        key: &Self::Key,
+    ) -> Self::Ciphertext {
+        let carry = self.majority(c0, c1, c2, key);
+        self.xor_inplace(c0, c1, key);
+        self.xor_inplace(c0, c2, key);
+        carry
+    }
+
+    /// Returns trivial (i.e. noiseless and with mask set to 0) encryption of
+    /// `m`
+    fn trivial(&self, m: bool) -> Self::Ciphertext;
+
+    /// Returns encryptions of `out_bits` bits of `table[i]`, where `i` is the
+    /// value of little endian bits `index`, with a CMUX tree over circuit
+    /// bootstrapped bits of `index`. Bits of entries beyond the table are
+    /// false.
+    ///
+    /// Returns None if the evaluator does not support circuit bootstrapping,
+    /// in which case lookups are evaluated with gates (refer to
+    /// `arbitrary_bit_lookup`).
+    fn cmux_tree_lookup(
+        &mut self,
+        _index: &[Self::Ciphertext],
+        _table: &[Vec<bool>],
+        _out_bits: usize,
+        _key: &Self::Key,
+    ) -> Option<Vec<Self::Ciphertext>> {
+        None
+    }
+}
+
+/// Lookup tables over messages of upto 3 bits encrypted in a single
+/// ciphertext (refer to `FheShortUint`)
+pub(crate) trait LookupTables: BooleanGates {
+    type Message: Clone;
+
+    /// Returns max. message bits of lookup tables supported by the evaluator
+    fn max_message_bits(&self) -> usize;
+
+    /// Returns trivial encryption of message `m` of `bits` bits
+    fn trivial_message(&self, m: u64, bits: usize) -> Self::Message;
+
+    /// Sets `c0` to encryption of sum of messages of `c0` and `c1`. Sum must
+    /// be less than 2^{bits}.
+    fn add_message_inplace(
+        &mut self,
+        c0: &mut Self::Message,
+        c1: &Self::Message,
+        bits: usize,
+        key: &Self::Key,
+    );
+
+    /// Returns encryption of `f(m) mod 2^{bits}` where `m` is the message of
+    /// `c` of `bits` bits
+    fn apply_lut<F: Fn(u64) -> u64>(
+        &mut self,
+        c: &Self::Message,
+        bits: usize,
+        f: F,
+        key: &Self::Key,
+    ) -> Self::Message;
+
+    /// Returns encryption of bool `f(m)` where `m` is the message of `c` of
+    /// `bits` bits
+    fn apply_lut_to_bool<F: Fn(u64) -> bool>(
+        &mut self,
+        c: &Self::Message,
+        bits: usize,
+        f: F,
+        key: &Self::Key,
+    ) -> Self::Ciphertext;
+
+    /// Returns encryption of message `m` of `bits` bits if `c` is true, and of
+    /// 0 otherwise
+    fn bool_to_message(
+        &mut self,
+        c: &Self::Ciphertext,
+        bits: usize,
+        m: u64,
+        key: &Self::Key,
+    ) -> Self::Message;
+}
+
+/// Binds ciphertext type to the evaluator and the server key that evaluate
+/// boolean gates on it.
+///
+/// Frontend types (`FheBool<C>`, `FheUint<C, BITS>`, ...) are generic over
+/// ciphertext `C` and evaluate gates with `C::Evaluator` and server key
+/// `C::Key` of the current thread (accessed via `C::with_evaluator`).
+///
+/// Implemented for `Vec<u64>` (ciphertexts evaluated with `BoolEvaluator`)
+/// and `bool` (plaintexts evaluated with `MockBoolEvaluator`).
+#[cfg(any(feature = "interactive_mp", feature = "non_interactive_mp"))]
+pub(crate) trait Backend: Clone + 'static {
+    type Evaluator: LookupTables<Ciphertext = Self, Key = Self::Key> + crate::utils::WithLocal;
+    type Key;
+
+    /// Runs `func` with the evaluator and the server key of the current thread
+    fn with_evaluator<F, R>(func: F) -> R
+    where
+        F: FnOnce(&mut Self::Evaluator, &Self::Key) -> R;
+}
+
+#[cfg(any(feature = "interactive_mp", feature = "non_interactive_mp"))]
+impl Backend for Vec<u64> {
+    type Evaluator = BoolEvaluator;
+    type Key = RuntimeServerKey;
+
+    fn with_evaluator<F, R>(func: F) -> R
+    where
+        F: FnOnce(&mut BoolEvaluator, &RuntimeServerKey) -> R,
+    {
+        with_evaluator_and_server_key(func)
+    }
 }
 
 #[cfg(any(feature = "interactive_mp", feature = "non_interactive_mp"))]
-mod impl_bool_frontend {
-    use crate::MultiPartyDecryptor;
+pub(crate) mod impl_bool_frontend {
+    use crate::{Decryptor, Encryptor, MultiPartyDecryptor};
+
+    use super::MockClientKey;
 
     /// Fhe Bool ciphertext
     #[derive(Clone)]
@@ -104,94 +346,255 @@ mod impl_bool_frontend {
             self.aggregate_decryption_shares(&c.data, shares)
         }
 
+        fn try_aggregate_decryption_shares(
+            &self,
+            c: &FheBool<C>,
+            shares: &[Self::DecryptionShare],
+        ) -> Result<bool, crate::Error> {
+            self.try_aggregate_decryption_shares(&c.data, shares)
+        }
+
         fn gen_decryption_share(&self, c: &FheBool<C>) -> Self::DecryptionShare {
             self.gen_decryption_share(&c.data)
         }
+
+        fn try_gen_decryption_share(
+            &self,
+            c: &FheBool<C>,
+        ) -> Result<Self::DecryptionShare, crate::Error> {
+            self.try_gen_decryption_share(&c.data)
+        }
+    }
+
+    mod impl_serialization {
+        use crate::serialization::{
+            bits_of_largest, impl_binary_encoding, tag, Decode, DecodeError, Encode, Reader, Writer,
+        };
+
+        use super::FheBool;
+
+        impl Encode for FheBool<Vec<u64>> {
+            fn encode(&self, w: &mut Writer) {
+                w.packed(&self.data, bits_of_largest(&self.data));
+            }
+        }
+
+        impl Decode for FheBool<Vec<u64>> {
+            fn decode(r: &mut Reader) -> Result<Self, DecodeError> {
+                let data = r.packed()?;
+                if data.is_empty() {
+                    return Err(DecodeError::Invalid("empty ciphertext".to_string()));
+                }
+                Ok(FheBool { data })
+            }
+        }
+
+        impl_binary_encoding!(tag::FHE_BOOL, impl[] FheBool<Vec<u64>>);
+    }
+
+    impl Encryptor<bool, FheBool<bool>> for MockClientKey {
+        fn encrypt(&self, m: &bool) -> FheBool<bool> {
+            FheBool { data: *m }
+        }
+    }
+
+    impl Decryptor<bool, FheBool<bool>> for MockClientKey {
+        fn decrypt(&self, c: &FheBool<bool>) -> bool {
+            c.data
+        }
     }
 
     mod ops {
         use std::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Not};
 
         use crate::{
-            utils::{Global, WithLocal},
-            BooleanGates,
+            bool::{Backend, BooleanGates},
+            utils::WithLocal,
         };
 
-        use super::super::{BoolEvaluator, RuntimeServerKey};
+        use super::FheBool;
+
+        impl<C: Backend> FheBool<C> {
+            /// Returns trivial encryption of `m`.
+            ///
+            /// Trivial encryption is noiseless and does not require any secret
+            /// key. Hence it can be created by the server to use constants in
+            /// the circuit. Note that trivial encryption does not hide `m`.
+            pub fn trivial(m: bool) -> FheBool<C> {
+                C::Evaluator::with_local(|e| FheBool { data: e.trivial(m) })
+            }
+
+            /// Returns `!(self & other)`. Requires a single bootstrap.
+            pub fn nand(&self, other: &FheBool<C>) -> FheBool<C> {
+                C::with_evaluator(|e, key| FheBool {
+                    data: e.nand(self.data(), other.data(), key),
+                })
+            }
+
+            /// Returns `!(self | other)`. Requires a single bootstrap.
+            pub fn nor(&self, other: &FheBool<C>) -> FheBool<C> {
+                C::with_evaluator(|e, key| FheBool {
+                    data: e.nor(self.data(), other.data(), key),
+                })
+            }
+
+            /// Returns `!self & other`. Requires a single bootstrap.
+            pub fn andny(&self, other: &FheBool<C>) -> FheBool<C> {
+                C::with_evaluator(|e, key| FheBool {
+                    data: e.andny(self.data(), other.data(), key),
+                })
+            }
+
+            /// Returns `self & !other`. Requires a single bootstrap.
+            pub fn andyn(&self, other: &FheBool<C>) -> FheBool<C> {
+                C::with_evaluator(|e, key| FheBool {
+                    data: e.andyn(self.data(), other.data(), key),
+                })
+            }
+
+            /// Returns `!self | other`. Requires a single bootstrap.
+            pub fn orny(&self, other: &FheBool<C>) -> FheBool<C> {
+                C::with_evaluator(|e, key| FheBool {
+                    data: e.orny(self.data(), other.data(), key),
+                })
+            }
+
+            /// Returns `self | !other`. Requires a single bootstrap.
+            pub fn oryn(&self, other: &FheBool<C>) -> FheBool<C> {
+                C::with_evaluator(|e, key| FheBool {
+                    data: e.oryn(self.data(), other.data(), key),
+                })
+            }
 
-        type FheBool = super::super::FheBool;
+            /// Returns majority of `self`, `b`, and `c`, that is true iff at
+            /// least 2 of them are true. Requires 2 bootstraps.
+            pub fn majority(&self, b: &FheBool<C>, c: &FheBool<C>) -> FheBool<C> {
+                C::with_evaluator(|e, key| FheBool {
+                    data: e.majority(self.data(), b.data(), c.data(), key),
+                })
+            }
+        }
 
-        impl BitAnd for &FheBool {
-            type Output = FheBool;
+        impl<C: Backend> BitAnd for &FheBool<C> {
+            type Output = FheBool<C>;
             fn bitand(self, rhs: Self) -> Self::Output {
-                BoolEvaluator::with_local_mut(|e| {
-                    let key = RuntimeServerKey::global();
-                    FheBool {
-                        data: e.and(self.data(), rhs.data(), key),
-                    }
+                C::with_evaluator(|e, key| FheBool {
+                    data: e.and(self.data(), rhs.data(), key),
                 })
             }
         }
 
-        impl BitAndAssign for FheBool {
+        impl<C: Backend> BitAndAssign for FheBool<C> {
             fn bitand_assign(&mut self, rhs: Self) {
-                BoolEvaluator::with_local_mut_mut(&mut |e| {
-                    let key = RuntimeServerKey::global();
+                C::with_evaluator(|e, key| {
                     e.and_inplace(&mut self.data_mut(), rhs.data(), key);
                 });
             }
         }
 
-        impl BitOr for &FheBool {
-            type Output = FheBool;
+        impl<C: Backend> BitOr for &FheBool<C> {
+            type Output = FheBool<C>;
             fn bitor(self, rhs: Self) -> Self::Output {
-                BoolEvaluator::with_local_mut(|e| {
-                    let key = RuntimeServerKey::global();
-                    FheBool {
-                        data: e.or(self.data(), rhs.data(), key),
-                    }
+                C::with_evaluator(|e, key| FheBool {
+                    data: e.or(self.data(), rhs.data(), key),
                 })
             }
         }
 
-        impl BitOrAssign for FheBool {
+        impl<C: Backend> BitOrAssign for FheBool<C> {
             fn bitor_assign(&mut self, rhs: Self) {
-                BoolEvaluator::with_local_mut_mut(&mut |e| {
-                    let key = RuntimeServerKey::global();
+                C::with_evaluator(|e, key| {
                     e.or_inplace(&mut self.data_mut(), rhs.data(), key);
                 });
             }
         }
 
-        impl BitXor for &FheBool {
-            type Output = FheBool;
+        impl<C: Backend> BitXor for &FheBool<C> {
+            type Output = FheBool<C>;
             fn bitxor(self, rhs: Self) -> Self::Output {
-                BoolEvaluator::with_local_mut(|e| {
-                    let key = RuntimeServerKey::global();
-                    FheBool {
-                        data: e.xor(self.data(), rhs.data(), key),
-                    }
+                C::with_evaluator(|e, key| FheBool {
+                    data: e.xor(self.data(), rhs.data(), key),
                 })
             }
         }
 
-        impl BitXorAssign for FheBool {
+        impl<C: Backend> BitXorAssign for FheBool<C> {
             fn bitxor_assign(&mut self, rhs: Self) {
-                BoolEvaluator::with_local_mut_mut(&mut |e| {
-                    let key = RuntimeServerKey::global();
+                C::with_evaluator(|e, key| {
                     e.xor_inplace(&mut self.data_mut(), rhs.data(), key);
                 });
             }
         }
 
-        impl Not for &FheBool {
-            type Output = FheBool;
+        impl<C: Backend> Not for &FheBool<C> {
+            type Output = FheBool<C>;
             fn not(self) -> Self::Output {
-                BoolEvaluator::with_local(|e| FheBool {
+                C::Evaluator::with_local(|e| FheBool {
                     data: e.not(self.data()),
                 })
             }
         }
+
+        // Operations with plaintext bool are constant folded and do not require
+        // bootstrapping
+
+        impl<C: Backend> BitAnd<bool> for &FheBool<C> {
+            type Output = FheBool<C>;
+            fn bitand(self, rhs: bool) -> Self::Output {
+                if rhs {
+                    self.clone()
+                } else {
+                    C::Evaluator::with_local(|e| FheBool {
+                        data: e.trivial(false),
+                    })
+                }
+            }
+        }
+
+        impl<C: Backend> BitAnd<&FheBool<C>> for bool {
+            type Output = FheBool<C>;
+            fn bitand(self, rhs: &FheBool<C>) -> Self::Output {
+                rhs & self
+            }
+        }
+
+        impl<C: Backend> BitOr<bool> for &FheBool<C> {
+            type Output = FheBool<C>;
+            fn bitor(self, rhs: bool) -> Self::Output {
+                if rhs {
+                    C::Evaluator::with_local(|e| FheBool {
+                        data: e.trivial(true),
+                    })
+                } else {
+                    self.clone()
+                }
+            }
+        }
+
+        impl<C: Backend> BitOr<&FheBool<C>> for bool {
+            type Output = FheBool<C>;
+            fn bitor(self, rhs: &FheBool<C>) -> Self::Output {
+                rhs | self
+            }
+        }
+
+        impl<C: Backend> BitXor<bool> for &FheBool<C> {
+            type Output = FheBool<C>;
+            fn bitxor(self, rhs: bool) -> Self::Output {
+                if rhs {
+                    !self
+                } else {
+                    self.clone()
+                }
+            }
+        }
+
+        impl<C: Backend> BitXor<&FheBool<C>> for bool {
+            type Output = FheBool<C>;
+            fn bitxor(self, rhs: &FheBool<C>) -> Self::Output {
+                rhs ^ self
+            }
+        }
     }
 }
 
@@ -1,10 +1,18 @@
-use std::{cell::RefCell, sync::OnceLock};
+use std::{
+    cell::RefCell,
+    marker::PhantomData,
+    sync::{Arc, OnceLock},
+};
 
 use crate::{
     backend::{ModularOpsU64, ModulusPowerOf2},
+    ledger::Run,
     ntt::NttBackendU64,
     random::{DefaultSecureRng, NewWithSeed},
+    serialization::{hash_bytes, Fingerprint},
+    shortint::{ErrorFlags, ERROR_FLAGS},
     utils::{Global, WithLocal},
+    BinaryEncoding, Error,
 };
 
 use super::{evaluator::InteractiveMultiPartyCrs, keys::*, parameters::*, ClientKey};
@@ -17,9 +25,22 @@ pub(crate) type BoolEvaluator = super::evaluator::BoolEvaluator<
     ShoupServerKeyEvaluationDomain<Vec<Vec<u64>>>,
 >;
 
+/// Server key share of a user generated in round 2 of the protocol
+type ServerKeyShare = CommonReferenceSeededInteractiveMultiPartyServerKeyShare<
+    Vec<Vec<u64>>,
+    BoolParameters<u64>,
+    InteractiveMultiPartyCrs<[u8; 32]>,
+>;
+/// Server key aggregated from server key shares of all users
+type ServerKey = SeededInteractiveMultiPartyServerKey<
+    Vec<Vec<u64>>,
+    InteractiveMultiPartyCrs<[u8; 32]>,
+    BoolParameters<u64>,
+>;
+
 thread_local! {
     static BOOL_EVALUATOR: RefCell<Option<BoolEvaluator>> = RefCell::new(None);
-
+    static ACTIVE_SESSION: RefCell<Option<ActiveSession>> = const { RefCell::new(None) };
 }
 static BOOL_SERVER_KEY: OnceLock<ShoupServerKeyEvaluationDomain<Vec<Vec<u64>>>> = OnceLock::new();
 
@@ -29,31 +50,344 @@ pub enum ParameterSelector {
     InteractiveLTE2Party,
     InteractiveLTE4Party,
     InteractiveLTE8Party,
+    /// At most 4 parties. Supports lookup tables over 2 bit messages (i.e.
+    /// `FheShortUint<2>`), whereas parameter sets above support lookup tables
+    /// over 1 bit messages only.
+    InteractiveLTE4PartyLut2Bits,
+    /// At most 4 parties. Supports lookup tables over 3 bit messages.
+    InteractiveLTE4PartyLut3Bits,
+    /// At most 2 parties. Server key supports circuit bootstrapping, hence
+    /// lookups (e.g. `FheUint8::lookup`) are evaluated with CMUX trees instead
+    /// of muxes.
+    InteractiveLTE2PartyCbs,
+}
+
+impl ParameterSelector {
+    fn parameters(&self) -> BoolParameters<u64> {
+        match self {
+            ParameterSelector::InteractiveLTE2Party => I_2P_LB_SR,
+            ParameterSelector::InteractiveLTE4Party => I_4P,
+            ParameterSelector::InteractiveLTE8Party => I_8P,
+            ParameterSelector::InteractiveLTE4PartyLut2Bits => I_4P_LUT2,
+            ParameterSelector::InteractiveLTE4PartyLut3Bits => I_4P_LUT3,
+            ParameterSelector::InteractiveLTE2PartyCbs => I_2P_CBS,
+        }
+    }
 }
 
 /// Select Interactive multi-party parameter variant
 pub fn set_parameter_set(select: ParameterSelector) {
-    match select {
-        ParameterSelector::InteractiveLTE2Party => {
-            BOOL_EVALUATOR.with_borrow_mut(|v| *v = Some(BoolEvaluator::new(I_2P_LB_SR)));
+    BOOL_EVALUATOR.with_borrow_mut(|v| *v = Some(BoolEvaluator::new(select.parameters())));
+}
+
+/// Same as `set_parameter_set`, but returns `Error::ParametersAlreadySet`
+/// instead of replacing different parameters already set on the current
+/// thread
+pub fn try_set_parameter_set(select: ParameterSelector) -> Result<(), Error> {
+    let parameters = select.parameters();
+    BOOL_EVALUATOR.with_borrow_mut(|v| match v {
+        Some(e) if e.parameters() != &parameters => Err(Error::ParametersAlreadySet),
+        Some(_) => Ok(()),
+        None => {
+            *v = Some(BoolEvaluator::new(parameters));
+            Ok(())
         }
-        ParameterSelector::InteractiveLTE4Party => {
-            BOOL_EVALUATOR.with_borrow_mut(|v| *v = Some(BoolEvaluator::new(I_4P)));
+    })
+}
+
+/// Set application specific interactive multi-party common reference string
+pub fn set_common_reference_seed(seed: [u8; 32]) {
+    try_set_common_reference_seed(seed).unwrap_or_else(|e| panic!("{e}"))
+}
+
+/// Same as `set_common_reference_seed`, but returns
+/// `Error::CommonReferenceSeedAlreadySet` instead of panicking if the seed is
+/// already set
+pub fn try_set_common_reference_seed(seed: [u8; 32]) -> Result<(), Error> {
+    MULTI_PARTY_CRS
+        .set(InteractiveMultiPartyCrs { seed })
+        .map_err(|_| Error::CommonReferenceSeedAlreadySet)
+}
+
+/// Interactive multi-party session of a single application
+///
+/// Session owns parameters, common reference string, and the server key of
+/// the application. Unlike `set_parameter_set`, `set_common_reference_seed`,
+/// and `set_server_key` which set them once for the whole process, any no. of
+/// sessions can coexist in a single process and a session is torn down once
+/// it, and all its clones, are dropped.
+///
+/// Functions of the protocol (`gen_client_key`, `collective_pk_share`, ...)
+/// and all operations on ciphertexts use the session entered on the current
+/// thread with `Session::enter`. If no session is entered, they fall back to
+/// the process wide parameters, common reference string, and server key.
+///
+/// Session is cheap to clone and can be shared across threads. Each thread
+/// must enter the session before using it.
+#[derive(Clone)]
+pub struct Session {
+    inner: Arc<SessionInner>,
+}
+
+struct SessionInner {
+    parameters: BoolParameters<u64>,
+    crs: InteractiveMultiPartyCrs<[u8; 32]>,
+    id: u64,
+    server_key: OnceLock<RuntimeServerKey>,
+    error_flags: ErrorFlags,
+}
+
+/// Session entered on a thread along with the thread's evaluator
+struct ActiveSession {
+    session: Arc<SessionInner>,
+    evaluator: BoolEvaluator,
+}
+
+/// Keeps the session entered on the current thread until dropped. Returned by
+/// `Session::enter`.
+///
+/// On drop, the previously entered session (if any) is entered again. Hence
+/// nested guards must be dropped in the reverse order of creation.
+#[must_use = "session is exited as soon as the guard is dropped"]
+pub struct SessionGuard {
+    previous: Option<ActiveSession>,
+    // session is entered on the thread that created the guard
+    _not_send: PhantomData<*const ()>,
+}
+
+impl Session {
+    /// Creates new session with parameter variant `select` and application
+    /// specific common reference seed `seed`
+    pub fn new(select: ParameterSelector, seed: [u8; 32]) -> Session {
+        Session::with_parameters(select.parameters(), seed, 0)
+    }
+
+    /// Creates new session like `Session::new` with application specific
+    /// session id `id`. All parties of the session must use the same id.
+    ///
+    /// Values encoded with `BinaryEncoding` in the session can only be decoded
+    /// in a session with the same id (refer to `fingerprint`). Hence the id
+    /// separates protocol instances that share parameters and common
+    /// reference seed. Session created with `Session::new` has id 0.
+    pub fn with_id(select: ParameterSelector, seed: [u8; 32], id: u64) -> Session {
+        Session::with_parameters(select.parameters(), seed, id)
+    }
+
+    pub(crate) fn with_parameters(
+        parameters: BoolParameters<u64>,
+        seed: [u8; 32],
+        id: u64,
+    ) -> Session {
+        Session {
+            inner: Arc::new(SessionInner {
+                parameters,
+                crs: InteractiveMultiPartyCrs { seed },
+                id,
+                server_key: OnceLock::new(),
+                error_flags: ErrorFlags::default(),
+            }),
         }
-        ParameterSelector::InteractiveLTE8Party => {
-            BOOL_EVALUATOR.with_borrow_mut(|v| *v = Some(BoolEvaluator::new(I_8P)));
+    }
+
+    pub fn id(&self) -> u64 {
+        self.inner.id
+    }
+
+    /// Returns fingerprint of parameters, common reference seed, and id of
+    /// the session
+    pub fn fingerprint(&self) -> Fingerprint {
+        self.inner.fingerprint()
+    }
+
+    /// Enters the session on the current thread until the returned guard is
+    /// dropped
+    pub fn enter(&self) -> SessionGuard {
+        self.enter_with_evaluator(BoolEvaluator::new(self.inner.parameters.clone()))
+    }
+
+    /// Enters the session with `evaluator`, which must have been created for
+    /// the session. Evaluator can be taken back with `SessionGuard::exit`.
+    pub(crate) fn enter_with_evaluator(&self, evaluator: BoolEvaluator) -> SessionGuard {
+        let active = ActiveSession {
+            session: self.inner.clone(),
+            evaluator,
+        };
+        SessionGuard {
+            previous: ACTIVE_SESSION.with_borrow_mut(|s| s.replace(active)),
+            _not_send: PhantomData,
+        }
+    }
+
+    /// Sets the server key of the session
+    pub fn set_server_key(
+        &self,
+        server_key: &SeededInteractiveMultiPartyServerKey<
+            Vec<Vec<u64>>,
+            InteractiveMultiPartyCrs<[u8; 32]>,
+            BoolParameters<u64>,
+        >,
+    ) {
+        self.set_runtime_server_key(ShoupServerKeyEvaluationDomain::from(
+            ServerKeyEvaluationDomain::<_, _, DefaultSecureRng, NttBackendU64>::from(server_key),
+        ))
+        .unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Same as `Session::set_server_key`, but returns `Error` instead of
+    /// panicking if the server key of the session is already set, or
+    /// `Error::ParameterMismatch` if `server_key` was generated with
+    /// different parameters or common reference seed than the session's
+    pub fn try_set_server_key(&self, server_key: &ServerKey) -> Result<(), Error> {
+        if server_key.parameters() != &self.inner.parameters
+            || server_key.cr_seed() != &self.inner.crs
+        {
+            return Err(Error::ParameterMismatch);
+        }
+        if self.inner.server_key.get().is_some() {
+            return Err(Error::ServerKeyAlreadySet);
         }
+        self.set_runtime_server_key(ShoupServerKeyEvaluationDomain::from(
+            ServerKeyEvaluationDomain::<_, _, DefaultSecureRng, NttBackendU64>::from(server_key),
+        ))
+    }
+
+    /// Sets the server key of the session, or returns
+    /// `Error::ServerKeyAlreadySet` if it is already set
+    pub(crate) fn set_runtime_server_key(&self, server_key: RuntimeServerKey) -> Result<(), Error> {
+        self.inner
+            .server_key
+            .set(server_key)
+            .map_err(|_| Error::ServerKeyAlreadySet)
     }
 }
 
-/// Set application specific interactive multi-party common reference string
-pub fn set_common_reference_seed(seed: [u8; 32]) {
-    assert!(
-        MULTI_PARTY_CRS
-            .set(InteractiveMultiPartyCrs { seed: seed })
-            .is_ok(),
-        "Attempted to set MP SEED twice."
-    )
+impl SessionInner {
+    fn fingerprint(&self) -> Fingerprint {
+        Fingerprint::new(&self.parameters, &self.crs.seed, self.id)
+    }
+}
+
+impl SessionGuard {
+    /// Exits the session and returns evaluator of the session on the current
+    /// thread
+    pub(crate) fn exit(mut self) -> BoolEvaluator {
+        let previous = self.previous.take();
+        let active = ACTIVE_SESSION.with_borrow_mut(|s| std::mem::replace(s, previous));
+        // previous session is already restored
+        std::mem::forget(self);
+        active.expect("Session not entered").evaluator
+    }
+}
+
+impl Drop for SessionGuard {
+    fn drop(&mut self) {
+        let previous = self.previous.take();
+        ACTIVE_SESSION.with_borrow_mut(|s| *s = previous);
+    }
+}
+
+/// Returns common reference string of the active session, otherwise the
+/// process wide common reference string
+fn multi_party_crs() -> InteractiveMultiPartyCrs<[u8; 32]> {
+    ACTIVE_SESSION.with_borrow(|s| match s {
+        Some(active) => active.session.crs.clone(),
+        None => InteractiveMultiPartyCrs::global().clone(),
+    })
+}
+
+/// Returns fingerprint of the active session, otherwise fingerprint of the
+/// process wide parameters and common reference string (with session id 0)
+///
+/// Values encoded with `BinaryEncoding` carry the fingerprint they were
+/// encoded with and can only be decoded where `fingerprint` returns the same.
+///
+/// Panics if no session is entered and parameters or the common reference seed
+/// are not set
+pub fn fingerprint() -> Fingerprint {
+    try_fingerprint().unwrap_or_else(|e| panic!("{e}"))
+}
+
+/// Same as `fingerprint`, but returns `Error::ParametersNotSet` or
+/// `Error::CommonReferenceSeedNotSet` instead of panicking if no session is
+/// entered and parameters or the process wide common reference string are not
+/// set
+pub(crate) fn try_fingerprint() -> Result<Fingerprint, Error> {
+    let active =
+        ACTIVE_SESSION.with_borrow(|s| s.as_ref().map(|active| active.session.fingerprint()));
+    match active {
+        Some(fingerprint) => Ok(fingerprint),
+        None => try_with_evaluator(|e| {
+            Ok(Fingerprint::new(
+                e.parameters(),
+                &try_multi_party_crs()?.seed,
+                0,
+            ))
+        })?,
+    }
+}
+
+/// Same as `multi_party_crs`, but returns `Error::CommonReferenceSeedNotSet`
+/// instead of panicking if neither a session is entered nor the process wide
+/// common reference string is set
+fn try_multi_party_crs() -> Result<InteractiveMultiPartyCrs<[u8; 32]>, Error> {
+    ACTIVE_SESSION.with_borrow(|s| match s {
+        Some(active) => Ok(active.session.crs.clone()),
+        None => MULTI_PARTY_CRS
+            .get()
+            .cloned()
+            .ok_or(Error::CommonReferenceSeedNotSet),
+    })
+}
+
+/// Same as `BoolEvaluator::with_local`, but returns `Error::ParametersNotSet`
+/// instead of panicking if neither a session is entered nor parameters are set
+/// on the current thread
+pub(crate) fn try_with_evaluator<F, R>(func: F) -> Result<R, Error>
+where
+    F: FnOnce(&BoolEvaluator) -> R,
+{
+    ACTIVE_SESSION.with_borrow(|s| match s {
+        Some(active) => Ok(func(&active.evaluator)),
+        None => BOOL_EVALUATOR.with_borrow(|e| e.as_ref().map(func).ok_or(Error::ParametersNotSet)),
+    })
+}
+
+/// Runs `func` with error flags of the active session, otherwise with error
+/// flags of the current thread
+pub(crate) fn with_error_flags<F, R>(func: F) -> R
+where
+    F: FnOnce(&ErrorFlags) -> R,
+{
+    // session is not borrowed while `func` runs, hence `func` can evaluate
+    // gates
+    match ACTIVE_SESSION.with_borrow(|s| s.as_ref().map(|active| active.session.clone())) {
+        Some(session) => func(&session.error_flags),
+        None => ERROR_FLAGS.with(func),
+    }
+}
+
+/// Runs `func` with the evaluator and the server key of the active session,
+/// otherwise with the thread local evaluator and the process wide server key
+pub(crate) fn with_evaluator_and_server_key<F, R>(func: F) -> R
+where
+    F: FnOnce(&mut BoolEvaluator, &RuntimeServerKey) -> R,
+{
+    ACTIVE_SESSION.with_borrow_mut(|s| match s {
+        Some(active) => {
+            let server_key = active
+                .session
+                .server_key
+                .get()
+                .expect("Server key of the session not set!");
+            func(&mut active.evaluator, server_key)
+        }
+        None => BOOL_EVALUATOR.with_borrow_mut(|e| {
+            func(
+                e.as_mut().expect("Parameters not set"),
+                RuntimeServerKey::global(),
+            )
+        }),
+    })
 }
 
 /// Generate client key for interactive multi-party protocol
@@ -66,14 +400,18 @@ pub fn gen_client_key() -> ClientKey {
 pub fn collective_pk_share(
     ck: &ClientKey,
 ) -> CommonReferenceSeededCollectivePublicKeyShare<Vec<u64>, [u8; 32], BoolParameters<u64>> {
+    let crs = multi_party_crs();
     BoolEvaluator::with_local(|e| {
-        let pk_share = e.multi_party_public_key_share(InteractiveMultiPartyCrs::global(), ck);
+        let pk_share = e.multi_party_public_key_share(&crs, ck);
         pk_share
     })
 }
 
 /// Generate clients share for collective server key, i.e. round 2, of the
 /// 2 round protocol
+///
+/// Panics if `ck` already generated a different share with the same common
+/// reference seed (refer to `ClientKey::allow_conflicting_runs`)
 pub fn collective_server_key_share<R, ModOp>(
     ck: &ClientKey,
     user_id: usize,
@@ -84,16 +422,40 @@ pub fn collective_server_key_share<R, ModOp>(
     BoolParameters<u64>,
     InteractiveMultiPartyCrs<[u8; 32]>,
 > {
-    BoolEvaluator::with_local_mut(|e| {
-        let server_key_share = e.gen_interactive_multi_party_server_key_share(
+    try_collective_server_key_share(ck, user_id, total_users, pk).unwrap_or_else(|e| panic!("{e}"))
+}
+
+/// Same as `collective_server_key_share`, but returns `Error` instead of
+/// panicking if parameters or common reference seed are not set, `ck` was
+/// generated with different parameters, `user_id` is not less than
+/// `total_users`, or `ck` already participated in a different run with the
+/// same common reference seed
+pub fn try_collective_server_key_share<R, ModOp>(
+    ck: &ClientKey,
+    user_id: usize,
+    total_users: usize,
+    pk: &PublicKey<Vec<Vec<u64>>, R, ModOp>,
+) -> Result<ServerKeyShare, Error> {
+    let crs = try_multi_party_crs()?;
+    if !try_with_evaluator(|e| e.parameters() == ck.parameters())? {
+        return Err(Error::ParameterMismatch);
+    }
+    if user_id >= total_users {
+        return Err(Error::InvalidUserId {
             user_id,
             total_users,
-            InteractiveMultiPartyCrs::global(),
-            pk.key(),
-            ck,
-        );
-        server_key_share
-    })
+        });
+    }
+    let server_key_share = BoolEvaluator::with_local_mut(|e| {
+        e.gen_interactive_multi_party_server_key_share(user_id, total_users, &crs, pk.key(), ck)
+    });
+    // share is handed out only after its run is recorded
+    ck.runs().record(Run {
+        cr_seed: crs.seed,
+        user_id,
+        key_share_hash: hash_bytes(server_key_share.to_bytes()?),
+    })?;
+    Ok(server_key_share)
 }
 
 /// Aggregate public key shares from all parties.
@@ -122,7 +484,16 @@ pub fn aggregate_server_key_shares(
     InteractiveMultiPartyCrs<[u8; 32]>,
     BoolParameters<u64>,
 > {
-    BoolEvaluator::with_local(|e| e.aggregate_interactive_multi_party_server_key_shares(shares))
+    try_aggregate_server_key_shares(shares).unwrap_or_else(|e| panic!("{e}"))
+}
+
+/// Same as `aggregate_server_key_shares`, but returns `Error` instead of
+/// panicking if parameters or common reference seed are not set, or if
+/// `shares` are not exactly one share per user generated with parameters and
+/// common reference seed of the protocol instance
+pub fn try_aggregate_server_key_shares(shares: &[ServerKeyShare]) -> Result<ServerKey, Error> {
+    let crs = try_multi_party_crs()?;
+    try_with_evaluator(|e| e.aggregate_interactive_multi_party_server_key_shares(&crs, shares))?
 }
 
 impl
@@ -134,14 +505,20 @@ impl
 {
     /// Sets the server key as a global reference for circuit evaluation
     pub fn set_server_key(&self) {
-        assert!(
-            BOOL_SERVER_KEY
-                .set(ShoupServerKeyEvaluationDomain::from(
-                    ServerKeyEvaluationDomain::<_, _, DefaultSecureRng, NttBackendU64>::from(self),
-                ))
-                .is_ok(),
-            "Attempted to set server key twice."
-        );
+        self.try_set_server_key().unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Same as `set_server_key`, but returns `Error::ServerKeyAlreadySet`
+    /// instead of panicking if the server key is already set
+    pub fn try_set_server_key(&self) -> Result<(), Error> {
+        if BOOL_SERVER_KEY.get().is_some() {
+            return Err(Error::ServerKeyAlreadySet);
+        }
+        BOOL_SERVER_KEY
+            .set(ShoupServerKeyEvaluationDomain::from(
+                ServerKeyEvaluationDomain::<_, _, DefaultSecureRng, NttBackendU64>::from(self),
+            ))
+            .map_err(|_| Error::ServerKeyAlreadySet)
     }
 }
 
@@ -160,21 +537,34 @@ impl WithLocal for BoolEvaluator {
     where
         F: Fn(&Self) -> R,
     {
-        BOOL_EVALUATOR.with_borrow(|s| func(s.as_ref().expect("Parameters not set")))
+        ACTIVE_SESSION.with_borrow(|s| match s {
+            Some(active) => func(&active.evaluator),
+            None => BOOL_EVALUATOR.with_borrow(|s| func(s.as_ref().expect("Parameters not set"))),
+        })
     }
 
     fn with_local_mut<F, R>(func: F) -> R
     where
         F: Fn(&mut Self) -> R,
     {
-        BOOL_EVALUATOR.with_borrow_mut(|s| func(s.as_mut().expect("Parameters not set")))
+        ACTIVE_SESSION.with_borrow_mut(|s| match s {
+            Some(active) => func(&mut active.evaluator),
+            None => {
+                BOOL_EVALUATOR.with_borrow_mut(|s| func(s.as_mut().expect("Parameters not set")))
+            }
+        })
     }
 
     fn with_local_mut_mut<F, R>(func: &mut F) -> R
     where
         F: FnMut(&mut Self) -> R,
     {
-        BOOL_EVALUATOR.with_borrow_mut(|s| func(s.as_mut().expect("Parameters not set")))
+        ACTIVE_SESSION.with_borrow_mut(|s| match s {
+            Some(active) => func(&mut active.evaluator),
+            None => {
+                BOOL_EVALUATOR.with_borrow_mut(|s| func(s.as_mut().expect("Parameters not set")))
+            }
+        })
     }
 }
 
@@ -189,7 +579,8 @@ mod impl_enc_dec {
     use crate::{
         bool::evaluator::BoolEncoding,
         multi_party::{
-            multi_party_aggregate_decryption_shares_and_decrypt, multi_party_decryption_share,
+            check_decryption_shares, multi_party_aggregate_decryption_shares_and_decrypt,
+            multi_party_decryption_share, DecryptionShare,
         },
         pbs::{sample_extract, PbsInfo},
         rgsw::public_key_encrypt_rlwe,
@@ -269,19 +660,41 @@ mod impl_enc_dec {
         <Mat as Matrix>::R:
             TryConvertFrom1<[K::Element], CiphertextModulus<<Mat as Matrix>::MatElement>>,
     {
-        type DecryptionShare = <Mat as Matrix>::MatElement;
-
+        type DecryptionShare = DecryptionShare;
+
+        /// Returns the share previously generated for `c`, if any, since
+        /// generating two different shares of the same ciphertext leaks the
+        /// secret
+        ///
+        /// Panics if the key participated in more than one run of the protocol
+        /// with the active common reference seed, or the share cannot be
+        /// recorded
         fn gen_decryption_share(&self, c: &<Mat as Matrix>::R) -> Self::DecryptionShare {
-            BoolEvaluator::with_local(|e| {
-                DefaultSecureRng::with_local_mut(|rng| {
-                    multi_party_decryption_share(
-                        c,
-                        self.sk_rlwe().as_slice(),
-                        e.pbs_info().modop_rlweq(),
-                        rng,
-                    )
+            self.try_gen_decryption_share(c)
+                .unwrap_or_else(|e| panic!("{e}"))
+        }
+
+        fn try_gen_decryption_share(
+            &self,
+            c: &<Mat as Matrix>::R,
+        ) -> Result<Self::DecryptionShare, Error> {
+            if let Ok(crs) = try_multi_party_crs() {
+                self.runs().check(&crs.seed)?;
+            }
+            self.decryption_shares()
+                .get_or_insert_with(c, || {
+                    BoolEvaluator::with_local(|e| {
+                        DefaultSecureRng::with_local_mut(|rng| {
+                            multi_party_decryption_share(
+                                c,
+                                self.sk_rlwe().as_slice(),
+                                e.pbs_info().modop_rlweq(),
+                                rng,
+                            )
+                        })
+                    })
                 })
-            })
+                .map(DecryptionShare)
         }
 
         fn aggregate_decryption_shares(
@@ -289,16 +702,34 @@ mod impl_enc_dec {
             c: &<Mat as Matrix>::R,
             shares: &[Self::DecryptionShare],
         ) -> bool {
+            let shares = shares.iter().map(|s| s.0).collect_vec();
             BoolEvaluator::with_local(|e| {
                 let noisy_m = multi_party_aggregate_decryption_shares_and_decrypt(
                     c,
-                    shares,
+                    &shares,
                     e.pbs_info().modop_rlweq(),
                 );
 
                 e.pbs_info().rlwe_q().decode(noisy_m)
             })
         }
+
+        fn try_aggregate_decryption_shares(
+            &self,
+            c: &<Mat as Matrix>::R,
+            shares: &[Self::DecryptionShare],
+        ) -> Result<bool, Error> {
+            let values = shares.iter().map(|s| s.0).collect_vec();
+            try_with_evaluator(|e| {
+                check_decryption_shares(
+                    c,
+                    &values,
+                    e.parameters().rlwe_n().0,
+                    e.pbs_info().rlwe_q(),
+                )
+            })??;
+            Ok(self.aggregate_decryption_shares(c, shares))
+        }
     }
 }
 
@@ -329,60 +760,919 @@ mod tests {
 
         let parameters = BoolEvaluator::with_local(|e| e.parameters().clone());
 
-        let batch_size = parameters.rlwe_n().0 * 3 + 123;
-        let m = (0..batch_size)
-            .map(|_| thread_rng().gen::<u8>())
-            .collect_vec();
+        let batch_size = parameters.rlwe_n().0 * 3 + 123;
+        let m = (0..batch_size)
+            .map(|_| thread_rng().gen::<u8>())
+            .collect_vec();
+
+        let seeded_ct = pk.encrypt(m.as_slice());
+
+        let m_back = (0..batch_size)
+            .map(|i| {
+                let ct = seeded_ct.extract_at(i);
+                cks[0].aggregate_decryption_shares(
+                    &ct,
+                    &cks.iter()
+                        .map(|k| k.gen_decryption_share(&ct))
+                        .collect_vec(),
+                )
+            })
+            .collect_vec();
+
+        assert_eq!(m, m_back);
+
+        // batch of u16s
+        let m = (0..123).map(|_| thread_rng().gen::<u16>()).collect_vec();
+        let seeded_ct = pk.encrypt(m.as_slice());
+        let m_back = seeded_ct
+            .extract_all()
+            .iter()
+            .map(|ct| {
+                cks[0].aggregate_decryption_shares(
+                    ct,
+                    &cks.iter().map(|k| k.gen_decryption_share(ct)).collect_vec(),
+                )
+            })
+            .collect_vec();
+
+        assert_eq!(m, m_back);
+    }
+
+    #[test]
+    fn sessions_are_independent() {
+        use crate::FheUint8;
+
+        /// Runs the 2 round protocol in `session` and returns decryption of
+        /// `m0 + m1` evaluated in the session
+        fn run(session: &Session, m0: u8, m1: u8) -> u8 {
+            let _guard = session.enter();
+
+            let parties = 2;
+            let cks = (0..parties).map(|_| gen_client_key()).collect_vec();
+            let pk_shares = cks.iter().map(collective_pk_share).collect_vec();
+            let pk = aggregate_public_key_shares(&pk_shares);
+            let server_key_shares = cks
+                .iter()
+                .enumerate()
+                .map(|(user_id, k)| collective_server_key_share(k, user_id, parties, &pk))
+                .collect_vec();
+            session.set_server_key(&aggregate_server_key_shares(&server_key_shares));
+
+            let c0: FheUint8 = pk.encrypt(&m0);
+            let c1: FheUint8 = pk.encrypt(&m1);
+            let c_out = &c0 + &c1;
+            cks[0].aggregate_decryption_shares(
+                &c_out,
+                &cks.iter()
+                    .map(|k| k.gen_decryption_share(&c_out))
+                    .collect_vec(),
+            )
+        }
+
+        let mut seed = [0u8; 32];
+        thread_rng().fill_bytes(&mut seed);
+        let session_a = Session::new(ParameterSelector::InteractiveLTE2Party, seed);
+        thread_rng().fill_bytes(&mut seed);
+        let session_b = Session::new(ParameterSelector::InteractiveLTE2Party, seed);
+
+        let (m0, m1) = (thread_rng().gen::<u8>(), thread_rng().gen::<u8>());
+        let handle = {
+            let session_b = session_b.clone();
+            std::thread::spawn(move || run(&session_b, m1, m0))
+        };
+        assert_eq!(run(&session_a, m0, m1), m0.wrapping_add(m1));
+        assert_eq!(handle.join().unwrap(), m1.wrapping_add(m0));
+
+        // sessions can be entered again, from any thread, and nested
+        {
+            let _guard = session_a.enter();
+            {
+                let _nested = session_b.enter();
+                let c = FheUint8::trivial(m0);
+                let _ = &c + &c;
+            }
+            // session a is entered again once the nested guard is dropped
+            let c = FheUint8::trivial(m0);
+            let _ = &c + &c;
+        }
+        drop(session_a);
+        drop(session_b);
+    }
+
+    #[test]
+    fn serialized_keys_and_ciphertexts_round_trip() {
+        use crate::{BinaryEncoding, FheBool, FheUint8};
+
+        /// Returns `value` decoded from its bytes after checking that the
+        /// decoded value encodes to the same bytes
+        fn round_trip<T: BinaryEncoding>(value: &T) -> T {
+            let bytes = value.to_bytes().unwrap();
+            let decoded = T::from_bytes(&bytes).unwrap();
+            assert_eq!(decoded.to_bytes().unwrap(), bytes);
+            decoded
+        }
+
+        let mut seed = [0u8; 32];
+        thread_rng().fill_bytes(&mut seed);
+        let session = Session::new(ParameterSelector::InteractiveLTE2Party, seed);
+        let _guard = session.enter();
+
+        let parties = 2;
+        let cks = (0..parties)
+            .map(|_| round_trip(&gen_client_key()))
+            .collect_vec();
+        let pk_shares = cks
+            .iter()
+            .map(|k| round_trip(&collective_pk_share(k)))
+            .collect_vec();
+        let pk = round_trip(&aggregate_public_key_shares(&pk_shares));
+        let server_key_shares = cks
+            .iter()
+            .enumerate()
+            .map(|(user_id, k)| round_trip(&collective_server_key_share(k, user_id, parties, &pk)))
+            .collect_vec();
+        let server_key = round_trip(&aggregate_server_key_shares(&server_key_shares));
+        session.set_server_key(&server_key);
+
+        let (m0, m1) = (thread_rng().gen::<u8>(), thread_rng().gen::<u8>());
+        let c0: FheUint8 = round_trip(&pk.encrypt(&m0));
+        let c1: FheUint8 = round_trip(&pk.encrypt(&m1));
+        let c_out = round_trip(&(&c0 + &c1));
+        let m_out = cks[0].aggregate_decryption_shares(
+            &c_out,
+            &cks.iter()
+                .map(|k| round_trip(&k.gen_decryption_share(&c_out)))
+                .collect_vec(),
+        );
+        assert_eq!(m_out, m0.wrapping_add(m1));
+
+        let c_bool: FheBool = round_trip(&c0.lt(&c1));
+        let m_bool = cks[0].aggregate_decryption_shares(
+            &c_bool,
+            &cks.iter()
+                .map(|k| round_trip(&k.gen_decryption_share(&c_bool)))
+                .collect_vec(),
+        );
+        assert_eq!(m_bool, m0 < m1);
+
+        // bytes of one type are rejected by another
+        assert!(FheBool::from_bytes(&c0.to_bytes().unwrap()).is_err());
+        assert!(FheUint8::from_bytes(&server_key.to_bytes().unwrap()).is_err());
+
+        // serde uses the same encoding
+        #[cfg(feature = "serde")]
+        {
+            let json = serde_json::to_string(&c_out).unwrap();
+            let c_back: FheUint8 = serde_json::from_str(&json).unwrap();
+            assert_eq!(c_back.to_bytes(), c_out.to_bytes());
+
+            let json = serde_json::to_string(&server_key_shares[0]).unwrap();
+            let share_back: CommonReferenceSeededInteractiveMultiPartyServerKeyShare<
+                Vec<Vec<u64>>,
+                BoolParameters<u64>,
+                InteractiveMultiPartyCrs<[u8; 32]>,
+            > = serde_json::from_str(&json).unwrap();
+            assert_eq!(share_back.to_bytes(), server_key_shares[0].to_bytes());
+        }
+    }
+
+    #[test]
+    fn error_flags_are_bound_to_the_session() {
+        use crate::{mock_div_zero_error_flag, Decryptor, MockClientKey, MockFheUint8};
+
+        let mut seed = [0u8; 32];
+        thread_rng().fill_bytes(&mut seed);
+        let session = Session::new(ParameterSelector::InteractiveLTE2Party, seed);
+        let other_session = Session::new(ParameterSelector::InteractiveLTE2Party, seed);
+
+        {
+            let _guard = session.enter();
+            let _ = MockFheUint8::trivial(7u8).div_rem(&MockFheUint8::trivial(0u8));
+            assert!(MockClientKey.decrypt(&mock_div_zero_error_flag().unwrap()));
+        }
+
+        // flag is shared by all threads of the session
+        std::thread::scope(|s| {
+            s.spawn(|| {
+                let _guard = session.enter();
+                assert!(MockClientKey.decrypt(&mock_div_zero_error_flag().unwrap()));
+            });
+        });
+
+        // but not set in other sessions or outside sessions
+        {
+            let _guard = other_session.enter();
+            assert!(mock_div_zero_error_flag().is_none());
+        }
+        assert!(mock_div_zero_error_flag().is_none());
+    }
+
+    #[test]
+    fn serialized_values_are_bound_to_the_session() {
+        use crate::{BinaryEncoding, DecodeError, FheUint8};
+
+        let mut seed = [0u8; 32];
+        thread_rng().fill_bytes(&mut seed);
+        let session = Session::new(ParameterSelector::InteractiveLTE2Party, seed);
+        let bytes = {
+            let _guard = session.enter();
+            FheUint8::trivial(7).to_bytes().unwrap()
+        };
+
+        let other_sessions = [
+            Session::new(ParameterSelector::InteractiveLTE4Party, seed),
+            Session::new(ParameterSelector::InteractiveLTE2Party, [0; 32]),
+            Session::with_id(ParameterSelector::InteractiveLTE2Party, seed, 1),
+        ];
+        for other in other_sessions {
+            let _guard = other.enter();
+            assert_eq!(
+                FheUint8::from_bytes(&bytes).err(),
+                Some(DecodeError::FingerprintMismatch {
+                    expected: other.fingerprint(),
+                    found: session.fingerprint()
+                })
+            );
+        }
+
+        let _guard = Session::with_id(ParameterSelector::InteractiveLTE2Party, seed, 0).enter();
+        assert!(FheUint8::from_bytes(&bytes).is_ok());
+    }
+
+    #[test]
+    #[should_panic(expected = "generated with different parameters or common reference seed")]
+    fn aggregation_rejects_shares_of_other_sessions() {
+        let mut seed = [0u8; 32];
+        thread_rng().fill_bytes(&mut seed);
+        let session = Session::new(ParameterSelector::InteractiveLTE2Party, seed);
+        let server_key_shares = {
+            let _guard = session.enter();
+            let cks = (0..2).map(|_| gen_client_key()).collect_vec();
+            let pk_shares = cks.iter().map(collective_pk_share).collect_vec();
+            let pk = aggregate_public_key_shares(&pk_shares);
+            cks.iter()
+                .enumerate()
+                .map(|(user_id, k)| collective_server_key_share(k, user_id, 2, &pk))
+                .collect_vec()
+        };
+
+        thread_rng().fill_bytes(&mut seed);
+        let _guard = Session::new(ParameterSelector::InteractiveLTE2Party, seed).enter();
+        aggregate_server_key_shares(&server_key_shares);
+    }
+
+    #[test]
+    fn table_lookup_with_circuit_bootstrapping() {
+        use crate::{stats, FheUint8};
+
+        let mut seed = [0u8; 32];
+        thread_rng().fill_bytes(&mut seed);
+        let session = Session::new(ParameterSelector::InteractiveLTE2PartyCbs, seed);
+        let _guard = session.enter();
+
+        let parties = 2;
+        let cks = (0..parties).map(|_| gen_client_key()).collect_vec();
+        let pk_shares = cks.iter().map(collective_pk_share).collect_vec();
+        let pk = aggregate_public_key_shares(&pk_shares);
+        let server_key_shares = cks
+            .iter()
+            .enumerate()
+            .map(|(user_id, k)| collective_server_key_share(k, user_id, parties, &pk))
+            .collect_vec();
+        session.set_server_key(&aggregate_server_key_shares(&server_key_shares));
+
+        let mut table = [0u8; 256];
+        thread_rng().fill_bytes(&mut table);
+        let m = thread_rng().gen::<u8>();
+        let index: FheUint8 = pk.encrypt(&m);
+
+        // Aggregated server key contains the scheme switching key, hence lookup
+        // requires a circuit bootstrap per index bit and no gates
+        stats::reset();
+        let out = index.lookup(&table);
+        let (rlrg_d_a, rlrg_d_b) = I_2P_CBS.rlwe_rgsw_decomposition_count();
+        assert_eq!(
+            stats::take().gates,
+            stats::GateCounts {
+                circuit_bootstrap: 8,
+                circuit_bootstrap_blind_rotations: 8 * (rlrg_d_a.0 + rlrg_d_b.0),
+                ..Default::default()
+            }
+        );
+
+        let m_out = cks[0].aggregate_decryption_shares(
+            &out,
+            &cks.iter()
+                .map(|k| k.gen_decryption_share(&out))
+                .collect_vec(),
+        );
+        assert_eq!(m_out, table[m as usize]);
+    }
+
+    #[test]
+    fn fallible_api_returns_errors() {
+        use crate::{Error, FheBool, FheUint8};
+
+        set_parameter_set(ParameterSelector::InteractiveLTE2Party);
+        assert!(try_set_parameter_set(ParameterSelector::InteractiveLTE2Party).is_ok());
+        assert!(matches!(
+            try_set_parameter_set(ParameterSelector::InteractiveLTE4Party),
+            Err(Error::ParametersAlreadySet)
+        ));
+
+        let gen_shares = |session: &Session| {
+            let _guard = session.enter();
+            // user 0 generates two shares in the same run
+            let cks = (0..2)
+                .map(|_| gen_client_key().allow_conflicting_runs())
+                .collect_vec();
+            let pk_shares = cks.iter().map(collective_pk_share).collect_vec();
+            let pk = aggregate_public_key_shares(&pk_shares);
+            assert!(matches!(
+                try_collective_server_key_share(&cks[0], 2, 2, &pk).err(),
+                Some(Error::InvalidUserId {
+                    user_id: 2,
+                    total_users: 2
+                })
+            ));
+            // first two shares are of user 0
+            let shares = [0, 0, 1]
+                .into_iter()
+                .map(|user_id| collective_server_key_share(&cks[user_id], user_id, 2, &pk))
+                .collect_vec();
+            (cks, shares)
+        };
+
+        let mut seed = [0u8; 32];
+        thread_rng().fill_bytes(&mut seed);
+        let session = Session::new(ParameterSelector::InteractiveLTE2Party, seed);
+        let (cks, shares) = gen_shares(&session);
+        thread_rng().fill_bytes(&mut seed);
+        let (_, other_shares) =
+            gen_shares(&Session::new(ParameterSelector::InteractiveLTE2Party, seed));
+
+        let _guard = session.enter();
+        assert!(matches!(
+            try_aggregate_server_key_shares(&[]).err(),
+            Some(Error::NoShares)
+        ));
+        assert!(matches!(
+            try_aggregate_server_key_shares(&shares[..2]).err(),
+            Some(Error::DuplicateShare { user_id: 0 })
+        ));
+        assert!(matches!(
+            try_aggregate_server_key_shares(&other_shares[..1]).err(),
+            Some(Error::ShareParameterMismatch { user_id: 0 })
+        ));
+
+        let server_key = try_aggregate_server_key_shares(&shares[1..]).unwrap();
+        assert!(session.try_set_server_key(&server_key).is_ok());
+        assert!(matches!(
+            session.try_set_server_key(&server_key),
+            Err(Error::ServerKeyAlreadySet)
+        ));
+
+        let ct = FheBool::trivial(true);
+        assert!(matches!(
+            cks[0].try_aggregate_decryption_shares(&ct, &[]),
+            Err(Error::NoShares)
+        ));
+        let ct = FheUint8::trivial(7);
+        let mut dec_shares = cks
+            .iter()
+            .map(|k| k.gen_decryption_share(&ct))
+            .collect_vec();
+        assert_eq!(
+            cks[0]
+                .try_aggregate_decryption_shares(&ct, &dec_shares)
+                .unwrap(),
+            7
+        );
+        dec_shares[1].pop();
+        assert!(matches!(
+            cks[0].try_aggregate_decryption_shares(&ct, &dec_shares),
+            Err(Error::InvalidDecryptionShare { index: 1 })
+        ));
+    }
+
+    #[test]
+    fn decryption_shares_are_generated_once() {
+        use crate::{BinaryEncoding, FheBool, FheUint8, FileDecryptionShareStore};
+
+        let mut seed = [0u8; 32];
+        thread_rng().fill_bytes(&mut seed);
+        let _guard = Session::new(ParameterSelector::InteractiveLTE2Party, seed).enter();
+
+        let ck = gen_client_key();
+        let ct = FheBool::trivial(true);
+        let share = ck.gen_decryption_share(&ct);
+        assert_eq!(ck.gen_decryption_share(&ct), share);
+        // clones of the key share the record
+        assert_eq!(ck.clone().gen_decryption_share(&ct), share);
+
+        let ct = FheUint8::trivial(thread_rng().gen::<u8>());
+        let shares = ck.gen_decryption_share(&ct);
+        assert_eq!(ck.gen_decryption_share(&ct), shares);
+
+        // share depends only on the mask of the ciphertext, hence altering the
+        // body must not produce a fresh share of the same mask
+        let pk = aggregate_public_key_shares(&[collective_pk_share(&ck)]);
+        let ct: FheUint8 = pk.encrypt(&thread_rng().gen::<u8>());
+        let shares = ck.gen_decryption_share(&ct);
+        let mut altered = ct.bits()[0].clone();
+        altered.data_mut()[0] = altered.data()[0].wrapping_add(1 << 40);
+        assert_eq!(ck.gen_decryption_share(&altered), shares[0]);
+
+        // shares recorded in a file are returned by the same key read back
+        // with the same file
+        let path = std::env::temp_dir().join(format!(
+            "phantom-zone-mp-api-decryption-shares-{}",
+            thread_rng().gen::<u64>()
+        ));
+        let bytes = ck.to_bytes().unwrap();
+        let shares = ClientKey::from_bytes(&bytes)
+            .unwrap()
+            .with_decryption_share_store(FileDecryptionShareStore::open(&path).unwrap())
+            .gen_decryption_share(&ct);
+        let ck_back = ClientKey::from_bytes(&bytes)
+            .unwrap()
+            .with_decryption_share_store(FileDecryptionShareStore::open(&path).unwrap());
+        assert_eq!(ck_back.gen_decryption_share(&ct), shares);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn client_key_refuses_conflicting_runs() {
+        use crate::Error;
+
+        let mut seed = [0u8; 32];
+        thread_rng().fill_bytes(&mut seed);
+        let run = |seed| {
+            let _guard = Session::new(ParameterSelector::InteractiveLTE2Party, seed).enter();
+            let cks = (0..2).map(|_| gen_client_key()).collect_vec();
+            let pk_shares = cks.iter().map(collective_pk_share).collect_vec();
+            let pk = aggregate_public_key_shares(&pk_shares);
+            for (user_id, k) in cks.iter().enumerate() {
+                collective_server_key_share(k, user_id, 2, &pk);
+            }
+            (cks, pk)
+        };
+        let (cks, pk) = run(seed);
+
+        let _guard = Session::new(ParameterSelector::InteractiveLTE2Party, seed).enter();
+        for user_id in [0, 1] {
+            assert!(matches!(
+                try_collective_server_key_share(&cks[0].clone(), user_id, 2, &pk).err(),
+                Some(Error::ConflictingRun { user_id: 0 })
+            ));
+        }
+
+        // same keys can participate in a run with another seed
+        thread_rng().fill_bytes(&mut seed);
+        let _guard = Session::new(ParameterSelector::InteractiveLTE2Party, seed).enter();
+        let pk_shares = cks.iter().map(collective_pk_share).collect_vec();
+        let pk = aggregate_public_key_shares(&pk_shares);
+        assert!(try_collective_server_key_share(&cks[1], 0, 2, &pk).is_ok());
+    }
+
+    #[test]
+    #[should_panic(expected = "in a different run with the same common reference seed")]
+    fn decryption_refuses_conflicting_runs() {
+        use crate::{BinaryEncoding, FheBool, FileRunStore};
+
+        let mut seed = [0u8; 32];
+        thread_rng().fill_bytes(&mut seed);
+        let _guard = Session::new(ParameterSelector::InteractiveLTE2Party, seed).enter();
+
+        let path = std::env::temp_dir().join(format!(
+            "phantom-zone-mp-api-runs-{}",
+            thread_rng().gen::<u64>()
+        ));
+        let ck = gen_client_key()
+            .allow_conflicting_runs()
+            .with_run_store(FileRunStore::open(&path).unwrap());
+        let pk = aggregate_public_key_shares(&[collective_pk_share(&ck)]);
+        collective_server_key_share(&ck, 0, 1, &pk);
+        collective_server_key_share(&ck, 0, 1, &pk);
+
+        // the same key without override refuses to decrypt
+        let ck = ClientKey::from_bytes(&ck.to_bytes().unwrap())
+            .unwrap()
+            .with_run_store(FileRunStore::open(&path).unwrap());
+        std::fs::remove_file(&path).unwrap();
+        ck.gen_decryption_share(&FheBool::trivial(true));
+    }
+
+    #[test]
+    fn trivial_ciphertexts_multi_party_decryption_works() {
+        use crate::{FheBool, FheShortUint, FheUint8};
+
+        set_parameter_set(ParameterSelector::InteractiveLTE2Party);
+        let parties = 2;
+        let cks = (0..parties).map(|_| gen_client_key()).collect_vec();
+
+        for m in [0u8, 1, thread_rng().gen::<u8>(), 255] {
+            let ct = FheUint8::trivial(m);
+            let m_back = cks[0].aggregate_decryption_shares(
+                &ct,
+                &cks.iter()
+                    .map(|k| k.gen_decryption_share(&ct))
+                    .collect_vec(),
+            );
+            assert_eq!(m, m_back);
+        }
+
+        for m in [true, false] {
+            let ct = FheBool::trivial(m);
+            let m_back = cks[0].aggregate_decryption_shares(
+                &ct,
+                &cks.iter()
+                    .map(|k| k.gen_decryption_share(&ct))
+                    .collect_vec(),
+            );
+            assert_eq!(m, m_back);
+        }
+
+        for m in [0u8, 1] {
+            let ct = FheShortUint::<1>::trivial(m);
+            let m_back = cks[0].aggregate_decryption_shares(
+                &ct,
+                &cks.iter()
+                    .map(|k| k.gen_decryption_share(&ct))
+                    .collect_vec(),
+            );
+            assert_eq!(m, m_back);
+        }
+    }
+
+    #[test]
+    fn mock_types_match_plaintext() {
+        use crate::{
+            mock_div_zero_error_flag, reset_error_flags, Decryptor, MockClientKey, MockFheBool,
+            MockFheInt8, MockFheUint8,
+        };
+
+        let ck = MockClientKey;
+        for _ in 0..1000 {
+            let (m0, m1) = (thread_rng().gen::<u8>(), thread_rng().gen::<u8>());
+            let c0: MockFheUint8 = ck.encrypt(&m0);
+            let c1: MockFheUint8 = ck.encrypt(&m1);
+            assert_eq!(ck.decrypt(&(&c0 + &c1)), m0.wrapping_add(m1));
+            assert_eq!(ck.decrypt(&(&c0 - &c1)), m0.wrapping_sub(m1));
+            assert_eq!(ck.decrypt(&(&c0 * &c1)), m0.wrapping_mul(m1));
+            assert_eq!(ck.decrypt(&(&c0 ^ &c1)), m0 ^ m1);
+            assert_eq!(ck.decrypt(&(&c0 << &c1)), m0.wrapping_shl(m1 as u32));
+            assert_eq!(ck.decrypt(&c0.lt(&c1)), m0 < m1);
+            if let (Some(want_q), Some(want_r)) = (m0.checked_div(m1), m0.checked_rem(m1)) {
+                let (q, r) = c0.div_rem(&c1);
+                assert_eq!(ck.decrypt(&q), want_q);
+                assert_eq!(ck.decrypt(&r), want_r);
+            }
+
+            let (m0, m1) = (m0 as i8, m1 as i8);
+            let c0: MockFheInt8 = ck.encrypt(&m0);
+            let c1: MockFheInt8 = ck.encrypt(&m1);
+            assert_eq!(ck.decrypt(&(&c0 * &c1)), m0.wrapping_mul(m1));
+            assert_eq!(ck.decrypt(&c0.ge(&c1)), m0 >= m1);
+            if m1 != 0 {
+                assert_eq!(ck.decrypt(&(&c0 / &c1)), m0.wrapping_div(m1));
+            }
+        }
+
+        reset_error_flags();
+        let c0: MockFheUint8 = ck.encrypt(&7u8);
+        let (q, r) = c0.div_rem(&MockFheUint8::trivial(0u8));
+        assert_eq!(ck.decrypt(&q), 255);
+        assert_eq!(ck.decrypt(&r), 7);
+        let flag: MockFheBool = mock_div_zero_error_flag().unwrap();
+        assert!(ck.decrypt(&flag));
+
+        // so does division by plaintext 0
+        reset_error_flags();
+        assert_eq!(ck.decrypt(&(&c0 / 0u8)), 255);
+        assert_eq!(ck.decrypt(&(&c0 % 0u8)), 7);
+        let flag: MockFheBool = mock_div_zero_error_flag().unwrap();
+        assert!(ck.decrypt(&flag));
+    }
+
+    #[test]
+    fn mock_wider_uint_div_rem() {
+        use crate::{
+            mock_div_zero_error_flag, reset_error_flags, Decryptor, MockClientKey, MockFheBool,
+            MockFheUint16, MockFheUint32, MockFheUint64,
+        };
+
+        let ck = MockClientKey;
+        macro_rules! check_div_rem {
+            ($ty:ty, $m:ty) => {
+                for _ in 0..100 {
+                    let (m0, m1) = (thread_rng().gen::<$m>(), thread_rng().gen::<$m>());
+                    // small divisors yield quotients with many bits set
+                    let m2 = m1 >> thread_rng().gen_range(0..<$m>::BITS);
+                    for m1 in [m1, m2.max(1)] {
+                        let c0: $ty = ck.encrypt(&m0);
+                        let c1: $ty = ck.encrypt(&m1);
+                        let (q, r) = c0.div_rem(&c1);
+                        assert_eq!(ck.decrypt(&q), m0 / m1);
+                        assert_eq!(ck.decrypt(&r), m0 % m1);
+                        assert_eq!(ck.decrypt(&(&c0 / &c1)), m0 / m1);
+                        assert_eq!(ck.decrypt(&(&c0 % &c1)), m0 % m1);
+                    }
+                }
+
+                // Division by zero sets quotient to all ones and remainder to dividend
+                reset_error_flags();
+                let m0 = thread_rng().gen::<$m>();
+                let c0: $ty = ck.encrypt(&m0);
+                let (q, r) = c0.div_rem(&<$ty>::trivial(0));
+                assert_eq!(ck.decrypt(&q), <$m>::MAX);
+                assert_eq!(ck.decrypt(&r), m0);
+                let flag: MockFheBool = mock_div_zero_error_flag().unwrap();
+                assert!(ck.decrypt(&flag));
+            };
+        }
+
+        check_div_rem!(MockFheUint16, u16);
+        check_div_rem!(MockFheUint32, u32);
+        check_div_rem!(MockFheUint64, u64);
+    }
+
+    #[test]
+    fn stats_record_mock_gates() {
+        use crate::{
+            stats::{self, GateCounts},
+            MockClientKey, MockFheBool, MockFheUint8,
+        };
+
+        let ck = MockClientKey;
+        let c0: MockFheUint8 = ck.encrypt(&thread_rng().gen::<u8>());
+        let c1: MockFheUint8 = ck.encrypt(&thread_rng().gen::<u8>());
+
+        // 1 half adder and 7 full adders
+        stats::reset();
+        let _ = &c0 + &c1;
+        let report = stats::take();
+        assert_eq!(
+            report.gates,
+            GateCounts {
+                half_adder: 1,
+                full_adder: 7,
+                ..Default::default()
+            }
+        );
+        assert_eq!(report.gates.bootstraps(), 23);
+        // Mock types do not execute bootstraps
+        assert_eq!(report.executed_bootstraps, 0);
+        assert_eq!(report.mean_bootstrap_time(), None);
+
+        // NOT does not require a bootstrap
+        let b: MockFheBool = ck.encrypt(&true);
+        let _ = !&b;
+        let report = stats::take();
+        assert_eq!(report.gates.not, 1);
+        assert_eq!(report.gates.bootstraps(), 0);
+        assert_eq!(stats::peek(), stats::Stats::default());
+    }
+
+    #[test]
+    fn mock_short_uint_lookup_tables() {
+        use crate::{stats, Decryptor, MockClientKey, MockFheBool, MockFheShortUint};
+
+        let ck = MockClientKey;
+        for m in 0..8u8 {
+            let bits: Vec<MockFheBool> = (0..3).map(|i| ck.encrypt(&((m >> i) & 1 == 1))).collect();
+
+            stats::reset();
+            let c = MockFheShortUint::<3>::from_bits(&bits);
+            assert_eq!(ck.decrypt(&c), m);
+            let f = |m: u8| (5 * m + 3) % 8;
+            let c_f = c.apply_lut(f);
+            assert_eq!(ck.decrypt(&c_f), f(m));
+            assert_eq!(ck.decrypt(&c.apply_lut(|m| m + 20)), (m + 20) % 8);
+            assert_eq!(ck.decrypt(&c_f.apply_lut_to_bool(|m| m > 3)), f(m) > 3);
+            let bits_back: Vec<bool> = c.to_bits().iter().map(|b| ck.decrypt(b)).collect();
+            assert_eq!(bits_back, (0..3).map(|i| (m >> i) & 1 == 1).collect_vec());
+
+            // from_bits, 2 lookup tables, apply_lut_to_bool, to_bits
+            let report = stats::take();
+            assert_eq!(report.gates.lut, 3 + 2 + 1 + 3);
+            assert_eq!(report.gates.bootstraps(), report.gates.lut);
+
+            let half: MockFheShortUint<3> = ck.encrypt(&(m / 2));
+            assert_eq!(
+                ck.decrypt(&(&half + &MockFheShortUint::trivial(m - m / 2))),
+                m
+            );
+            assert_eq!(stats::take().gates.bootstraps(), 0);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "not supported")]
+    fn mock_short_uint_requires_at_most_3_bits() {
+        use crate::MockFheShortUint;
+
+        MockFheShortUint::<4>::trivial(0);
+    }
+
+    #[test]
+    #[should_panic(expected = "overflows")]
+    fn mock_short_uint_add_must_not_overflow() {
+        use crate::{MockClientKey, MockFheShortUint};
+
+        let c: MockFheShortUint<2> = MockClientKey.encrypt(&3);
+        let _ = &c + &c;
+    }
+
+    #[test]
+    fn mock_radix_uint8_arithmetic() {
+        use crate::{stats, Decryptor, MockClientKey, MockFheBool, MockFheRadixUint8};
+
+        let ck = MockClientKey;
+        for _ in 0..256 {
+            let a = thread_rng().gen::<u8>();
+            let b = thread_rng().gen::<u8>();
+            let c_a: MockFheRadixUint8 = ck.encrypt(&a);
+            let c_b: MockFheRadixUint8 = ck.encrypt(&b);
+
+            stats::reset();
+            assert_eq!(ck.decrypt(&(&c_a + &c_b)), a.wrapping_add(b));
+            assert_eq!(stats::take().gates.lut, 7);
+            assert_eq!(ck.decrypt(&(&c_a - &c_b)), a.wrapping_sub(b));
+            assert_eq!(stats::take().gates.lut, 11);
+
+            let bits: Vec<MockFheBool> = (0..8).map(|i| ck.encrypt(&((a >> i) & 1 == 1))).collect();
+            let c = MockFheRadixUint8::from_bits(&bits);
+            assert_eq!(ck.decrypt(&c), a);
+            let bits_back: Vec<bool> = c.to_bits().iter().map(|b| ck.decrypt(b)).collect();
+            assert_eq!(bits_back, (0..8).map(|i| (a >> i) & 1 == 1).collect_vec());
+
+            assert_eq!(
+                ck.decrypt(&(&MockFheRadixUint8::trivial(a) + &c_b)),
+                a.wrapping_add(b)
+            );
+        }
+    }
+
+    #[test]
+    fn mock_table_lookup() {
+        use crate::{stats, Decryptor, MockClientKey, MockFheUint8};
+
+        let ck = MockClientKey;
+        let mut table = [0u8; 256];
+        thread_rng().fill_bytes(&mut table);
+
+        for m in 0..=255u8 {
+            let index: MockFheUint8 = ck.encrypt(&m);
+            assert_eq!(ck.decrypt(&index.lookup(&table)), table[m as usize]);
+            // out of bounds indices select 0
+            let out = MockFheUint8::select_from(&table[..100], &index);
+            let expected = if m < 100 { table[m as usize] } else { 0 };
+            assert_eq!(ck.decrypt(&out), expected);
+        }
+
+        // far fewer than the 255 muxes per output bit of a chain of muxes
+        let index: MockFheUint8 = ck.encrypt(&thread_rng().gen::<u8>());
+        stats::reset();
+        let _ = index.lookup(&table);
+        let bootstraps = stats::take().gates.bootstraps();
+        assert!(bootstraps < 255 * 8, "{bootstraps} bootstraps per lookup");
+
+        // lookup of a function of some of the index bits requires only the gates of
+        // the function
+        let identity = std::array::from_fn(|i| i as u8);
+        let _ = index.lookup(&identity);
+        assert_eq!(stats::take().gates.bootstraps(), 0);
+        let parity = std::array::from_fn(|i| (i as u8 & 0b11).count_ones() as u8 & 1);
+        let _ = index.lookup(&parity);
+        assert_eq!(stats::take().gates.bootstraps(), 1);
+    }
+
+    #[test]
+    fn mock_array_and_grid() {
+        use crate::{Decryptor, FheArray, FheGrid, MockClientKey, MockFheBool, MockFheUint8};
+        use itertools::izip;
+
+        let ck = MockClientKey;
+        let encrypt = |m: u8| -> MockFheUint8 { ck.encrypt(&m) };
+
+        for len in [0, 1, 5, 16, 100, 256] {
+            let mut plain = (0..len).map(|_| thread_rng().gen::<u8>()).collect_vec();
+            let mut array = FheArray::new(plain.iter().map(|m| encrypt(*m)).collect_vec());
+            for _ in 0..10 {
+                let index = thread_rng().gen::<u8>();
+                let value = thread_rng().gen::<u8>();
+                array.set(&encrypt(index), &encrypt(value));
+                if let Some(v) = plain.get_mut(index as usize) {
+                    *v = value;
+                }
 
-        let seeded_ct = pk.encrypt(m.as_slice());
+                let index = thread_rng().gen::<u8>();
+                let expected = plain.get(index as usize).copied().unwrap_or(0);
+                assert_eq!(ck.decrypt(&array.get(&encrypt(index))), expected);
+            }
+            let elements = array.elements().iter().map(|c| ck.decrypt(c)).collect_vec();
+            assert_eq!(elements, plain);
+        }
 
-        let m_back = (0..batch_size)
-            .map(|i| {
-                let ct = seeded_ct.extract_at(i);
-                cks[0].aggregate_decryption_shares(
-                    &ct,
-                    &cks.iter()
-                        .map(|k| k.gen_decryption_share(&ct))
-                        .collect_vec(),
+        let (width, height) = (7, 12);
+        let mut plain = vec![false; width * height];
+        let mut grid = FheGrid::new(
+            width,
+            height,
+            vec![MockFheBool::trivial(false); width * height],
+        );
+        let updates = (0..20)
+            .map(|_| {
+                let x = thread_rng().gen_range(0..width as u8 + 2);
+                let y = thread_rng().gen_range(0..height as u8 + 2);
+                let value = thread_rng().gen_bool(0.5);
+                if (x as usize) < width && (y as usize) < height {
+                    plain[y as usize * width + x as usize] = value;
+                }
+                (encrypt(x), encrypt(y), ck.encrypt(&value))
+            })
+            .collect_vec();
+        grid.set_many(&updates);
+        let cells = grid.cells().iter().map(|c| ck.decrypt(c)).collect_vec();
+        assert_eq!(cells, plain);
+
+        let coordinates = (0..20)
+            .map(|_| {
+                (
+                    thread_rng().gen_range(0..width as u8 + 2),
+                    thread_rng().gen_range(0..height as u8 + 2),
                 )
             })
             .collect_vec();
-
-        assert_eq!(m, m_back);
+        let values = grid.get_many(
+            &coordinates
+                .iter()
+                .map(|(x, y)| (encrypt(*x), encrypt(*y)))
+                .collect_vec(),
+        );
+        izip!(coordinates.iter(), values.iter()).for_each(|((x, y), value)| {
+            let expected = (*x as usize) < width
+                && (*y as usize) < height
+                && plain[*y as usize * width + *x as usize];
+            assert_eq!(ck.decrypt(value), expected);
+        });
     }
 
     mod sp_api {
         use num_traits::ToPrimitive;
 
         use crate::{
-            bool::impl_bool_frontend::FheBool, pbs::PbsInfo, rgsw::seeded_secret_key_encrypt_rlwe,
-            Decryptor,
+            bool::{evaluator::MessageEncoding, impl_bool_frontend::FheBool},
+            lwe::decrypt_lwe,
+            pbs::PbsInfo,
+            rgsw::seeded_secret_key_encrypt_rlwe,
+            Decryptor, FheShortUint,
         };
 
         use super::*;
 
-        pub(crate) fn set_single_party_parameter_sets(parameter: BoolParameters<u64>) {
-            BOOL_EVALUATOR.with_borrow_mut(|e| *e = Some(BoolEvaluator::new(parameter)));
+        /// Enters new session with `parameter` on the current thread. Tests
+        /// run in separate sessions, hence can run in parallel.
+        pub(crate) fn set_single_party_parameter_sets(
+            parameter: BoolParameters<u64>,
+        ) -> SessionGuard {
+            let mut seed = [0u8; 32];
+            thread_rng().fill_bytes(&mut seed);
+            Session::with_parameters(parameter, seed, 0).enter()
+        }
+
+        /// Returns the session entered on the current thread
+        pub(crate) fn entered_session() -> Session {
+            ACTIVE_SESSION.with_borrow(|s| Session {
+                inner: s.as_ref().expect("Session not entered").session.clone(),
+            })
         }
 
         // SERVER KEY EVAL (/SHOUP) DOMAIN //
         impl SeededSinglePartyServerKey<Vec<Vec<u64>>, BoolParameters<u64>, [u8; 32]> {
+            /// Sets the server key of the session entered on the current thread,
+            /// otherwise the process wide server key
             pub fn set_server_key(&self) {
-                assert!(
-                    BOOL_SERVER_KEY
-                        .set(
-                            ShoupServerKeyEvaluationDomain::from(ServerKeyEvaluationDomain::<
-                                _,
-                                _,
-                                DefaultSecureRng,
-                                NttBackendU64,
-                            >::from(
-                                self
-                            ),)
-                        )
-                        .is_ok(),
-                    "Attempted to set server key twice."
+                self.try_set_server_key().unwrap_or_else(|e| panic!("{e}"))
+            }
+
+            /// Same as `set_server_key`, but returns `Error::ServerKeyAlreadySet`
+            /// instead of panicking if the server key is already set
+            pub fn try_set_server_key(&self) -> Result<(), Error> {
+                let server_key = ShoupServerKeyEvaluationDomain::from(
+                    ServerKeyEvaluationDomain::<_, _, DefaultSecureRng, NttBackendU64>::from(self),
                 );
+                ACTIVE_SESSION.with_borrow(|s| match s {
+                    Some(active) => active
+                        .session
+                        .server_key
+                        .set(server_key)
+                        .map_err(|_| Error::ServerKeyAlreadySet),
+                    None => BOOL_SERVER_KEY
+                        .set(server_key)
+                        .map_err(|_| Error::ServerKeyAlreadySet),
+                })
             }
         }
 
@@ -430,6 +1720,17 @@ mod tests {
             }
         }
 
+        impl<K: SinglePartyClientKey<Element = i32>, const BITS: usize>
+            Decryptor<u8, FheShortUint<BITS>> for K
+        {
+            fn decrypt(&self, c: &FheShortUint<BITS>) -> u8 {
+                BoolEvaluator::with_local(|e| {
+                    let m = decrypt_lwe(&c.data, &self.sk_rlwe(), e.pbs_info().modop_rlweq());
+                    e.pbs_info().rlwe_q().decode_message(m, BITS) as u8
+                })
+            }
+        }
+
         impl<K> Encryptor<[bool], (Vec<Vec<u64>>, [u8; 32])> for K
         where
             K: SinglePartyClientKey<Element = i32>,
@@ -495,13 +1796,13 @@ mod tests {
 
             use crate::{div_zero_error_flag, FheBool};
 
-            set_single_party_parameter_sets(SP_TEST_BOOL_PARAMS);
+            let _session = set_single_party_parameter_sets(SP_TEST_ARITHMETIC_PARAMS);
 
             let (ck, sk) = gen_keys();
             sk.set_server_key();
 
-            for i in 0..=255 {
-                for j in 0..=255 {
+            for i in 0..=255u8 {
+                for j in 0..=255u8 {
                     let m0 = i;
                     let m1 = j;
                     let c0 = ck.encrypt(&m0);
@@ -659,12 +1960,439 @@ mod tests {
             }
         }
 
+        #[test]
+        #[cfg(feature = "interactive_mp")]
+        fn wider_uint_apis() {
+            use crate::{div_zero_error_flag, reset_error_flags, FheUint16, FheUint32, FheUint64};
+
+            let _session = set_single_party_parameter_sets(SP_TEST_ARITHMETIC_PARAMS);
+
+            let (ck, sk) = gen_keys();
+            sk.set_server_key();
+            assert!(matches!(
+                sk.try_set_server_key(),
+                Err(Error::ServerKeyAlreadySet)
+            ));
+
+            {
+                let m0 = thread_rng().gen::<u16>();
+                let m1 = thread_rng().gen::<u16>();
+                let c0: FheUint16 = ck.encrypt(&m0);
+                let c1: FheUint16 = ck.encrypt(&m1);
+
+                let (c_add, c_overflow) = c0.clone().overflowing_add(&c1);
+                let (want_add, want_overflow) = m0.overflowing_add(m1);
+                assert_eq!(ck.decrypt(&c_add), want_add);
+                assert_eq!(ck.decrypt(&c_overflow), want_overflow);
+
+                assert_eq!(ck.decrypt(&(&c0 - &c1)), m0.wrapping_sub(m1));
+                assert_eq!(ck.decrypt(&(&c0 * &c1)), m0.wrapping_mul(m1));
+                assert_eq!(ck.decrypt(&c0.lt(&c1)), m0 < m1);
+                assert!(ck.decrypt(&c0.eq(&c0)));
+
+                let m1 = m1.max(1);
+                let c1: FheUint16 = ck.encrypt(&m1);
+                let (c_quotient, c_rem) = c0.div_rem(&c1);
+                assert_eq!(ck.decrypt(&c_quotient), m0 / m1);
+                assert_eq!(ck.decrypt(&c_rem), m0 % m1);
+
+                reset_error_flags();
+                let (c_quotient, c_rem) = c0.div_rem(&FheUint16::trivial(0));
+                assert_eq!(ck.decrypt(&c_quotient), u16::MAX);
+                assert_eq!(ck.decrypt(&c_rem), m0);
+                assert!(ck.decrypt(&div_zero_error_flag().unwrap()));
+            }
+
+            {
+                let m0 = thread_rng().gen::<u32>();
+                let m1 = thread_rng().gen::<u32>();
+                let c0: FheUint32 = ck.encrypt(&m0);
+                let c1: FheUint32 = ck.encrypt(&m1);
+                assert_eq!(ck.decrypt(&(&c0 + &c1)), m0.wrapping_add(m1));
+            }
+
+            {
+                let m = thread_rng().gen::<u64>();
+                let c: FheUint64 = ck.encrypt(&m);
+                assert_eq!(ck.decrypt(&c), m);
+            }
+        }
+
+        #[test]
+        #[cfg(feature = "interactive_mp")]
+        fn bitwise_uint8_apis() {
+            use crate::FheUint8;
+
+            let _session = set_single_party_parameter_sets(SP_TEST_BOOL_PARAMS);
+
+            let (ck, sk) = gen_keys();
+            sk.set_server_key();
+
+            for _ in 0..2 {
+                let m0 = thread_rng().gen::<u8>();
+                let m1 = thread_rng().gen::<u8>();
+                let c0: FheUint8 = ck.encrypt(&m0);
+                let c1: FheUint8 = ck.encrypt(&m1);
+
+                assert_eq!(ck.decrypt(&(&c0 & &c1)), m0 & m1);
+                assert_eq!(ck.decrypt(&(&c0 | &c1)), m0 | m1);
+                assert_eq!(ck.decrypt(&(&c0 ^ &c1)), m0 ^ m1);
+                assert_eq!(ck.decrypt(&!&c0), !m0);
+
+                let n = thread_rng().gen_range(0..8u32);
+                assert_eq!(ck.decrypt(&(&c0 << n)), m0 << n);
+                assert_eq!(ck.decrypt(&(&c0 >> n)), m0 >> n);
+                assert_eq!(ck.decrypt(&c0.rotate_left(n)), m0.rotate_left(n));
+                assert_eq!(ck.decrypt(&c0.rotate_right(n)), m0.rotate_right(n));
+
+                assert_eq!(ck.decrypt(&(&c0 << &c1)), m0.wrapping_shl(m1 as u32));
+                assert_eq!(ck.decrypt(&(&c0 >> &c1)), m0.wrapping_shr(m1 as u32));
+                assert_eq!(
+                    ck.decrypt(&c0.rotate_left_by(&c1)),
+                    m0.rotate_left(m1 as u32)
+                );
+                assert_eq!(
+                    ck.decrypt(&c0.rotate_right_by(&c1)),
+                    m0.rotate_right(m1 as u32)
+                );
+            }
+        }
+
+        #[test]
+        #[cfg(feature = "interactive_mp")]
+        fn scalar_uint8_apis() {
+            use crate::{FheBool, FheUint8};
+
+            let _session = set_single_party_parameter_sets(SP_TEST_ARITHMETIC_PARAMS);
+
+            let (ck, sk) = gen_keys();
+            sk.set_server_key();
+
+            for _ in 0..2 {
+                let m0 = thread_rng().gen::<u8>();
+                let m1 = thread_rng().gen::<u8>();
+                let c0: FheUint8 = ck.encrypt(&m0);
+
+                let (c_add, c_overflow) = c0.overflowing_add_const(m1);
+                let (want_add, want_overflow) = m0.overflowing_add(m1);
+                assert_eq!(ck.decrypt(&c_add), want_add);
+                assert_eq!(ck.decrypt(&c_overflow), want_overflow);
+
+                let (c_sub, c_overflow) = c0.overflowing_sub_const(m1);
+                let (want_sub, want_overflow) = m0.overflowing_sub(m1);
+                assert_eq!(ck.decrypt(&c_sub), want_sub);
+                assert_eq!(ck.decrypt(&c_overflow), want_overflow);
+
+                assert_eq!(ck.decrypt(&(&c0 * m1)), m0.wrapping_mul(m1));
+
+                let divisor = m1.max(1);
+                let (c_q, c_r) = c0.div_rem_const(divisor);
+                assert_eq!(ck.decrypt(&c_q), m0 / divisor);
+                assert_eq!(ck.decrypt(&c_r), m0 % divisor);
+
+                assert_eq!(ck.decrypt(&(&c0 & m1)), m0 & m1);
+                assert_eq!(ck.decrypt(&(&c0 | m1)), m0 | m1);
+                assert_eq!(ck.decrypt(&(&c0 ^ m1)), m0 ^ m1);
+
+                assert!(ck.decrypt(&c0.eq_const(m0)));
+                assert_eq!(ck.decrypt(&c0.eq_const(m1)), m0 == m1);
+                assert_eq!(ck.decrypt(&c0.lt_const(m1)), m0 < m1);
+                assert_eq!(ck.decrypt(&c0.gt_const(m1)), m0 > m1);
+                assert_eq!(ck.decrypt(&c0.le_const(m1)), m0 <= m1);
+                assert_eq!(ck.decrypt(&c0.ge_const(m1)), m0 >= m1);
+            }
+
+            for a in [true, false] {
+                let c_a: FheBool = ck.encrypt(&a);
+                for b in [true, false] {
+                    assert_eq!(ck.decrypt(&(&c_a & b)), a & b);
+                    assert_eq!(ck.decrypt(&(b | &c_a)), a | b);
+                    assert_eq!(ck.decrypt(&(b ^ &c_a)), a ^ b);
+                }
+            }
+        }
+
+        #[test]
+        #[cfg(feature = "interactive_mp")]
+        fn trivial_ciphertext_apis() {
+            use crate::{FheBool, FheInt8, FheUint8};
+
+            let _session = set_single_party_parameter_sets(SP_TEST_ARITHMETIC_PARAMS);
+
+            let (ck, sk) = gen_keys();
+            sk.set_server_key();
+
+            // count no. of values greater than threshold starting with trivial 0
+            let values = (0..3).map(|_| thread_rng().gen::<u8>()).collect_vec();
+            let threshold = thread_rng().gen::<u8>();
+            let c_threshold: FheUint8 = ck.encrypt(&threshold);
+            let mut count = FheUint8::trivial(0u8);
+            let one = FheUint8::trivial(1u8);
+            let zero = FheUint8::trivial(0u8);
+            for v in values.iter() {
+                let c_v: FheUint8 = ck.encrypt(v);
+                count += &one.mux(&zero, &c_v.gt(&c_threshold));
+            }
+            let want_count = values.iter().filter(|v| **v > threshold).count() as u8;
+            assert_eq!(ck.decrypt(&count), want_count);
+
+            // trivial ciphertexts in both operands
+            let m0 = thread_rng().gen::<u8>();
+            let m1 = thread_rng().gen::<u8>();
+            let c0 = FheUint8::trivial(m0);
+            let c1 = FheUint8::trivial(m1);
+            assert_eq!(ck.decrypt(&(&c0 * &c1)), m0.wrapping_mul(m1));
+            assert_eq!(ck.decrypt(&c0.lt(&c1)), m0 < m1);
+
+            let c_neg = FheInt8::trivial(-5i8);
+            assert_eq!(ck.decrypt(&c_neg.abs()), 5);
+
+            let a = thread_rng().gen_bool(0.5);
+            let c_a: FheBool = ck.encrypt(&a);
+            assert_eq!(ck.decrypt(&(&c_a & &FheBool::trivial(true))), a);
+            assert_eq!(ck.decrypt(&(&c_a ^ &FheBool::trivial(true))), !a);
+        }
+
+        #[test]
+        #[cfg(feature = "interactive_mp")]
+        fn generic_integer_ops_match_plaintext() {
+            use crate::{FheBool, FheBoolOps, FheInt8, FheIntegerOps, FheUint8, Select};
+
+            fn circuit<T: FheIntegerOps>(a: &T, b: &T) -> (T, T, T::Bool) {
+                let (q, r) = a.div_rem(b);
+                let sum = q.wrapping_add(&r).wrapping_mul(b).wrapping_sub(a);
+                let is_lt = a.fhe_lt(b).fhe_or(&a.fhe_eq(b).fhe_not());
+                (sum, a.fhe_max(b), is_lt)
+            }
+
+            let _session = set_single_party_parameter_sets(SP_TEST_ARITHMETIC_PARAMS);
+
+            let (ck, sk) = gen_keys();
+            sk.set_server_key();
+
+            for (m0, m1) in [(thread_rng().gen::<u8>(), thread_rng().gen::<u8>()), (7, 0)] {
+                let c0: FheUint8 = ck.encrypt(&m0);
+                let c1: FheUint8 = ck.encrypt(&m1);
+                let (want_sum, want_max, want_lt) = circuit(&m0, &m1);
+                let (c_sum, c_max, c_lt) = circuit(&c0, &c1);
+                assert_eq!(ck.decrypt(&c_sum), want_sum);
+                assert_eq!(ck.decrypt(&c_max), want_max);
+                assert_eq!(ck.decrypt(&c_lt), want_lt);
+            }
+
+            {
+                let (m0, m1) = (-7i8, 0i8);
+                let c0: FheInt8 = ck.encrypt(&m0);
+                let c1: FheInt8 = ck.encrypt(&m1);
+                let (want_sum, _, want_lt) = circuit(&m0, &m1);
+                let (c_sum, _, c_lt) = circuit(&c0, &c1);
+                assert_eq!(ck.decrypt(&c_sum), want_sum);
+                assert_eq!(ck.decrypt(&c_lt), want_lt);
+            }
+
+            // methods of the traits do not shadow methods of `PartialEq` and
+            // `PartialOrd`
+            assert!(7u8.lt(&8) && 7u8.eq(&7) && (-1i8).le(&0));
+
+            for (a, b, selector) in [(true, false, true), (true, false, false)] {
+                let c_a: FheBool = ck.encrypt(&a);
+                let c_b: FheBool = ck.encrypt(&b);
+                let c_selector: FheBool = ck.encrypt(&selector);
+                assert_eq!(
+                    ck.decrypt(&c_a.mux(&c_b, &c_selector)),
+                    a.mux(&b, &selector)
+                );
+            }
+        }
+
+        #[test]
+        #[cfg(feature = "interactive_mp")]
+        fn mock_and_fhe_types_agree() {
+            use crate::{stats, FheIntegerOps, FheUint8, MockClientKey, MockFheUint8};
+
+            fn circuit<T: FheIntegerOps>(a: &T, b: &T) -> T {
+                let (q, r) = a.div_rem(b);
+                q.wrapping_mul(b).wrapping_add(&r).fhe_max(a)
+            }
+
+            let _session = set_single_party_parameter_sets(SP_TEST_ARITHMETIC_PARAMS);
+
+            let (ck, sk) = gen_keys();
+            sk.set_server_key();
+
+            let (m0, m1) = (thread_rng().gen::<u8>(), thread_rng().gen::<u8>());
+            let c0: FheUint8 = ck.encrypt(&m0);
+            let c1: FheUint8 = ck.encrypt(&m1);
+            let mock_c0: MockFheUint8 = MockClientKey.encrypt(&m0);
+            let mock_c1: MockFheUint8 = MockClientKey.encrypt(&m1);
+
+            stats::reset();
+            let mock_out = circuit(&mock_c0, &mock_c1);
+            let mock_report = stats::take();
+            let out = circuit(&c0, &c1);
+            let report = stats::take();
+            assert_eq!(ck.decrypt(&out), MockClientKey.decrypt(&mock_out));
+
+            // Mock types evaluate the same gates as Fhe types
+            assert_eq!(mock_report.gates, report.gates);
+            assert!(report.gates.bootstraps() > 0);
+            assert_eq!(report.executed_bootstraps, report.gates.bootstraps());
+            assert!(report.bootstrap_time > std::time::Duration::ZERO);
+            let mean = report.mean_bootstrap_time().unwrap();
+            assert!(mock_report.estimated_time(mean) <= report.bootstrap_time);
+        }
+
+        #[test]
+        #[cfg(feature = "interactive_mp")]
+        fn thread_pool_par_map() {
+            use crate::{div_zero_error_flag, reset_error_flags, stats, FheUint8, ThreadPool};
+
+            let _session = set_single_party_parameter_sets(SP_TEST_ARITHMETIC_PARAMS);
+
+            let (ck, sk) = gen_keys();
+            sk.set_server_key();
+
+            let m = (0..7)
+                .map(|_| (thread_rng().gen::<u8>(), thread_rng().gen::<u8>()))
+                .collect_vec();
+            let c: Vec<(FheUint8, FheUint8)> = m
+                .iter()
+                .map(|(m0, m1)| (ck.encrypt(m0), ck.encrypt(m1)))
+                .collect_vec();
+
+            let pool = ThreadPool::new(&entered_session(), 3);
+            stats::reset();
+            let sums: Vec<FheUint8> = pool.par_map(&c, |(c0, c1)| c0 + c1);
+            let report = stats::take();
+            m.iter().zip(sums.iter()).for_each(|((m0, m1), c_sum)| {
+                assert_eq!(ck.decrypt(c_sum), m0.wrapping_add(*m1));
+            });
+
+            // gates evaluated by the workers are recorded on the current thread
+            let want_report = {
+                let _ = &c[0].0 + &c[0].1;
+                stats::take()
+            };
+            assert_eq!(
+                report.gates.bootstraps(),
+                7 * want_report.gates.bootstraps()
+            );
+            assert_eq!(report.executed_bootstraps, report.gates.bootstraps());
+
+            // division by zero on a worker sets error flag of the session
+            reset_error_flags();
+            let zero: FheUint8 = ck.encrypt(&0u8);
+            let divisors: [FheUint8; 3] = [ck.encrypt(&3u8), zero, ck.encrypt(&5u8)];
+            let quotients: Vec<FheUint8> = pool.par_map(&divisors, |d| &c[0].0 / d);
+            assert_eq!(ck.decrypt(&quotients[0]), m[0].0 / 3);
+            assert_eq!(ck.decrypt(&quotients[1]), u8::MAX);
+            let div_zero_error: bool = ck.decrypt(&div_zero_error_flag().unwrap());
+            assert!(div_zero_error);
+
+            reset_error_flags();
+            let _: Vec<FheUint8> = pool.par_map(&divisors[..1], |d| &c[0].0 / d);
+            let div_zero_error: bool = ck.decrypt(&div_zero_error_flag().unwrap());
+            assert!(!div_zero_error);
+        }
+
+        #[test]
+        #[cfg(feature = "interactive_mp")]
+        fn recorded_circuit_evaluates_in_parallel() {
+            use crate::{stats, Circuit, FheUint8, LazyFheUint8, ThreadPool};
+
+            let _session = set_single_party_parameter_sets(SP_TEST_ARITHMETIC_PARAMS);
+
+            let (ck, sk) = gen_keys();
+            sk.set_server_key();
+
+            let circuit = Circuit::record(|| {
+                let a = LazyFheUint8::input();
+                let b = LazyFheUint8::input();
+                let mut outputs = (&a * &b).bits();
+                outputs.push(a.lt(&b));
+                outputs
+            });
+
+            let (m0, m1) = (thread_rng().gen::<u8>(), thread_rng().gen::<u8>());
+            let c0: FheUint8 = ck.encrypt(&m0);
+            let c1: FheUint8 = ck.encrypt(&m1);
+            let inputs = [c0.bits(), c1.bits()].concat();
+
+            let pool = ThreadPool::new(&entered_session(), 4);
+            stats::reset();
+            let outputs = circuit.evaluate(&pool, &inputs);
+            let report = stats::take();
+
+            assert_eq!(
+                ck.decrypt(&FheUint8::from_bits(&outputs[..8])),
+                m0.wrapping_mul(m1)
+            );
+            assert_eq!(ck.decrypt(&outputs[8]), m0 < m1);
+            assert_eq!(report.executed_bootstraps, circuit.bootstraps());
+        }
+
+        #[test]
+        #[cfg(feature = "interactive_mp")]
+        fn signed_int_apis() {
+            use crate::{div_zero_error_flag, reset_error_flags, FheInt8};
+
+            let _session = set_single_party_parameter_sets(SP_TEST_ARITHMETIC_PARAMS);
+
+            let (ck, sk) = gen_keys();
+            sk.set_server_key();
+
+            let cases = [
+                (thread_rng().gen::<i8>(), thread_rng().gen::<i8>()),
+                (-100i8, 7i8),
+                (i8::MIN, -1i8),
+            ];
+            for (m0, m1) in cases {
+                let c0: FheInt8 = ck.encrypt(&m0);
+                let c1: FheInt8 = ck.encrypt(&m1);
+
+                let (c_add, c_overflow) = c0.overflowing_add(&c1);
+                let (want_add, want_overflow) = m0.overflowing_add(m1);
+                assert_eq!(ck.decrypt(&c_add), want_add);
+                assert_eq!(ck.decrypt(&c_overflow), want_overflow);
+
+                let (c_sub, c_overflow) = c0.overflowing_sub(&c1);
+                let (want_sub, want_overflow) = m0.overflowing_sub(m1);
+                assert_eq!(ck.decrypt(&c_sub), want_sub);
+                assert_eq!(ck.decrypt(&c_overflow), want_overflow);
+
+                assert_eq!(ck.decrypt(&(&c0 * &c1)), m0.wrapping_mul(m1));
+
+                let (c_q, c_r) = c0.div_rem(&c1);
+                assert_eq!(ck.decrypt(&c_q), m0.wrapping_div(m1));
+                assert_eq!(ck.decrypt(&c_r), m0.wrapping_rem(m1));
+
+                assert_eq!(ck.decrypt(&(-&c0)), m0.wrapping_neg());
+                assert_eq!(ck.decrypt(&c0.abs()), m0.wrapping_abs());
+
+                assert_eq!(ck.decrypt(&c0.lt(&c1)), m0 < m1);
+                assert_eq!(ck.decrypt(&c0.ge(&c1)), m0 >= m1);
+                assert_eq!(ck.decrypt(&c0.max(&c1)), m0.max(m1));
+            }
+
+            // division by zero
+            {
+                reset_error_flags();
+                let m0 = -5i8;
+                let c0: FheInt8 = ck.encrypt(&m0);
+                let c1: FheInt8 = ck.encrypt(&0i8);
+                let (c_q, c_r) = c0.div_rem(&c1);
+                assert_eq!(ck.decrypt(&c_q), 1);
+                assert_eq!(ck.decrypt(&c_r), m0);
+                assert!(ck.decrypt(&div_zero_error_flag().unwrap()));
+            }
+        }
+
         #[test]
         #[cfg(feature = "interactive_mp")]
         fn all_bool_apis() {
             use crate::FheBool;
 
-            set_single_party_parameter_sets(SP_TEST_BOOL_PARAMS);
+            let _session = set_single_party_parameter_sets(SP_TEST_BOOL_PARAMS);
 
             let (ck, sk) = gen_keys();
             sk.set_server_key();
@@ -693,5 +2421,413 @@ mod tests {
                 assert_eq!(out, !a, "Expected {} but got {out}", !a);
             }
         }
+
+        #[test]
+        #[cfg(feature = "interactive_mp")]
+        fn extended_gates() {
+            use crate::{
+                bool::{Backend, BooleanGates},
+                stats, FheBool, Select,
+            };
+
+            let _session = set_single_party_parameter_sets(SP_TEST_BOOL_PARAMS);
+
+            let (ck, sk) = gen_keys();
+            sk.set_server_key();
+
+            for _ in 0..10 {
+                for (a, b, c) in itertools::iproduct!([false, true], [false, true], [false, true]) {
+                    let [c_a, c_b, c_c]: [FheBool; 3] = [a, b, c].map(|m| ck.encrypt(&m));
+                    let decrypt = |c: Vec<u64>| -> bool { ck.decrypt(&FheBool { data: c }) };
+
+                    stats::reset();
+                    let outs = Vec::<u64>::with_evaluator(|e, key| {
+                        let (a, b, c) = (c_a.data(), c_b.data(), c_c.data());
+                        [
+                            e.nand(a, b, key),
+                            e.nor(a, b, key),
+                            e.andny(a, b, key),
+                            e.andyn(a, b, key),
+                            e.orny(a, b, key),
+                            e.oryn(a, b, key),
+                            e.majority(a, b, c, key),
+                            e.mux(a, b, c, key),
+                        ]
+                    });
+                    let report = stats::take();
+                    // majority requires 2 bootstraps and MUX requires 3
+                    assert_eq!(report.gates.bootstraps(), 11);
+                    assert_eq!(report.executed_bootstraps, 11);
+
+                    // frontend evaluates the same gates
+                    let frontend = [
+                        c_a.nand(&c_b),
+                        c_a.nor(&c_b),
+                        c_a.andny(&c_b),
+                        c_a.andyn(&c_b),
+                        c_a.orny(&c_b),
+                        c_a.oryn(&c_b),
+                        c_a.majority(&c_b, &c_c),
+                        c_b.mux(&c_c, &c_a),
+                    ];
+                    assert_eq!(stats::take().gates, report.gates);
+
+                    let frontend = frontend.map(|c| c.data);
+                    for outs in [outs, frontend] {
+                        let [nand, nor, andny, andyn, orny, oryn, majority, mux] =
+                            outs.map(decrypt);
+                        assert_eq!(nand, !(a & b));
+                        assert_eq!(nor, !(a | b));
+                        assert_eq!(andny, !a & b);
+                        assert_eq!(andyn, a & !b);
+                        assert_eq!(orny, !a | b);
+                        assert_eq!(oryn, a | !b);
+                        assert_eq!(majority, (a & b) | (b & c) | (a & c));
+                        assert_eq!(mux, if a { b } else { c });
+                    }
+                }
+            }
+        }
+
+        #[test]
+        #[cfg(feature = "interactive_mp")]
+        fn multi_value_adders() {
+            use crate::{
+                bool::{Backend, BooleanGates},
+                stats, FheBool,
+            };
+
+            let _session = set_single_party_parameter_sets(SP_TEST_ARITHMETIC_PARAMS);
+
+            let (ck, sk) = gen_keys();
+            sk.set_server_key();
+
+            for _ in 0..10 {
+                for (a, b, c) in itertools::iproduct!([false, true], [false, true], [false, true]) {
+                    let [c_a, c_b, c_c]: [FheBool; 3] = [a, b, c].map(|m| ck.encrypt(&m));
+                    let decrypt =
+                        |c: &Vec<u64>| -> bool { ck.decrypt(&FheBool { data: c.clone() }) };
+
+                    stats::reset();
+                    let (half_sum, half_carry, full_sum, full_carry, chained_sum, chained_carry) =
+                        Vec::<u64>::with_evaluator(|e, key| {
+                            let (a, b, c) = (c_a.data(), c_b.data(), c_c.data());
+                            let mut half_sum = a.clone();
+                            let half_carry = e.half_adder(&mut half_sum, b, key);
+                            let mut full_sum = a.clone();
+                            let full_carry = e.full_adder(&mut full_sum, b, c, key);
+                            // outputs of adders as inputs of adders
+                            let mut chained_sum = full_sum.clone();
+                            let chained_carry =
+                                e.full_adder(&mut chained_sum, &full_carry, &half_sum, key);
+                            (
+                                half_sum,
+                                half_carry,
+                                full_sum,
+                                full_carry,
+                                chained_sum,
+                                chained_carry,
+                            )
+                        });
+                    let report = stats::take();
+                    // Half adder requires 2 bootstraps and full adder 3
+                    assert_eq!(report.gates.half_adder, 1);
+                    assert_eq!(report.gates.full_adder, 2);
+                    assert_eq!(report.executed_bootstraps, 8);
+
+                    assert_eq!(decrypt(&half_sum), a ^ b);
+                    assert_eq!(decrypt(&half_carry), a & b);
+                    let (s, carry) = (a ^ b ^ c, (a & b) | (b & c) | (a & c));
+                    assert_eq!(decrypt(&full_sum), s);
+                    assert_eq!(decrypt(&full_carry), carry);
+                    let half_s = a ^ b;
+                    assert_eq!(decrypt(&chained_sum), s ^ carry ^ half_s);
+                    assert_eq!(
+                        decrypt(&chained_carry),
+                        (s & carry) | (carry & half_s) | (s & half_s)
+                    );
+                }
+            }
+        }
+
+        #[test]
+        #[cfg(feature = "interactive_mp")]
+        fn cmux_tree_lookup_with_circuit_bootstrapping() {
+            use crate::{
+                bool::{Backend, BooleanGates},
+                stats, FheBool,
+            };
+
+            let _session = set_single_party_parameter_sets(SP_TEST_CBS_PARAMS);
+
+            let (ck, sk) = gen_keys();
+            sk.set_server_key();
+
+            // Table of 200 entries, hence entries beyond the table are false
+            let table = (0..200)
+                .map(|_| (0..8).map(|_| thread_rng().gen::<bool>()).collect_vec())
+                .collect_vec();
+            for m in [0usize, 199, 255, thread_rng().gen::<u8>() as usize] {
+                let index = (0..8)
+                    .map(|i| ck.encrypt(&((m >> i) & 1 == 1)))
+                    .collect_vec();
+
+                stats::reset();
+                let out_cts = Vec::<u64>::with_evaluator(|e, key| {
+                    let index = index
+                        .iter()
+                        .map(|c: &FheBool| c.data().clone())
+                        .collect_vec();
+                    e.cmux_tree_lookup(&index, &table, 8, key)
+                })
+                .unwrap();
+                // Lookup requires a circuit bootstrap per index bit, each with
+                // 5 + 5 blind rotations
+                let gates = stats::take().gates;
+                assert_eq!(
+                    gates,
+                    stats::GateCounts {
+                        circuit_bootstrap: 8,
+                        circuit_bootstrap_blind_rotations: 80,
+                        ..Default::default()
+                    }
+                );
+                assert_eq!(gates.bootstraps(), 80);
+
+                let expected = table.get(m).cloned().unwrap_or(vec![false; 8]);
+                for (c, bit) in out_cts.iter().zip(expected) {
+                    assert_eq!(ck.decrypt(&FheBool { data: c.clone() }), bit);
+
+                    // Outputs of lookups can be inputs of gates
+                    let b: FheBool = ck.encrypt(&true);
+                    let out = Vec::<u64>::with_evaluator(|e, key| e.xor(c, b.data(), key));
+                    assert_eq!(ck.decrypt(&FheBool { data: out }), !bit);
+                }
+            }
+        }
+
+        #[test]
+        #[cfg(feature = "interactive_mp")]
+        fn table_lookup() {
+            use crate::{stats, FheUint8, MockClientKey, MockFheUint8};
+
+            let _session = set_single_party_parameter_sets(SP_TEST_BOOL_PARAMS);
+
+            let (ck, sk) = gen_keys();
+            sk.set_server_key();
+
+            let mut table = [0u8; 256];
+            thread_rng().fill_bytes(&mut table);
+            for _ in 0..2 {
+                let m = thread_rng().gen::<u8>();
+                let index: FheUint8 = ck.encrypt(&m);
+                let mock_index: MockFheUint8 = MockClientKey.encrypt(&m);
+
+                stats::reset();
+                let out = index.lookup(&table);
+                let report = stats::take();
+                let _ = mock_index.lookup(&table);
+                assert_eq!(ck.decrypt(&out), table[m as usize]);
+                assert_eq!(report.gates, stats::take().gates);
+
+                let out = FheUint8::select_from(&table[..10], &index);
+                assert_eq!(ck.decrypt(&out), if m < 10 { table[m as usize] } else { 0 });
+            }
+        }
+
+        #[test]
+        #[cfg(feature = "interactive_mp")]
+        fn table_lookup_with_circuit_bootstrapping() {
+            use crate::{stats, FheBool, FheUint8};
+
+            let _session = set_single_party_parameter_sets(SP_TEST_CBS_PARAMS);
+
+            let (ck, sk) = gen_keys();
+            sk.set_server_key();
+
+            let mut table = [0u8; 256];
+            thread_rng().fill_bytes(&mut table);
+            for m in [0, 255, thread_rng().gen::<u8>()] {
+                let index: FheUint8 = ck.encrypt(&m);
+
+                // Lookup requires a circuit bootstrap per index bit and no gates
+                stats::reset();
+                let out = index.lookup(&table);
+                let report = stats::take();
+                assert_eq!(ck.decrypt(&out), table[m as usize]);
+                assert_eq!(
+                    report.gates,
+                    stats::GateCounts {
+                        circuit_bootstrap: 8,
+                        circuit_bootstrap_blind_rotations: 80,
+                        ..Default::default()
+                    }
+                );
+
+                let out = FheUint8::select_from(&table[..10], &index);
+                assert_eq!(ck.decrypt(&out), if m < 10 { table[m as usize] } else { 0 });
+
+                // Outputs of lookups can be inputs of gates
+                let b: FheBool = ck.encrypt(&true);
+                let out = &index.lookup(&table) ^ &FheUint8::from_bits(&vec![b; 8]);
+                assert_eq!(ck.decrypt(&out), !table[m as usize]);
+            }
+        }
+
+        #[test]
+        #[cfg(feature = "interactive_mp")]
+        fn array_and_grid() {
+            use crate::{FheArray, FheBool, FheGrid, FheUint8};
+
+            let _session = set_single_party_parameter_sets(SP_TEST_BOOL_PARAMS);
+
+            let (ck, sk) = gen_keys();
+            sk.set_server_key();
+
+            let mut plain = (0..5).map(|_| thread_rng().gen::<u8>()).collect_vec();
+            let mut array = FheArray::new(
+                plain
+                    .iter()
+                    .map(|m| -> FheUint8 { ck.encrypt(m) })
+                    .collect_vec(),
+            );
+            let (index, value) = (thread_rng().gen_range(0..5), thread_rng().gen::<u8>());
+            array.set(&ck.encrypt(&index), &ck.encrypt(&value));
+            plain[index as usize] = value;
+            for index in [thread_rng().gen_range(0..5), 5] {
+                let out = array.get(&ck.encrypt(&index));
+                let expected = plain.get(index as usize).copied().unwrap_or(0);
+                assert_eq!(ck.decrypt(&out), expected);
+            }
+
+            let mut grid = FheGrid::new(3, 2, vec![FheBool::trivial(false); 6]);
+            let (x, y) = (
+                thread_rng().gen_range(0..3u8),
+                thread_rng().gen_range(0..2u8),
+            );
+            let (x, y): (FheUint8, FheUint8) = (ck.encrypt(&x), ck.encrypt(&y));
+            grid.set(&x, &y, &FheBool::trivial(true));
+            assert!(ck.decrypt(&grid.get(&x, &y)));
+            let cells = grid.cells().iter().map(|c| ck.decrypt(c)).collect_vec();
+            assert_eq!(cells.iter().filter(|c| **c).count(), 1);
+        }
+
+        #[test]
+        fn short_uint_lookup_tables() {
+            use crate::{stats, FheBool, FheShortUint};
+
+            let _session = set_single_party_parameter_sets(SP_TEST_LUT_PARAMS);
+
+            let (ck, sk) = gen_keys();
+            sk.set_server_key();
+
+            for m in 0..4u8 {
+                let bits: Vec<FheBool> = [m & 1 == 1, m >> 1 == 1]
+                    .iter()
+                    .map(|b| ck.encrypt(b))
+                    .collect();
+
+                stats::reset();
+                let c = FheShortUint::<2>::from_bits(&bits);
+                assert_eq!(ck.decrypt(&c), m);
+
+                let f = |m: u8| (3 * m + 1) % 4;
+                let c_f = c.apply_lut(f);
+                assert_eq!(ck.decrypt(&c_f), f(m));
+
+                // output of a lookup table is input of the next
+                let c_ff = c_f.apply_lut(f);
+                assert_eq!(ck.decrypt(&c_ff), f(f(m)));
+
+                // f(m) is reduced modulo 2^BITS
+                assert_eq!(ck.decrypt(&c.apply_lut(|m| m + 6)), (m + 6) % 4);
+
+                let is_odd = c_f.apply_lut_to_bool(|m| m & 1 == 1);
+                assert_eq!(ck.decrypt(&is_odd), f(m) & 1 == 1);
+
+                let bits_back: Vec<bool> = c.to_bits().iter().map(|b| ck.decrypt(b)).collect();
+                assert_eq!(bits_back, vec![m & 1 == 1, m >> 1 == 1]);
+
+                // from_bits, 3 lookup tables, apply_lut_to_bool, to_bits
+                let report = stats::take();
+                assert_eq!(report.gates.lut, 2 + 3 + 1 + 2);
+                assert_eq!(report.executed_bootstraps, report.gates.lut);
+
+                // addition does not bootstrap
+                let one = FheShortUint::<2>::trivial(1);
+                let c_min = c.apply_lut(|m| m.min(2));
+                assert_eq!(ck.decrypt(&(&c_min + &one)), m.min(2) + 1);
+                assert_eq!(
+                    ck.decrypt(&(&c_min + &one).apply_lut(|m| 3 - m)),
+                    3 - (m.min(2) + 1)
+                );
+            }
+        }
+
+        #[test]
+        fn short_uint_lookup_tables_over_3_bits() {
+            use crate::{FheBool, FheShortUint};
+
+            let _session = set_single_party_parameter_sets(SP_TEST_LUT_PARAMS);
+
+            let (ck, sk) = gen_keys();
+            sk.set_server_key();
+
+            for _ in 0..8 {
+                let m = thread_rng().gen_range(0..8u8);
+                let bits: Vec<FheBool> = (0..3).map(|i| ck.encrypt(&((m >> i) & 1 == 1))).collect();
+
+                // input of the lookup table is sum of 3 bootstrapped bits
+                let c = FheShortUint::<3>::from_bits(&bits);
+                let f = |m: u8| (5 * m + 3) % 8;
+                let c_f = c.apply_lut(f);
+                assert_eq!(ck.decrypt(&c_f), f(m));
+
+                let bits_back: Vec<bool> = c_f.to_bits().iter().map(|b| ck.decrypt(b)).collect();
+                assert_eq!(
+                    bits_back,
+                    (0..3).map(|i| (f(m) >> i) & 1 == 1).collect_vec()
+                );
+            }
+        }
+
+        #[test]
+        fn radix_uint8_arithmetic() {
+            use crate::{FheBool, FheRadixUint8};
+
+            let _session = set_single_party_parameter_sets(SP_TEST_LUT_PARAMS);
+
+            let (ck, sk) = gen_keys();
+            sk.set_server_key();
+
+            for _ in 0..4 {
+                let a = thread_rng().gen::<u8>();
+                let b = thread_rng().gen::<u8>();
+                let bits_a: Vec<FheBool> =
+                    (0..8).map(|i| ck.encrypt(&((a >> i) & 1 == 1))).collect();
+                let bits_b: Vec<FheBool> =
+                    (0..8).map(|i| ck.encrypt(&((b >> i) & 1 == 1))).collect();
+                let c_a = FheRadixUint8::from_bits(&bits_a);
+                let c_b = FheRadixUint8::from_bits(&bits_b);
+
+                let c_sum = &c_a + &c_b;
+                assert_eq!(ck.decrypt(&c_sum), a.wrapping_add(b));
+                // output of an addition is input of the next
+                let c_diff = &c_sum - &c_b;
+                assert_eq!(ck.decrypt(&c_diff), a);
+
+                let bits_back: Vec<bool> = c_diff.to_bits().iter().map(|b| ck.decrypt(b)).collect();
+                assert_eq!(bits_back, (0..8).map(|i| (a >> i) & 1 == 1).collect_vec());
+            }
+        }
+
+        #[test]
+        #[should_panic]
+        fn short_uint_requires_supported_message_bits() {
+            use crate::FheShortUint;
+
+            let _session = set_single_party_parameter_sets(SP_TEST_BOOL_PARAMS);
+            FheShortUint::<2>::trivial(0);
+        }
     }
 }
@@ -1,21 +1,30 @@
-use std::{cell::RefCell, sync::OnceLock};
+use std::{
+    cell::RefCell,
+    marker::PhantomData,
+    sync::{Arc, OnceLock},
+};
 
 use crate::{
     backend::ModulusPowerOf2,
     bool::parameters::ParameterVariant,
+    ledger::Run,
     random::DefaultSecureRng,
+    serialization::{hash_bytes, Fingerprint},
+    shortint::{ErrorFlags, ERROR_FLAGS},
     utils::{Global, WithLocal},
-    ModularOpsU64, NttBackendU64,
+    BinaryEncoding, Error, ModularOpsU64, NttBackendU64,
 };
 
 use super::{
     evaluator::NonInteractiveMultiPartyCrs,
     keys::{
         CommonReferenceSeededNonInteractiveMultiPartyServerKeyShare,
-        NonInteractiveServerKeyEvaluationDomain, SeededNonInteractiveMultiPartyServerKey,
-        ShoupNonInteractiveServerKeyEvaluationDomain,
+        NonInteractiveMultiPartyClientKey, NonInteractiveServerKeyEvaluationDomain,
+        SeededNonInteractiveMultiPartyServerKey, ShoupNonInteractiveServerKeyEvaluationDomain,
+    },
+    parameters::{
+        BoolParameters, CiphertextModulus, NI_2P, NI_4P_HB_FR, NI_4P_LUT2, NI_4P_LUT3, NI_8P,
     },
-    parameters::{BoolParameters, CiphertextModulus, NI_2P, NI_4P_HB_FR, NI_8P},
     ClientKey,
 };
 
@@ -27,9 +36,22 @@ pub(crate) type BoolEvaluator = super::evaluator::BoolEvaluator<
     ShoupNonInteractiveServerKeyEvaluationDomain<Vec<Vec<u64>>>,
 >;
 
+/// Server key share of a user
+type ServerKeyShare = CommonReferenceSeededNonInteractiveMultiPartyServerKeyShare<
+    Vec<Vec<u64>>,
+    BoolParameters<u64>,
+    NonInteractiveMultiPartyCrs<[u8; 32]>,
+>;
+/// Server key aggregated from server key shares of all users
+type ServerKey = SeededNonInteractiveMultiPartyServerKey<
+    Vec<Vec<u64>>,
+    NonInteractiveMultiPartyCrs<[u8; 32]>,
+    BoolParameters<u64>,
+>;
+
 thread_local! {
     static BOOL_EVALUATOR: RefCell<Option<BoolEvaluator>> = RefCell::new(None);
-
+    static ACTIVE_SESSION: RefCell<Option<ActiveSession>> = const { RefCell::new(None) };
 }
 static BOOL_SERVER_KEY: OnceLock<ShoupNonInteractiveServerKeyEvaluationDomain<Vec<Vec<u64>>>> =
     OnceLock::new();
@@ -40,43 +62,361 @@ pub enum ParameterSelector {
     NonInteractiveLTE2Party,
     NonInteractiveLTE4Party,
     NonInteractiveLTE8Party,
+    /// At most 4 parties. Supports lookup tables over 2 bit messages (i.e.
+    /// `FheShortUint<2>`), whereas parameter sets above support lookup tables
+    /// over 1 bit messages only.
+    NonInteractiveLTE4PartyLut2Bits,
+    /// At most 4 parties. Supports lookup tables over 3 bit messages.
+    NonInteractiveLTE4PartyLut3Bits,
+}
+
+impl ParameterSelector {
+    fn parameters(&self) -> BoolParameters<u64> {
+        match self {
+            ParameterSelector::NonInteractiveLTE2Party => NI_2P,
+            ParameterSelector::NonInteractiveLTE4Party => NI_4P_HB_FR,
+            ParameterSelector::NonInteractiveLTE8Party => NI_8P,
+            ParameterSelector::NonInteractiveLTE4PartyLut2Bits => NI_4P_LUT2,
+            ParameterSelector::NonInteractiveLTE4PartyLut3Bits => NI_4P_LUT3,
+        }
+    }
 }
 
 pub fn set_parameter_set(select: ParameterSelector) {
-    match select {
-        ParameterSelector::NonInteractiveLTE2Party => {
-            BOOL_EVALUATOR.with_borrow_mut(|v| *v = Some(BoolEvaluator::new(NI_2P)));
+    BOOL_EVALUATOR.with_borrow_mut(|v| *v = Some(BoolEvaluator::new(select.parameters())));
+}
+
+/// Same as `set_parameter_set`, but returns `Error::ParametersAlreadySet`
+/// instead of replacing different parameters already set on the current
+/// thread
+pub fn try_set_parameter_set(select: ParameterSelector) -> Result<(), Error> {
+    let parameters = select.parameters();
+    BOOL_EVALUATOR.with_borrow_mut(|v| match v {
+        Some(e) if e.parameters() != &parameters => Err(Error::ParametersAlreadySet),
+        Some(_) => Ok(()),
+        None => {
+            *v = Some(BoolEvaluator::new(parameters));
+            Ok(())
         }
-        ParameterSelector::NonInteractiveLTE4Party => {
-            BOOL_EVALUATOR.with_borrow_mut(|v| *v = Some(BoolEvaluator::new(NI_4P_HB_FR)));
+    })
+}
+
+pub fn set_common_reference_seed(seed: [u8; 32]) {
+    try_set_common_reference_seed(seed).unwrap_or_else(|e| panic!("{e}"))
+}
+
+/// Same as `set_common_reference_seed`, but returns `Error` instead of
+/// panicking if parameters are not set or do not support non-interactive
+/// multi-party, or if the seed is already set
+pub fn try_set_common_reference_seed(seed: [u8; 32]) -> Result<(), Error> {
+    if try_with_evaluator(|e| e.parameters().variant().clone())?
+        != ParameterVariant::NonInteractiveMultiParty
+    {
+        return Err(Error::UnsupportedParameters);
+    }
+    MULTI_PARTY_CRS
+        .set(NonInteractiveMultiPartyCrs { seed })
+        .map_err(|_| Error::CommonReferenceSeedAlreadySet)
+}
+
+/// Non-interactive multi-party session of a single application
+///
+/// Session owns parameters, common reference string, and the server key of
+/// the application. Unlike `set_parameter_set`, `set_common_reference_seed`,
+/// and `set_server_key` which set them once for the whole process, any no. of
+/// sessions can coexist in a single process and a session is torn down once
+/// it, and all its clones, are dropped.
+///
+/// Functions of the protocol (`gen_client_key`, `gen_server_key_share`, ...)
+/// and all operations on ciphertexts use the session entered on the current
+/// thread with `Session::enter`. If no session is entered, they fall back to
+/// the process wide parameters, common reference string, and server key.
+///
+/// Session is cheap to clone and can be shared across threads. Each thread
+/// must enter the session before using it.
+#[derive(Clone)]
+pub struct Session {
+    inner: Arc<SessionInner>,
+}
+
+struct SessionInner {
+    parameters: BoolParameters<u64>,
+    crs: NonInteractiveMultiPartyCrs<[u8; 32]>,
+    id: u64,
+    server_key: OnceLock<RuntimeServerKey>,
+    error_flags: ErrorFlags,
+}
+
+/// Session entered on a thread along with the thread's evaluator
+struct ActiveSession {
+    session: Arc<SessionInner>,
+    evaluator: BoolEvaluator,
+}
+
+/// Keeps the session entered on the current thread until dropped. Returned by
+/// `Session::enter`.
+///
+/// On drop, the previously entered session (if any) is entered again. Hence
+/// nested guards must be dropped in the reverse order of creation.
+#[must_use = "session is exited as soon as the guard is dropped"]
+pub struct SessionGuard {
+    previous: Option<ActiveSession>,
+    // session is entered on the thread that created the guard
+    _not_send: PhantomData<*const ()>,
+}
+
+impl Session {
+    /// Creates new session with parameter variant `select` and application
+    /// specific common reference seed `seed`
+    pub fn new(select: ParameterSelector, seed: [u8; 32]) -> Session {
+        Session::with_id(select, seed, 0)
+    }
+
+    /// Creates new session like `Session::new` with application specific
+    /// session id `id`. All parties of the session must use the same id.
+    ///
+    /// Values encoded with `BinaryEncoding` in the session can only be decoded
+    /// in a session with the same id (refer to `fingerprint`). Hence the id
+    /// separates protocol instances that share parameters and common
+    /// reference seed. Session created with `Session::new` has id 0.
+    pub fn with_id(select: ParameterSelector, seed: [u8; 32], id: u64) -> Session {
+        Session {
+            inner: Arc::new(SessionInner {
+                parameters: select.parameters(),
+                crs: NonInteractiveMultiPartyCrs { seed },
+                id,
+                server_key: OnceLock::new(),
+                error_flags: ErrorFlags::default(),
+            }),
         }
-        ParameterSelector::NonInteractiveLTE8Party => {
-            BOOL_EVALUATOR.with_borrow_mut(|v| *v = Some(BoolEvaluator::new(NI_8P)));
+    }
+
+    pub fn id(&self) -> u64 {
+        self.inner.id
+    }
+
+    /// Returns fingerprint of parameters, common reference seed, and id of
+    /// the session
+    pub fn fingerprint(&self) -> Fingerprint {
+        self.inner.fingerprint()
+    }
+
+    /// Enters the session on the current thread until the returned guard is
+    /// dropped
+    pub fn enter(&self) -> SessionGuard {
+        self.enter_with_evaluator(BoolEvaluator::new(self.inner.parameters.clone()))
+    }
+
+    /// Enters the session with `evaluator`, which must have been created for
+    /// the session. Evaluator can be taken back with `SessionGuard::exit`.
+    pub(crate) fn enter_with_evaluator(&self, evaluator: BoolEvaluator) -> SessionGuard {
+        let active = ActiveSession {
+            session: self.inner.clone(),
+            evaluator,
+        };
+        SessionGuard {
+            previous: ACTIVE_SESSION.with_borrow_mut(|s| s.replace(active)),
+            _not_send: PhantomData,
         }
     }
-}
 
-pub fn set_common_reference_seed(seed: [u8; 32]) {
-    BoolEvaluator::with_local(|e| {
-        assert_eq!(
-            e.parameters().variant(),
-            &ParameterVariant::NonInteractiveMultiParty,
-            "Set parameters do not support Non interactive multi-party"
+    /// Sets the server key of the session
+    pub fn set_server_key(
+        &self,
+        server_key: &SeededNonInteractiveMultiPartyServerKey<
+            Vec<Vec<u64>>,
+            NonInteractiveMultiPartyCrs<[u8; 32]>,
+            BoolParameters<u64>,
+        >,
+    ) {
+        let eval_key = NonInteractiveServerKeyEvaluationDomain::<
+            _,
+            BoolParameters<u64>,
+            DefaultSecureRng,
+            NttBackendU64,
+        >::from(server_key);
+        assert!(
+            self.inner
+                .server_key
+                .set(ShoupNonInteractiveServerKeyEvaluationDomain::from(eval_key))
+                .is_ok(),
+            "Attempted to set server key of the session twice!"
         );
-    });
+    }
 
-    assert!(
-        MULTI_PARTY_CRS
-            .set(NonInteractiveMultiPartyCrs { seed: seed })
-            .is_ok(),
-        "Attempted to set MP SEED twice."
-    )
+    /// Same as `Session::set_server_key`, but returns `Error` instead of
+    /// panicking if the server key of the session is already set, or
+    /// `Error::ParameterMismatch` if `server_key` was generated with
+    /// different parameters or common reference seed than the session's
+    pub fn try_set_server_key(&self, server_key: &ServerKey) -> Result<(), Error> {
+        if server_key.parameters() != &self.inner.parameters
+            || server_key.cr_seed() != &self.inner.crs
+        {
+            return Err(Error::ParameterMismatch);
+        }
+        if self.inner.server_key.get().is_some() {
+            return Err(Error::ServerKeyAlreadySet);
+        }
+        let eval_key = NonInteractiveServerKeyEvaluationDomain::<
+            _,
+            BoolParameters<u64>,
+            DefaultSecureRng,
+            NttBackendU64,
+        >::from(server_key);
+        self.inner
+            .server_key
+            .set(ShoupNonInteractiveServerKeyEvaluationDomain::from(eval_key))
+            .map_err(|_| Error::ServerKeyAlreadySet)
+    }
+}
+
+impl SessionInner {
+    fn fingerprint(&self) -> Fingerprint {
+        Fingerprint::new(&self.parameters, &self.crs.seed, self.id)
+    }
+}
+
+impl SessionGuard {
+    /// Exits the session and returns evaluator of the session on the current
+    /// thread
+    pub(crate) fn exit(mut self) -> BoolEvaluator {
+        let previous = self.previous.take();
+        let active = ACTIVE_SESSION.with_borrow_mut(|s| std::mem::replace(s, previous));
+        // previous session is already restored
+        std::mem::forget(self);
+        active.expect("Session not entered").evaluator
+    }
+}
+
+impl Drop for SessionGuard {
+    fn drop(&mut self) {
+        let previous = self.previous.take();
+        ACTIVE_SESSION.with_borrow_mut(|s| *s = previous);
+    }
+}
+
+/// Returns fingerprint of the active session, otherwise fingerprint of the
+/// process wide parameters and common reference string (with session id 0)
+///
+/// Values encoded with `BinaryEncoding` carry the fingerprint they were
+/// encoded with and can only be decoded where `fingerprint` returns the same.
+///
+/// Panics if no session is entered and parameters or the common reference seed
+/// are not set
+pub fn fingerprint() -> Fingerprint {
+    try_fingerprint().unwrap_or_else(|e| panic!("{e}"))
+}
+
+/// Same as `fingerprint`, but returns `Error::ParametersNotSet` or
+/// `Error::CommonReferenceSeedNotSet` instead of panicking if no session is
+/// entered and parameters or the process wide common reference string are not
+/// set
+pub(crate) fn try_fingerprint() -> Result<Fingerprint, Error> {
+    let active =
+        ACTIVE_SESSION.with_borrow(|s| s.as_ref().map(|active| active.session.fingerprint()));
+    match active {
+        Some(fingerprint) => Ok(fingerprint),
+        None => try_with_evaluator(|e| {
+            Ok(Fingerprint::new(
+                e.parameters(),
+                &try_multi_party_crs()?.seed,
+                0,
+            ))
+        })?,
+    }
+}
+
+/// Same as `multi_party_crs`, but returns `Error::CommonReferenceSeedNotSet`
+/// instead of panicking if neither a session is entered nor the process wide
+/// common reference string is set
+fn try_multi_party_crs() -> Result<NonInteractiveMultiPartyCrs<[u8; 32]>, Error> {
+    ACTIVE_SESSION.with_borrow(|s| match s {
+        Some(active) => Ok(active.session.crs.clone()),
+        None => MULTI_PARTY_CRS
+            .get()
+            .cloned()
+            .ok_or(Error::CommonReferenceSeedNotSet),
+    })
+}
+
+/// Same as `BoolEvaluator::with_local`, but returns `Error::ParametersNotSet`
+/// instead of panicking if neither a session is entered nor parameters are set
+/// on the current thread
+pub(crate) fn try_with_evaluator<F, R>(func: F) -> Result<R, Error>
+where
+    F: FnOnce(&BoolEvaluator) -> R,
+{
+    ACTIVE_SESSION.with_borrow(|s| match s {
+        Some(active) => Ok(func(&active.evaluator)),
+        None => BOOL_EVALUATOR.with_borrow(|e| e.as_ref().map(func).ok_or(Error::ParametersNotSet)),
+    })
+}
+
+/// Runs `func` with error flags of the active session, otherwise with error
+/// flags of the current thread
+pub(crate) fn with_error_flags<F, R>(func: F) -> R
+where
+    F: FnOnce(&ErrorFlags) -> R,
+{
+    // session is not borrowed while `func` runs, hence `func` can evaluate
+    // gates
+    match ACTIVE_SESSION.with_borrow(|s| s.as_ref().map(|active| active.session.clone())) {
+        Some(session) => func(&session.error_flags),
+        None => ERROR_FLAGS.with(func),
+    }
+}
+
+/// Same as `with_evaluator_and_server_key`, but returns `Error` instead of
+/// panicking if parameters or the server key are not set
+fn try_with_evaluator_and_server_key<F, R>(func: F) -> Result<R, Error>
+where
+    F: FnOnce(&mut BoolEvaluator, &RuntimeServerKey) -> R,
+{
+    ACTIVE_SESSION.with_borrow_mut(|s| match s {
+        Some(active) => match active.session.server_key.get() {
+            Some(server_key) => Ok(func(&mut active.evaluator, server_key)),
+            None => Err(Error::ServerKeyNotSet),
+        },
+        None => BOOL_EVALUATOR.with_borrow_mut(|e| match (e.as_mut(), BOOL_SERVER_KEY.get()) {
+            (None, _) => Err(Error::ParametersNotSet),
+            (_, None) => Err(Error::ServerKeyNotSet),
+            (Some(e), Some(server_key)) => Ok(func(e, server_key)),
+        }),
+    })
+}
+
+/// Runs `func` with the evaluator and the server key of the active session,
+/// otherwise with the thread local evaluator and the process wide server key
+pub(crate) fn with_evaluator_and_server_key<F, R>(func: F) -> R
+where
+    F: FnOnce(&mut BoolEvaluator, &RuntimeServerKey) -> R,
+{
+    ACTIVE_SESSION.with_borrow_mut(|s| match s {
+        Some(active) => {
+            let server_key = active
+                .session
+                .server_key
+                .get()
+                .expect("Server key of the session not set!");
+            func(&mut active.evaluator, server_key)
+        }
+        None => BOOL_EVALUATOR.with_borrow_mut(|e| {
+            func(
+                e.as_mut().expect("Parameters not set"),
+                RuntimeServerKey::global(),
+            )
+        }),
+    })
 }
 
 pub fn gen_client_key() -> ClientKey {
     BoolEvaluator::with_local(|e| e.client_key())
 }
 
+/// Generate client's share of the server key
+///
+/// Panics if `client_key` already generated a different share with the same
+/// common reference seed (refer to `ClientKey::allow_conflicting_runs`)
 pub fn gen_server_key_share(
     user_id: usize,
     total_users: usize,
@@ -86,10 +426,39 @@ pub fn gen_server_key_share(
     BoolParameters<u64>,
     NonInteractiveMultiPartyCrs<[u8; 32]>,
 > {
-    BoolEvaluator::with_local(|e| {
-        let cr_seed = NonInteractiveMultiPartyCrs::global();
-        e.gen_non_interactive_multi_party_key_share(cr_seed, user_id, total_users, client_key)
-    })
+    try_gen_server_key_share(user_id, total_users, client_key).unwrap_or_else(|e| panic!("{e}"))
+}
+
+/// Same as `gen_server_key_share`, but returns `Error` instead of panicking if
+/// parameters or common reference seed are not set, `client_key` was generated
+/// with different parameters, `user_id` is not less than `total_users`, or
+/// `client_key` already participated in a different run with the same common
+/// reference seed
+pub fn try_gen_server_key_share(
+    user_id: usize,
+    total_users: usize,
+    client_key: &ClientKey,
+) -> Result<ServerKeyShare, Error> {
+    let cr_seed = try_multi_party_crs()?;
+    if !try_with_evaluator(|e| e.parameters() == client_key.parameters())? {
+        return Err(Error::ParameterMismatch);
+    }
+    if user_id >= total_users {
+        return Err(Error::InvalidUserId {
+            user_id,
+            total_users,
+        });
+    }
+    let server_key_share = BoolEvaluator::with_local(|e| {
+        e.gen_non_interactive_multi_party_key_share(&cr_seed, user_id, total_users, client_key)
+    });
+    // share is handed out only after its run is recorded
+    client_key.runs().record(Run {
+        cr_seed: cr_seed.seed,
+        user_id,
+        key_share_hash: hash_bytes(server_key_share.to_bytes()?),
+    })?;
+    Ok(server_key_share)
 }
 
 pub fn aggregate_server_key_shares(
@@ -103,10 +472,18 @@ pub fn aggregate_server_key_shares(
     NonInteractiveMultiPartyCrs<[u8; 32]>,
     BoolParameters<u64>,
 > {
-    BoolEvaluator::with_local(|e| {
-        let cr_seed = NonInteractiveMultiPartyCrs::global();
-        e.aggregate_non_interactive_multi_party_server_key_shares(cr_seed, shares)
-    })
+    try_aggregate_server_key_shares(shares).unwrap_or_else(|e| panic!("{e}"))
+}
+
+/// Same as `aggregate_server_key_shares`, but returns `Error` instead of
+/// panicking if parameters or common reference seed are not set, or if
+/// `shares` are not exactly one share per user generated with parameters and
+/// common reference seed of the protocol instance
+pub fn try_aggregate_server_key_shares(shares: &[ServerKeyShare]) -> Result<ServerKey, Error> {
+    let cr_seed = try_multi_party_crs()?;
+    try_with_evaluator(|e| {
+        e.aggregate_non_interactive_multi_party_server_key_shares(&cr_seed, shares)
+    })?
 }
 
 impl
@@ -117,18 +494,24 @@ impl
     >
 {
     pub fn set_server_key(&self) {
+        self.try_set_server_key().unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Same as `set_server_key`, but returns `Error::ServerKeyAlreadySet`
+    /// instead of panicking if the server key is already set
+    pub fn try_set_server_key(&self) -> Result<(), Error> {
+        if BOOL_SERVER_KEY.get().is_some() {
+            return Err(Error::ServerKeyAlreadySet);
+        }
         let eval_key = NonInteractiveServerKeyEvaluationDomain::<
             _,
             BoolParameters<u64>,
             DefaultSecureRng,
             NttBackendU64,
         >::from(self);
-        assert!(
-            BOOL_SERVER_KEY
-                .set(ShoupNonInteractiveServerKeyEvaluationDomain::from(eval_key))
-                .is_ok(),
-            "Attempted to set server key twice!"
-        );
+        BOOL_SERVER_KEY
+            .set(ShoupNonInteractiveServerKeyEvaluationDomain::from(eval_key))
+            .map_err(|_| Error::ServerKeyAlreadySet)
     }
 }
 
@@ -146,21 +529,34 @@ impl WithLocal for BoolEvaluator {
     where
         F: Fn(&Self) -> R,
     {
-        BOOL_EVALUATOR.with_borrow(|s| func(s.as_ref().expect("Parameters not set")))
+        ACTIVE_SESSION.with_borrow(|s| match s {
+            Some(active) => func(&active.evaluator),
+            None => BOOL_EVALUATOR.with_borrow(|s| func(s.as_ref().expect("Parameters not set"))),
+        })
     }
 
     fn with_local_mut<F, R>(func: F) -> R
     where
         F: Fn(&mut Self) -> R,
     {
-        BOOL_EVALUATOR.with_borrow_mut(|s| func(s.as_mut().expect("Parameters not set")))
+        ACTIVE_SESSION.with_borrow_mut(|s| match s {
+            Some(active) => func(&mut active.evaluator),
+            None => {
+                BOOL_EVALUATOR.with_borrow_mut(|s| func(s.as_mut().expect("Parameters not set")))
+            }
+        })
     }
 
     fn with_local_mut_mut<F, R>(func: &mut F) -> R
     where
         F: FnMut(&mut Self) -> R,
     {
-        BOOL_EVALUATOR.with_borrow_mut(|s| func(s.as_mut().expect("Parameters not set")))
+        ACTIVE_SESSION.with_borrow_mut(|s| match s {
+            Some(active) => func(&mut active.evaluator),
+            None => {
+                BOOL_EVALUATOR.with_borrow_mut(|s| func(s.as_mut().expect("Parameters not set")))
+            }
+        })
     }
 }
 
@@ -200,7 +596,8 @@ mod impl_enc_dec {
     use crate::{
         bool::{evaluator::BoolEncoding, keys::NonInteractiveMultiPartyClientKey},
         multi_party::{
-            multi_party_aggregate_decryption_shares_and_decrypt, multi_party_decryption_share,
+            check_decryption_shares, multi_party_aggregate_decryption_shares_and_decrypt,
+            multi_party_decryption_share, DecryptionShare,
         },
         pbs::{sample_extract, PbsInfo, WithShoupRepr},
         random::{NewWithSeed, RandomFillUniformInModulus},
@@ -360,19 +757,41 @@ mod impl_enc_dec {
         <Mat as Matrix>::R:
             TryConvertFrom1<[K::Element], CiphertextModulus<<Mat as Matrix>::MatElement>>,
     {
-        type DecryptionShare = <Mat as Matrix>::MatElement;
+        type DecryptionShare = DecryptionShare;
 
+        /// Returns the share previously generated for `c`, if any, since
+        /// generating two different shares of the same ciphertext leaks the
+        /// secret
+        ///
+        /// Panics if the key participated in more than one run of the protocol
+        /// with the active common reference seed, or the share cannot be
+        /// recorded
         fn gen_decryption_share(&self, c: &<Mat as Matrix>::R) -> Self::DecryptionShare {
-            BoolEvaluator::with_local(|e| {
-                DefaultSecureRng::with_local_mut(|rng| {
-                    multi_party_decryption_share(
-                        c,
-                        self.sk_rlwe().as_slice(),
-                        e.pbs_info().modop_rlweq(),
-                        rng,
-                    )
+            self.try_gen_decryption_share(c)
+                .unwrap_or_else(|e| panic!("{e}"))
+        }
+
+        fn try_gen_decryption_share(
+            &self,
+            c: &<Mat as Matrix>::R,
+        ) -> Result<Self::DecryptionShare, Error> {
+            if let Ok(crs) = try_multi_party_crs() {
+                self.runs().check(&crs.seed)?;
+            }
+            self.decryption_shares()
+                .get_or_insert_with(c, || {
+                    BoolEvaluator::with_local(|e| {
+                        DefaultSecureRng::with_local_mut(|rng| {
+                            multi_party_decryption_share(
+                                c,
+                                self.sk_rlwe().as_slice(),
+                                e.pbs_info().modop_rlweq(),
+                                rng,
+                            )
+                        })
+                    })
                 })
-            })
+                .map(DecryptionShare)
         }
 
         fn aggregate_decryption_shares(
@@ -380,16 +799,34 @@ mod impl_enc_dec {
             c: &<Mat as Matrix>::R,
             shares: &[Self::DecryptionShare],
         ) -> bool {
+            let shares = shares.iter().map(|s| s.0).collect_vec();
             BoolEvaluator::with_local(|e| {
                 let noisy_m = multi_party_aggregate_decryption_shares_and_decrypt(
                     c,
-                    shares,
+                    &shares,
                     e.pbs_info().modop_rlweq(),
                 );
 
                 e.pbs_info().rlwe_q().decode(noisy_m)
             })
         }
+
+        fn try_aggregate_decryption_shares(
+            &self,
+            c: &<Mat as Matrix>::R,
+            shares: &[Self::DecryptionShare],
+        ) -> Result<bool, Error> {
+            let values = shares.iter().map(|s| s.0).collect_vec();
+            try_with_evaluator(|e| {
+                check_decryption_shares(
+                    c,
+                    &values,
+                    e.parameters().rlwe_n().0,
+                    e.pbs_info().rlwe_q(),
+                )
+            })??;
+            Ok(self.aggregate_decryption_shares(c, shares))
+        }
     }
 
     impl KeySwitchWithId<Mat> for Mat {
@@ -398,9 +835,8 @@ mod impl_enc_dec {
         ///
         /// - user_id: user j's user_id in the protocol
         fn key_switch(&self, user_id: usize) -> Mat {
-            BoolEvaluator::with_local(|e| {
+            with_evaluator_and_server_key(|e, server_key| {
                 assert!(self.dimension() == (2, e.parameters().rlwe_n().0));
-                let server_key = BOOL_SERVER_KEY.get().unwrap();
                 let ksk = server_key.ui_to_s_ksk(user_id);
                 let decomposer = e.ni_ui_to_s_ks_decomposer().as_ref().unwrap();
 
@@ -415,6 +851,24 @@ mod impl_enc_dec {
                 )
             })
         }
+
+        fn try_key_switch(&self, user_id: usize) -> Result<Mat, Error> {
+            try_with_evaluator_and_server_key(|e, server_key| {
+                let total_users = server_key.total_users();
+                if user_id >= total_users {
+                    return Err(Error::InvalidUserId {
+                        user_id,
+                        total_users,
+                    });
+                }
+                let ring_size = e.parameters().rlwe_n().0;
+                if self.len() != 2 || self.iter().any(|r| r.len() != ring_size) {
+                    return Err(Error::InvalidCiphertext);
+                }
+                Ok(())
+            })??;
+            Ok(self.key_switch(user_id))
+        }
     }
 
     impl<C> KeySwitchWithId<BatchedFheBools<C>> for NonInteractiveBatchedFheBools<C>
@@ -437,23 +891,143 @@ mod impl_enc_dec {
                 .collect_vec();
             BatchedFheBools { data }
         }
+
+        fn try_key_switch(&self, user_id: usize) -> Result<BatchedFheBools<C>, Error> {
+            let data = self
+                .data
+                .iter()
+                .map(|c| c.try_key_switch(user_id))
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(BatchedFheBools { data })
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use itertools::Itertools;
+    use itertools::{izip, Itertools};
     use rand::{thread_rng, RngCore};
 
-    use crate::{
-        backend::Modulus,
-        bool::{
-            keys::tests::{ideal_sk_rlwe, measure_noise_lwe},
-            BooleanGates,
-        },
-        utils::tests::Stats,
-        Encoder, Encryptor, KeySwitchWithId, MultiPartyDecryptor,
-    };
+    use crate::{Encryptor, KeySwitchWithId, MultiPartyDecryptor};
 
     use super::*;
+
+    #[test]
+    fn serialized_keys_and_ciphertexts_round_trip() {
+        use crate::{BinaryEncoding, FheUint8, SampleExtractor};
+
+        /// Returns `value` decoded from its bytes after checking that the
+        /// decoded value encodes to the same bytes
+        fn round_trip<T: BinaryEncoding>(value: &T) -> T {
+            let bytes = value.to_bytes().unwrap();
+            let decoded = T::from_bytes(&bytes).unwrap();
+            assert_eq!(decoded.to_bytes().unwrap(), bytes);
+            decoded
+        }
+
+        let mut seed = [0u8; 32];
+        thread_rng().fill_bytes(&mut seed);
+        let session = Session::new(ParameterSelector::NonInteractiveLTE2Party, seed);
+        let _guard = session.enter();
+
+        let parties = 2;
+        let cks = (0..parties)
+            .map(|_| round_trip(&gen_client_key()))
+            .collect_vec();
+        let m = [13u8, 200];
+        let seeded_cts = izip!(cks.iter(), m.iter())
+            .map(|(k, m)| round_trip(&k.encrypt(vec![*m].as_slice())))
+            .collect_vec();
+        let server_key_shares = cks
+            .iter()
+            .enumerate()
+            .map(|(user_id, k)| round_trip(&gen_server_key_share(user_id, parties, k)))
+            .collect_vec();
+        let server_key = round_trip(&aggregate_server_key_shares(&server_key_shares));
+        session.set_server_key(&server_key);
+
+        let cts: Vec<FheUint8> = seeded_cts
+            .iter()
+            .enumerate()
+            .map(|(user_id, c)| {
+                round_trip(
+                    &c.unseed::<Vec<Vec<u64>>>()
+                        .key_switch(user_id)
+                        .extract_at(0),
+                )
+            })
+            .collect_vec();
+        let c_out = &cts[0] + &cts[1];
+        let m_out = cks[0].aggregate_decryption_shares(
+            &c_out,
+            &cks.iter()
+                .map(|k| round_trip(&k.gen_decryption_share(&c_out)))
+                .collect_vec(),
+        );
+        assert_eq!(m_out, m[0].wrapping_add(m[1]));
+    }
+
+    #[test]
+    fn fallible_api_returns_errors() {
+        use crate::{Error, FheUint8, SampleExtractor};
+
+        let mut seed = [0u8; 32];
+        thread_rng().fill_bytes(&mut seed);
+        let session = Session::new(ParameterSelector::NonInteractiveLTE2Party, seed);
+        let _guard = session.enter();
+
+        let parties = 2;
+        let cks = (0..parties).map(|_| gen_client_key()).collect_vec();
+        assert!(matches!(
+            try_gen_server_key_share(parties, parties, &cks[0]).err(),
+            Some(Error::InvalidUserId {
+                user_id: 2,
+                total_users: 2
+            })
+        ));
+
+        let ct = cks[1]
+            .encrypt(vec![42u8].as_slice())
+            .unseed::<Vec<Vec<u64>>>();
+        assert!(matches!(
+            ct.try_key_switch(1).err(),
+            Some(Error::ServerKeyNotSet)
+        ));
+
+        let server_key_shares = cks
+            .iter()
+            .enumerate()
+            .map(|(user_id, k)| try_gen_server_key_share(user_id, parties, k).unwrap())
+            .collect_vec();
+        assert!(matches!(
+            try_gen_server_key_share(1, parties, &cks[0]).err(),
+            Some(Error::ConflictingRun { user_id: 0 })
+        ));
+        assert!(matches!(
+            try_aggregate_server_key_shares(&server_key_shares[..1]).err(),
+            Some(Error::InvalidShare { user_id: 0 })
+        ));
+        let server_key = try_aggregate_server_key_shares(&server_key_shares).unwrap();
+        assert!(session.try_set_server_key(&server_key).is_ok());
+        assert!(matches!(
+            session.try_set_server_key(&server_key),
+            Err(Error::ServerKeyAlreadySet)
+        ));
+
+        assert!(matches!(
+            ct.try_key_switch(parties).err(),
+            Some(Error::InvalidUserId {
+                user_id: 2,
+                total_users: 2
+            })
+        ));
+        let ct: FheUint8 = ct.try_key_switch(1).unwrap().extract_at(0);
+        let m = cks[0].try_aggregate_decryption_shares(
+            &ct,
+            &cks.iter()
+                .map(|k| k.gen_decryption_share(&ct))
+                .collect_vec(),
+        );
+        assert_eq!(m.unwrap(), 42u8);
+    }
 }
@@ -158,10 +158,24 @@ pub struct BoolParameters<El> {
     /// and must be supplied only for non-interactive multi-party
     non_interactive_ui_to_s_key_switch_decomposer:
         Option<(DecompostionLogBase, DecompositionCount)>,
+    /// Decomposition parameters for scheme switching RLWE(m) to RLWE(-sm)
+    /// with key switching key RLWE'(s^2) in circuit bootstrapping.
+    ///
+    /// Decomposition parameters for scheme switching are optional and must be
+    /// supplied only if server key supports circuit bootstrapping
+    scheme_switch_decomposer_params: Option<(DecompostionLogBase, DecompositionCount)>,
     /// Group generator for Z^*_{br_q}
     g: usize,
     /// Window size parameter for LMKC++ blind rotation
     w: usize,
+    /// Max. message bits of lookup tables (refer to `FheShortUint`) for which
+    /// bootstrap fails with probability at most 2^-40 with max. no. of parties
+    /// of the parameter set.
+    ///
+    /// Every additional message bit halves the noise margin at the input of
+    /// blind rotation. Refer to `print_noise::check_lut_failure_probability`
+    /// for the measurement.
+    max_lut_message_bits: usize,
     /// Parameter variant
     variant: ParameterVariant,
 }
@@ -203,6 +217,10 @@ impl<El> BoolParameters<El> {
         self.w
     }
 
+    pub(crate) fn max_lut_message_bits(&self) -> usize {
+        self.max_lut_message_bits
+    }
+
     pub(crate) fn rlwe_by_rgsw_decomposition_params(
         &self,
     ) -> &(
@@ -357,6 +375,30 @@ impl<El> BoolParameters<El> {
         )
     }
 
+    #[cfg(test)]
+    pub(crate) fn scheme_switch_decomposition_count(&self) -> DecompositionCount {
+        let params = self.scheme_switch_decomposer_params.expect(&format!(
+            "Parameter variant {:?} does not support circuit bootstrapping",
+            self.variant
+        ));
+        params.decomposition_count()
+    }
+
+    /// Returns decomposer for scheme switching in circuit bootstrapping if
+    /// parameters support it
+    pub(crate) fn scheme_switch_decomposer<D: Decomposer<Element = El>>(&self) -> Option<D>
+    where
+        El: Copy,
+    {
+        self.scheme_switch_decomposer_params.map(|params| {
+            D::new(
+                self.rlwe_q.0,
+                params.decomposition_base().0,
+                params.decomposition_count().0,
+            )
+        })
+    }
+
     /// Returns dlogs of `g` for which auto keys are required as
     /// per the parameter. Given that autos are required for [-g, g, g^2, ...,
     /// g^w] function returns the following [0, 1, 2, ..., w] where `w` is
@@ -406,7 +448,7 @@ impl<T: ConstZero> CiphertextModulus<T> {
         Self(T::ZERO, true)
     }
 
-    const fn new_non_native(q: T) -> Self {
+    pub(crate) const fn new_non_native(q: T) -> Self {
         Self(q, false)
     }
 }
@@ -523,6 +565,195 @@ where
     }
 }
 
+#[cfg(any(feature = "interactive_mp", feature = "non_interactive_mp"))]
+mod impl_serialization {
+    use crate::serialization::{Decode, DecodeError, Encode, Reader, Writer};
+
+    use super::*;
+
+    impl Encode for CiphertextModulus<u64> {
+        fn encode(&self, w: &mut Writer) {
+            w.u64(self.0);
+            w.bool(self.1);
+        }
+    }
+
+    impl Decode for CiphertextModulus<u64> {
+        fn decode(r: &mut Reader) -> Result<Self, DecodeError> {
+            let (q, native) = (r.u64()?, r.bool()?);
+            if (native && q != 0) || (!native && q < 2) {
+                return Err(DecodeError::Invalid(format!("invalid modulus {q}")));
+            }
+            Ok(CiphertextModulus(q, native))
+        }
+    }
+
+    impl Encode for SecretKeyDistribution {
+        fn encode(&self, w: &mut Writer) {
+            w.u8(match self {
+                SecretKeyDistribution::ErrorDistribution => 0,
+                SecretKeyDistribution::TernaryDistribution => 1,
+            });
+        }
+    }
+
+    impl Decode for SecretKeyDistribution {
+        fn decode(r: &mut Reader) -> Result<Self, DecodeError> {
+            match r.u8()? {
+                0 => Ok(SecretKeyDistribution::ErrorDistribution),
+                1 => Ok(SecretKeyDistribution::TernaryDistribution),
+                v => Err(DecodeError::Invalid(format!(
+                    "invalid secret key distribution {v}"
+                ))),
+            }
+        }
+    }
+
+    impl Encode for ParameterVariant {
+        fn encode(&self, w: &mut Writer) {
+            w.u8(match self {
+                ParameterVariant::SingleParty => 0,
+                ParameterVariant::InteractiveMultiParty => 1,
+                ParameterVariant::NonInteractiveMultiParty => 2,
+            });
+        }
+    }
+
+    impl Decode for ParameterVariant {
+        fn decode(r: &mut Reader) -> Result<Self, DecodeError> {
+            match r.u8()? {
+                0 => Ok(ParameterVariant::SingleParty),
+                1 => Ok(ParameterVariant::InteractiveMultiParty),
+                2 => Ok(ParameterVariant::NonInteractiveMultiParty),
+                v => Err(DecodeError::Invalid(format!(
+                    "invalid parameter variant {v}"
+                ))),
+            }
+        }
+    }
+
+    fn encode_single(w: &mut Writer, params: &(DecompostionLogBase, DecompositionCount)) {
+        w.usize(params.0 .0);
+        w.usize(params.1 .0);
+    }
+
+    fn decode_single(
+        r: &mut Reader,
+    ) -> Result<(DecompostionLogBase, DecompositionCount), DecodeError> {
+        Ok((
+            DecompostionLogBase(r.usize()?),
+            DecompositionCount(r.usize()?),
+        ))
+    }
+
+    fn encode_double(
+        w: &mut Writer,
+        params: &(
+            DecompostionLogBase,
+            (DecompositionCount, DecompositionCount),
+        ),
+    ) {
+        w.usize(params.0 .0);
+        w.usize(params.1 .0 .0);
+        w.usize(params.1 .1 .0);
+    }
+
+    fn decode_double(
+        r: &mut Reader,
+    ) -> Result<
+        (
+            DecompostionLogBase,
+            (DecompositionCount, DecompositionCount),
+        ),
+        DecodeError,
+    > {
+        Ok((
+            DecompostionLogBase(r.usize()?),
+            (
+                DecompositionCount(r.usize()?),
+                DecompositionCount(r.usize()?),
+            ),
+        ))
+    }
+
+    impl Encode for BoolParameters<u64> {
+        fn encode(&self, w: &mut Writer) {
+            self.rlwe_secret_key_dist.encode(w);
+            self.lwe_secret_key_dist.encode(w);
+            self.rlwe_q.encode(w);
+            self.lwe_q.encode(w);
+            w.usize(self.br_q);
+            w.usize(self.rlwe_n.0);
+            w.usize(self.lwe_n.0);
+            encode_single(w, &self.lwe_decomposer_params);
+            encode_double(w, &self.rlrg_decomposer_params);
+            encode_single(w, &self.auto_decomposer_params);
+            w.bool(self.rgrg_decomposer_params.is_some());
+            if let Some(params) = &self.rgrg_decomposer_params {
+                encode_double(w, params);
+            }
+            w.bool(self.non_interactive_ui_to_s_key_switch_decomposer.is_some());
+            if let Some(params) = &self.non_interactive_ui_to_s_key_switch_decomposer {
+                encode_single(w, params);
+            }
+            w.bool(self.scheme_switch_decomposer_params.is_some());
+            if let Some(params) = &self.scheme_switch_decomposer_params {
+                encode_single(w, params);
+            }
+            w.usize(self.g);
+            w.usize(self.w);
+            w.usize(self.max_lut_message_bits);
+            self.variant.encode(w);
+        }
+    }
+
+    impl Decode for BoolParameters<u64> {
+        fn decode(r: &mut Reader) -> Result<Self, DecodeError> {
+            let parameters = BoolParameters {
+                rlwe_secret_key_dist: SecretKeyDistribution::decode(r)?,
+                lwe_secret_key_dist: SecretKeyDistribution::decode(r)?,
+                rlwe_q: CiphertextModulus::decode(r)?,
+                lwe_q: CiphertextModulus::decode(r)?,
+                br_q: r.usize()?,
+                rlwe_n: PolynomialSize(r.usize()?),
+                lwe_n: LweDimension(r.usize()?),
+                lwe_decomposer_params: decode_single(r)?,
+                rlrg_decomposer_params: decode_double(r)?,
+                auto_decomposer_params: decode_single(r)?,
+                rgrg_decomposer_params: if r.bool()? {
+                    Some(decode_double(r)?)
+                } else {
+                    None
+                },
+                non_interactive_ui_to_s_key_switch_decomposer: if r.bool()? {
+                    Some(decode_single(r)?)
+                } else {
+                    None
+                },
+                scheme_switch_decomposer_params: if r.bool()? {
+                    Some(decode_single(r)?)
+                } else {
+                    None
+                },
+                g: r.usize()?,
+                w: r.usize()?,
+                max_lut_message_bits: r.usize()?,
+                variant: ParameterVariant::decode(r)?,
+            };
+            // Reject dimensions that cannot belong to any parameter set, so that
+            // keys are never allocated with them
+            if !parameters.rlwe_n.0.is_power_of_two()
+                || parameters.rlwe_n.0 > 1 << 17
+                || !parameters.br_q.is_power_of_two()
+                || parameters.lwe_n.0 > parameters.rlwe_n.0
+            {
+                return Err(DecodeError::Invalid("invalid parameters".to_string()));
+            }
+            Ok(parameters)
+        }
+    }
+}
+
 pub(crate) const I_2P_LB_SR: BoolParameters<u64> = BoolParameters::<u64> {
     rlwe_secret_key_dist: SecretKeyDistribution::TernaryDistribution,
     lwe_secret_key_dist: SecretKeyDistribution::TernaryDistribution,
@@ -542,8 +773,10 @@ pub(crate) const I_2P_LB_SR: BoolParameters<u64> = BoolParameters::<u64> {
     )),
     auto_decomposer_params: (DecompostionLogBase(24), DecompositionCount(1)),
     non_interactive_ui_to_s_key_switch_decomposer: None,
+    scheme_switch_decomposer_params: None,
     g: 5,
     w: 10,
+    max_lut_message_bits: 1,
     variant: ParameterVariant::InteractiveMultiParty,
 };
 
@@ -566,8 +799,10 @@ pub(crate) const I_4P: BoolParameters<u64> = BoolParameters::<u64> {
     )),
     auto_decomposer_params: (DecompostionLogBase(24), DecompositionCount(1)),
     non_interactive_ui_to_s_key_switch_decomposer: None,
+    scheme_switch_decomposer_params: None,
     g: 5,
     w: 10,
+    max_lut_message_bits: 1,
     variant: ParameterVariant::InteractiveMultiParty,
 };
 
@@ -590,8 +825,10 @@ pub(crate) const I_8P: BoolParameters<u64> = BoolParameters::<u64> {
     )),
     auto_decomposer_params: (DecompostionLogBase(24), DecompositionCount(1)),
     non_interactive_ui_to_s_key_switch_decomposer: None,
+    scheme_switch_decomposer_params: None,
     g: 5,
     w: 10,
+    max_lut_message_bits: 1,
     variant: ParameterVariant::InteractiveMultiParty,
 };
 
@@ -617,8 +854,10 @@ pub(crate) const NI_2P: BoolParameters<u64> = BoolParameters::<u64> {
         DecompostionLogBase(1),
         DecompositionCount(50),
     )),
+    scheme_switch_decomposer_params: None,
     g: 5,
     w: 10,
+    max_lut_message_bits: 1,
     variant: ParameterVariant::NonInteractiveMultiParty,
 };
 
@@ -644,8 +883,10 @@ pub(crate) const NI_4P_HB_FR: BoolParameters<u64> = BoolParameters::<u64> {
         DecompostionLogBase(1),
         DecompositionCount(50),
     )),
+    scheme_switch_decomposer_params: None,
     g: 5,
     w: 10,
+    max_lut_message_bits: 1,
     variant: ParameterVariant::NonInteractiveMultiParty,
 };
 
@@ -671,8 +912,10 @@ pub(crate) const NI_4P_LB_SR: BoolParameters<u64> = BoolParameters::<u64> {
         DecompostionLogBase(1),
         DecompositionCount(50),
     )),
+    scheme_switch_decomposer_params: None,
     g: 5,
     w: 10,
+    max_lut_message_bits: 1,
     variant: ParameterVariant::NonInteractiveMultiParty,
 };
 
@@ -698,11 +941,105 @@ pub(crate) const NI_8P: BoolParameters<u64> = BoolParameters::<u64> {
         DecompostionLogBase(1),
         DecompositionCount(50),
     )),
+    scheme_switch_decomposer_params: None,
     g: 5,
     w: 10,
+    max_lut_message_bits: 1,
     variant: ParameterVariant::NonInteractiveMultiParty,
 };
 
+/// `I_8P` used with at most 4 parties for lookup tables over 2 bit messages.
+///
+/// Error of the output of a bootstrap is dominated by error of RGSW
+/// ciphertexts times digits of rlrg decomposition. Since input of a lookup
+/// table may be sum of upto 4 outputs of lookup tables, rlrg decomposition
+/// base is smaller than that of `I_8P`.
+#[cfg(feature = "interactive_mp")]
+pub(crate) const I_4P_LUT2: BoolParameters<u64> = BoolParameters::<u64> {
+    rlrg_decomposer_params: (
+        DecompostionLogBase(11),
+        (DecompositionCount(4), DecompositionCount(4)),
+    ),
+    rgrg_decomposer_params: Some((
+        DecompostionLogBase(6),
+        (DecompositionCount(7), DecompositionCount(6)),
+    )),
+    max_lut_message_bits: 2,
+    ..I_8P
+};
+
+/// Parameters for lookup tables over 3 bit messages with at most 4 parties.
+///
+/// Error at the input of blind rotation is dominated by error of odd mod switch
+/// to q, which is independent of q. Hence, to halve the error relative to the
+/// message interval, q and ring dimension are doubled compared to
+/// `I_4P_LUT2`, and RLWE modulus is 1 mod 2^13. LWE modulus is larger to keep
+/// error of LWE key switch small.
+#[cfg(feature = "interactive_mp")]
+pub(crate) const I_4P_LUT3: BoolParameters<u64> = BoolParameters::<u64> {
+    rlwe_q: CiphertextModulus::new_non_native(18014398509309953),
+    lwe_q: CiphertextModulus::new_non_native(1 << 20),
+    br_q: 1 << 13,
+    rlwe_n: PolynomialSize(1 << 12),
+    lwe_n: LweDimension(800),
+    lwe_decomposer_params: (DecompostionLogBase(1), DecompositionCount(17)),
+    max_lut_message_bits: 3,
+    ..I_4P_LUT2
+};
+
+/// Parameters for circuit bootstrapping, that is lookups with CMUX trees
+/// (refer to `BooleanGates::cmux_tree_lookup`), with at most 2 parties.
+///
+/// Trace in circuit bootstrapping requires automorphisms of the full ring,
+/// hence br_q = 2N as in `I_8P`. RGSW ciphertexts output by circuit
+/// bootstrapping have error of a blind rotation, which CMUXs multiply with
+/// digits of rlrg decomposition. Hence digits of rlrg decomposition are much
+/// smaller than those of `I_8P`, and RGSW ciphertexts of the bootstrapping key
+/// are of 2 parties as in `I_2P_LB_SR`. Trace adds error of N automorphisms,
+/// hence auto decomposition is finer as well.
+#[cfg(feature = "interactive_mp")]
+pub(crate) const I_2P_CBS: BoolParameters<u64> = BoolParameters::<u64> {
+    rlrg_decomposer_params: (
+        DecompostionLogBase(5),
+        (DecompositionCount(7), DecompositionCount(7)),
+    ),
+    rgrg_decomposer_params: Some((
+        DecompostionLogBase(7),
+        (DecompositionCount(6), DecompositionCount(5)),
+    )),
+    auto_decomposer_params: (DecompostionLogBase(12), DecompositionCount(4)),
+    scheme_switch_decomposer_params: Some((DecompostionLogBase(9), DecompositionCount(5))),
+    ..I_8P
+};
+
+/// Non-interactive counterpart of `I_4P_LUT2`
+#[cfg(feature = "non_interactive_mp")]
+pub(crate) const NI_4P_LUT2: BoolParameters<u64> = BoolParameters::<u64> {
+    rlrg_decomposer_params: (
+        DecompostionLogBase(11),
+        (DecompositionCount(4), DecompositionCount(4)),
+    ),
+    rgrg_decomposer_params: Some((
+        DecompostionLogBase(3),
+        (DecompositionCount(13), DecompositionCount(12)),
+    )),
+    max_lut_message_bits: 2,
+    ..NI_8P
+};
+
+/// Non-interactive counterpart of `I_4P_LUT3`
+#[cfg(feature = "non_interactive_mp")]
+pub(crate) const NI_4P_LUT3: BoolParameters<u64> = BoolParameters::<u64> {
+    rlwe_q: CiphertextModulus::new_non_native(18014398509309953),
+    lwe_q: CiphertextModulus::new_non_native(1 << 20),
+    br_q: 1 << 13,
+    rlwe_n: PolynomialSize(1 << 12),
+    lwe_n: LweDimension(800),
+    lwe_decomposer_params: (DecompostionLogBase(1), DecompositionCount(17)),
+    max_lut_message_bits: 3,
+    ..NI_4P_LUT2
+};
+
 #[cfg(test)]
 pub(crate) const SP_TEST_BOOL_PARAMS: BoolParameters<u64> = BoolParameters::<u64> {
     rlwe_secret_key_dist: SecretKeyDistribution::TernaryDistribution,
@@ -720,11 +1057,65 @@ pub(crate) const SP_TEST_BOOL_PARAMS: BoolParameters<u64> = BoolParameters::<u64
     rgrg_decomposer_params: None,
     auto_decomposer_params: (DecompostionLogBase(7), DecompositionCount(4)),
     non_interactive_ui_to_s_key_switch_decomposer: None,
+    scheme_switch_decomposer_params: None,
     g: 5,
     w: 5,
+    max_lut_message_bits: 1,
     variant: ParameterVariant::SingleParty,
 };
 
+/// Single party parameters for tests of arithmetic, which evaluate thousands
+/// of bootstraps (including multi-value bootstraps of adders).
+///
+/// Bootstraps with `SP_TEST_BOOL_PARAMS` fail with probability around 2^-11
+/// due to error of LWE key switch, hence arithmetic tests would fail at random.
+/// Exact LWE key switch with ternary LWE secret brings failure probability
+/// far below 2^-40 (refer to `print_noise`).
+#[cfg(all(test, feature = "interactive_mp"))]
+pub(crate) const SP_TEST_ARITHMETIC_PARAMS: BoolParameters<u64> = BoolParameters::<u64> {
+    lwe_secret_key_dist: SecretKeyDistribution::TernaryDistribution,
+    lwe_decomposer_params: (DecompostionLogBase(1), DecompositionCount(16)),
+    ..SP_TEST_BOOL_PARAMS
+};
+
+/// Single party parameters for tests of lookup tables over upto 3 bit
+/// messages
+#[cfg(all(test, feature = "interactive_mp"))]
+pub(crate) const SP_TEST_LUT_PARAMS: BoolParameters<u64> = BoolParameters::<u64> {
+    lwe_secret_key_dist: SecretKeyDistribution::TernaryDistribution,
+    lwe_q: CiphertextModulus::new_non_native(1 << 20),
+    br_q: 1 << 12,
+    rlwe_n: PolynomialSize(1 << 11),
+    lwe_decomposer_params: (DecompostionLogBase(1), DecompositionCount(20)),
+    rlrg_decomposer_params: (
+        DecompostionLogBase(5),
+        (DecompositionCount(5), DecompositionCount(5)),
+    ),
+    max_lut_message_bits: 3,
+    ..SP_TEST_BOOL_PARAMS
+};
+
+/// Single party parameters for tests of circuit bootstrapping.
+///
+/// Trace in circuit bootstrapping requires automorphisms of the full ring,
+/// hence br_q = 2N. RGSW ciphertexts output by circuit bootstrapping have
+/// error of a bootstrap times the secret, which CMUXs multiply with digits of
+/// rlrg decomposition. Hence RLWE modulus is much larger than that of
+/// `SP_TEST_ARITHMETIC_PARAMS`.
+#[cfg(all(test, feature = "interactive_mp"))]
+pub(crate) const SP_TEST_CBS_PARAMS: BoolParameters<u64> = BoolParameters::<u64> {
+    rlwe_q: CiphertextModulus::new_non_native(18014398509404161),
+    br_q: 1 << 10,
+    rlrg_decomposer_params: (
+        DecompostionLogBase(9),
+        (DecompositionCount(5), DecompositionCount(5)),
+    ),
+    auto_decomposer_params: (DecompostionLogBase(9), DecompositionCount(5)),
+    scheme_switch_decomposer_params: Some((DecompostionLogBase(9), DecompositionCount(5))),
+    w: 10,
+    ..SP_TEST_ARITHMETIC_PARAMS
+};
+
 // #[cfg(test)]
 // mod tests {
 
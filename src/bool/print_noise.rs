@@ -1,25 +1,31 @@
 use std::{fmt::Debug, iter::Sum};
 
-use itertools::izip;
+use itertools::{izip, Itertools};
 use num_traits::{FromPrimitive, PrimInt, Zero};
+use rand::{thread_rng, Rng};
 use rand_distr::uniform::SampleUniform;
 
 use crate::{
-    backend::{GetModulus, Modulus},
-    decomposer::{Decomposer, NumInfo, RlweDecomposer},
+    backend::{GetModulus, ModularOpsU64, Modulus},
+    decomposer::{Decomposer, DefaultDecomposer, NumInfo, RlweDecomposer},
     lwe::{decrypt_lwe, lwe_key_switch},
     parameters::{BoolParameters, CiphertextModulus},
+    pbs::{mod_switch_odd, PbsKey},
     random::{DefaultSecureRng, RandomFillUniformInModulus},
     rgsw::{
         decrypt_rlwe, rlwe_auto, rlwe_auto_scratch_rows, RlweCiphertextMutRef, RlweKskRef,
         RuntimeScratchMutRef,
     },
     utils::{encode_x_pow_si_with_emebedding_factor, tests::Stats, TryConvertFrom1},
-    ArithmeticOps, ClientKey, MatrixEntity, MatrixMut, ModInit, Ntt, NttInit, RowEntity, RowMut,
-    VectorOps,
+    ArithmeticOps, BooleanGates, ClientKey, Encoder, LookupTables, MatrixEntity, MatrixMut,
+    ModInit, Ntt, NttInit, RowEntity, RowMut, VectorOps,
 };
 
-use super::keys::tests::{ideal_sk_lwe, ideal_sk_rlwe};
+use super::{
+    evaluator::MessageEncoding,
+    keys::tests::{ideal_sk_lwe, ideal_sk_rlwe, measure_noise_lwe},
+    lut::MAX_MESSAGE_BITS,
+};
 
 pub(crate) trait CollectRuntimeServerKeyStats {
     type M;
@@ -379,6 +385,338 @@ where
     // Ksk noise
 }
 
+/// Returns error in Z_q (q: blind rotation modulus) at the input of blind
+/// rotation of LWE ciphertext `lwe_in` that encrypts `m` under the ideal RLWE
+/// secret. That is, error of `lwe_in` scaled by q/Q plus error added by mod
+/// switch Q -> Q_{ks}, LWE key switch, and odd mod switch Q_{ks} -> q.
+fn blind_rotation_input_noise(
+    parameters: &BoolParameters<u64>,
+    ideal_sk_lwe: &[i32],
+    lwe_ksk: &Vec<Vec<u64>>,
+    lwe_in: &[u64],
+    m: u64,
+) -> i64 {
+    let rlwe_q = *parameters.rlwe_q();
+    let lwe_q = *parameters.lwe_q();
+    let br_q = CiphertextModulus::new_non_native(*parameters.br_q() as u64);
+    let lwe_modop = ModularOpsU64::new(lwe_q);
+    let br_modop = ModularOpsU64::new(br_q);
+    let lwe_ks_decomposer = parameters.lwe_decomposer::<DefaultDecomposer<u64>>();
+
+    // Q -> Q_{ks}
+    let lwe_in = lwe_in
+        .iter()
+        .map(|v| {
+            ((*v as f64 * lwe_q.q_as_f64().unwrap()) / rlwe_q.q_as_f64().unwrap()).round() as u64
+        })
+        .collect_vec();
+
+    // Key switch
+    let mut lwe_out = vec![0u64; parameters.lwe_n().0 + 1];
+    lwe_key_switch(
+        &mut lwe_out,
+        &lwe_in,
+        lwe_ksk,
+        &lwe_modop,
+        &lwe_ks_decomposer,
+    );
+
+    // Q_{ks} -> q
+    lwe_out.iter_mut().for_each(|v| {
+        *v = mod_switch_odd(
+            *v as f64,
+            lwe_q.q_as_f64().unwrap(),
+            br_q.q_as_f64().unwrap(),
+        ) as u64
+    });
+
+    let m = ((m as f64 * br_q.q_as_f64().unwrap()) / rlwe_q.q_as_f64().unwrap()).round() as u64
+        % *parameters.br_q() as u64;
+    let noise = br_modop.sub(&decrypt_lwe(&lwe_out, ideal_sk_lwe, &br_modop), &m);
+    br_q.map_element_to_i64(&noise)
+}
+
+/// Collects error in Z_q (q: blind rotation modulus) added by mod switch Q ->
+/// Q_{ks}, LWE key switch, and odd mod switch Q_{ks} -> q to LWE ciphertexts
+/// at the input of blind rotation. Input ciphertexts are noiseless, hence
+/// callers must add error of their inputs, scaled by q/Q.
+fn collect_blind_rotation_input_noise(
+    parameters: &BoolParameters<u64>,
+    client_keys: &[ClientKey],
+    lwe_ksk: &Vec<Vec<u64>>,
+    samples: usize,
+) -> Stats<i64> {
+    let ideal_sk_rlwe = ideal_sk_rlwe(client_keys);
+    let ideal_sk_lwe = ideal_sk_lwe(client_keys);
+
+    let rlwe_q = *parameters.rlwe_q();
+    let rlwe_modop = ModularOpsU64::new(rlwe_q);
+
+    let mut rng = DefaultSecureRng::new();
+    let mut stats = Stats::new();
+    for _ in 0..samples {
+        // LWE_{Q, s}(0)
+        let mut lwe_in = vec![0u64; parameters.rlwe_n().0 + 1];
+        RandomFillUniformInModulus::random_fill(&mut rng, &rlwe_q, &mut lwe_in[1..]);
+        lwe_in[0] = rlwe_modop.neg(&decrypt_lwe(&lwe_in, &ideal_sk_rlwe, &rlwe_modop));
+
+        stats.add_sample(blind_rotation_input_noise(
+            parameters,
+            &ideal_sk_lwe,
+            lwe_ksk,
+            &lwe_in,
+            0,
+        ));
+    }
+    stats
+}
+
+/// Returns log2 of probability that gaussian error with standard deviation
+/// `std_dev` exceeds `margin` in either direction, that is log2(erfc(margin
+/// / (std_dev * sqrt(2)))).
+fn log2_failure_probability(std_dev: f64, margin: f64) -> f64 {
+    // erfc(z) = t * exp(-z^2 + P(t)) with fractional error less than 1.2e-7
+    // (Numerical Recipes, 6.2)
+    let z = margin / (std_dev * std::f64::consts::SQRT_2);
+    let t = 1.0 / (1.0 + 0.5 * z);
+    let p = -1.26551223
+        + t * (1.00002368
+            + t * (0.37409196
+                + t * (0.09678418
+                    + t * (-0.18628806
+                        + t * (0.27886807
+                            + t * (-1.13520398
+                                + t * (1.48851587 + t * (-0.82215223 + t * 0.17087277))))))));
+    (t.ln() - z * z + p) / std::f64::consts::LN_2
+}
+
+/// Max. no. of outputs of lookup tables summed at the input of a lookup table
+const LUT_INPUT_SUMMANDS: usize = 4;
+
+/// Prints standard deviation of error at the input of blind rotation of lookup
+/// tables and failure probability of lookup tables over messages of 1 to
+/// `MAX_MESSAGE_BITS` bits. Asserts that lookup tables over messages of max. message bits
+/// supported by the parameter set fail with probability at most 2^-40.
+///
+/// Input of a lookup table is a sum of upto `LUT_INPUT_SUMMANDS` outputs of
+/// lookup tables (e.g. `FheShortUint::from_bits`, or sum of digits and carry in
+/// radix addition). Hence error is measured on such sums and includes error of
+/// the outputs, scaled by q/Q, and error added by mod switch Q -> Q_{ks}, LWE
+/// key switch, and mod switch Q_{ks} -> q. Outputs are bootstrapped from
+/// `inputs`, encryptions of random bits, since bootstrap of a trivial
+/// ciphertext outputs the same ciphertext every time. Consecutive sums share
+/// all but one output.
+fn check_lut_failure_probability<E, K>(
+    evaluator: &mut E,
+    server_key: &K,
+    parameters: &BoolParameters<u64>,
+    client_keys: &[ClientKey],
+    inputs: &[(Vec<u64>, bool)],
+) where
+    E: LookupTables<Ciphertext = Vec<u64>, Message = Vec<u64>, Key = K>,
+    K: PbsKey<LweKskKey = Vec<Vec<u64>>>,
+{
+    let ideal_sk_lwe = ideal_sk_lwe(client_keys);
+    let rlwe_q = *parameters.rlwe_q();
+    let rlwe_modop = ModularOpsU64::new(rlwe_q);
+
+    // Error of the output of a bootstrap is independent of the test vector.
+    // Hence outputs encrypt random messages or 0.
+    let bits = parameters.max_lut_message_bits();
+    let outs = inputs
+        .iter()
+        .map(|(c, b)| {
+            let m = thread_rng().gen_range(0..1u64 << bits);
+            (
+                evaluator.bool_to_message(c, bits, m, server_key),
+                if *b { m } else { 0 },
+            )
+        })
+        .collect_vec();
+
+    let mut stats = Stats::new();
+    for window in outs.windows(LUT_INPUT_SUMMANDS) {
+        let mut sum = vec![0u64; parameters.rlwe_n().0 + 1];
+        let mut sum_m = 0u64;
+        for (c, m) in window {
+            rlwe_modop.elwise_add_mut(&mut sum, c);
+            sum_m = rlwe_modop.add(&sum_m, &rlwe_q.message_el(*m, bits));
+        }
+        stats.add_sample(blind_rotation_input_noise(
+            parameters,
+            &ideal_sk_lwe,
+            server_key.lwe_ksk(),
+            &sum,
+            sum_m,
+        ));
+    }
+
+    let std_dev = stats.std_dev();
+    println!(
+        "Parties: {} Lookup table input noise std_dev: {std_dev:.1}",
+        client_keys.len()
+    );
+    for bits in 1..=MAX_MESSAGE_BITS {
+        // Message of `bits` bits occupies interval of q/2^{bits+1} and is
+        // shifted to the center of the interval before bootstrapping
+        let margin = (*parameters.br_q() >> (bits + 2)) as f64 - stats.mean().abs();
+        let log2_p = log2_failure_probability(std_dev, margin);
+        println!("  {bits} bit lookup table failure probability: 2^{log2_p:.1}");
+        if bits == parameters.max_lut_message_bits() {
+            assert!(
+                log2_p <= -40.0,
+                "{bits} bit lookup tables fail with probability 2^{log2_p}"
+            );
+        }
+    }
+}
+
+/// Prints standard deviation of error in outputs of gates that bootstrap more
+/// than once, and of adders, and failure probability of bootstraps that consume
+/// them. Asserts that bootstraps of sum of 2 outputs (e.g. AND of 2 MUX
+/// outputs) fail with probability at most 2^-40.
+///
+/// Error at the input of the consuming bootstrap is the sum of error of 2
+/// outputs, scaled by q/Q, and error added by key switch and mod switches,
+/// whose variances are measured separately.
+///
+/// MUXes are chained, that is each MUX selects between a fresh ciphertext and
+/// the output of the previous MUX, as MUXes are in lookup trees and barrel
+/// shifters. Likewise full adders are chained through their carry, as in
+/// ripple carry adders. `inputs` are encryptions of random bits, 3 per sample.
+fn check_gate_failure_probability<E, K>(
+    evaluator: &mut E,
+    server_key: &K,
+    parameters: &BoolParameters<u64>,
+    client_keys: &[ClientKey],
+    inputs: &[(Vec<u64>, bool)],
+) where
+    E: BooleanGates<Ciphertext = Vec<u64>, Key = K>,
+    K: PbsKey<LweKskKey = Vec<Vec<u64>>>,
+{
+    let ideal_sk_rlwe = ideal_sk_rlwe(client_keys);
+    let rlwe_q = *parameters.rlwe_q();
+    let rlwe_modop = ModularOpsU64::new(rlwe_q);
+
+    let mut mux_outs = vec![];
+    let mut majority_outs = vec![];
+    let mut half_adder_outs = vec![];
+    let mut full_adder_outs = vec![];
+    let (mut acc, mut acc_m) = inputs[0].clone();
+    let (mut carry, mut carry_m) = inputs[0].clone();
+    for ((a, a_m), (b, b_m), (c, c_m)) in inputs.iter().tuples() {
+        acc = evaluator.mux(a, b, &acc, server_key);
+        acc_m = if *a_m { *b_m } else { acc_m };
+        mux_outs.push((acc.clone(), acc_m));
+
+        majority_outs.push((
+            evaluator.majority(a, b, c, server_key),
+            (*a_m as u8 + *b_m as u8 + *c_m as u8) >= 2,
+        ));
+
+        let mut sum = a.clone();
+        let half_carry = evaluator.half_adder(&mut sum, c, server_key);
+        half_adder_outs.push((sum, a_m ^ c_m));
+        half_adder_outs.push((half_carry, a_m & c_m));
+
+        let mut sum = a.clone();
+        carry = evaluator.full_adder(&mut sum, b, &carry, server_key);
+        full_adder_outs.push((sum, a_m ^ b_m ^ carry_m));
+        carry_m = (*a_m as u8 + *b_m as u8 + carry_m as u8) >= 2;
+        full_adder_outs.push((carry.clone(), carry_m));
+    }
+
+    println!("Parties: {}", client_keys.len());
+    let ks_noise =
+        collect_blind_rotation_input_noise(parameters, client_keys, server_key.lwe_ksk(), 2000);
+    for (gate, outs) in [
+        ("MUX", mux_outs),
+        ("Majority", majority_outs),
+        ("Half adder", half_adder_outs),
+        ("Full adder", full_adder_outs),
+    ] {
+        let mut output_noise = Stats::new();
+        for (c, m) in outs.iter() {
+            let noise = measure_noise_lwe(c, rlwe_q.encode(*m), &ideal_sk_rlwe, &rlwe_modop);
+            output_noise.add_sample(rlwe_q.map_element_to_i64(&noise));
+        }
+
+        let scale = *parameters.br_q() as f64 / rlwe_q.q_as_f64().unwrap();
+        let var = ks_noise.variance() + 2.0 * output_noise.variance() * scale * scale;
+        let margin = (*parameters.br_q() >> 3) as f64 - ks_noise.mean().abs();
+        let log2_p = log2_failure_probability(var.sqrt(), margin);
+        println!(
+            "  {gate} output noise std_dev log2: {:.1}, consuming bootstrap input noise std_dev: {:.1} failure probability: 2^{log2_p:.1}",
+            output_noise.std_dev().log2(),
+            var.sqrt(),
+        );
+        assert!(
+            log2_p <= -40.0,
+            "Bootstraps consuming {gate} outputs fail with probability 2^{log2_p}"
+        );
+    }
+}
+
+/// Prints standard deviation of error in outputs of lookups with CMUX trees
+/// over circuit bootstrapped index bits, and failure probability of bootstraps
+/// that consume them. Asserts that bootstraps of sum of 2 outputs fail with
+/// probability at most 2^-40.
+///
+/// Each output is the constant term of the output of a CMUX tree of depth 8,
+/// hence error of an output is error of RGSW ciphertexts output by circuit
+/// bootstraps times digits of rlrg decomposition. `inputs` are encryptions of
+/// random bits, 8 (i.e. one index) per lookup.
+fn check_cmux_tree_lookup_failure_probability<E, K>(
+    evaluator: &mut E,
+    server_key: &K,
+    parameters: &BoolParameters<u64>,
+    client_keys: &[ClientKey],
+    inputs: &[(Vec<u64>, bool)],
+) where
+    E: BooleanGates<Ciphertext = Vec<u64>, Key = K>,
+    K: PbsKey<LweKskKey = Vec<Vec<u64>>>,
+{
+    let ideal_sk_rlwe = ideal_sk_rlwe(client_keys);
+    let rlwe_q = *parameters.rlwe_q();
+    let rlwe_modop = ModularOpsU64::new(rlwe_q);
+
+    let table = (0..256)
+        .map(|_| (0..8).map(|_| thread_rng().gen_bool(0.5)).collect_vec())
+        .collect_vec();
+    let mut output_noise = Stats::new();
+    for index in inputs.chunks_exact(8) {
+        let i = index
+            .iter()
+            .rev()
+            .fold(0, |i, (_, b)| (i << 1) | (*b as usize));
+        let index = index.iter().map(|(c, _)| c.clone()).collect_vec();
+        let outs = evaluator
+            .cmux_tree_lookup(&index, &table, 8, server_key)
+            .expect("Server key does not support circuit bootstrapping");
+        for (c, m) in izip!(outs.iter(), table[i].iter()) {
+            let noise = measure_noise_lwe(c, rlwe_q.encode(*m), &ideal_sk_rlwe, &rlwe_modop);
+            output_noise.add_sample(rlwe_q.map_element_to_i64(&noise));
+        }
+    }
+
+    let ks_noise =
+        collect_blind_rotation_input_noise(parameters, client_keys, server_key.lwe_ksk(), 2000);
+    let scale = *parameters.br_q() as f64 / rlwe_q.q_as_f64().unwrap();
+    let var = ks_noise.variance() + 2.0 * output_noise.variance() * scale * scale;
+    let margin = (*parameters.br_q() >> 3) as f64 - ks_noise.mean().abs();
+    let log2_p = log2_failure_probability(var.sqrt(), margin);
+    println!(
+        "Parties: {} CMUX tree lookup output noise std_dev log2: {:.1}, consuming bootstrap input noise std_dev: {:.1} failure probability: 2^{log2_p:.1}",
+        client_keys.len(),
+        output_noise.std_dev().log2(),
+        var.sqrt(),
+    );
+    assert!(
+        log2_p <= -40.0,
+        "Bootstraps consuming outputs of CMUX tree lookups fail with probability 2^{log2_p}"
+    );
+}
+
 #[cfg(test)]
 mod tests {
     use itertools::Itertools;
@@ -471,6 +809,16 @@ mod tests {
     }
 
     const K: usize = 10;
+    /// No. of samples of each gate output in gate failure probability tests
+    const GATE_SAMPLES: usize = 64;
+
+    /// No. of sums of outputs of lookup tables in lookup table failure
+    /// probability tests
+    const LUT_SAMPLES: usize = 256;
+
+    /// No. of lookups in CMUX tree lookup failure probability tests. Each
+    /// lookup outputs 8 samples, but requires 8 circuit bootstraps.
+    const CMUX_TREE_LOOKUP_SAMPLES: usize = 2;
 
     #[test]
     #[cfg(feature = "interactive_mp")]
@@ -1017,4 +1365,338 @@ mod tests {
             stats_ms_rounding_err.variance()
         );
     }
+
+    #[test]
+    #[cfg(feature = "interactive_mp")]
+    fn interactive_lut_failure_probability() {
+        use rand::{thread_rng, Rng};
+
+        use crate::{
+            aggregate_public_key_shares, aggregate_server_key_shares,
+            bool::{evaluator::InteractiveMultiPartyCrs, mp_api::with_evaluator_and_server_key},
+            collective_pk_share, collective_server_key_share, gen_client_key,
+            utils::WithLocal,
+            BoolEvaluator, Encryptor, ParameterSelector, Session,
+        };
+
+        use super::*;
+
+        for (selector, parties) in [
+            (ParameterSelector::InteractiveLTE2Party, 2),
+            (ParameterSelector::InteractiveLTE4Party, 4),
+            (ParameterSelector::InteractiveLTE8Party, 8),
+            (ParameterSelector::InteractiveLTE4PartyLut2Bits, 4),
+            (ParameterSelector::InteractiveLTE4PartyLut3Bits, 4),
+        ] {
+            let session = Session::new(selector, InteractiveMultiPartyCrs::random().seed);
+            let _guard = session.enter();
+
+            let cks = (0..parties).map(|_| gen_client_key()).collect_vec();
+            let pk_shares = cks.iter().map(|k| collective_pk_share(k)).collect_vec();
+            let pk = aggregate_public_key_shares(&pk_shares);
+            let server_key_shares = cks
+                .iter()
+                .enumerate()
+                .map(|(index, k)| collective_server_key_share(k, index, parties, &pk))
+                .collect_vec();
+            session.set_server_key(&aggregate_server_key_shares(&server_key_shares));
+
+            let inputs = (0..LUT_SAMPLES + LUT_INPUT_SUMMANDS - 1)
+                .map(|_| {
+                    let m = thread_rng().gen_bool(0.5);
+                    let c: Vec<u64> = pk.encrypt(&m);
+                    (c, m)
+                })
+                .collect_vec();
+
+            let parameters = BoolEvaluator::with_local(|e| e.parameters().clone());
+            with_evaluator_and_server_key(|e, key| {
+                check_lut_failure_probability(e, key, &parameters, &cks, &inputs)
+            });
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "interactive_mp")]
+    fn single_party_test_parameters_lut_failure_probability() {
+        use rand::{thread_rng, Rng};
+
+        use crate::{
+            bool::{mp_api::with_evaluator_and_server_key, parameters::SP_TEST_LUT_PARAMS},
+            utils::WithLocal,
+            BoolEvaluator, Encryptor, Session,
+        };
+
+        use super::*;
+
+        let mut seed = [0u8; 32];
+        thread_rng().fill(&mut seed);
+        let _guard = Session::with_parameters(SP_TEST_LUT_PARAMS, seed, 0).enter();
+        let (ck, sk) = BoolEvaluator::with_local_mut(|e| {
+            let ck = e.client_key();
+            let sk = e.single_party_server_key(&ck);
+            (ck, sk)
+        });
+        sk.set_server_key();
+
+        let inputs = (0..LUT_SAMPLES + LUT_INPUT_SUMMANDS - 1)
+            .map(|_| {
+                let m = thread_rng().gen_bool(0.5);
+                let c: Vec<u64> = ck.encrypt(&m);
+                (c, m)
+            })
+            .collect_vec();
+
+        with_evaluator_and_server_key(|e, key| {
+            check_lut_failure_probability(
+                e,
+                key,
+                &SP_TEST_LUT_PARAMS,
+                std::slice::from_ref(&ck),
+                &inputs,
+            )
+        });
+    }
+
+    #[test]
+    #[cfg(feature = "non_interactive_mp")]
+    fn non_interactive_lut_failure_probability() {
+        use rand::{thread_rng, Rng};
+
+        use crate::{
+            aggregate_server_key_shares,
+            bool::{
+                evaluator::NonInteractiveMultiPartyCrs, ni_mp_api::with_evaluator_and_server_key,
+                NonInteractiveBatchedFheBools,
+            },
+            gen_client_key, gen_server_key_share,
+            utils::WithLocal,
+            BoolEvaluator, Encryptor, KeySwitchWithId, ParameterSelector, Session,
+        };
+
+        use super::*;
+
+        for (selector, parties) in [
+            (ParameterSelector::NonInteractiveLTE2Party, 2),
+            (ParameterSelector::NonInteractiveLTE4Party, 4),
+            (ParameterSelector::NonInteractiveLTE8Party, 8),
+            (ParameterSelector::NonInteractiveLTE4PartyLut2Bits, 4),
+            (ParameterSelector::NonInteractiveLTE4PartyLut3Bits, 4),
+        ] {
+            let session = Session::new(selector, NonInteractiveMultiPartyCrs::random().seed);
+            let _guard = session.enter();
+
+            let cks = (0..parties).map(|_| gen_client_key()).collect_vec();
+            let server_key_shares = cks
+                .iter()
+                .enumerate()
+                .map(|(user_id, k)| gen_server_key_share(user_id, parties, k))
+                .collect_vec();
+            session.set_server_key(&aggregate_server_key_shares(&server_key_shares));
+
+            let inputs = (0..LUT_SAMPLES + LUT_INPUT_SUMMANDS - 1)
+                .map(|i| {
+                    let m = thread_rng().gen_bool(0.5);
+                    let user_id = i % parties;
+                    let c: NonInteractiveBatchedFheBools<_> =
+                        cks[user_id].encrypt(vec![m].as_slice());
+                    (c.key_switch(user_id).extract(0), m)
+                })
+                .collect_vec();
+
+            let parameters = BoolEvaluator::with_local(|e| e.parameters().clone());
+            with_evaluator_and_server_key(|e, key| {
+                check_lut_failure_probability(e, key, &parameters, &cks, &inputs)
+            });
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "interactive_mp")]
+    fn interactive_gate_failure_probability() {
+        use rand::{thread_rng, Rng};
+
+        use crate::{
+            aggregate_public_key_shares, aggregate_server_key_shares,
+            bool::{evaluator::InteractiveMultiPartyCrs, mp_api::with_evaluator_and_server_key},
+            collective_pk_share, collective_server_key_share, gen_client_key,
+            utils::WithLocal,
+            BoolEvaluator, Encryptor, ParameterSelector, Session,
+        };
+
+        use super::*;
+
+        for (selector, parties) in [
+            (ParameterSelector::InteractiveLTE2Party, 2),
+            (ParameterSelector::InteractiveLTE4Party, 4),
+            (ParameterSelector::InteractiveLTE8Party, 8),
+        ] {
+            let session = Session::new(selector, InteractiveMultiPartyCrs::random().seed);
+            let _guard = session.enter();
+
+            let cks = (0..parties).map(|_| gen_client_key()).collect_vec();
+            let pk_shares = cks.iter().map(|k| collective_pk_share(k)).collect_vec();
+            let pk = aggregate_public_key_shares(&pk_shares);
+            let server_key_shares = cks
+                .iter()
+                .enumerate()
+                .map(|(index, k)| collective_server_key_share(k, index, parties, &pk))
+                .collect_vec();
+            session.set_server_key(&aggregate_server_key_shares(&server_key_shares));
+
+            let inputs = (0..3 * GATE_SAMPLES)
+                .map(|_| {
+                    let m = thread_rng().gen_bool(0.5);
+                    let c: Vec<u64> = pk.encrypt(&m);
+                    (c, m)
+                })
+                .collect_vec();
+
+            let parameters = BoolEvaluator::with_local(|e| e.parameters().clone());
+            with_evaluator_and_server_key(|e, key| {
+                check_gate_failure_probability(e, key, &parameters, &cks, &inputs)
+            });
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "interactive_mp")]
+    fn interactive_cmux_tree_lookup_failure_probability() {
+        use rand::{thread_rng, Rng};
+
+        use crate::{
+            aggregate_public_key_shares, aggregate_server_key_shares,
+            bool::{evaluator::InteractiveMultiPartyCrs, mp_api::with_evaluator_and_server_key},
+            collective_pk_share, collective_server_key_share, gen_client_key,
+            utils::WithLocal,
+            BoolEvaluator, Encryptor, ParameterSelector, Session,
+        };
+
+        use super::*;
+
+        let parties = 2;
+        let session = Session::new(
+            ParameterSelector::InteractiveLTE2PartyCbs,
+            InteractiveMultiPartyCrs::random().seed,
+        );
+        let _guard = session.enter();
+
+        let cks = (0..parties).map(|_| gen_client_key()).collect_vec();
+        let pk_shares = cks.iter().map(collective_pk_share).collect_vec();
+        let pk = aggregate_public_key_shares(&pk_shares);
+        let server_key_shares = cks
+            .iter()
+            .enumerate()
+            .map(|(index, k)| collective_server_key_share(k, index, parties, &pk))
+            .collect_vec();
+        session.set_server_key(&aggregate_server_key_shares(&server_key_shares));
+
+        let inputs = (0..8 * CMUX_TREE_LOOKUP_SAMPLES)
+            .map(|_| {
+                let m = thread_rng().gen_bool(0.5);
+                let c: Vec<u64> = pk.encrypt(&m);
+                (c, m)
+            })
+            .collect_vec();
+
+        let parameters = BoolEvaluator::with_local(|e| e.parameters().clone());
+        with_evaluator_and_server_key(|e, key| {
+            check_cmux_tree_lookup_failure_probability(e, key, &parameters, &cks, &inputs)
+        });
+    }
+
+    #[test]
+    #[cfg(feature = "interactive_mp")]
+    fn single_party_test_parameters_gate_failure_probability() {
+        use rand::{thread_rng, Rng};
+
+        use crate::{
+            bool::{mp_api::with_evaluator_and_server_key, parameters::SP_TEST_ARITHMETIC_PARAMS},
+            utils::WithLocal,
+            BoolEvaluator, Encryptor, Session,
+        };
+
+        use super::*;
+
+        // Single party API tests evaluate many thousands of bootstraps, hence
+        // test parameters must not fail more often than the parameter sets
+        let mut seed = [0u8; 32];
+        thread_rng().fill(&mut seed);
+        let _guard = Session::with_parameters(SP_TEST_ARITHMETIC_PARAMS, seed, 0).enter();
+        let (ck, sk) = BoolEvaluator::with_local_mut(|e| {
+            let ck = e.client_key();
+            let sk = e.single_party_server_key(&ck);
+            (ck, sk)
+        });
+        sk.set_server_key();
+
+        let inputs = (0..3 * GATE_SAMPLES)
+            .map(|_| {
+                let m = thread_rng().gen_bool(0.5);
+                let c: Vec<u64> = ck.encrypt(&m);
+                (c, m)
+            })
+            .collect_vec();
+
+        with_evaluator_and_server_key(|e, key| {
+            check_gate_failure_probability(
+                e,
+                key,
+                &SP_TEST_ARITHMETIC_PARAMS,
+                &[ck.clone()],
+                &inputs,
+            )
+        });
+    }
+
+    #[test]
+    #[cfg(feature = "non_interactive_mp")]
+    fn non_interactive_gate_failure_probability() {
+        use rand::{thread_rng, Rng};
+
+        use crate::{
+            aggregate_server_key_shares,
+            bool::{
+                evaluator::NonInteractiveMultiPartyCrs, ni_mp_api::with_evaluator_and_server_key,
+                NonInteractiveBatchedFheBools,
+            },
+            gen_client_key, gen_server_key_share,
+            utils::WithLocal,
+            BoolEvaluator, Encryptor, KeySwitchWithId, ParameterSelector, Session,
+        };
+
+        use super::*;
+
+        for (selector, parties) in [
+            (ParameterSelector::NonInteractiveLTE2Party, 2),
+            (ParameterSelector::NonInteractiveLTE4Party, 4),
+            (ParameterSelector::NonInteractiveLTE8Party, 8),
+        ] {
+            let session = Session::new(selector, NonInteractiveMultiPartyCrs::random().seed);
+            let _guard = session.enter();
+
+            let cks = (0..parties).map(|_| gen_client_key()).collect_vec();
+            let server_key_shares = cks
+                .iter()
+                .enumerate()
+                .map(|(user_id, k)| gen_server_key_share(user_id, parties, k))
+                .collect_vec();
+            session.set_server_key(&aggregate_server_key_shares(&server_key_shares));
+
+            let inputs = (0..3 * GATE_SAMPLES)
+                .map(|i| {
+                    let m = thread_rng().gen_bool(0.5);
+                    let user_id = i % parties;
+                    let c: NonInteractiveBatchedFheBools<_> =
+                        cks[user_id].encrypt(vec![m].as_slice());
+                    (c.key_switch(user_id).extract(0), m)
+                })
+                .collect_vec();
+
+            let parameters = BoolEvaluator::with_local(|e| e.parameters().clone());
+            with_evaluator_and_server_key(|e, key| {
+                check_gate_failure_probability(e, key, &parameters, &cks, &inputs)
+            });
+        }
+    }
 }
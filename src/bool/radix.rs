@@ -0,0 +1,220 @@
+//! Unsigned 8 bit integers in radix representation evaluated with lookup
+//! tables
+
+use std::ops::{Add, Sub};
+
+use itertools::Itertools;
+
+use crate::{Decryptor, Encryptor, Error, MultiPartyDecryptor};
+
+use super::{impl_bool_frontend::FheBool, lut::FheShortUint, Backend};
+
+/// Message bits of a digit
+const DIGIT_BITS: usize = 2;
+/// No. of digits of `FheRadixUint8`
+const DIGITS: usize = 4;
+
+/// Digit of 2 message bits and a carry bit. Sum of two digits and a carry is at
+/// most 7, hence fits in 3 bits.
+type Digit<C> = FheShortUint<C, 3>;
+
+/// Fhe unsigned 8 bit integer in radix 4.
+///
+/// Unlike `FheUint8`, that is a ciphertext per bit, `FheRadixUint8` stores 4
+/// digits of 2 bits, each in a `FheShortUint<3>` (2 message bits and a carry
+/// bit). Hence addition and subtraction require a few lookup tables per digit
+/// instead of a boolean circuit per bit:
+///
+/// - `&a + &b` requires 7 bootstraps (a digit and a carry lookup table per
+///   digit, except for the carry of the most significant digit)
+/// - `&a - &b` requires 11 bootstraps (complement of each digit of `b` and
+///   `a + !b + 1`)
+///
+/// Both wrap around on overflow. Every digit is output of a lookup table, so
+/// that input of a lookup table is sum of at most 3 outputs of lookup tables.
+///
+/// Requires parameters that support lookup tables over 3 bit messages (refer
+/// to `ParameterSelector`).
+#[derive(Clone)]
+pub struct FheRadixUint8<C: Backend> {
+    pub(crate) digits: Vec<Digit<C>>,
+}
+
+impl<C: Backend> FheRadixUint8<C> {
+    /// Returns trivial encryption of `m`. Note that trivial encryption does not
+    /// hide `m`.
+    pub fn trivial(m: u8) -> FheRadixUint8<C> {
+        FheRadixUint8 {
+            digits: (0..DIGITS)
+                .map(|i| Digit::trivial((m >> (i * DIGIT_BITS)) & 3))
+                .collect(),
+        }
+    }
+
+    /// Returns integer with bits `bits` in little endian order. Missing bits
+    /// are set to 0. Requires 3 bootstraps per digit.
+    ///
+    /// Panics if more than 8 bits are supplied
+    pub fn from_bits(bits: &[FheBool<C>]) -> FheRadixUint8<C> {
+        assert!(
+            bits.len() <= DIGITS * DIGIT_BITS,
+            "Expected at most 8 bits, got {}",
+            bits.len()
+        );
+        let digits = (0..DIGITS)
+            .map(|i| {
+                let digit_bits = bits
+                    .iter()
+                    .skip(i * DIGIT_BITS)
+                    .take(DIGIT_BITS)
+                    .cloned()
+                    .collect_vec();
+                // Digit is sum of outputs of a bootstrap per bit. Refresh it
+                // such that it is output of a single lookup table.
+                Digit::from_bits(&digit_bits).apply_lut(|m| m)
+            })
+            .collect();
+        FheRadixUint8 { digits }
+    }
+
+    /// Returns bits of the integer in little endian order. Requires a
+    /// bootstrap per bit.
+    pub fn to_bits(&self) -> Vec<FheBool<C>> {
+        self.digits
+            .iter()
+            .flat_map(|d| {
+                (0..DIGIT_BITS).map(move |j| d.apply_lut_to_bool(move |m| (m >> j) & 1 == 1))
+            })
+            .collect()
+    }
+
+    /// Returns `a + b + carry` digit by digit, propagating carries with a
+    /// lookup table per digit
+    fn add_digits(a: &[Digit<C>], b: &[Digit<C>], mut carry: Option<Digit<C>>) -> Self {
+        let mut digits = Vec::with_capacity(DIGITS);
+        for (i, (a_i, b_i)) in a.iter().zip(b.iter()).enumerate() {
+            let mut sum = a_i + b_i;
+            if let Some(carry) = carry.as_ref() {
+                sum = &sum + carry;
+            }
+            digits.push(sum.apply_lut(|m| m & 3));
+            carry = (i + 1 < DIGITS).then(|| sum.apply_lut(|m| m >> DIGIT_BITS));
+        }
+        FheRadixUint8 { digits }
+    }
+}
+
+impl<C: Backend> Add<&FheRadixUint8<C>> for &FheRadixUint8<C> {
+    type Output = FheRadixUint8<C>;
+
+    fn add(self, rhs: &FheRadixUint8<C>) -> Self::Output {
+        FheRadixUint8::add_digits(&self.digits, &rhs.digits, None)
+    }
+}
+
+/// `a - b` is evaluated as `a + !b + 1`, where `!b` is complement of `b` digit
+/// by digit
+impl<C: Backend> Sub<&FheRadixUint8<C>> for &FheRadixUint8<C> {
+    type Output = FheRadixUint8<C>;
+
+    fn sub(self, rhs: &FheRadixUint8<C>) -> Self::Output {
+        let rhs_complement = rhs
+            .digits
+            .iter()
+            .map(|d| d.apply_lut(|m| 3 - (m & 3)))
+            .collect_vec();
+        FheRadixUint8::add_digits(&self.digits, &rhs_complement, Some(Digit::trivial(1)))
+    }
+}
+
+fn from_digits(digits: impl Iterator<Item = u8>) -> u8 {
+    digits
+        .enumerate()
+        .fold(0, |m, (i, d)| m | ((d & 3) << (i * DIGIT_BITS)))
+}
+
+impl<C, K> MultiPartyDecryptor<u8, FheRadixUint8<C>> for K
+where
+    C: Backend,
+    K: MultiPartyDecryptor<u8, Digit<C>>,
+    <Self as MultiPartyDecryptor<u8, Digit<C>>>::DecryptionShare: Clone,
+{
+    type DecryptionShare = Vec<<Self as MultiPartyDecryptor<u8, Digit<C>>>::DecryptionShare>;
+
+    fn gen_decryption_share(&self, c: &FheRadixUint8<C>) -> Self::DecryptionShare {
+        c.digits
+            .iter()
+            .map(|d| MultiPartyDecryptor::<u8, Digit<C>>::gen_decryption_share(self, d))
+            .collect_vec()
+    }
+
+    fn try_gen_decryption_share(
+        &self,
+        c: &FheRadixUint8<C>,
+    ) -> Result<Self::DecryptionShare, Error> {
+        c.digits
+            .iter()
+            .map(|d| MultiPartyDecryptor::<u8, Digit<C>>::try_gen_decryption_share(self, d))
+            .collect()
+    }
+
+    fn aggregate_decryption_shares(
+        &self,
+        c: &FheRadixUint8<C>,
+        shares: &[Self::DecryptionShare],
+    ) -> u8 {
+        from_digits(c.digits.iter().enumerate().map(|(i, d)| {
+            // Collect i^th digit decryption share of each party
+            let digit_shares = shares.iter().map(|s| s[i].clone()).collect_vec();
+            MultiPartyDecryptor::<u8, Digit<C>>::aggregate_decryption_shares(self, d, &digit_shares)
+        }))
+    }
+
+    fn try_aggregate_decryption_shares(
+        &self,
+        c: &FheRadixUint8<C>,
+        shares: &[Self::DecryptionShare],
+    ) -> Result<u8, Error> {
+        if let Some(index) = shares.iter().position(|s| s.len() != DIGITS) {
+            return Err(Error::InvalidDecryptionShare { index });
+        }
+        let digits = c
+            .digits
+            .iter()
+            .enumerate()
+            .map(|(i, d)| {
+                let digit_shares = shares.iter().map(|s| s[i].clone()).collect_vec();
+                MultiPartyDecryptor::<u8, Digit<C>>::try_aggregate_decryption_shares(
+                    self,
+                    d,
+                    &digit_shares,
+                )
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(from_digits(digits.into_iter()))
+    }
+}
+
+impl<C, K> Encryptor<u8, FheRadixUint8<C>> for K
+where
+    C: Backend,
+    K: Encryptor<u8, Digit<C>>,
+{
+    fn encrypt(&self, m: &u8) -> FheRadixUint8<C> {
+        FheRadixUint8 {
+            digits: (0..DIGITS)
+                .map(|i| self.encrypt(&((m >> (i * DIGIT_BITS)) & 3)))
+                .collect(),
+        }
+    }
+}
+
+impl<C, K> Decryptor<u8, FheRadixUint8<C>> for K
+where
+    C: Backend,
+    K: Decryptor<u8, Digit<C>>,
+{
+    fn decrypt(&self, c: &FheRadixUint8<C>) -> u8 {
+        from_digits(c.digits.iter().map(|d| self.decrypt(d)))
+    }
+}
@@ -0,0 +1,117 @@
+//! Parallel evaluation of independent operations across worker threads
+//!
+//! Evaluator is thread local. Hence, to evaluate operations on a thread, the
+//! thread must enter a `Session` (refer to `Session::enter`). `ThreadPool`
+//! does so on behalf of its workers: all workers share the read-only server
+//! key of the session and each worker evaluates with its own evaluator (and
+//! hence its own scratch space). For example
+//!
+//! ```ignore
+//! let pool = ThreadPool::new(&session, 4);
+//! let sums: Vec<FheUint8> = pool.par_map(&pairs, |(a, b)| a + b);
+//! ```
+
+use std::{
+    panic,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+    thread,
+};
+
+use crate::stats;
+
+use super::{BoolEvaluator, Session, SessionGuard};
+
+/// Pool of worker threads that evaluate operations within a session
+///
+/// Worker threads are spawned per call to `par_map` whereas evaluators of
+/// the workers are cached and reused across calls.
+pub struct ThreadPool {
+    session: Session,
+    threads: usize,
+    evaluators: Mutex<Vec<BoolEvaluator>>,
+}
+
+impl ThreadPool {
+    /// Creates pool of `threads` workers evaluating within `session`
+    pub fn new(session: &Session, threads: usize) -> ThreadPool {
+        assert!(threads > 0, "Thread pool must have at least one thread");
+        ThreadPool {
+            session: session.clone(),
+            threads,
+            evaluators: Mutex::new(vec![]),
+        }
+    }
+
+    /// Returns no. of worker threads
+    pub fn threads(&self) -> usize {
+        self.threads
+    }
+
+    /// Returns `items.iter().map(f)` with `f` evaluated in parallel by the
+    /// workers
+    ///
+    /// Gates evaluated by the workers are added to `stats` of the current
+    /// thread. If any division by zero is attempted by the workers, the Div by
+    /// Zero error flag of the session is set, as error flags are shared by all
+    /// threads of a session.
+    pub fn par_map<T, U, F>(&self, items: &[T], f: F) -> Vec<U>
+    where
+        T: Sync,
+        U: Send,
+        F: Fn(&T) -> U + Sync,
+    {
+        let next = AtomicUsize::new(0);
+        let workers = self.threads.min(items.len());
+
+        let outputs = thread::scope(|s| {
+            let handles = (0..workers)
+                .map(|_| {
+                    s.spawn(|| {
+                        let guard = self.enter();
+                        let mut outputs = vec![];
+                        loop {
+                            let index = next.fetch_add(1, Ordering::Relaxed);
+                            if index >= items.len() {
+                                break;
+                            }
+                            outputs.push((index, f(&items[index])));
+                        }
+                        self.evaluators.lock().unwrap().push(guard.exit());
+                        (outputs, stats::take())
+                    })
+                })
+                .collect::<Vec<_>>();
+
+            handles
+                .into_iter()
+                .map(|h| h.join().unwrap_or_else(|e| panic::resume_unwind(e)))
+                .collect::<Vec<_>>()
+        });
+
+        let mut mapped = (0..items.len()).map(|_| None).collect::<Vec<_>>();
+        for (worker_outputs, worker_stats) in outputs {
+            worker_outputs
+                .into_iter()
+                .for_each(|(index, u)| mapped[index] = Some(u));
+            stats::merge(worker_stats);
+        }
+
+        mapped
+            .into_iter()
+            .map(|u| u.expect("Every item is mapped"))
+            .collect()
+    }
+
+    /// Enters the session on the current thread with a cached evaluator, if
+    /// any, otherwise with a new one
+    fn enter(&self) -> SessionGuard {
+        let evaluator = self.evaluators.lock().unwrap().pop();
+        match evaluator {
+            Some(evaluator) => self.session.enter_with_evaluator(evaluator),
+            None => self.session.enter(),
+        }
+    }
+}
@@ -0,0 +1,732 @@
+//! Parser of combinational netlists in BLIF, as written by Yosys
+//! `write_blif`
+//!
+//! Supported statements are `.model`, `.inputs`, `.outputs`, `.names`,
+//! `.conn`, `.end`, and `.subckt`/`.gate` of Yosys internal gate cells
+//! `$_AND_`, `$_NAND_`, `$_OR_`, `$_NOR_`, `$_XOR_`, `$_XNOR_`, `$_ANDNOT_`,
+//! `$_ORNOT_`, `$_NOT_`, `$_BUF_`, and `$_MUX_`. For example, a netlist
+//! synthesized with
+//!
+//! ```text
+//! yosys -p "synth; abc -g AND,NAND,OR,NOR,XOR,XNOR,MUX; write_blif -icells out.blif" design.v
+//! ```
+//!
+//! `.names` with upto 2 inputs are mapped to a single gate. `.names` with more
+//! inputs are evaluated as sum of products.
+//!
+//! Yosys names bit `i` of port `a` as `a[i]`. Bits of a port are grouped in a
+//! vector of bits, in little endian, of the port `a`.
+
+use std::collections::HashMap;
+
+use itertools::Itertools;
+
+use super::{Circuit, ParseError};
+use crate::{
+    bool::impl_bool_frontend, Backend, FheBool, LazyFheBool, MockFheBool, Select, ThreadPool,
+};
+
+/// Input or output port of a netlist
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Port {
+    pub name: String,
+    /// No. of bits
+    pub width: usize,
+}
+
+/// Circuit parsed from a BLIF netlist
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BlifCircuit {
+    pub circuit: Circuit,
+    /// Input ports in the order of declaration. Bits of the ports, in little
+    /// endian, are inputs of `circuit` in order.
+    pub inputs: Vec<Port>,
+    /// Output ports in the order of declaration. Bits of the ports, in little
+    /// endian, are outputs of `circuit` in order.
+    pub outputs: Vec<Port>,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Cell {
+    And,
+    Nand,
+    Or,
+    Nor,
+    Xor,
+    Xnor,
+    AndNot,
+    OrNot,
+    Not,
+    Buf,
+    Mux,
+}
+
+impl Cell {
+    /// Returns the cell of Yosys cell type `name` and its input pins
+    fn from_name(name: &str) -> Option<(Cell, &'static [&'static str])> {
+        let cell = match name {
+            "$_AND_" => (Cell::And, &["A", "B"][..]),
+            "$_NAND_" => (Cell::Nand, &["A", "B"][..]),
+            "$_OR_" => (Cell::Or, &["A", "B"][..]),
+            "$_NOR_" => (Cell::Nor, &["A", "B"][..]),
+            "$_XOR_" => (Cell::Xor, &["A", "B"][..]),
+            "$_XNOR_" => (Cell::Xnor, &["A", "B"][..]),
+            "$_ANDNOT_" => (Cell::AndNot, &["A", "B"][..]),
+            "$_ORNOT_" => (Cell::OrNot, &["A", "B"][..]),
+            "$_NOT_" => (Cell::Not, &["A"][..]),
+            "$_BUF_" => (Cell::Buf, &["A"][..]),
+            "$_MUX_" => (Cell::Mux, &["A", "B", "S"][..]),
+            _ => return None,
+        };
+        Some(cell)
+    }
+
+    fn evaluate(&self, inputs: &[LazyFheBool]) -> LazyFheBool {
+        match self {
+            Cell::And => &inputs[0] & &inputs[1],
+            Cell::Nand => !&(&inputs[0] & &inputs[1]),
+            Cell::Or => &inputs[0] | &inputs[1],
+            Cell::Nor => !&(&inputs[0] | &inputs[1]),
+            Cell::Xor => &inputs[0] ^ &inputs[1],
+            Cell::Xnor => !&(&inputs[0] ^ &inputs[1]),
+            Cell::AndNot => inputs[0].andyn(&inputs[1]),
+            Cell::OrNot => inputs[0].oryn(&inputs[1]),
+            Cell::Not => !&inputs[0],
+            Cell::Buf => inputs[0].clone(),
+            // Y = S ? B : A
+            Cell::Mux => inputs[1].mux(&inputs[0], &inputs[2]),
+        }
+    }
+}
+
+/// Logic driving a net
+enum Driver {
+    Cell(Cell, Vec<String>),
+    /// `.names` with input nets, cubes of the cover, and whether cubes are of
+    /// the on-set
+    Cover(Vec<String>, Vec<Vec<Option<bool>>>, bool),
+}
+
+impl Driver {
+    fn inputs(&self) -> &[String] {
+        match self {
+            Driver::Cell(_, inputs) => inputs,
+            Driver::Cover(inputs, _, _) => inputs,
+        }
+    }
+}
+
+/// Returns output of function with truth table `table` (indexed by
+/// `a + 2b`) on `a` and `b` using a single gate
+fn two_input_function(table: [bool; 4], a: &LazyFheBool, b: &LazyFheBool) -> LazyFheBool {
+    let functions: [fn(bool, bool) -> bool; 3] = [|a, b| a & b, |a, b| a | b, |a, b| a ^ b];
+    for (f, negate_a, negate_b, negate_out) in
+        itertools::iproduct!(0..3, [false, true], [false, true], [false, true])
+    {
+        let matches = (0..4).all(|i| {
+            let (x, y) = (i & 1 == 1, i & 2 == 2);
+            functions[f](x ^ negate_a, y ^ negate_b) ^ negate_out == table[i]
+        });
+        if matches {
+            let a = if negate_a { !a } else { a.clone() };
+            let b = if negate_b { !b } else { b.clone() };
+            let out = match f {
+                0 => &a & &b,
+                1 => &a | &b,
+                _ => &a ^ &b,
+            };
+            return if negate_out { !&out } else { out };
+        }
+    }
+    unreachable!("Every function of 2 inputs is a gate or a function of a single input")
+}
+
+/// Returns output of `.names` with `cubes` on `inputs`
+fn evaluate_cover(
+    inputs: &[LazyFheBool],
+    cubes: &[Vec<Option<bool>>],
+    on_set: bool,
+) -> LazyFheBool {
+    let cube_matches = |m: &[bool]| {
+        cubes
+            .iter()
+            .any(|cube| cube.iter().zip(m).all(|(c, m)| c.is_none_or(|c| c == *m)))
+    };
+    // value of the function on `m`
+    let f = |m: &[bool]| cube_matches(m) == on_set;
+
+    match inputs.len() {
+        0 => LazyFheBool::trivial(f(&[])),
+        1 => match (f(&[false]), f(&[true])) {
+            (false, false) => LazyFheBool::trivial(false),
+            (true, true) => LazyFheBool::trivial(true),
+            (false, true) => inputs[0].clone(),
+            (true, false) => !&inputs[0],
+        },
+        2 => {
+            let table = [0, 1, 2, 3].map(|i| f(&[i & 1 == 1, i & 2 == 2]));
+            match table {
+                [false, false, false, false] => LazyFheBool::trivial(false),
+                [true, true, true, true] => LazyFheBool::trivial(true),
+                [false, true, false, true] => inputs[0].clone(),
+                [true, false, true, false] => !&inputs[0],
+                [false, false, true, true] => inputs[1].clone(),
+                [true, true, false, false] => !&inputs[1],
+                table => two_input_function(table, &inputs[0], &inputs[1]),
+            }
+        }
+        _ => {
+            // sum of products
+            let sum = cubes
+                .iter()
+                .map(|cube| {
+                    cube.iter()
+                        .zip(inputs)
+                        .filter_map(|(c, input)| c.map(|c| if c { input.clone() } else { !input }))
+                        .reduce(|a, b| &a & &b)
+                        .unwrap_or_else(|| LazyFheBool::trivial(true))
+                })
+                .reduce(|a, b| &a | &b)
+                .unwrap_or_else(|| LazyFheBool::trivial(false));
+            if on_set {
+                sum
+            } else {
+                !&sum
+            }
+        }
+    }
+}
+
+/// Returns port name and bit index of net `name`
+fn port_bit(name: &str) -> (&str, usize) {
+    if let Some(base) = name.strip_suffix(']') {
+        if let Some((port, index)) = base.rsplit_once('[') {
+            if let Ok(index) = index.parse::<usize>() {
+                return (port, index);
+            }
+        }
+    }
+    (name, 0)
+}
+
+/// Groups `nets` by port. Returns ports and nets of each port in little
+/// endian.
+fn group_ports(line: usize, nets: &[String]) -> Result<(Vec<Port>, Vec<String>), ParseError> {
+    let mut bits: Vec<(&str, Vec<(usize, &String)>)> = vec![];
+    for net in nets {
+        let (port, index) = port_bit(net);
+        match bits.iter_mut().find(|(p, _)| *p == port) {
+            Some((_, b)) => b.push((index, net)),
+            None => bits.push((port, vec![(index, net)])),
+        }
+    }
+
+    let mut ports = vec![];
+    let mut ordered = vec![];
+    for (port, mut b) in bits {
+        b.sort();
+        if b.iter().enumerate().any(|(i, (index, _))| i != *index) {
+            return Err(ParseError::new(
+                line,
+                format!("Bits of port `{port}` are not contiguous from 0"),
+            ));
+        }
+        ports.push(Port {
+            name: port.to_string(),
+            width: b.len(),
+        });
+        ordered.extend(b.into_iter().map(|(_, net)| net.clone()));
+    }
+    Ok((ports, ordered))
+}
+
+/// Statements of the netlist with continuation lines joined and comments
+/// removed, along with their line no.
+fn statements(netlist: &str) -> Vec<(usize, Vec<&str>)> {
+    let mut statements: Vec<(usize, Vec<&str>)> = vec![];
+    let mut continued = false;
+    for (index, line) in netlist.lines().enumerate() {
+        let line = line.split('#').next().unwrap();
+        let (line, continues) = match line.trim_end().strip_suffix('\\') {
+            Some(line) => (line, true),
+            None => (line, false),
+        };
+        let tokens = line.split_whitespace().collect_vec();
+        if continued {
+            statements.last_mut().unwrap().1.extend(tokens);
+        } else if !tokens.is_empty() {
+            statements.push((index + 1, tokens));
+        }
+        continued = continues;
+    }
+    statements
+}
+
+impl Circuit {
+    /// Parses combinational BLIF `netlist`. Refer to the module documentation
+    /// of supported statements.
+    pub fn from_blif(netlist: &str) -> Result<BlifCircuit, ParseError> {
+        let statements = statements(netlist);
+
+        let mut input_nets = vec![];
+        let mut output_nets = vec![];
+        let mut drivers: HashMap<String, (usize, Driver)> = HashMap::new();
+        let mut models = 0;
+        let mut last_line = 0;
+
+        let mut index = 0;
+        while index < statements.len() {
+            let (line, tokens) = &statements[index];
+            let line = *line;
+            last_line = line;
+            index += 1;
+
+            let mut drive = |net: &str, driver: Driver| {
+                if drivers.insert(net.to_string(), (line, driver)).is_some() {
+                    Err(ParseError::new(
+                        line,
+                        format!("Net `{net}` is driven more than once"),
+                    ))
+                } else {
+                    Ok(())
+                }
+            };
+
+            match tokens[0] {
+                ".model" => {
+                    models += 1;
+                    if models > 1 {
+                        return Err(ParseError::new(
+                            line,
+                            "Only netlists with a single model are supported".to_string(),
+                        ));
+                    }
+                }
+                ".inputs" => input_nets.extend(tokens[1..].iter().map(|t| t.to_string())),
+                ".outputs" => output_nets.extend(tokens[1..].iter().map(|t| t.to_string())),
+                ".names" => {
+                    let Some((output, inputs)) = tokens[1..].split_last() else {
+                        return Err(ParseError::new(
+                            line,
+                            ".names expects an output net".to_string(),
+                        ));
+                    };
+                    // cover lines follow until the next statement
+                    let mut cubes = vec![];
+                    let mut on_set = None;
+                    while index < statements.len() && !statements[index].1[0].starts_with('.') {
+                        let (cube_line, cube_tokens) = &statements[index];
+                        index += 1;
+                        let (cube, value) = match (inputs.len(), cube_tokens.as_slice()) {
+                            (0, [value]) => ("", *value),
+                            (_, [cube, value]) if cube.len() == inputs.len() => (*cube, *value),
+                            _ => {
+                                return Err(ParseError::new(
+                                    *cube_line,
+                                    format!("Expected a cube of {} inputs", inputs.len()),
+                                ))
+                            }
+                        };
+                        let value = match value {
+                            "1" => true,
+                            "0" => false,
+                            _ => {
+                                return Err(ParseError::new(
+                                    *cube_line,
+                                    format!("Invalid output `{value}`"),
+                                ))
+                            }
+                        };
+                        if *on_set.get_or_insert(value) != value {
+                            return Err(ParseError::new(
+                                *cube_line,
+                                "Cover mixes on-set and off-set cubes".to_string(),
+                            ));
+                        }
+                        let cube = cube
+                            .chars()
+                            .map(|c| match c {
+                                '1' => Ok(Some(true)),
+                                '0' => Ok(Some(false)),
+                                '-' => Ok(None),
+                                _ => Err(ParseError::new(
+                                    *cube_line,
+                                    format!("Invalid literal `{c}`"),
+                                )),
+                            })
+                            .collect::<Result<Vec<_>, _>>()?;
+                        cubes.push(cube);
+                    }
+                    drive(
+                        output,
+                        Driver::Cover(
+                            inputs.iter().map(|i| i.to_string()).collect(),
+                            cubes,
+                            on_set.unwrap_or(true),
+                        ),
+                    )?;
+                }
+                ".subckt" | ".gate" => {
+                    let Some((cell, input_pins)) =
+                        tokens.get(1).and_then(|name| Cell::from_name(name))
+                    else {
+                        return Err(ParseError::new(
+                            line,
+                            format!("Unsupported cell `{}`", tokens.get(1).unwrap_or(&"")),
+                        ));
+                    };
+                    let pins = tokens[2..]
+                        .iter()
+                        .map(|t| {
+                            t.split_once('=').ok_or_else(|| {
+                                ParseError::new(line, format!("Expected pin=net but found `{t}`"))
+                            })
+                        })
+                        .collect::<Result<HashMap<_, _>, _>>()?;
+                    let net = |pin: &str| {
+                        pins.get(pin).map(|n| n.to_string()).ok_or_else(|| {
+                            ParseError::new(line, format!("Pin `{pin}` is not connected"))
+                        })
+                    };
+                    let inputs = input_pins
+                        .iter()
+                        .map(|pin| net(pin))
+                        .collect::<Result<Vec<_>, _>>()?;
+                    drive(&net("Y")?, Driver::Cell(cell, inputs))?;
+                }
+                ".conn" if tokens.len() == 3 => {
+                    drive(
+                        tokens[2],
+                        Driver::Cell(Cell::Buf, vec![tokens[1].to_string()]),
+                    )?;
+                }
+                ".end" => break,
+                ".latch" => {
+                    return Err(ParseError::new(
+                        line,
+                        "Sequential netlists are not supported".to_string(),
+                    ))
+                }
+                token => {
+                    return Err(ParseError::new(
+                        line,
+                        format!("Unsupported statement `{token}`"),
+                    ))
+                }
+            }
+        }
+
+        let (inputs, input_nets) = group_ports(last_line, &input_nets)?;
+        let (outputs, output_nets) = group_ports(last_line, &output_nets)?;
+        for net in input_nets.iter() {
+            if let Some((line, _)) = drivers.get(net) {
+                return Err(ParseError::new(
+                    *line,
+                    format!("Input net `{net}` is driven by a gate"),
+                ));
+            }
+        }
+
+        // order gates such that inputs of every gate precede the gate
+        let mut order = vec![];
+        let mut state: HashMap<&str, bool> =
+            input_nets.iter().map(|n| (n.as_str(), true)).collect();
+        for output in output_nets.iter() {
+            // depth first search, `bool` is true once inputs of the net are
+            // visited
+            let mut stack = vec![(output.as_str(), false)];
+            while let Some((net, inputs_visited)) = stack.pop() {
+                match state.get(net) {
+                    Some(true) => continue,
+                    Some(false) if !inputs_visited => {
+                        let line = drivers.get(net).map_or(last_line, |(line, _)| *line);
+                        return Err(ParseError::new(
+                            line,
+                            format!("Net `{net}` depends on itself"),
+                        ));
+                    }
+                    _ => {}
+                }
+                let Some((_, driver)) = drivers.get(net) else {
+                    return Err(ParseError::new(
+                        last_line,
+                        format!("Net `{net}` is not driven"),
+                    ));
+                };
+                if inputs_visited {
+                    state.insert(net, true);
+                    order.push(net);
+                } else {
+                    state.insert(net, false);
+                    stack.push((net, true));
+                    driver
+                        .inputs()
+                        .iter()
+                        .for_each(|input| stack.push((input.as_str(), false)));
+                }
+            }
+        }
+
+        let circuit = Circuit::record(|| {
+            let mut values: HashMap<&str, LazyFheBool> = input_nets
+                .iter()
+                .map(|net| (net.as_str(), LazyFheBool::input()))
+                .collect();
+            for net in order {
+                let driver = &drivers[net].1;
+                let inputs = driver
+                    .inputs()
+                    .iter()
+                    .map(|input| values[input.as_str()].clone())
+                    .collect_vec();
+                let value = match driver {
+                    Driver::Cell(cell, _) => cell.evaluate(&inputs),
+                    Driver::Cover(_, cubes, on_set) => evaluate_cover(&inputs, cubes, *on_set),
+                };
+                values.insert(net, value);
+            }
+            output_nets
+                .iter()
+                .map(|net| values[net.as_str()].clone())
+                .collect()
+        });
+
+        Ok(BlifCircuit {
+            circuit,
+            inputs,
+            outputs,
+        })
+    }
+}
+
+impl BlifCircuit {
+    /// Evaluates the circuit on bits of each input port in `inputs` and
+    /// returns bits of each output port. Refer to `Circuit::evaluate`.
+    pub fn evaluate(
+        &self,
+        pool: &ThreadPool,
+        inputs: &HashMap<&str, Vec<FheBool>>,
+    ) -> HashMap<String, Vec<FheBool>> {
+        let outputs = self.circuit.evaluate(pool, &self.input_bits(inputs));
+        self.output_ports(outputs)
+    }
+
+    /// Evaluates the circuit on bits of each Mock input port in `inputs` and
+    /// returns bits of each output port. Refer to `Circuit::evaluate_mock`.
+    pub fn evaluate_mock(
+        &self,
+        inputs: &HashMap<&str, Vec<MockFheBool>>,
+    ) -> HashMap<String, Vec<MockFheBool>> {
+        let outputs = self.circuit.evaluate_mock(&self.input_bits(inputs));
+        self.output_ports(outputs)
+    }
+
+    fn input_bits<C: Backend>(
+        &self,
+        inputs: &HashMap<&str, Vec<impl_bool_frontend::FheBool<C>>>,
+    ) -> Vec<impl_bool_frontend::FheBool<C>> {
+        assert!(
+            inputs.len() == self.inputs.len(),
+            "Expected {} input ports but {} were provided",
+            self.inputs.len(),
+            inputs.len()
+        );
+        self.inputs
+            .iter()
+            .flat_map(|port| {
+                let bits = inputs
+                    .get(port.name.as_str())
+                    .unwrap_or_else(|| panic!("Input port `{}` is not provided", port.name));
+                assert!(
+                    bits.len() == port.width,
+                    "Input port `{}` has {} bits but {} were provided",
+                    port.name,
+                    port.width,
+                    bits.len()
+                );
+                bits.iter().cloned()
+            })
+            .collect()
+    }
+
+    fn output_ports<C>(
+        &self,
+        outputs: Vec<impl_bool_frontend::FheBool<C>>,
+    ) -> HashMap<String, Vec<impl_bool_frontend::FheBool<C>>> {
+        let mut outputs = outputs.into_iter();
+        self.outputs
+            .iter()
+            .map(|port| {
+                (
+                    port.name.clone(),
+                    outputs.by_ref().take(port.width).collect(),
+                )
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+
+    use crate::{Decryptor, Encryptor, MockClientKey};
+
+    use super::*;
+
+    /// 2 bit adder `s = a + b` with `m = c ? b : a` written with a mix of
+    /// `.names` and Yosys cells, out of order, with a continuation line
+    const ADDER: &str = "
+# 2 bit adder
+.model adder
+.inputs a[0] a[1] b[0] \\
+    b[1] c
+.outputs s[0] s[1] s[2] m[1] m[0]
+.names a[0] b[0] s[0]
+10 1
+01 1
+.subckt $_XOR_ A=a[1] B=b[1] Y=x1
+.gate $_XNOR_ A=x1 B=c0 Y=nx
+.names nx s[1]
+0 1
+.names a[0] b[0] c0
+11 1
+.subckt $_AND_ A=a[1] B=b[1] Y=g1
+.subckt $_ANDNOT_ A=x1 B=nc0 Y=p1
+.subckt $_NOT_ A=c0 Y=nc0
+.names g1 p1 x1 s[2]
+1-- 1
+-1- 1
+.subckt $_MUX_ A=a[0] B=b[0] S=c Y=m[0]
+.subckt $_MUX_ A=a[1] B=b[1] S=c Y=m[1]
+.end
+";
+
+    fn decrypt(bits: &[MockFheBool]) -> u8 {
+        bits.iter()
+            .rev()
+            .fold(0, |acc, b| (acc << 1) | MockClientKey.decrypt(b) as u8)
+    }
+
+    #[test]
+    fn blif_adder() {
+        let netlist = Circuit::from_blif(ADDER).unwrap();
+        let port = |name: &str, width| Port {
+            name: name.to_string(),
+            width,
+        };
+        assert_eq!(
+            netlist.inputs,
+            vec![port("a", 2), port("b", 2), port("c", 1)]
+        );
+        assert_eq!(netlist.outputs, vec![port("s", 3), port("m", 2)]);
+
+        for (a, b, c) in itertools::iproduct!(0..4u8, 0..4u8, [false, true]) {
+            let encrypt = |v: u8, width| {
+                (0..width)
+                    .map(|i| MockClientKey.encrypt(&((v >> i) & 1 == 1)))
+                    .collect_vec()
+            };
+            let inputs = HashMap::from([
+                ("a", encrypt(a, 2)),
+                ("b", encrypt(b, 2)),
+                ("c", encrypt(c as u8, 1)),
+            ]);
+            let outputs = netlist.evaluate_mock(&inputs);
+            assert_eq!(decrypt(&outputs["s"]), a + b);
+            assert_eq!(decrypt(&outputs["m"]), if c { b } else { a });
+        }
+    }
+
+    #[test]
+    fn blif_names() {
+        let netlist = Circuit::from_blif(
+            "
+.model names
+.inputs a b c
+.outputs zero one nand nor xnor same majority
+.names zero
+.names one
+1
+.names a b nand
+0- 1
+-0 1
+.names a b nor
+00 1
+.names a b xnor
+01 0
+10 0
+.names a same
+1 1
+.names a b c majority
+11- 1
+1-1 1
+-11 1
+.end
+",
+        )
+        .unwrap();
+        // 2 input covers are single gates
+        assert_eq!(netlist.circuit.bootstraps(), 3 + 5);
+
+        for (a, b, c) in itertools::iproduct!([false, true], [false, true], [false, true]) {
+            let inputs = HashMap::from([
+                ("a", vec![MockClientKey.encrypt(&a)]),
+                ("b", vec![MockClientKey.encrypt(&b)]),
+                ("c", vec![MockClientKey.encrypt(&c)]),
+            ]);
+            let outputs = netlist.evaluate_mock(&inputs);
+            let output = |name: &str| -> bool { MockClientKey.decrypt(&outputs[name][0]) };
+            assert!(!output("zero"));
+            assert!(output("one"));
+            assert_eq!(output("nand"), !(a & b));
+            assert_eq!(output("nor"), !(a | b));
+            assert_eq!(output("xnor"), a == b);
+            assert_eq!(output("same"), a);
+            assert_eq!(output("majority"), (a as u8 + b as u8 + c as u8) >= 2);
+        }
+    }
+
+    #[test]
+    fn blif_errors() {
+        let error = |netlist: &str| Circuit::from_blif(netlist).unwrap_err();
+
+        let e = error(".model m\n.inputs a\n.outputs y\n.latch a y re clk 0\n");
+        assert_eq!(e.line, 4);
+        assert_eq!(
+            e.to_string(),
+            "line 4: Sequential netlists are not supported"
+        );
+
+        let e = error(".inputs a b\n.outputs y\n.subckt $_AOI3_ A=a B=b C=a Y=y\n");
+        assert_eq!(e.to_string(), "line 3: Unsupported cell `$_AOI3_`");
+
+        let e = error(".inputs a b\n.outputs y\n.subckt $_AND_ A=a Y=y\n");
+        assert_eq!(e.to_string(), "line 3: Pin `B` is not connected");
+
+        let e = error(".inputs a b\n.outputs y\n.names a b y\n11 1\n00 0\n");
+        assert_eq!(
+            e.to_string(),
+            "line 5: Cover mixes on-set and off-set cubes"
+        );
+
+        let e = error(".inputs a b\n.outputs y\n.names a b y\n1 1\n");
+        assert_eq!(e.to_string(), "line 4: Expected a cube of 2 inputs");
+
+        let e = error(".inputs a\n.outputs y\n.names a y\n1 1\n.names a y\n0 1\n");
+        assert_eq!(e.to_string(), "line 5: Net `y` is driven more than once");
+
+        let e = error(".inputs a\n.outputs y\n.subckt $_AND_ A=a B=x Y=y\n");
+        assert_eq!(e.to_string(), "line 3: Net `x` is not driven");
+
+        let e =
+            error(".inputs a\n.outputs y\n.subckt $_AND_ A=a B=x Y=y\n.subckt $_NOT_ A=y Y=x\n");
+        assert!(e.message.contains("depends on itself"));
+
+        let e = error(".inputs a[0] a[2]\n.outputs y\n.names y\n.end\n");
+        assert_eq!(
+            e.to_string(),
+            "line 4: Bits of port `a` are not contiguous from 0"
+        );
+    }
+}
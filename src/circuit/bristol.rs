@@ -0,0 +1,437 @@
+//! Parser of circuits in Bristol Fashion
+//!
+//! A Bristol Fashion netlist starts with a header
+//!
+//! ```text
+//! <no. of gates> <no. of wires>
+//! <no. of input values> <bits of 1st input value> ...
+//! <no. of output values> <bits of 1st output value> ...
+//! ```
+//!
+//! followed by a gate per line. Input values are assigned to the first wires
+//! and output values to the last wires, in order. Supported gates are
+//!
+//! ```text
+//! 2 1 <in> <in> <out> XOR
+//! 2 1 <in> <in> <out> AND
+//! 1 1 <in> <out> INV
+//! 1 1 <0 or 1> <out> EQ
+//! 1 1 <in> <out> EQW
+//! 2k k <in_1> ... <in_2k> <out_1> ... <out_k> MAND
+//! ```
+//!
+//! where MAND evaluates k AND gates on `(in_i, in_{k+i})`.
+
+use std::collections::HashMap;
+
+use super::{Circuit, ParseError};
+use crate::LazyFheBool;
+
+/// Circuit parsed from a Bristol Fashion netlist
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BristolCircuit {
+    pub circuit: Circuit,
+    /// No. of bits of each input value. Bits of input values are inputs of
+    /// `circuit` in order.
+    pub input_sizes: Vec<usize>,
+    /// No. of bits of each output value. Bits of output values are outputs
+    /// of `circuit` in order.
+    pub output_sizes: Vec<usize>,
+}
+
+enum BristolGate {
+    Xor(usize, usize, usize),
+    And(usize, usize, usize),
+    Inv(usize, usize),
+    Eq(bool, usize),
+    Eqw(usize, usize),
+}
+
+/// Tokenized lines of the netlist along with their line no.
+struct Lines<'a> {
+    lines: std::vec::IntoIter<(usize, Vec<&'a str>)>,
+    last: usize,
+}
+
+impl<'a> Lines<'a> {
+    fn new(netlist: &'a str) -> Lines<'a> {
+        Lines {
+            lines: netlist
+                .lines()
+                .enumerate()
+                .map(|(index, line)| (index + 1, line.split_whitespace().collect::<Vec<_>>()))
+                .filter(|(_, tokens)| !tokens.is_empty())
+                .collect::<Vec<_>>()
+                .into_iter(),
+            last: 0,
+        }
+    }
+
+    fn next(&mut self, expected: &str) -> Result<(usize, Vec<&'a str>), ParseError> {
+        match self.lines.next() {
+            Some((line, tokens)) => {
+                self.last = line;
+                Ok((line, tokens))
+            }
+            None => Err(ParseError::new(
+                self.last + 1,
+                format!("Expected {expected} but found end of netlist"),
+            )),
+        }
+    }
+}
+
+fn parse_usize(line: usize, token: &str) -> Result<usize, ParseError> {
+    token
+        .parse::<usize>()
+        .map_err(|_| ParseError::new(line, format!("Expected a number but found `{token}`")))
+}
+
+/// Returns sum of `values`, or `ParseError` if it overflows
+fn checked_sum(line: usize, values: &[usize]) -> Result<usize, ParseError> {
+    values
+        .iter()
+        .try_fold(0usize, |sum, v| sum.checked_add(*v))
+        .ok_or_else(|| ParseError::new(line, "No. of wires overflows".to_string()))
+}
+
+/// Parses `<count> <size_1> ... <size_count>`
+fn parse_sizes(line: usize, tokens: &[&str]) -> Result<Vec<usize>, ParseError> {
+    let sizes = tokens
+        .iter()
+        .map(|t| parse_usize(line, t))
+        .collect::<Result<Vec<_>, _>>()?;
+    if sizes.is_empty() || sizes[0] != sizes.len() - 1 {
+        return Err(ParseError::new(
+            line,
+            "Expected no. of values followed by size of each value".to_string(),
+        ));
+    }
+    Ok(sizes[1..].to_vec())
+}
+
+impl Circuit {
+    /// Parses Bristol Fashion `netlist`. Refer to the module documentation of
+    /// the format.
+    pub fn from_bristol(netlist: &str) -> Result<BristolCircuit, ParseError> {
+        let mut lines = Lines::new(netlist);
+
+        let (line, header) = lines.next("header")?;
+        if header.len() != 2 {
+            return Err(ParseError::new(
+                line,
+                "Expected no. of gates and no. of wires".to_string(),
+            ));
+        }
+        let gate_count = parse_usize(line, header[0])?;
+        let wire_count = parse_usize(line, header[1])?;
+
+        let (line, tokens) = lines.next("input sizes")?;
+        let input_sizes = parse_sizes(line, &tokens)?;
+        let inputs = checked_sum(line, &input_sizes)?;
+        let (line, tokens) = lines.next("output sizes")?;
+        let output_sizes = parse_sizes(line, &tokens)?;
+        let outputs = checked_sum(line, &output_sizes)?;
+        if checked_sum(line, &[inputs, outputs])? > wire_count {
+            return Err(ParseError::new(
+                line,
+                format!("{inputs} inputs and {outputs} outputs do not fit in {wire_count} wires"),
+            ));
+        }
+
+        // Wires assigned by gates are indexed in order of assignment after
+        // the inputs, hence no. of wires allocated is bounded by no. of gates
+        // instead of no. of wires claimed by the header
+        let mut assigned: HashMap<usize, usize> = HashMap::new();
+        let index = |assigned: &HashMap<usize, usize>, w: usize| {
+            if w < inputs {
+                Some(w)
+            } else {
+                assigned.get(&w).copied()
+            }
+        };
+
+        let mut gates = vec![];
+        for _ in 0..gate_count {
+            let (line, tokens) = lines.next("gate")?;
+            let (op, operands) = tokens.split_last().unwrap();
+            let operands = operands
+                .iter()
+                .map(|t| parse_usize(line, t))
+                .collect::<Result<Vec<_>, _>>()?;
+            if operands.len() < 2
+                || operands[0].checked_add(operands[1]) != Some(operands.len() - 2)
+            {
+                return Err(ParseError::new(
+                    line,
+                    "Expected no. of inputs and outputs followed by the wires".to_string(),
+                ));
+            }
+            let arity = operands[0];
+            let (ins, outs) = operands[2..].split_at(arity);
+            let expected = match *op {
+                "XOR" | "AND" => (2, 1),
+                "INV" | "EQ" | "EQW" => (1, 1),
+                "MAND" => (2 * outs.len(), outs.len()),
+                _ => return Err(ParseError::new(line, format!("Unsupported gate `{op}`"))),
+            };
+            if (arity, outs.len()) != expected {
+                return Err(ParseError::new(
+                    line,
+                    format!(
+                        "{op} expects {} inputs and {} outputs",
+                        expected.0, expected.1
+                    ),
+                ));
+            }
+
+            // EQ assigns a constant instead of a wire
+            let wire_ins = if *op == "EQ" { &[] } else { ins };
+            let wire_ins = wire_ins
+                .iter()
+                .map(|w| {
+                    index(&assigned, *w).ok_or_else(|| {
+                        ParseError::new(line, format!("Wire {w} is used before it is assigned"))
+                    })
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            let mut wire_outs = vec![];
+            for w in outs {
+                if *w >= wire_count || index(&assigned, *w).is_some() {
+                    return Err(ParseError::new(
+                        line,
+                        format!("Wire {w} is out of range or assigned twice"),
+                    ));
+                }
+                let index = inputs + assigned.len();
+                assigned.insert(*w, index);
+                wire_outs.push(index);
+            }
+            let (ins, outs) = (wire_ins, wire_outs);
+
+            match *op {
+                "XOR" => gates.push(BristolGate::Xor(ins[0], ins[1], outs[0])),
+                "AND" => gates.push(BristolGate::And(ins[0], ins[1], outs[0])),
+                "INV" => gates.push(BristolGate::Inv(ins[0], outs[0])),
+                "EQ" => match operands[2] {
+                    0 | 1 => gates.push(BristolGate::Eq(operands[2] == 1, outs[0])),
+                    _ => {
+                        return Err(ParseError::new(
+                            line,
+                            "EQ expects constant 0 or 1".to_string(),
+                        ))
+                    }
+                },
+                "EQW" => gates.push(BristolGate::Eqw(ins[0], outs[0])),
+                _ => (0..outs.len()).for_each(|i| {
+                    gates.push(BristolGate::And(ins[i], ins[outs.len() + i], outs[i]))
+                }),
+            }
+        }
+        if let Ok((line, _)) = lines.next("end of netlist") {
+            return Err(ParseError::new(
+                line,
+                format!("Expected {gate_count} gates but found more"),
+            ));
+        }
+        // stops at the first unassigned wire, hence iterates at most over
+        // assigned wires
+        let output_wires = (wire_count - outputs..wire_count)
+            .map(|w| {
+                index(&assigned, w).ok_or_else(|| {
+                    ParseError::new(lines.last, format!("Output wire {w} is never assigned"))
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let circuit = Circuit::record(|| {
+            let mut wires: Vec<Option<LazyFheBool>> = vec![None; inputs + assigned.len()];
+            wires[..inputs]
+                .iter_mut()
+                .for_each(|w| *w = Some(LazyFheBool::input()));
+            let wire = |wires: &[Option<LazyFheBool>], w: usize| {
+                wires[w].clone().expect("Wire is assigned before use")
+            };
+            for gate in gates {
+                match gate {
+                    BristolGate::Xor(a, b, out) => {
+                        wires[out] = Some(&wire(&wires, a) ^ &wire(&wires, b))
+                    }
+                    BristolGate::And(a, b, out) => {
+                        wires[out] = Some(&wire(&wires, a) & &wire(&wires, b))
+                    }
+                    BristolGate::Inv(a, out) => wires[out] = Some(!&wire(&wires, a)),
+                    BristolGate::Eq(m, out) => wires[out] = Some(LazyFheBool::trivial(m)),
+                    BristolGate::Eqw(a, out) => wires[out] = Some(wire(&wires, a)),
+                }
+            }
+            output_wires.iter().map(|w| wire(&wires, *w)).collect()
+        });
+
+        Ok(BristolCircuit {
+            circuit,
+            input_sizes,
+            output_sizes,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+    use rand::{thread_rng, Rng};
+
+    use crate::{Decryptor, Encryptor, FhePlaintext, MockClientKey, MockFheBool};
+
+    use super::*;
+
+    /// Returns Bristol Fashion netlist of 8 bit ripple carry adder with inputs
+    /// and output in little endian
+    fn adder_netlist() -> String {
+        // wires: a = 0..8, b = 8..16, carries = 16.., sum = last 8 wires
+        let mut gates = vec![];
+        let mut next = 16;
+        let mut wire = || {
+            next += 1;
+            next - 1
+        };
+        let sum_start = 16 + 4 * 7;
+        let mut carry = None;
+        for i in 0..8 {
+            let (a, b, sum) = (i, 8 + i, sum_start + i);
+            match carry {
+                None => {
+                    gates.push(format!("2 1 {a} {b} {sum} XOR"));
+                    let c = wire();
+                    gates.push(format!("2 1 {a} {b} {c} AND"));
+                    carry = Some(c);
+                }
+                Some(c) if i < 7 => {
+                    // carry_out = ((a ^ c) & (b ^ c)) ^ c
+                    let (a_c, b_c, and, c_out) = (wire(), wire(), wire(), wire());
+                    gates.push(format!("2 1 {a} {c} {a_c} XOR"));
+                    gates.push(format!("2 1 {b} {c} {b_c} XOR"));
+                    gates.push(format!("2 1 {a_c} {b} {sum} XOR"));
+                    gates.push(format!("2 1 {a_c} {b_c} {and} AND"));
+                    gates.push(format!("2 1 {and} {c} {c_out} XOR"));
+                    carry = Some(c_out);
+                }
+                Some(c) => {
+                    let a_c = wire();
+                    gates.push(format!("2 1 {a} {c} {a_c} XOR"));
+                    gates.push(format!("2 1 {a_c} {b} {sum} XOR"));
+                }
+            }
+        }
+        let wires = sum_start + 8;
+        format!(
+            "{} {wires}\n2 8 8\n1 8\n\n{}\n",
+            gates.len(),
+            gates.join("\n")
+        )
+    }
+
+    fn evaluate(circuit: &Circuit, inputs: &[bool]) -> Vec<bool> {
+        let inputs = inputs
+            .iter()
+            .map(|m| MockClientKey.encrypt(m))
+            .collect::<Vec<MockFheBool>>();
+        circuit
+            .evaluate_mock(&inputs)
+            .iter()
+            .map(|c| MockClientKey.decrypt(c))
+            .collect()
+    }
+
+    #[test]
+    fn bristol_adder() {
+        let parsed = Circuit::from_bristol(&adder_netlist()).unwrap();
+        assert_eq!(parsed.input_sizes, vec![8, 8]);
+        assert_eq!(parsed.output_sizes, vec![8]);
+        assert_eq!(parsed.circuit.inputs(), 16);
+        assert_eq!(parsed.circuit.outputs(), 8);
+        assert_eq!(parsed.circuit.gate_counts().and, 7);
+
+        for _ in 0..100 {
+            let (m0, m1) = (thread_rng().gen::<u8>(), thread_rng().gen::<u8>());
+            let inputs = [m0.to_bits(), m1.to_bits()].concat();
+            let out = evaluate(&parsed.circuit, &inputs);
+            assert_eq!(u8::from_bits(&out), m0.wrapping_add(m1));
+        }
+    }
+
+    #[test]
+    fn bristol_gates() {
+        // out_0 = !(a & b), out_1 = 1, out_2 = c, out_3 = a & c, out_4 = b & c
+        let netlist = "\
+            6 11\n\
+            2 2 1\n\
+            1 5\n\
+            \n\
+            2 1 0 1 3 AND\n\
+            1 1 3 6 INV\n\
+            1 1 1 7 EQ\n\
+            1 1 2 8 EQW\n\
+            4 2 0 1 2 2 9 10 MAND\n\
+            1 1 0 4 EQ\n";
+        let parsed = Circuit::from_bristol(netlist).unwrap();
+        assert_eq!(parsed.circuit.bootstraps(), 3);
+        for m in (0..8).map(|v: u8| (0..3).map(|i| (v >> i) & 1 == 1).collect_vec()) {
+            let (a, b, c) = (m[0], m[1], m[2]);
+            assert_eq!(
+                evaluate(&parsed.circuit, &m),
+                vec![!(a & b), true, c, a & c, b & c]
+            );
+        }
+    }
+
+    #[test]
+    fn bristol_errors() {
+        let cases = [
+            ("", 1, "Expected header"),
+            ("1 3\n1 1\n1 1\n2 1 0 1 2 OR\n", 4, "Unsupported gate `OR`"),
+            ("1 3\n1 1\n1 1\n2 1 0 1 2 AND\n", 4, "Wire 1 is used before"),
+            (
+                "1 3\n2 1 1\n1 1\n2 1 0 1 1 AND\n",
+                4,
+                "Wire 1 is out of range",
+            ),
+            ("1 3\n2 1 1\n1 1\n2 1 0 x 2 AND\n", 4, "Expected a number"),
+            (
+                "1 3\n2 1 1\n1 1\n3 1 0 1 2 AND\n",
+                4,
+                "Expected no. of inputs",
+            ),
+            ("1 3\n2 1 1\n1 1\n1 1 0 2 AND\n", 4, "AND expects 2 inputs"),
+            ("2 3\n2 1 1\n1 1\n2 1 0 1 2 AND\n", 5, "Expected gate"),
+            ("1 4\n2 1 1\n1 1\n2 1 0 1 2 AND\n", 4, "Output wire 3"),
+            ("1 3\n2 1\n1 1\n", 2, "Expected no. of values"),
+            ("1 3\n18446744073709551615 1\n", 2, "Expected no. of values"),
+            (
+                "1 3\n2 18446744073709551615 1\n1 1\n",
+                2,
+                "No. of wires overflows",
+            ),
+            (
+                "1 3\n1 18446744073709551615\n1 1\n",
+                3,
+                "No. of wires overflows",
+            ),
+            (
+                "1 3\n2 1 1\n1 1\n18446744073709551615 1 0 1 2 AND\n",
+                4,
+                "Expected no. of inputs",
+            ),
+            (
+                "1 18446744073709551615\n2 1 1\n1 1\n2 1 0 1 2 AND\n",
+                4,
+                "Output wire 18446744073709551614",
+            ),
+        ];
+        for (netlist, line, message) in cases {
+            let error = Circuit::from_bristol(netlist).unwrap_err();
+            assert_eq!(error.line, line, "{netlist:?}: {error}");
+            assert!(error.message.starts_with(message), "{netlist:?}: {error}");
+        }
+    }
+}
@@ -0,0 +1,855 @@
+//! Deferred evaluation of circuits
+//!
+//! Operations on Lazy types (`LazyFheBool`, `LazyFheUint8`, `LazyFheInt8`,
+//! ...) do not evaluate any gate. Instead each gate is recorded as a node of a
+//! directed acyclic graph, a `Circuit`. The circuit can then be evaluated on
+//! Fhe types with `Circuit::evaluate`, which evaluates all gates at the same
+//! depth (i.e. gates that do not depend on each other) in parallel. For
+//! example
+//!
+//! ```ignore
+//! let circuit = Circuit::record(|| {
+//!     let a = LazyFheUint8::input();
+//!     let b = LazyFheUint8::input();
+//!     (&a + &b).bits()
+//! });
+//! let inputs = [a.bits(), b.bits()].concat();
+//! let sum = FheUint8::from_bits(&circuit.evaluate(&pool, &inputs));
+//! ```
+
+mod blif;
+mod bristol;
+mod optimizer;
+
+use std::{cell::RefCell, fmt::Display};
+
+use crate::{
+    bool::{
+        impl_bool_frontend, lut::MAX_MESSAGE_BITS, Backend, BooleanGates, FheBool, LazyFheBool,
+        LazyFheShortUint, LookupTables, MockFheBool, ThreadPool,
+    },
+    shortint::{
+        ops::arbitrary_bit_lookup, take_div_zero_error_flag, DivZeroErrorFlag, LazyFheInt,
+        LazyFheUint,
+    },
+    stats::GateCounts,
+    utils::WithLocal,
+};
+
+pub use blif::{BlifCircuit, Port};
+pub use bristol::BristolCircuit;
+pub use optimizer::OptimizationReport;
+
+thread_local! {
+    static CIRCUIT_RECORDER: RefCell<Option<CircuitRecorder>> = const { RefCell::new(None) };
+}
+
+/// Output of a node of `Circuit`, possibly negated
+///
+/// Negation is free (it does not require a bootstrap), hence it is recorded
+/// on the wire instead of as a separate node.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Wire {
+    pub(crate) node: usize,
+    pub(crate) negated: bool,
+}
+
+impl Wire {
+    /// Wire of constant `m`. Node 0 of every circuit is constant false.
+    pub(crate) fn constant(m: bool) -> Wire {
+        Wire {
+            node: 0,
+            negated: m,
+        }
+    }
+}
+
+/// Boolean gates of 2 inputs that require a bootstrap
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Gate {
+    And,
+    Nand,
+    Or,
+    Nor,
+    Xor,
+    Xnor,
+}
+
+impl Gate {
+    /// Evaluates the gate on `c0` and `c1` and stores the output in `c0`
+    pub(crate) fn apply<E: BooleanGates>(
+        &self,
+        evaluator: &mut E,
+        c0: &mut E::Ciphertext,
+        c1: &E::Ciphertext,
+        key: &E::Key,
+    ) {
+        match self {
+            Gate::And => evaluator.and_inplace(c0, c1, key),
+            Gate::Nand => evaluator.nand_inplace(c0, c1, key),
+            Gate::Or => evaluator.or_inplace(c0, c1, key),
+            Gate::Nor => evaluator.nor_inplace(c0, c1, key),
+            Gate::Xor => evaluator.xor_inplace(c0, c1, key),
+            Gate::Xnor => evaluator.xnor_inplace(c0, c1, key),
+        }
+    }
+
+    fn count(&self, counts: &mut GateCounts) {
+        match self {
+            Gate::And => counts.and += 1,
+            Gate::Nand => counts.nand += 1,
+            Gate::Or => counts.or += 1,
+            Gate::Nor => counts.nor += 1,
+            Gate::Xor => counts.xor += 1,
+            Gate::Xnor => counts.xnor += 1,
+        }
+    }
+}
+
+/// Boolean gates of 3 inputs
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TernaryGate {
+    /// True iff at least 2 of the inputs are true
+    Majority,
+    /// First input selects the second input if true and the third otherwise
+    Mux,
+}
+
+impl TernaryGate {
+    /// Evaluates the gate on `c0`, `c1`, and `c2` and returns the output
+    pub(crate) fn apply<E: BooleanGates>(
+        &self,
+        evaluator: &mut E,
+        c0: &E::Ciphertext,
+        c1: &E::Ciphertext,
+        c2: &E::Ciphertext,
+        key: &E::Key,
+    ) -> E::Ciphertext {
+        match self {
+            TernaryGate::Majority => evaluator.majority(c0, c1, c2, key),
+            TernaryGate::Mux => evaluator.mux(c0, c1, c2, key),
+        }
+    }
+
+    fn count(&self, counts: &mut GateCounts) {
+        match self {
+            TernaryGate::Majority => counts.majority += 1,
+            TernaryGate::Mux => counts.mux += 1,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum Node {
+    /// Constant false
+    Constant,
+    /// `i`th input of the circuit
+    Input(usize),
+    Gate(Gate, Wire, Wire),
+    TernaryGate(TernaryGate, Wire, Wire, Wire),
+}
+
+impl Node {
+    /// Returns input wires of the node
+    pub(crate) fn inputs(&self) -> Vec<Wire> {
+        match self {
+            Node::Constant | Node::Input(_) => vec![],
+            Node::Gate(_, c0, c1) => vec![*c0, *c1],
+            Node::TernaryGate(_, c0, c1, c2) => vec![*c0, *c1, *c2],
+        }
+    }
+}
+
+/// Directed acyclic graph of boolean gates
+///
+/// Nodes are stored in topological order, that is inputs of a gate always
+/// precede the gate.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Circuit {
+    pub(crate) nodes: Vec<Node>,
+    pub(crate) inputs: usize,
+    pub(crate) outputs: Vec<Wire>,
+}
+
+/// Error of parsing a circuit netlist
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseError {
+    /// Line no. (starting at 1) of the netlist where parsing failed
+    pub line: usize,
+    pub message: String,
+}
+
+impl ParseError {
+    pub(crate) fn new(line: usize, message: String) -> ParseError {
+        ParseError { line, message }
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Records gates evaluated on `Wire`s of the circuit being recorded on the
+/// current thread
+pub(crate) struct CircuitRecorder {
+    nodes: Vec<Node>,
+    inputs: usize,
+}
+
+impl CircuitRecorder {
+    fn new() -> CircuitRecorder {
+        CircuitRecorder {
+            nodes: vec![Node::Constant],
+            inputs: 0,
+        }
+    }
+
+    fn push(&mut self, node: Node) -> Wire {
+        self.nodes.push(node);
+        Wire {
+            node: self.nodes.len() - 1,
+            negated: false,
+        }
+    }
+
+    fn input(&mut self) -> Wire {
+        self.inputs += 1;
+        self.push(Node::Input(self.inputs - 1))
+    }
+
+    fn gate(&mut self, gate: Gate, c0: &mut Wire, c1: &Wire) {
+        *c0 = self.push(Node::Gate(gate, *c0, *c1));
+    }
+
+    fn ternary_gate(&mut self, gate: TernaryGate, c0: &Wire, c1: &Wire, c2: &Wire) -> Wire {
+        self.push(Node::TernaryGate(gate, *c0, *c1, *c2))
+    }
+}
+
+impl WithLocal for CircuitRecorder {
+    fn with_local<F, R>(func: F) -> R
+    where
+        F: Fn(&Self) -> R,
+    {
+        CIRCUIT_RECORDER.with_borrow(|r| func(r.as_ref().expect("Circuit is not being recorded")))
+    }
+
+    fn with_local_mut<F, R>(func: F) -> R
+    where
+        F: Fn(&mut Self) -> R,
+    {
+        CIRCUIT_RECORDER
+            .with_borrow_mut(|r| func(r.as_mut().expect("Circuit is not being recorded")))
+    }
+
+    fn with_local_mut_mut<F, R>(func: &mut F) -> R
+    where
+        F: FnMut(&mut Self) -> R,
+    {
+        CIRCUIT_RECORDER
+            .with_borrow_mut(|r| func(r.as_mut().expect("Circuit is not being recorded")))
+    }
+}
+
+impl Backend for Wire {
+    type Evaluator = CircuitRecorder;
+    type Key = ();
+
+    fn with_evaluator<F, R>(func: F) -> R
+    where
+        F: FnOnce(&mut CircuitRecorder, &()) -> R,
+    {
+        CIRCUIT_RECORDER
+            .with_borrow_mut(|r| func(r.as_mut().expect("Circuit is not being recorded"), &()))
+    }
+}
+
+impl BooleanGates for CircuitRecorder {
+    type Ciphertext = Wire;
+    type Key = ();
+
+    fn and_inplace(&mut self, c0: &mut Wire, c1: &Wire, _key: &()) {
+        self.gate(Gate::And, c0, c1);
+    }
+
+    fn nand_inplace(&mut self, c0: &mut Wire, c1: &Wire, _key: &()) {
+        self.gate(Gate::Nand, c0, c1);
+    }
+
+    fn or_inplace(&mut self, c0: &mut Wire, c1: &Wire, _key: &()) {
+        self.gate(Gate::Or, c0, c1);
+    }
+
+    fn nor_inplace(&mut self, c0: &mut Wire, c1: &Wire, _key: &()) {
+        self.gate(Gate::Nor, c0, c1);
+    }
+
+    fn xor_inplace(&mut self, c0: &mut Wire, c1: &Wire, _key: &()) {
+        self.gate(Gate::Xor, c0, c1);
+    }
+
+    fn xnor_inplace(&mut self, c0: &mut Wire, c1: &Wire, _key: &()) {
+        self.gate(Gate::Xnor, c0, c1);
+    }
+
+    // Negated inputs are free, hence AND-NOT and OR-NOT gates are recorded as
+    // AND and OR gates with a negated input
+
+    fn andny_inplace(&mut self, c0: &mut Wire, c1: &Wire, _key: &()) {
+        self.not_inplace(c0);
+        self.gate(Gate::And, c0, c1);
+    }
+
+    fn andyn_inplace(&mut self, c0: &mut Wire, c1: &Wire, _key: &()) {
+        self.gate(Gate::And, c0, &self.not(c1));
+    }
+
+    fn orny_inplace(&mut self, c0: &mut Wire, c1: &Wire, _key: &()) {
+        self.not_inplace(c0);
+        self.gate(Gate::Or, c0, c1);
+    }
+
+    fn oryn_inplace(&mut self, c0: &mut Wire, c1: &Wire, _key: &()) {
+        self.gate(Gate::Or, c0, &self.not(c1));
+    }
+
+    fn not_inplace(&self, c: &mut Wire) {
+        c.negated = !c.negated;
+    }
+
+    fn and(&mut self, c0: &Wire, c1: &Wire, key: &()) -> Wire {
+        let mut out = *c0;
+        self.and_inplace(&mut out, c1, key);
+        out
+    }
+
+    fn nand(&mut self, c0: &Wire, c1: &Wire, key: &()) -> Wire {
+        let mut out = *c0;
+        self.nand_inplace(&mut out, c1, key);
+        out
+    }
+
+    fn or(&mut self, c0: &Wire, c1: &Wire, key: &()) -> Wire {
+        let mut out = *c0;
+        self.or_inplace(&mut out, c1, key);
+        out
+    }
+
+    fn nor(&mut self, c0: &Wire, c1: &Wire, key: &()) -> Wire {
+        let mut out = *c0;
+        self.nor_inplace(&mut out, c1, key);
+        out
+    }
+
+    fn xor(&mut self, c0: &Wire, c1: &Wire, key: &()) -> Wire {
+        let mut out = *c0;
+        self.xor_inplace(&mut out, c1, key);
+        out
+    }
+
+    fn xnor(&mut self, c0: &Wire, c1: &Wire, key: &()) -> Wire {
+        let mut out = *c0;
+        self.xnor_inplace(&mut out, c1, key);
+        out
+    }
+
+    fn andny(&mut self, c0: &Wire, c1: &Wire, key: &()) -> Wire {
+        let mut out = *c0;
+        self.andny_inplace(&mut out, c1, key);
+        out
+    }
+
+    fn andyn(&mut self, c0: &Wire, c1: &Wire, key: &()) -> Wire {
+        let mut out = *c0;
+        self.andyn_inplace(&mut out, c1, key);
+        out
+    }
+
+    fn orny(&mut self, c0: &Wire, c1: &Wire, key: &()) -> Wire {
+        let mut out = *c0;
+        self.orny_inplace(&mut out, c1, key);
+        out
+    }
+
+    fn oryn(&mut self, c0: &Wire, c1: &Wire, key: &()) -> Wire {
+        let mut out = *c0;
+        self.oryn_inplace(&mut out, c1, key);
+        out
+    }
+
+    fn not(&self, c: &Wire) -> Wire {
+        let mut out = *c;
+        self.not_inplace(&mut out);
+        out
+    }
+
+    fn majority(&mut self, c0: &Wire, c1: &Wire, c2: &Wire, _key: &()) -> Wire {
+        self.ternary_gate(TernaryGate::Majority, c0, c1, c2)
+    }
+
+    fn mux(&mut self, selector: &Wire, if_true: &Wire, if_false: &Wire, _key: &()) -> Wire {
+        self.ternary_gate(TernaryGate::Mux, selector, if_true, if_false)
+    }
+
+    fn trivial(&self, m: bool) -> Wire {
+        Wire::constant(m)
+    }
+}
+
+/// Circuits are evaluated on `FheBool`s, hence messages of `LazyFheShortUint`
+/// are recorded as wires of their bits in little endian, and lookup tables as
+/// lookups over the bits (refer to `FheUint8::lookup`). Note that, unlike
+/// `FheShortUint`, a recorded lookup table requires a mux tree, instead of a
+/// single bootstrap, per output bit.
+impl LookupTables for CircuitRecorder {
+    type Message = Vec<Wire>;
+
+    fn max_message_bits(&self) -> usize {
+        MAX_MESSAGE_BITS
+    }
+
+    fn trivial_message(&self, m: u64, bits: usize) -> Vec<Wire> {
+        (0..bits)
+            .map(|i| Wire::constant((m >> i) & 1 == 1))
+            .collect()
+    }
+
+    fn add_message_inplace(&mut self, c0: &mut Vec<Wire>, c1: &Vec<Wire>, bits: usize, key: &()) {
+        // Sum is less than 2^{bits}, hence carry out of the last bit is
+        // always false and is left to dead gate removal
+        let mut carry = None;
+        c0.iter_mut().zip(c1.iter()).take(bits).for_each(|(a, b)| {
+            carry = Some(match &carry {
+                None => self.half_adder(a, b, key),
+                Some(c) => self.full_adder(a, b, c, key),
+            });
+        });
+    }
+
+    fn apply_lut<F: Fn(u64) -> u64>(
+        &mut self,
+        c: &Vec<Wire>,
+        bits: usize,
+        f: F,
+        key: &(),
+    ) -> Vec<Wire> {
+        let table = (0..1u64 << bits)
+            .map(|m| (0..bits).map(|i| (f(m) >> i) & 1 == 1).collect())
+            .collect::<Vec<Vec<bool>>>();
+        arbitrary_bit_lookup(self, c, &table, bits, key)
+    }
+
+    fn apply_lut_to_bool<F: Fn(u64) -> bool>(
+        &mut self,
+        c: &Vec<Wire>,
+        bits: usize,
+        f: F,
+        key: &(),
+    ) -> Wire {
+        let table = (0..1u64 << bits).map(|m| vec![f(m)]).collect::<Vec<_>>();
+        arbitrary_bit_lookup(self, c, &table, 1, key)[0]
+    }
+
+    fn bool_to_message(&mut self, c: &Wire, bits: usize, m: u64, _key: &()) -> Vec<Wire> {
+        (0..bits)
+            .map(|i| {
+                if (m >> i) & 1 == 1 {
+                    *c
+                } else {
+                    Wire::constant(false)
+                }
+            })
+            .collect()
+    }
+}
+
+impl LazyFheBool {
+    /// Returns next input of the circuit being recorded on the current thread
+    pub fn input() -> LazyFheBool {
+        Wire::with_evaluator(|e, _| LazyFheBool { data: e.input() })
+    }
+}
+
+impl<const BITS: usize> LazyFheUint<BITS> {
+    /// Returns next `BITS` inputs, in little endian, of the circuit being
+    /// recorded on the current thread
+    pub fn input() -> LazyFheUint<BITS> {
+        LazyFheUint::from_bits(&(0..BITS).map(|_| LazyFheBool::input()).collect::<Vec<_>>())
+    }
+}
+
+impl<const BITS: usize> LazyFheShortUint<BITS> {
+    /// Returns next `BITS` inputs, in little endian, of the circuit being
+    /// recorded on the current thread
+    pub fn input() -> LazyFheShortUint<BITS> {
+        LazyFheShortUint {
+            data: (0..BITS).map(|_| LazyFheBool::input().data).collect(),
+        }
+    }
+}
+
+impl<const BITS: usize> LazyFheInt<BITS> {
+    /// Returns next `BITS` inputs, in little endian, of the circuit being
+    /// recorded on the current thread
+    pub fn input() -> LazyFheInt<BITS> {
+        LazyFheInt::from_bits(&(0..BITS).map(|_| LazyFheBool::input()).collect::<Vec<_>>())
+    }
+}
+
+/// Work item of evaluation of a circuit
+enum Task {
+    Constant,
+    Gate(Gate, Wire, Wire),
+    TernaryGate(TernaryGate, Wire, Wire, Wire),
+    Output(Wire),
+}
+
+impl Task {
+    fn evaluate<C: Backend>(&self, values: &[Option<C>]) -> C {
+        C::with_evaluator(|e, key| match self {
+            Task::Constant => e.trivial(false),
+            Task::Gate(gate, c0, c1) => {
+                let mut out = read(e, values, c0);
+                gate.apply(e, &mut out, &read(e, values, c1), key);
+                out
+            }
+            Task::TernaryGate(gate, c0, c1, c2) => {
+                let (c0, c1, c2) = (
+                    read(e, values, c0),
+                    read(e, values, c1),
+                    read(e, values, c2),
+                );
+                gate.apply(e, &c0, &c1, &c2, key)
+            }
+            Task::Output(wire) => read(e, values, wire),
+        })
+    }
+}
+
+/// Returns value of `wire` given `values` of the nodes
+fn read<E: BooleanGates>(
+    evaluator: &E,
+    values: &[Option<E::Ciphertext>],
+    wire: &Wire,
+) -> E::Ciphertext
+where
+    E::Ciphertext: Clone,
+{
+    let mut c = values[wire.node]
+        .clone()
+        .expect("Inputs of a node are evaluated before the node");
+    if wire.negated {
+        evaluator.not_inplace(&mut c);
+    }
+    c
+}
+
+impl Circuit {
+    /// Records circuit of operations on Lazy types executed by `f`. `f`
+    /// returns outputs of the circuit.
+    ///
+    /// Inputs of the circuit are created with `LazyFheBool::input`,
+    /// `LazyFheUint8::input`, etc. in the order they are expected by
+    /// `Circuit::evaluate`.
+    pub fn record<F: FnOnce() -> Vec<LazyFheBool>>(f: F) -> Circuit {
+        let previous = CIRCUIT_RECORDER.with_borrow_mut(|r| r.replace(CircuitRecorder::new()));
+        let previous_div_zero_error = take_div_zero_error_flag::<Wire>();
+
+        let outputs = f();
+
+        let recorder = CIRCUIT_RECORDER
+            .with_borrow_mut(|r| std::mem::replace(r, previous))
+            .expect("Circuit is being recorded");
+        Wire::with_div_zero_error_flag(|flag| *flag = previous_div_zero_error);
+
+        Circuit {
+            nodes: recorder.nodes,
+            inputs: recorder.inputs,
+            outputs: outputs.into_iter().map(|o| o.data).collect(),
+        }
+    }
+
+    /// Returns no. of inputs
+    pub fn inputs(&self) -> usize {
+        self.inputs
+    }
+
+    /// Returns no. of outputs
+    pub fn outputs(&self) -> usize {
+        self.outputs.len()
+    }
+
+    /// Returns no. of gates per gate type
+    pub fn gate_counts(&self) -> GateCounts {
+        let mut counts = GateCounts::default();
+        self.nodes.iter().for_each(|node| match node {
+            Node::Gate(gate, _, _) => gate.count(&mut counts),
+            Node::TernaryGate(gate, _, _, _) => gate.count(&mut counts),
+            Node::Constant | Node::Input(_) => {}
+        });
+        counts
+    }
+
+    /// Returns no. of bootstraps required to evaluate the circuit
+    pub fn bootstraps(&self) -> usize {
+        self.gate_counts().bootstraps()
+    }
+
+    /// Returns depth of the circuit, that is no. of gates on the longest path
+    /// from an input to an output
+    pub fn depth(&self) -> usize {
+        self.levels().len()
+    }
+
+    /// Returns gates grouped by depth. Gates at `i`th level only depend on
+    /// inputs and gates at levels < `i`.
+    pub(crate) fn levels(&self) -> Vec<Vec<usize>> {
+        let mut depth = vec![0; self.nodes.len()];
+        let mut levels: Vec<Vec<usize>> = vec![];
+        self.nodes.iter().enumerate().for_each(|(index, node)| {
+            if let Node::Gate(..) | Node::TernaryGate(..) = node {
+                depth[index] = 1 + node.inputs().iter().map(|w| depth[w.node]).max().unwrap();
+                if levels.len() < depth[index] {
+                    levels.push(vec![]);
+                }
+                levels[depth[index] - 1].push(index);
+            }
+        });
+        levels
+    }
+
+    /// Evaluates the circuit on `inputs` and returns the outputs
+    ///
+    /// Gates of each level are evaluated in parallel by workers of `pool`.
+    pub fn evaluate(&self, pool: &ThreadPool, inputs: &[FheBool]) -> Vec<FheBool> {
+        self.evaluate_with(inputs, |tasks, values| {
+            pool.par_map(tasks, |task| task.evaluate(values))
+        })
+    }
+
+    /// Evaluates the circuit on Mock `inputs` on the current thread and
+    /// returns the outputs
+    pub fn evaluate_mock(&self, inputs: &[MockFheBool]) -> Vec<MockFheBool> {
+        self.evaluate_with(inputs, |tasks, values| {
+            tasks.iter().map(|task| task.evaluate(values)).collect()
+        })
+    }
+
+    /// Evaluates the circuit level by level. `map` evaluates all tasks of a
+    /// level given values of the nodes evaluated so far.
+    fn evaluate_with<C: Backend, M>(
+        &self,
+        inputs: &[impl_bool_frontend::FheBool<C>],
+        map: M,
+    ) -> Vec<impl_bool_frontend::FheBool<C>>
+    where
+        M: Fn(&[Task], &[Option<C>]) -> Vec<C>,
+    {
+        assert!(
+            inputs.len() == self.inputs,
+            "Circuit expects {} inputs but {} were provided",
+            self.inputs,
+            inputs.len()
+        );
+
+        let mut values = vec![None; self.nodes.len()];
+        self.nodes.iter().enumerate().for_each(|(index, node)| {
+            if let Node::Input(i) = node {
+                values[index] = Some(inputs[*i].data().clone());
+            }
+        });
+        values[0] = map(&[Task::Constant], &values).pop();
+
+        for level in self.levels() {
+            let tasks = level
+                .iter()
+                .map(|index| match &self.nodes[*index] {
+                    Node::Gate(gate, c0, c1) => Task::Gate(*gate, *c0, *c1),
+                    Node::TernaryGate(gate, c0, c1, c2) => Task::TernaryGate(*gate, *c0, *c1, *c2),
+                    _ => unreachable!("Levels only contain gates"),
+                })
+                .collect::<Vec<_>>();
+            let outputs = map(&tasks, &values);
+            level
+                .into_iter()
+                .zip(outputs)
+                .for_each(|(index, c)| values[index] = Some(c));
+        }
+
+        let tasks = self
+            .outputs
+            .iter()
+            .map(|wire| Task::Output(*wire))
+            .collect::<Vec<_>>();
+        map(&tasks, &values)
+            .into_iter()
+            .map(|data| impl_bool_frontend::FheBool { data })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+    use rand::{thread_rng, Rng};
+
+    use crate::{
+        lazy_div_zero_error_flag, reset_error_flags, stats, Decryptor, Encryptor, FheIntegerOps,
+        LazyFheInt8, LazyFheRadixUint8, LazyFheShortUint, LazyFheUint8, MockClientKey, MockFheInt8,
+        MockFheShortUint, MockFheUint8,
+    };
+
+    use super::*;
+
+    #[test]
+    fn recorded_circuit_matches_eager_evaluation() {
+        fn circuit<T: FheIntegerOps>(a: &T, b: &T, c: &T) -> (T, T::Bool) {
+            let (q, r) = a.div_rem(b);
+            (q.wrapping_mul(c).wrapping_sub(&r).fhe_max(a), a.fhe_le(c))
+        }
+
+        let recorded = Circuit::record(|| {
+            let a = LazyFheUint8::input();
+            let b = LazyFheUint8::input();
+            let c = LazyFheUint8::input();
+            let (out, le) = circuit(&a, &b, &c);
+            let mut outputs = out.bits();
+            outputs.push(le);
+            outputs.push(lazy_div_zero_error_flag().unwrap());
+            outputs
+        });
+        assert_eq!(recorded.inputs(), 24);
+        assert_eq!(recorded.outputs(), 10);
+        assert!(recorded.depth() > 0 && recorded.depth() < recorded.bootstraps());
+
+        for _ in 0..100 {
+            let m = (0..3).map(|_| thread_rng().gen::<u8>()).collect_vec();
+            let c = m
+                .iter()
+                .map(|m| MockClientKey.encrypt(m))
+                .collect::<Vec<MockFheUint8>>();
+
+            reset_error_flags();
+            stats::reset();
+            let (want_out, want_le) = circuit(&c[0], &c[1], &c[2]);
+            let want_report = stats::take();
+
+            let inputs = c.iter().flat_map(|c| c.bits()).collect_vec();
+            let outputs = recorded.evaluate_mock(&inputs);
+            let report = stats::take();
+
+            let out = MockFheUint8::from_bits(&outputs[..8]);
+            assert_eq!(
+                MockClientKey.decrypt(&out),
+                MockClientKey.decrypt(&want_out)
+            );
+            assert_eq!(
+                MockClientKey.decrypt(&outputs[8]),
+                MockClientKey.decrypt(&want_le)
+            );
+            assert_eq!(MockClientKey.decrypt(&outputs[9]), m[1] == 0);
+
+            // NOTs may differ since negated wires are negated per reader.
+            // Recorded circuit records adders as separate XOR, AND, and
+            // majority gates, hence eager evaluation may require fewer
+            // bootstraps.
+            assert_eq!(report.gates.bootstraps(), recorded.bootstraps());
+            assert!(report.gates.bootstraps() >= want_report.gates.bootstraps());
+        }
+    }
+
+    #[test]
+    fn recorded_constants_and_negations() {
+        let recorded = Circuit::record(|| {
+            let a = LazyFheInt8::input();
+            let out = -&(&a - &LazyFheInt8::trivial(-3i8));
+            let mut outputs = out.bits();
+            outputs.push(!&a.bits()[0]);
+            outputs.push(LazyFheBool::trivial(true));
+            outputs
+        });
+        assert_eq!(recorded.inputs(), 8);
+
+        for m in [-128i8, -3, 0, 5, 127] {
+            let c: MockFheInt8 = MockClientKey.encrypt(&m);
+            let outputs = recorded.evaluate_mock(&c.bits());
+            let out = MockFheInt8::from_bits(&outputs[..8]);
+            assert_eq!(
+                MockClientKey.decrypt(&out),
+                m.wrapping_add(3).wrapping_neg()
+            );
+            assert_eq!(MockClientKey.decrypt(&outputs[8]), m & 1 == 0);
+            assert!(MockClientKey.decrypt(&outputs[9]));
+        }
+    }
+
+    #[test]
+    fn recorded_short_uint_lookup_tables() {
+        let f = |m: u8| (3 * m + 1) % 8;
+        let recorded = Circuit::record(|| {
+            let a = LazyFheShortUint::<3>::input();
+            let b = LazyFheShortUint::<3>::from_bits(&[LazyFheBool::input()]);
+            let mut outputs = (&a.apply_lut(|m| m / 2) + &b).apply_lut(f).to_bits();
+            outputs.push(a.apply_lut_to_bool(|m| m % 3 == 0));
+            outputs
+        });
+        assert_eq!(recorded.inputs(), 4);
+        assert_eq!(recorded.outputs(), 4);
+
+        for m in 0..8u8 {
+            for bit in [false, true] {
+                let mut inputs = MockFheShortUint::<3>::trivial(m).to_bits();
+                inputs.push(MockFheBool::trivial(bit));
+                let outputs = recorded
+                    .evaluate_mock(&inputs)
+                    .iter()
+                    .map(|c| MockClientKey.decrypt(c))
+                    .collect_vec();
+
+                let want = f(m / 2 + bit as u8);
+                assert_eq!(
+                    outputs[..3],
+                    (0..3).map(|i| (want >> i) & 1 == 1).collect_vec()
+                );
+                assert_eq!(outputs[3], m % 3 == 0);
+            }
+        }
+    }
+
+    #[test]
+    fn recorded_radix_uint8_arithmetic() {
+        let recorded = Circuit::record(|| {
+            let a =
+                LazyFheRadixUint8::from_bits(&(0..8).map(|_| LazyFheBool::input()).collect_vec());
+            let b =
+                LazyFheRadixUint8::from_bits(&(0..8).map(|_| LazyFheBool::input()).collect_vec());
+            let mut outputs = (&a + &b).to_bits();
+            outputs.extend((&a - &b).to_bits());
+            outputs
+        });
+        assert_eq!(recorded.inputs(), 16);
+        assert_eq!(recorded.outputs(), 16);
+
+        for _ in 0..64 {
+            let a = thread_rng().gen::<u8>();
+            let b = thread_rng().gen::<u8>();
+            let mut inputs = MockFheUint8::trivial(a).bits();
+            inputs.extend(MockFheUint8::trivial(b).bits());
+            let outputs = recorded.evaluate_mock(&inputs);
+            let sum = MockFheUint8::from_bits(&outputs[..8]);
+            let diff = MockFheUint8::from_bits(&outputs[8..]);
+            assert_eq!(MockClientKey.decrypt(&sum), a.wrapping_add(b));
+            assert_eq!(MockClientKey.decrypt(&diff), a.wrapping_sub(b));
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Circuit is not being recorded")]
+    fn lazy_types_require_recording() {
+        let _ = LazyFheBool::input();
+    }
+}
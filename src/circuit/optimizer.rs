@@ -0,0 +1,543 @@
+//! Optimization passes over recorded circuits
+//!
+//! All gates except NOT require a bootstrap whereas NOT is free. Hence the
+//! optimizer minimises no. of gates other than NOT with following passes:
+//!
+//! 1. Constant propagation: gates with a constant input (for example, from
+//!    `trivial`) or with both inputs on the same node are replaced by a
+//!    constant or one of their inputs.
+//!    Likewise majority and MUX gates with a constant input or repeated inputs
+//!    are replaced by a 2 input gate or one of their inputs.
+//! 2. NOT pushing: NAND, NOR, and XNOR are rewritten as negated AND, OR, and
+//!    XOR, negated inputs of XOR are moved to its output, and AND (resp. OR)
+//!    with both inputs negated is rewritten as negated OR (resp. AND).
+//!    Similarly majority with at least 2 inputs negated and MUX with both
+//!    branches negated are rewritten with the negation on the output, and MUX
+//!    with negated selector swaps its branches. Hence NOTs only remain on
+//!    wires, where they are free, and equivalent gates have the same form.
+//! 3. Common subexpression elimination: identical gates on identical inputs
+//!    are evaluated once.
+//! 4. Dead gate removal: gates that do not affect any output are removed.
+
+use std::{collections::HashMap, fmt::Display};
+
+use super::{Circuit, Gate, Node, TernaryGate, Wire};
+
+/// Cost of a circuit before and after `Circuit::optimize`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OptimizationReport {
+    pub bootstraps_before: usize,
+    pub bootstraps_after: usize,
+    pub depth_before: usize,
+    pub depth_after: usize,
+}
+
+impl OptimizationReport {
+    /// Returns no. of bootstraps saved
+    pub fn saved_bootstraps(&self) -> usize {
+        self.bootstraps_before - self.bootstraps_after
+    }
+}
+
+impl Display for OptimizationReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Bootstraps: {} -> {} (saved {}), Depth: {} -> {}",
+            self.bootstraps_before,
+            self.bootstraps_after,
+            self.saved_bootstraps(),
+            self.depth_before,
+            self.depth_after
+        )
+    }
+}
+
+/// Output of simplifying a gate
+enum Simplified {
+    /// Gate is equivalent to an existing wire
+    Wire(Wire),
+    /// Gate is equivalent to `gate` on the inputs, negated if `bool` is true
+    Gate(Gate, Wire, Wire, bool),
+    /// Gate is equivalent to ternary `gate` on the inputs, negated if `bool`
+    /// is true
+    TernaryGate(TernaryGate, Wire, Wire, Wire, bool),
+}
+
+/// Rewrites `gate` on `a` and `b` in terms of AND, OR, and XOR and folds
+/// constants and repeated inputs
+fn simplify(gate: Gate, mut a: Wire, mut b: Wire) -> Simplified {
+    let (gate, mut negated) = match gate {
+        Gate::And => (Gate::And, false),
+        Gate::Nand => (Gate::And, true),
+        Gate::Or => (Gate::Or, false),
+        Gate::Nor => (Gate::Or, true),
+        Gate::Xor => (Gate::Xor, false),
+        Gate::Xnor => (Gate::Xor, true),
+    };
+    let negate = |w: Wire, negated: bool| Wire {
+        node: w.node,
+        negated: w.negated ^ negated,
+    };
+
+    // constant inputs
+    if b.node == 0 {
+        std::mem::swap(&mut a, &mut b);
+    }
+    if a.node == 0 {
+        let a = a.negated;
+        let out = match (gate, a) {
+            (Gate::And, false) => Wire::constant(false),
+            (Gate::And, true) => b,
+            (Gate::Or, false) => b,
+            (Gate::Or, true) => Wire::constant(true),
+            (_, a) => negate(b, a),
+        };
+        return Simplified::Wire(negate(out, negated));
+    }
+
+    // both inputs on the same node
+    if a.node == b.node {
+        let out = match (gate, a.negated == b.negated) {
+            (Gate::Xor, equal) => Wire::constant(!equal),
+            (_, true) => a,
+            (Gate::And, false) => Wire::constant(false),
+            (_, false) => Wire::constant(true),
+        };
+        return Simplified::Wire(negate(out, negated));
+    }
+
+    let gate = match gate {
+        Gate::Xor => {
+            negated ^= a.negated ^ b.negated;
+            a.negated = false;
+            b.negated = false;
+            gate
+        }
+        _ if a.negated && b.negated => {
+            // De Morgan: !a & !b = !(a | b) and !a | !b = !(a & b)
+            negated = !negated;
+            a.negated = false;
+            b.negated = false;
+            if gate == Gate::And {
+                Gate::Or
+            } else {
+                Gate::And
+            }
+        }
+        _ => gate,
+    };
+    let (a, b) = if (a.node, a.negated) <= (b.node, b.negated) {
+        (a, b)
+    } else {
+        (b, a)
+    };
+    Simplified::Gate(gate, a, b, negated)
+}
+
+/// Simplifies majority of `inputs`
+fn simplify_majority(mut inputs: [Wire; 3]) -> Simplified {
+    // maj(0, b, c) = b & c and maj(1, b, c) = b | c
+    if let Some(i) = inputs.iter().position(|w| w.node == 0) {
+        let gate = if inputs[i].negated {
+            Gate::Or
+        } else {
+            Gate::And
+        };
+        return simplify(gate, inputs[(i + 1) % 3], inputs[(i + 2) % 3]);
+    }
+
+    // maj(a, a, c) = a and maj(a, !a, c) = c
+    for (i, j, k) in [(0, 1, 2), (0, 2, 1), (1, 2, 0)] {
+        if inputs[i].node == inputs[j].node {
+            let out = if inputs[i].negated == inputs[j].negated {
+                inputs[i]
+            } else {
+                inputs[k]
+            };
+            return Simplified::Wire(out);
+        }
+    }
+
+    // majority is self dual: maj(!a, !b, !c) = !maj(a, b, c)
+    let negated = inputs.iter().filter(|w| w.negated).count() >= 2;
+    if negated {
+        inputs.iter_mut().for_each(|w| w.negated = !w.negated);
+    }
+    inputs.sort_by_key(|w| (w.node, w.negated));
+    Simplified::TernaryGate(
+        TernaryGate::Majority,
+        inputs[0],
+        inputs[1],
+        inputs[2],
+        negated,
+    )
+}
+
+/// Simplifies `s ? t : f`
+fn simplify_mux(mut s: Wire, mut t: Wire, mut f: Wire) -> Simplified {
+    if s.node == 0 {
+        return Simplified::Wire(if s.negated { t } else { f });
+    }
+    // !s ? t : f = s ? f : t
+    if s.negated {
+        std::mem::swap(&mut t, &mut f);
+        s.negated = false;
+    }
+
+    // branches on the selector's node are constant in the branch
+    if t.node == s.node {
+        t = Wire::constant(!t.negated);
+    }
+    if f.node == s.node {
+        f = Wire::constant(f.negated);
+    }
+    let not_s = Wire {
+        node: s.node,
+        negated: true,
+    };
+    // s ? 1 : f = s | f, s ? 0 : f = !s & f, s ? t : 1 = !s | t, and
+    // s ? t : 0 = s & t
+    match (t.node == 0, f.node == 0) {
+        (true, _) if t.negated => return simplify(Gate::Or, s, f),
+        (true, _) => return simplify(Gate::And, not_s, f),
+        (_, true) if f.negated => return simplify(Gate::Or, not_s, t),
+        (_, true) => return simplify(Gate::And, s, t),
+        _ => {}
+    }
+
+    // s ? t : t = t and s ? t : !t = xnor(s, t)
+    if t.node == f.node {
+        if t.negated == f.negated {
+            return Simplified::Wire(t);
+        }
+        return simplify(Gate::Xnor, s, t);
+    }
+
+    // s ? !t : !f = !(s ? t : f)
+    let negated = t.negated && f.negated;
+    if negated {
+        t.negated = false;
+        f.negated = false;
+    }
+    Simplified::TernaryGate(TernaryGate::Mux, s, t, f, negated)
+}
+
+/// Simplifies ternary `gate` on `a`, `b`, and `c`
+fn simplify_ternary(gate: TernaryGate, a: Wire, b: Wire, c: Wire) -> Simplified {
+    match gate {
+        TernaryGate::Majority => simplify_majority([a, b, c]),
+        TernaryGate::Mux => simplify_mux(a, b, c),
+    }
+}
+
+impl Circuit {
+    /// Optimizes the circuit in place and returns its cost before and after
+    /// the optimization. Refer to the module documentation for the passes.
+    pub fn optimize(&mut self) -> OptimizationReport {
+        let bootstraps_before = self.bootstraps();
+        let depth_before = self.depth();
+
+        self.propagate();
+        self.remove_dead_gates();
+
+        OptimizationReport {
+            bootstraps_before,
+            bootstraps_after: self.bootstraps(),
+            depth_before,
+            depth_after: self.depth(),
+        }
+    }
+
+    /// Rebuilds the circuit with constants propagated, NOTs pushed to wires,
+    /// and common subexpressions eliminated
+    fn propagate(&mut self) {
+        let mut nodes = vec![Node::Constant];
+        // wire in the rebuilt circuit equivalent to each node
+        let mut mapped = Vec::with_capacity(self.nodes.len());
+        let mut gates = HashMap::new();
+        let mut ternary_gates = HashMap::new();
+
+        let map = |mapped: &[Wire], w: &Wire| {
+            let m: Wire = mapped[w.node];
+            Wire {
+                node: m.node,
+                negated: m.negated ^ w.negated,
+            }
+        };
+
+        for node in self.nodes.iter() {
+            let simplified = match node {
+                Node::Constant => Simplified::Wire(Wire::constant(false)),
+                Node::Input(i) => {
+                    nodes.push(Node::Input(*i));
+                    Simplified::Wire(Wire {
+                        node: nodes.len() - 1,
+                        negated: false,
+                    })
+                }
+                Node::Gate(gate, a, b) => simplify(*gate, map(&mapped, a), map(&mapped, b)),
+                Node::TernaryGate(gate, a, b, c) => {
+                    simplify_ternary(*gate, map(&mapped, a), map(&mapped, b), map(&mapped, c))
+                }
+            };
+            let wire = match simplified {
+                Simplified::Wire(w) => w,
+                Simplified::Gate(gate, a, b, negated) => {
+                    let node = *gates.entry((gate, a, b)).or_insert_with(|| {
+                        nodes.push(Node::Gate(gate, a, b));
+                        nodes.len() - 1
+                    });
+                    Wire { node, negated }
+                }
+                Simplified::TernaryGate(gate, a, b, c, negated) => {
+                    let node = *ternary_gates.entry((gate, a, b, c)).or_insert_with(|| {
+                        nodes.push(Node::TernaryGate(gate, a, b, c));
+                        nodes.len() - 1
+                    });
+                    Wire { node, negated }
+                }
+            };
+            mapped.push(wire);
+        }
+
+        self.outputs = self.outputs.iter().map(|w| map(&mapped, w)).collect();
+        self.nodes = nodes;
+    }
+
+    /// Removes gates that do not affect any output
+    fn remove_dead_gates(&mut self) {
+        let mut live = vec![false; self.nodes.len()];
+        self.outputs.iter().for_each(|w| live[w.node] = true);
+        for index in (0..self.nodes.len()).rev() {
+            match &self.nodes[index] {
+                // constant and inputs are always kept
+                Node::Constant | Node::Input(_) => live[index] = true,
+                node if live[index] => node.inputs().iter().for_each(|w| live[w.node] = true),
+                _ => {}
+            }
+        }
+
+        let mut new_index = vec![0; self.nodes.len()];
+        let mut nodes = vec![];
+        let remap = |new_index: &[usize], w: &Wire| Wire {
+            node: new_index[w.node],
+            negated: w.negated,
+        };
+        for (index, node) in std::mem::take(&mut self.nodes).into_iter().enumerate() {
+            if !live[index] {
+                continue;
+            }
+            let node = match node {
+                Node::Gate(gate, a, b) => {
+                    Node::Gate(gate, remap(&new_index, &a), remap(&new_index, &b))
+                }
+                Node::TernaryGate(gate, a, b, c) => Node::TernaryGate(
+                    gate,
+                    remap(&new_index, &a),
+                    remap(&new_index, &b),
+                    remap(&new_index, &c),
+                ),
+                node => node,
+            };
+            new_index[index] = nodes.len();
+            nodes.push(node);
+        }
+
+        self.outputs = self.outputs.iter().map(|w| remap(&new_index, w)).collect();
+        self.nodes = nodes;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+    use rand::{thread_rng, Rng};
+
+    use crate::{
+        bool::{Backend, BooleanGates},
+        Decryptor, Encryptor, FheIntegerOps, LazyFheBool, LazyFheUint8, MockClientKey, MockFheBool,
+    };
+
+    use super::*;
+
+    /// Asserts `optimized` computes the same outputs as `circuit` on random
+    /// inputs
+    fn assert_equivalent(circuit: &Circuit, optimized: &Circuit) {
+        assert_eq!(circuit.inputs(), optimized.inputs());
+        assert_eq!(circuit.outputs(), optimized.outputs());
+        for _ in 0..100 {
+            let inputs = (0..circuit.inputs())
+                .map(|_| MockClientKey.encrypt(&thread_rng().gen_bool(0.5)))
+                .collect::<Vec<MockFheBool>>();
+            let want = circuit
+                .evaluate_mock(&inputs)
+                .iter()
+                .map(|c| MockClientKey.decrypt(c))
+                .collect_vec();
+            let got = optimized
+                .evaluate_mock(&inputs)
+                .iter()
+                .map(|c| MockClientKey.decrypt(c))
+                .collect_vec();
+            assert_eq!(want, got);
+        }
+    }
+
+    #[test]
+    fn constants_are_propagated() {
+        let mut circuit = Circuit::record(|| {
+            let a = LazyFheBool::input();
+            let t = LazyFheBool::trivial(true);
+            let f = LazyFheBool::trivial(false);
+            vec![
+                &a & &t,
+                &a & &f,
+                &a | &t,
+                &f | &a,
+                &t ^ &a,
+                !&(&t & &f),
+                &a & &a,
+                &a ^ &a,
+                &a | &!&a,
+            ]
+        });
+        let original = circuit.clone();
+        let report = circuit.optimize();
+        assert_equivalent(&original, &circuit);
+        assert_eq!(report.bootstraps_before, 9);
+        assert_eq!(report.bootstraps_after, 0);
+        assert_eq!(circuit.depth(), 0);
+    }
+
+    #[test]
+    fn common_subexpressions_are_eliminated() {
+        let mut circuit = Circuit::record(|| {
+            let a = LazyFheBool::input();
+            let b = LazyFheBool::input();
+            vec![
+                &a & &b,
+                &b & &a,
+                // !a & !b = !(a | b) = nor(a, b)
+                &!&a & &!&b,
+                !&(&a | &b),
+                // xnor(a, b) = !(a ^ b) = !a ^ b
+                !&(&a ^ &b),
+                &!&a ^ &b,
+                &a ^ &b,
+            ]
+        });
+        let original = circuit.clone();
+        let report = circuit.optimize();
+        assert_equivalent(&original, &circuit);
+        assert_eq!(report.bootstraps_before, 7);
+        // a & b, a | b, and a ^ b
+        assert_eq!(report.bootstraps_after, 3);
+    }
+
+    #[test]
+    fn ternary_gates_are_simplified() {
+        let mut circuit = Circuit::record(|| {
+            let a = LazyFheBool::input();
+            let b = LazyFheBool::input();
+            let c = LazyFheBool::input();
+            let t = LazyFheBool::trivial(true);
+            let f = LazyFheBool::trivial(false);
+            let majority = |a: &LazyFheBool, b: &LazyFheBool, c: &LazyFheBool| {
+                Wire::with_evaluator(|e, key| LazyFheBool {
+                    data: e.majority(&a.data, &b.data, &c.data, key),
+                })
+            };
+            let mux = |s: &LazyFheBool, t: &LazyFheBool, f: &LazyFheBool| {
+                Wire::with_evaluator(|e, key| LazyFheBool {
+                    data: e.mux(&s.data, &t.data, &f.data, key),
+                })
+            };
+            vec![
+                // majority(a, b, c) is evaluated once
+                majority(&a, &b, &c),
+                majority(&c, &a, &b),
+                !&majority(&!&a, &!&b, &!&c),
+                // a & b, a | b, a, and c
+                majority(&a, &b, &f),
+                majority(&t, &a, &b),
+                majority(&a, &c, &a),
+                majority(&a, &b, &!&a),
+                // a ? b : c is evaluated once
+                mux(&a, &b, &c),
+                mux(&!&a, &c, &b),
+                !&mux(&a, &!&b, &!&c),
+                // b, !a & c, !a | b, a | c, xnor(a, b), b
+                mux(&t, &b, &c),
+                mux(&a, &f, &c),
+                mux(&a, &b, &t),
+                mux(&a, &a, &c),
+                mux(&a, &b, &!&b),
+                mux(&a, &b, &b),
+            ]
+        });
+        let original = circuit.clone();
+        let report = circuit.optimize();
+        assert_equivalent(&original, &circuit);
+        let counts = circuit.gate_counts();
+        assert_eq!(counts.majority, 1);
+        assert_eq!(counts.mux, 1);
+        // a & b, a | b, !a & c, !a | b, a | c, and xnor(a, b)
+        assert_eq!(counts.and + counts.or + counts.xor, 6);
+        assert_eq!(report.bootstraps_after, 2 + 3 + 6);
+    }
+
+    #[test]
+    fn dead_gates_are_removed() {
+        let mut circuit = Circuit::record(|| {
+            let a = LazyFheUint8::input();
+            let b = LazyFheUint8::input();
+            let _unused = &a * &b;
+            vec![a.lt(&b)]
+        });
+        let original = circuit.clone();
+        let lt_bootstraps = Circuit::record(|| {
+            let a = LazyFheUint8::input();
+            let b = LazyFheUint8::input();
+            vec![a.lt(&b)]
+        })
+        .bootstraps();
+
+        let report = circuit.optimize();
+        assert_equivalent(&original, &circuit);
+        assert!(report.bootstraps_after <= lt_bootstraps);
+        assert_eq!(circuit.inputs(), 16);
+    }
+
+    #[test]
+    fn trivial_operands_are_folded() {
+        let mut circuit = Circuit::record(|| {
+            let a = LazyFheUint8::input();
+            let b = LazyFheUint8::trivial(37u8);
+            let c = LazyFheUint8::trivial(0u8);
+            let out = a.wrapping_mul(&b).wrapping_add(&c).max(&b);
+            out.bits()
+        });
+        let original = circuit.clone();
+        let report = circuit.optimize();
+        assert_equivalent(&original, &circuit);
+        assert!(report.bootstraps_after < report.bootstraps_before / 2);
+        assert!(report.depth_after <= report.depth_before);
+        assert_eq!(
+            report.to_string(),
+            format!(
+                "Bootstraps: {} -> {} (saved {}), Depth: {} -> {}",
+                report.bootstraps_before,
+                report.bootstraps_after,
+                report.saved_bootstraps(),
+                report.depth_before,
+                report.depth_after
+            )
+        );
+
+        // optimization is idempotent
+        let optimized = circuit.clone();
+        assert_eq!(circuit.optimize().saved_bootstraps(), 0);
+        assert_eq!(circuit, optimized);
+    }
+}
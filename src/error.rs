@@ -0,0 +1,127 @@
+use std::fmt::Display;
+
+#[cfg(any(feature = "interactive_mp", feature = "non_interactive_mp"))]
+use crate::serialization::DecodeError;
+
+/// Error returned by fallible (`try_*`) variants of the API
+///
+/// Unlike their panicking counterparts, `try_*` variants let a server reject
+/// malformed input received from a client, for example a server key share of
+/// another protocol instance, without crashing.
+#[derive(Debug)]
+pub enum Error {
+    /// Parameters are not set on the current thread, nor is a session entered
+    ParametersNotSet,
+    /// Different parameters are already set on the current thread
+    ParametersAlreadySet,
+    /// Parameters set do not support the protocol
+    UnsupportedParameters,
+    /// Common reference seed is not set
+    CommonReferenceSeedNotSet,
+    /// Common reference seed is already set
+    CommonReferenceSeedAlreadySet,
+    /// Server key is not set
+    ServerKeyNotSet,
+    /// Server key is already set
+    ServerKeyAlreadySet,
+    /// Key was generated with different parameters or common reference seed
+    ParameterMismatch,
+    /// `user_id` is not less than total no. of users
+    InvalidUserId { user_id: usize, total_users: usize },
+    /// No shares to aggregate
+    NoShares,
+    /// More than one share of user `user_id`
+    DuplicateShare { user_id: usize },
+    /// Share of user `user_id` was generated with different parameters or
+    /// common reference seed
+    ShareParameterMismatch { user_id: usize },
+    /// Share of user `user_id` is inconsistent with the other shares (e.g.
+    /// generated for a different no. of users) or with parameters (e.g. a key
+    /// has wrong dimensions)
+    InvalidShare { user_id: usize },
+    /// Decryption share at `index` is malformed
+    InvalidDecryptionShare { index: usize },
+    /// Ciphertext is malformed
+    InvalidCiphertext,
+    /// Client key already participated as user `user_id` in a different run
+    /// of the protocol with the same common reference seed
+    ConflictingRun { user_id: usize },
+    /// Decryption share or run could not be recorded in the client key's
+    /// ledger (refer to `DecryptionShareStore` and `RunStore`)
+    Ledger(std::io::Error),
+    /// Bytes could not be decoded (refer to `BinaryEncoding`)
+    #[cfg(any(feature = "interactive_mp", feature = "non_interactive_mp"))]
+    Decode(DecodeError),
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::ParametersNotSet => write!(f, "Parameters not set"),
+            Error::ParametersAlreadySet => {
+                write!(f, "Different parameters are already set on the thread")
+            }
+            Error::UnsupportedParameters => {
+                write!(f, "Set parameters do not support the protocol")
+            }
+            Error::CommonReferenceSeedNotSet => write!(f, "Common reference seed not set"),
+            Error::CommonReferenceSeedAlreadySet => {
+                write!(f, "Attempted to set common reference seed twice")
+            }
+            Error::ServerKeyNotSet => write!(f, "Server key not set"),
+            Error::ServerKeyAlreadySet => write!(f, "Attempted to set server key twice"),
+            Error::ParameterMismatch => write!(
+                f,
+                "Key was generated with different parameters or common reference seed"
+            ),
+            Error::InvalidUserId {
+                user_id,
+                total_users,
+            } => write!(f, "Invalid user_id={user_id} for {total_users} users"),
+            Error::NoShares => write!(f, "No shares to aggregate"),
+            Error::DuplicateShare { user_id } => {
+                write!(f, "More than one share of user_id={user_id}")
+            }
+            Error::ShareParameterMismatch { user_id } => write!(
+                f,
+                "Share of user_id={user_id} was generated with different parameters or common \
+                 reference seed"
+            ),
+            Error::InvalidShare { user_id } => write!(
+                f,
+                "Share of user_id={user_id} is inconsistent with the other shares or parameters"
+            ),
+            Error::InvalidDecryptionShare { index } => {
+                write!(f, "Decryption share at index {index} is malformed")
+            }
+            Error::InvalidCiphertext => write!(f, "Ciphertext is malformed"),
+            Error::ConflictingRun { user_id } => write!(
+                f,
+                "Client key already participated as user_id={user_id} in a different run with \
+                 the same common reference seed, running the protocol twice with the same seed \
+                 can leak the secret"
+            ),
+            Error::Ledger(e) => write!(f, "Failed to record in ledger: {e}"),
+            #[cfg(any(feature = "interactive_mp", feature = "non_interactive_mp"))]
+            Error::Decode(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Ledger(e) => Some(e),
+            #[cfg(any(feature = "interactive_mp", feature = "non_interactive_mp"))]
+            Error::Decode(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(any(feature = "interactive_mp", feature = "non_interactive_mp"))]
+impl From<DecodeError> for Error {
+    fn from(e: DecodeError) -> Self {
+        Error::Decode(e)
+    }
+}
@@ -0,0 +1,517 @@
+//! Ledgers of values a client must never generate twice
+//!
+//! Generating two different decryption shares for the same ciphertext leaks
+//! the client's secret. Hence every `ClientKey` records decryption shares it
+//! generates in a `DecryptionShareStore` keyed by a digest of the mask of the
+//! LWE ciphertext, and `MultiPartyDecryptor::gen_decryption_share` returns the
+//! recorded share if asked for a share of a ciphertext with the same mask
+//! again. The share depends only on the mask, hence keying on the whole
+//! ciphertext would let anyone collect fresh shares of the same mask by
+//! altering the body of the ciphertext.
+//!
+//! Similarly running the protocol twice with the same common reference seed
+//! and producing different outputs leaks the secret. Hence every `ClientKey`
+//! records runs of the protocol (`Run`) it generated a server key share for in
+//! a `RunStore`. Generating a server key share for a common reference seed the
+//! key already generated a different share for is refused, and so is
+//! generating decryption shares with a common reference seed the store records
+//! more than one run of (refer to `ClientKey::allow_conflicting_runs`).
+//!
+//! By default both are kept in memory (`MemoryDecryptionShareStore` and
+//! `MemoryRunStore`) and are shared by all clones of the key. A client that
+//! restarts with the same key (for ex, a key read back with
+//! `BinaryEncoding::from_bytes`) must attach persistent stores, like
+//! `FileDecryptionShareStore` and `FileRunStore`, with
+//! `ClientKey::with_decryption_share_store` and `ClientKey::with_run_store`.
+
+use std::{
+    collections::HashMap,
+    fs::{File, OpenOptions},
+    io::{Read, Write},
+    path::Path,
+    sync::{Arc, Mutex},
+};
+
+use sha2::{Digest, Sha256};
+
+use crate::Error;
+
+/// Storage of decryption shares generated by a client key, keyed by SHA-256
+/// digest of the mask of the LWE ciphertext the share was generated for
+///
+/// Implement the trait to keep shares in storage of the application's choice.
+pub trait DecryptionShareStore {
+    /// Returns the decryption share recorded for ciphertexts with mask digest
+    /// `mask_digest`
+    fn get(&self, mask_digest: &[u8; 32]) -> Option<u64>;
+
+    /// Records `share` as the decryption share of ciphertexts with mask digest
+    /// `mask_digest`
+    ///
+    /// The share is handed out to the caller only after `insert` returns
+    /// `Ok`. Hence persistent stores must not return before the share is
+    /// persisted.
+    fn insert(&mut self, mask_digest: [u8; 32], share: u64) -> std::io::Result<()>;
+}
+
+/// Run of the protocol a client key generated a server key share for
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Run {
+    /// Common reference seed of the run
+    pub cr_seed: [u8; 32],
+    /// Id of the client in the run
+    pub user_id: usize,
+    /// Hash of the binary encoding of the server key share
+    pub key_share_hash: u64,
+}
+
+/// Storage of runs of the protocol a client key participated in
+///
+/// Implement the trait to keep runs in storage of the application's choice.
+pub trait RunStore {
+    /// Returns all recorded runs
+    fn runs(&self) -> &[Run];
+
+    /// Records `run`
+    ///
+    /// The server key share of the run is handed out to the caller only after
+    /// `insert` returns `Ok`. Hence persistent stores must not return before
+    /// the run is persisted.
+    fn insert(&mut self, run: Run) -> std::io::Result<()>;
+}
+
+/// Stores decryption shares in memory. Shares are lost when the process exits.
+#[derive(Clone, Debug, Default)]
+pub struct MemoryDecryptionShareStore {
+    shares: HashMap<[u8; 32], u64>,
+}
+
+impl MemoryDecryptionShareStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl DecryptionShareStore for MemoryDecryptionShareStore {
+    fn get(&self, mask_digest: &[u8; 32]) -> Option<u64> {
+        self.shares.get(mask_digest).copied()
+    }
+
+    fn insert(&mut self, mask_digest: [u8; 32], share: u64) -> std::io::Result<()> {
+        self.shares.insert(mask_digest, share);
+        Ok(())
+    }
+}
+
+/// Stores runs in memory. Runs are lost when the process exits.
+#[derive(Clone, Debug, Default)]
+pub struct MemoryRunStore {
+    runs: Vec<Run>,
+}
+
+impl MemoryRunStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl RunStore for MemoryRunStore {
+    fn runs(&self) -> &[Run] {
+        &self.runs
+    }
+
+    fn insert(&mut self, run: Run) -> std::io::Result<()> {
+        self.runs.push(run);
+        Ok(())
+    }
+}
+
+/// Append-only log of fixed size records in a file
+#[derive(Debug)]
+struct RecordLog<const N: usize> {
+    file: File,
+}
+
+impl<const N: usize> RecordLog<N> {
+    /// Opens log at `path`, creating the file if it does not exist, and
+    /// returns it along with the records in the log
+    ///
+    /// A trailing incomplete record, left by a crash in the middle of an
+    /// append, is discarded. Its value was never handed out.
+    fn open<P: AsRef<Path>>(path: P) -> std::io::Result<(Self, Vec<[u8; N]>)> {
+        let mut file = OpenOptions::new()
+            .read(true)
+            .append(true)
+            .create(true)
+            .open(path)?;
+
+        let mut bytes = vec![];
+        file.read_to_end(&mut bytes)?;
+        let complete = bytes.len() - bytes.len() % N;
+        if complete != bytes.len() {
+            file.set_len(complete as u64)?;
+        }
+
+        let records = bytes[..complete]
+            .chunks_exact(N)
+            .map(|record| record.try_into().unwrap())
+            .collect();
+        Ok((Self { file }, records))
+    }
+
+    /// Appends `record` and syncs it to disk
+    fn append(&mut self, record: &[u8; N]) -> std::io::Result<()> {
+        self.file.write_all(record)?;
+        self.file.sync_data()
+    }
+}
+
+/// Stores decryption shares in a file
+///
+/// File is an append-only log of 40 byte records, each containing mask digest
+/// followed by decryption share as little endian u64. Records are read into
+/// memory on `open` and every insert is synced to disk before returning.
+#[derive(Debug)]
+pub struct FileDecryptionShareStore {
+    log: RecordLog<40>,
+    shares: HashMap<[u8; 32], u64>,
+}
+
+impl FileDecryptionShareStore {
+    /// Opens decryption share store at `path`, creating the file if it does
+    /// not exist
+    pub fn open<P: AsRef<Path>>(path: P) -> std::io::Result<Self> {
+        let (log, records) = RecordLog::open(path)?;
+        let shares = records
+            .iter()
+            .map(|record| {
+                (
+                    record[..32].try_into().unwrap(),
+                    u64::from_le_bytes(record[32..].try_into().unwrap()),
+                )
+            })
+            .collect();
+        Ok(Self { log, shares })
+    }
+}
+
+impl DecryptionShareStore for FileDecryptionShareStore {
+    fn get(&self, mask_digest: &[u8; 32]) -> Option<u64> {
+        self.shares.get(mask_digest).copied()
+    }
+
+    fn insert(&mut self, mask_digest: [u8; 32], share: u64) -> std::io::Result<()> {
+        let mut record = [0u8; 40];
+        record[..32].copy_from_slice(&mask_digest);
+        record[32..].copy_from_slice(&share.to_le_bytes());
+        self.log.append(&record)?;
+        self.shares.insert(mask_digest, share);
+        Ok(())
+    }
+}
+
+/// Stores runs in a file
+///
+/// File is an append-only log of 48 byte records, each containing common
+/// reference seed followed by user id and key share hash as little endian
+/// u64s. Records are read into memory on `open` and every insert is synced to
+/// disk before returning.
+#[derive(Debug)]
+pub struct FileRunStore {
+    log: RecordLog<48>,
+    runs: Vec<Run>,
+}
+
+impl FileRunStore {
+    /// Opens run store at `path`, creating the file if it does not exist
+    pub fn open<P: AsRef<Path>>(path: P) -> std::io::Result<Self> {
+        let (log, records) = RecordLog::open(path)?;
+        let runs = records
+            .iter()
+            .map(|record| Run {
+                cr_seed: record[..32].try_into().unwrap(),
+                user_id: u64::from_le_bytes(record[32..40].try_into().unwrap()) as usize,
+                key_share_hash: u64::from_le_bytes(record[40..].try_into().unwrap()),
+            })
+            .collect();
+        Ok(Self { log, runs })
+    }
+}
+
+impl RunStore for FileRunStore {
+    fn runs(&self) -> &[Run] {
+        &self.runs
+    }
+
+    fn insert(&mut self, run: Run) -> std::io::Result<()> {
+        let mut record = [0u8; 48];
+        record[..32].copy_from_slice(&run.cr_seed);
+        record[32..40].copy_from_slice(&(run.user_id as u64).to_le_bytes());
+        record[40..].copy_from_slice(&run.key_share_hash.to_le_bytes());
+        self.log.append(&record)?;
+        self.runs.push(run);
+        Ok(())
+    }
+}
+
+/// Returns SHA-256 digest of mask `c[1..]` of LWE ciphertext `c`
+pub(crate) fn lwe_mask_digest(c: &[u64]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    c.iter()
+        .skip(1)
+        .for_each(|v| hasher.update(v.to_le_bytes()));
+    hasher.finalize().into()
+}
+
+/// Decryption shares of a client key, shared by all clones of the key
+#[derive(Clone)]
+pub(crate) struct DecryptionShareLedger {
+    store: Arc<Mutex<Box<dyn DecryptionShareStore + Send>>>,
+}
+
+impl DecryptionShareLedger {
+    pub(crate) fn new<S: DecryptionShareStore + Send + 'static>(store: S) -> Self {
+        Self {
+            store: Arc::new(Mutex::new(Box::new(store))),
+        }
+    }
+
+    /// Returns decryption share recorded for LWE ciphertexts with the same
+    /// mask as `c`. If there's none, generates the share with `gen_share` and
+    /// records it.
+    ///
+    /// Returns `Error::Ledger` if the share cannot be recorded, since handing
+    /// out an unrecorded share risks generating a different share for `c`
+    /// later.
+    pub(crate) fn get_or_insert_with<F: FnOnce() -> u64>(
+        &self,
+        c: &[u64],
+        gen_share: F,
+    ) -> Result<u64, Error> {
+        let mask_digest = lwe_mask_digest(c);
+        // Store is not modified unless a share is recorded, hence it's
+        // consistent even if an earlier `gen_share` panicked
+        let mut store = self.store.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(share) = store.get(&mask_digest) {
+            return Ok(share);
+        }
+        let share = gen_share();
+        store.insert(mask_digest, share).map_err(Error::Ledger)?;
+        Ok(share)
+    }
+}
+
+impl Default for DecryptionShareLedger {
+    fn default() -> Self {
+        Self::new(MemoryDecryptionShareStore::new())
+    }
+}
+
+/// Runs of the protocol a client key participated in, shared by all clones of
+/// the key
+#[derive(Clone)]
+pub(crate) struct RunLedger {
+    store: Arc<Mutex<Box<dyn RunStore + Send>>>,
+    allow_conflicts: bool,
+}
+
+impl RunLedger {
+    pub(crate) fn new<S: RunStore + Send + 'static>(store: S) -> Self {
+        Self {
+            store: Arc::new(Mutex::new(Box::new(store))),
+            allow_conflicts: false,
+        }
+    }
+
+    pub(crate) fn allow_conflicts(&mut self) {
+        self.allow_conflicts = true;
+    }
+
+    pub(crate) fn allows_conflicts(&self) -> bool {
+        self.allow_conflicts
+    }
+
+    /// Records `run` if there's no other recorded run with the same common
+    /// reference seed, otherwise returns `Error::ConflictingRun`
+    ///
+    /// Returns `Error::Ledger` if the run cannot be recorded, since handing out
+    /// a key share of an unrecorded run risks running the protocol again with
+    /// the same seed.
+    pub(crate) fn record(&self, run: Run) -> Result<(), Error> {
+        let mut store = self.store.lock().unwrap_or_else(|e| e.into_inner());
+        if store.runs().contains(&run) {
+            return Ok(());
+        }
+        if let Some(other) = store.runs().iter().find(|r| r.cr_seed == run.cr_seed) {
+            if !self.allow_conflicts {
+                return Err(Error::ConflictingRun {
+                    user_id: other.user_id,
+                });
+            }
+        }
+        store.insert(run).map_err(Error::Ledger)
+    }
+
+    /// Returns `Error::ConflictingRun` if more than one run with common
+    /// reference seed `cr_seed` is recorded
+    pub(crate) fn check(&self, cr_seed: &[u8; 32]) -> Result<(), Error> {
+        if self.allow_conflicts {
+            return Ok(());
+        }
+        let store = self.store.lock().unwrap_or_else(|e| e.into_inner());
+        let mut runs = store.runs().iter().filter(|r| &r.cr_seed == cr_seed);
+        match (runs.next(), runs.next()) {
+            (Some(run), Some(_)) => Err(Error::ConflictingRun {
+                user_id: run.user_id,
+            }),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl Default for RunLedger {
+    fn default() -> Self {
+        Self::new(MemoryRunStore::new())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!(
+            "phantom-zone-{name}-{}-{}",
+            std::process::id(),
+            rand::random::<u64>()
+        ))
+    }
+
+    #[test]
+    fn file_store_persists_shares() {
+        let path = temp_path("decryption-shares");
+
+        {
+            let ledger = DecryptionShareLedger::new(FileDecryptionShareStore::open(&path).unwrap());
+            assert_eq!(ledger.get_or_insert_with(&[1, 2, 3], || 10).unwrap(), 10);
+            assert_eq!(ledger.get_or_insert_with(&[1, 2, 3], || 11).unwrap(), 10);
+            // share depends only on the mask
+            assert_eq!(ledger.get_or_insert_with(&[0, 2, 3], || 11).unwrap(), 10);
+            assert_eq!(ledger.get_or_insert_with(&[3, 2, 1], || 12).unwrap(), 12);
+        }
+
+        // incomplete record of a crashed insert is discarded
+        OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap()
+            .write_all(&[0xff; 8])
+            .unwrap();
+
+        {
+            let ledger = DecryptionShareLedger::new(FileDecryptionShareStore::open(&path).unwrap());
+            assert_eq!(ledger.get_or_insert_with(&[1, 2, 3], || 13).unwrap(), 10);
+            assert_eq!(ledger.get_or_insert_with(&[3, 2, 1], || 14).unwrap(), 12);
+            assert_eq!(ledger.get_or_insert_with(&[4, 4], || 15).unwrap(), 15);
+        }
+
+        let store = FileDecryptionShareStore::open(&path).unwrap();
+        assert_eq!(store.shares.len(), 3);
+        assert_eq!(store.get(&lwe_mask_digest(&[5, 4])), Some(15));
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    /// Store that fails to record anything
+    struct FailingStore;
+
+    impl DecryptionShareStore for FailingStore {
+        fn get(&self, _: &[u8; 32]) -> Option<u64> {
+            None
+        }
+
+        fn insert(&mut self, _: [u8; 32], _: u64) -> std::io::Result<()> {
+            Err(std::io::ErrorKind::PermissionDenied.into())
+        }
+    }
+
+    impl RunStore for FailingStore {
+        fn runs(&self) -> &[Run] {
+            &[]
+        }
+
+        fn insert(&mut self, _: Run) -> std::io::Result<()> {
+            Err(std::io::ErrorKind::PermissionDenied.into())
+        }
+    }
+
+    #[test]
+    fn ledgers_return_store_errors() {
+        let ledger = DecryptionShareLedger::new(FailingStore);
+        assert!(matches!(
+            ledger.get_or_insert_with(&[1, 2, 3], || 10),
+            Err(Error::Ledger(e)) if e.kind() == std::io::ErrorKind::PermissionDenied
+        ));
+
+        let ledger = RunLedger::new(FailingStore);
+        assert!(matches!(
+            ledger.record(Run {
+                cr_seed: [1; 32],
+                user_id: 0,
+                key_share_hash: 7,
+            }),
+            Err(Error::Ledger(e)) if e.kind() == std::io::ErrorKind::PermissionDenied
+        ));
+    }
+
+    #[test]
+    fn run_ledger_refuses_conflicting_runs() {
+        let path = temp_path("runs");
+        let run = Run {
+            cr_seed: [1; 32],
+            user_id: 0,
+            key_share_hash: 7,
+        };
+
+        {
+            let ledger = RunLedger::new(FileRunStore::open(&path).unwrap());
+            assert!(ledger.record(run).is_ok());
+            assert!(ledger.record(run).is_ok());
+            assert!(ledger
+                .record(Run {
+                    cr_seed: [2; 32],
+                    ..run
+                })
+                .is_ok());
+            assert!(ledger.check(&run.cr_seed).is_ok());
+        }
+
+        let mut ledger = RunLedger::new(FileRunStore::open(&path).unwrap());
+        for conflict in [
+            Run {
+                key_share_hash: 8,
+                ..run
+            },
+            Run { user_id: 1, ..run },
+        ] {
+            assert!(matches!(
+                ledger.record(conflict),
+                Err(Error::ConflictingRun { user_id: 0 })
+            ));
+        }
+        assert!(ledger.check(&run.cr_seed).is_ok());
+
+        ledger.allow_conflicts();
+        assert!(ledger.record(Run { user_id: 1, ..run }).is_ok());
+        assert!(ledger.check(&run.cr_seed).is_ok());
+
+        let ledger = RunLedger::new(FileRunStore::open(&path).unwrap());
+        assert!(matches!(
+            ledger.check(&run.cr_seed),
+            Err(Error::ConflictingRun { user_id: 0 })
+        ));
+        assert!(ledger.check(&[2; 32]).is_ok());
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
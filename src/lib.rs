@@ -1,8 +1,18 @@
+// Frontend types (`FheBool`, `FheUint`, `FheShortUint`, ...) are instantiated
+// only with backends provided by the crate, hence `Backend` bounds of their
+// impls are crate private.
+#![allow(private_bounds)]
+
 use num_traits::Zero;
 
 mod backend;
 mod bool;
+#[cfg(any(feature = "interactive_mp", feature = "non_interactive_mp"))]
+mod circuit;
 mod decomposer;
+mod error;
+#[cfg(any(feature = "interactive_mp", feature = "non_interactive_mp"))]
+mod ledger;
 mod lwe;
 mod multi_party;
 mod ntt;
@@ -10,7 +20,14 @@ mod pbs;
 mod random;
 mod rgsw;
 #[cfg(any(feature = "interactive_mp", feature = "non_interactive_mp"))]
+pub mod serialization;
+#[cfg(any(feature = "interactive_mp", feature = "non_interactive_mp"))]
 mod shortint;
+#[cfg_attr(
+    not(any(feature = "interactive_mp", feature = "non_interactive_mp")),
+    allow(dead_code)
+)]
+pub mod stats;
 mod utils;
 
 pub use backend::{
@@ -18,9 +35,33 @@ pub use backend::{
 };
 
 pub use bool::*;
+pub use error::Error;
+#[cfg(any(feature = "interactive_mp", feature = "non_interactive_mp"))]
+pub use ledger::{
+    DecryptionShareStore, FileDecryptionShareStore, FileRunStore, MemoryDecryptionShareStore,
+    MemoryRunStore, Run, RunStore,
+};
+#[cfg(any(feature = "interactive_mp", feature = "non_interactive_mp"))]
+pub use multi_party::DecryptionShare;
 pub use ntt::{Ntt, NttBackendU64, NttInit};
 #[cfg(any(feature = "interactive_mp", feature = "non_interactive_mp"))]
-pub use shortint::{div_zero_error_flag, reset_error_flags, FheUint8};
+pub use serialization::{BinaryEncoding, DecodeError, Fingerprint};
+#[cfg(any(feature = "interactive_mp", feature = "non_interactive_mp"))]
+pub use shortint::{
+    div_zero_error_flag, lazy_div_zero_error_flag, mock_div_zero_error_flag, reset_error_flags,
+    ArrayElement, Bits, BitsPlaintext, Compare, FheArray, FheBoolOps, FheGrid, FheInt, FheInt16,
+    FheInt32, FheInt64, FheInt8, FheIntPlaintext, FheIntegerOps, FhePlaintext, FheUint, FheUint16,
+    FheUint32, FheUint64, FheUint8, FheUintPlaintext, LazyFheInt, LazyFheInt16, LazyFheInt32,
+    LazyFheInt64, LazyFheInt8, LazyFheUint, LazyFheUint16, LazyFheUint32, LazyFheUint64,
+    LazyFheUint8, MockFheInt, MockFheInt16, MockFheInt32, MockFheInt64, MockFheInt8, MockFheUint,
+    MockFheUint16, MockFheUint32, MockFheUint64, MockFheUint8, Select,
+};
+
+#[cfg(any(feature = "interactive_mp", feature = "non_interactive_mp"))]
+pub use circuit::{
+    BlifCircuit, BristolCircuit, Circuit, Gate, OptimizationReport, ParseError, Port, TernaryGate,
+    Wire,
+};
 
 pub use decomposer::{Decomposer, DecomposerIter, DefaultDecomposer};
 
@@ -170,11 +211,26 @@ pub trait MultiPartyDecryptor<M, C> {
     type DecryptionShare;
 
     fn gen_decryption_share(&self, c: &C) -> Self::DecryptionShare;
+    /// Same as `gen_decryption_share`, but returns `Error` instead of
+    /// panicking if the share cannot be recorded in the key's ledger or the
+    /// key participated in conflicting runs of the protocol
+    fn try_gen_decryption_share(&self, c: &C) -> Result<Self::DecryptionShare, Error>;
     fn aggregate_decryption_shares(&self, c: &C, shares: &[Self::DecryptionShare]) -> M;
+    /// Same as `aggregate_decryption_shares`, but returns `Error` if `c` or
+    /// any of the `shares` is malformed instead of panicking (or decrypting
+    /// garbage)
+    fn try_aggregate_decryption_shares(
+        &self,
+        c: &C,
+        shares: &[Self::DecryptionShare],
+    ) -> Result<M, Error>;
 }
 
 pub trait KeySwitchWithId<C> {
     fn key_switch(&self, user_id: usize) -> C;
+    /// Same as `key_switch`, but returns `Error` if server key is not set,
+    /// `user_id` is not a user of the server key, or `self` is malformed
+    fn try_key_switch(&self, user_id: usize) -> Result<C, Error>;
 }
 
 pub trait SampleExtractor<R> {
@@ -13,6 +13,14 @@ use crate::{
     ArithmeticOps, Matrix, MatrixEntity, MatrixMut, Row, RowEntity, RowMut,
 };
 
+/// Decryption share of a bool ciphertext (refer to `MultiPartyDecryptor`)
+///
+/// Decryption shares of integers are vectors of decryption shares of their
+/// bits (or digits).
+#[cfg(any(feature = "interactive_mp", feature = "non_interactive_mp"))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DecryptionShare(pub(crate) u64);
+
 pub(crate) fn public_key_share<
     R: Row + RowMut + RowEntity,
     S,
@@ -86,6 +94,33 @@ where
     share
 }
 
+/// Returns `Error` if `shares` cannot be aggregated to decrypt `lwe_ct`, that
+/// is if `lwe_ct` is not of dimension `lwe_n + 1`, there are no shares, or any
+/// share is not in range `[0, q)`
+#[cfg(any(feature = "interactive_mp", feature = "non_interactive_mp"))]
+pub(crate) fn check_decryption_shares<R: Row, Mod: Modulus<Element = R::Element>>(
+    lwe_ct: &R,
+    shares: &[R::Element],
+    lwe_n: usize,
+    q: &Mod,
+) -> Result<(), crate::Error>
+where
+    R::Element: PartialOrd,
+{
+    use crate::Error;
+
+    if lwe_ct.as_ref().len() != lwe_n + 1 {
+        return Err(Error::InvalidCiphertext);
+    }
+    if shares.is_empty() {
+        return Err(Error::NoShares);
+    }
+    match shares.iter().position(|s| *s > q.largest_unsigned_value()) {
+        Some(index) => Err(Error::InvalidDecryptionShare { index }),
+        None => Ok(()),
+    }
+}
+
 /// Aggregate decryption shares for `lwe_ct` and return noisy decryption output
 /// `m + e`
 pub(crate) fn multi_party_aggregate_decryption_shares_and_decrypt<
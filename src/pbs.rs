@@ -1,5 +1,6 @@
 use std::fmt::Display;
 
+use itertools::{izip, Itertools};
 use num_traits::{FromPrimitive, One, PrimInt, ToPrimitive, Zero};
 
 use crate::{
@@ -8,10 +9,10 @@ use crate::{
     lwe::lwe_key_switch,
     ntt::Ntt,
     rgsw::{
-        rlwe_auto_shoup, rlwe_by_rgsw_shoup, RgswCiphertextRef, RlweCiphertextMutRef, RlweKskRef,
-        RuntimeScratchMutRef,
+        decompose_r, poly_fma_routine, rlwe_auto_shoup, rlwe_by_rgsw, rlwe_by_rgsw_shoup,
+        RgswCiphertextRef, RlweCiphertextMutRef, RlweKsk, RlweKskRef, RuntimeScratchMutRef,
     },
-    Matrix, MatrixEntity, MatrixMut, RowMut,
+    Matrix, MatrixEntity, MatrixMut, RowEntity, RowMut,
 };
 pub(crate) trait PbsKey {
     type RgswCt;
@@ -24,6 +25,11 @@ pub(crate) trait PbsKey {
     fn galois_key_for_auto(&self, k: usize) -> &Self::AutoKey;
     /// LWE ksk to key switch from RLWE secret to LWE secret
     fn lwe_ksk(&self) -> &Self::LweKskKey;
+    /// Scheme switching key RLWE'(s^2) for circuit bootstrapping. Returns None
+    /// if the key does not support circuit bootstrapping
+    fn scheme_switch_key(&self) -> Option<&Self::AutoKey> {
+        None
+    }
 }
 
 pub(crate) trait WithShoupRepr: AsRef<Self::M> {
@@ -76,6 +82,8 @@ pub(crate) trait PbsInfo {
     fn rlwe_rgsw_decomposer(&self) -> &(Self::D, Self::D);
     /// RLWE auto decomposer
     fn auto_decomposer(&self) -> &Self::D;
+    /// Scheme switch decomposer of circuit bootstrapping, if supported
+    fn scheme_switch_decomposer(&self) -> Option<&Self::D>;
 
     /// LWE modulus operator
     fn modop_lweq(&self) -> &Self::LweModOp;
@@ -101,6 +109,7 @@ pub(crate) trait PbsInfo {
 /// - key switching
 /// - mod down
 /// - blind rotate
+/// - sample extract
 pub(crate) fn pbs<
     M: MatrixMut + MatrixEntity,
     MShoup: WithShoupRepr<M = M>,
@@ -116,6 +125,115 @@ pub(crate) fn pbs<
 ) where
     <M as Matrix>::R: RowMut,
     M::MatElement: PrimInt + FromPrimitive + One + Copy + Zero + Display,
+{
+    let rlwe = blind_rotate_lwe(
+        pbs_info,
+        test_vec,
+        lwe_in,
+        pbs_key,
+        scratch_lwe_vec,
+        scratch_blind_rotate_matrix,
+    );
+
+    // sample extract
+    sample_extract(lwe_in, &rlwe, pbs_info.modop_rlweq(), 0);
+}
+
+/// Multi-value PBS. Bootstraps `lwe_in` to encryptions of several functions
+/// of its phase with a single blind rotation.
+///
+/// Test vector of i^th function is `test_vec(X) * factors[i](X)`, where
+/// `test_vec` is common to all functions and `factors[i]` is a polynomial
+/// with coefficients in {-1, 0, 1}. `factors[i]` is stored as (exponent,
+/// sign) of its non-zero terms, where sign is true if the coefficient is 1.
+///
+/// Blind rotation only uses `test_vec` and outputs RLWE(t(X)) where t(X) =
+/// test_vec(X) * X^{-phase}. Since constant coefficient of t(X) * X^{k} equals
+/// negation of (N-k)^th coefficient of t(X), i^th output is sum of LWE
+/// ciphertexts sample extracted at index N-k for every term X^{k} of
+/// `factors[i]`. Hence noise of i^th output is noise of blind rotation times
+/// square root of no. of terms of `factors[i]`.
+///
+/// Returns outputs in the order of `factors`. `lwe_in` is used as scratch
+/// space.
+pub(crate) fn pbs_many<
+    M: MatrixMut + MatrixEntity,
+    MShoup: WithShoupRepr<M = M>,
+    P: PbsInfo<M = M>,
+    K: PbsKey<RgswCt = MShoup, AutoKey = MShoup, LweKskKey = M>,
+>(
+    pbs_info: &P,
+    test_vec: &M::R,
+    factors: &[Vec<(usize, bool)>],
+    lwe_in: &mut M::R,
+    pbs_key: &K,
+    scratch_lwe_vec: &mut M::R,
+    scratch_blind_rotate_matrix: &mut M,
+) -> Vec<M::R>
+where
+    <M as Matrix>::R: RowMut + RowEntity,
+    M::MatElement: PrimInt + FromPrimitive + One + Copy + Zero + Display,
+{
+    let rlwe = blind_rotate_lwe(
+        pbs_info,
+        test_vec,
+        lwe_in,
+        pbs_key,
+        scratch_lwe_vec,
+        scratch_blind_rotate_matrix,
+    );
+
+    let modop = pbs_info.modop_rlweq();
+    let rlwe_n = pbs_info.rlwe_n();
+    let embedding_factor = pbs_info.embedding_factor();
+    factors
+        .iter()
+        .map(|factor| {
+            let mut lwe_out = M::R::zeros(rlwe_n + 1);
+            factor.iter().for_each(|(exponent, sign)| {
+                // X^{k} in ring X^{q/2}+1 is X^{k * embedding_factor} in ring X^{N}+1
+                let (index, is_negated) = if *exponent == 0 {
+                    (0, !sign)
+                } else {
+                    (rlwe_n - exponent * embedding_factor, *sign)
+                };
+                sample_extract(lwe_in, &rlwe, modop, index);
+                izip!(lwe_out.as_mut().iter_mut(), lwe_in.as_ref().iter()).for_each(|(o, i)| {
+                    if is_negated {
+                        *o = modop.sub(o, i);
+                    } else {
+                        *o = modop.add(o, i);
+                    }
+                });
+            });
+            lwe_out
+        })
+        .collect()
+}
+
+/// - Mod down
+/// - key switching
+/// - mod down
+/// - blind rotate
+///
+/// Returns RLWE(test_vec(X) * X^{-phase}), where `phase` is phase of `lwe_in`
+/// switched to blind rotation modulus. `lwe_in` is used as scratch space.
+fn blind_rotate_lwe<
+    M: MatrixMut + MatrixEntity,
+    MShoup: WithShoupRepr<M = M>,
+    P: PbsInfo<M = M>,
+    K: PbsKey<RgswCt = MShoup, AutoKey = MShoup, LweKskKey = M>,
+>(
+    pbs_info: &P,
+    test_vec: &M::R,
+    lwe_in: &mut M::R,
+    pbs_key: &K,
+    scratch_lwe_vec: &mut M::R,
+    scratch_blind_rotate_matrix: &mut M,
+) -> M
+where
+    <M as Matrix>::R: RowMut,
+    M::MatElement: PrimInt + FromPrimitive + One + Copy + Zero + Display,
 {
     let rlwe_q = pbs_info.rlwe_q();
     let lwe_q = pbs_info.lwe_q();
@@ -125,10 +243,13 @@ pub(crate) fn pbs<
     let br_qf64 = br_q.to_f64().unwrap();
     let rlwe_n = pbs_info.rlwe_n();
 
-    // moddown Q -> Q_ks
+    // moddown Q -> Q_ks. Values close to Q round to Q_ks, hence are reduced
+    // mod Q_ks
     lwe_in.as_mut().iter_mut().for_each(|v| {
-        *v =
-            M::MatElement::from_f64(((v.to_f64().unwrap() * lwe_qf64) / rlwe_qf64).round()).unwrap()
+        *v = M::MatElement::from_f64(
+            ((v.to_f64().unwrap() * lwe_qf64) / rlwe_qf64).round() % lwe_qf64,
+        )
+        .unwrap()
     });
 
     // key switch RLWE secret to LWE secret
@@ -228,8 +349,315 @@ pub(crate) fn pbs<
     );
     // println!("Blind rotation time: {:?}", now.elapsed());
 
-    // sample extract
-    sample_extract(lwe_in, &trivial_rlwe_test_poly, pbs_info.modop_rlweq(), 0);
+    trivial_rlwe_test_poly
+}
+
+/// Circuit bootstrapping
+///
+/// Returns RGSW(m) with polynomials in evaluation domain, where `lwe_in` is
+/// bool ciphertext of m \in {0, 1} (i.e. true is Q/8 and false is -Q/8).
+///
+/// For each element \beta of RLWE x RGSW gadget vectors:
+/// - blind rotates constant test vector \beta/2 (mod Q), which maps true to
+///   \beta/2 and false to -\beta/2 in the constant term,
+/// - scales the RLWE by N^{-1} and evaluates trace, which clears all but the
+///   constant term,
+/// - adds \beta/2, which results in RLWE(\beta m).
+///
+/// RLWE(\beta m) are rows of RLWE'(m). Rows of RLWE'(-sm) are obtained by
+/// scheme switching RLWE(\beta m) with key RLWE'(s^2).
+///
+/// Trace requires automorphisms of the full ring, hence blind rotation modulus
+/// must be 2N.
+pub(crate) fn circuit_bootstrap<
+    M: MatrixMut + MatrixEntity,
+    MShoup: WithShoupRepr<M = M>,
+    P: PbsInfo<M = M>,
+    K: PbsKey<RgswCt = MShoup, AutoKey = MShoup, LweKskKey = M>,
+>(
+    pbs_info: &P,
+    lwe_in: &M::R,
+    pbs_key: &K,
+    scratch_lwe_vec: &mut M::R,
+    scratch_blind_rotate_matrix: &mut M,
+) -> M
+where
+    <M as Matrix>::R: RowMut + RowEntity + Clone,
+    M::MatElement: PrimInt + FromPrimitive + One + Copy + Zero + Display,
+    P::RlweModOp: VectorOps<Element = M::MatElement>,
+{
+    let ring_size = pbs_info.rlwe_n();
+    let mod_op = pbs_info.modop_rlweq();
+    let ntt_op = pbs_info.nttop_rlweq();
+    assert!(
+        pbs_info.br_q() == 2 * ring_size,
+        "Circuit bootstrapping requires blind rotation modulus 2N"
+    );
+
+    // Q is odd, hence 2^{-1} = (Q+1)/2 and N^{-1} = (2^{-1})^{log(N)}
+    let q = pbs_info.rlwe_q().q().unwrap();
+    assert!(q & M::MatElement::one() == M::MatElement::one());
+    let two_inv = (q >> 1) + M::MatElement::one();
+    let mut n_inv = M::MatElement::one();
+    (0..ring_size.trailing_zeros()).for_each(|_| n_inv = mod_op.mul(&n_inv, &two_inv));
+
+    let rlwe_rgsw_decomposer = pbs_info.rlwe_rgsw_decomposer();
+    let d_a = rlwe_rgsw_decomposer.a().decomposition_count().0;
+    let d_b = rlwe_rgsw_decomposer.b().decomposition_count().0;
+    let mut rgsw_out = M::zeros(d_a * 2 + d_b * 2, ring_size);
+
+    // RLWE(\beta m)
+    let mut rlwe_beta_m = |beta: &M::MatElement| {
+        let half_beta = mod_op.mul(beta, &two_inv);
+        let test_vec = {
+            let mut v = M::R::zeros(pbs_info.br_q() >> 1);
+            v.as_mut().fill(half_beta);
+            v
+        };
+        let mut rlwe = blind_rotate_lwe(
+            pbs_info,
+            &test_vec,
+            &mut lwe_in.clone(),
+            pbs_key,
+            scratch_lwe_vec,
+            scratch_blind_rotate_matrix,
+        );
+        rlwe.iter_rows_mut()
+            .for_each(|r| mod_op.elwise_scalar_mul_mut(r.as_mut(), &n_inv));
+        trace(pbs_info, &mut rlwe, pbs_key, scratch_blind_rotate_matrix);
+        let b0 = rlwe.get_row_mut(1);
+        b0[0] = mod_op.add(&b0[0], &half_beta);
+        rlwe
+    };
+
+    // RLWE'(m)
+    let gadget_b = rlwe_rgsw_decomposer.b().gadget_vector();
+    gadget_b.iter().enumerate().for_each(|(i, beta)| {
+        let mut rlwe = rlwe_beta_m(beta);
+        rlwe.iter_rows_mut()
+            .for_each(|r| ntt_op.forward(r.as_mut()));
+        rgsw_out
+            .get_row_mut(d_a * 2 + i)
+            .copy_from_slice(rlwe.get_row_slice(0));
+        rgsw_out
+            .get_row_mut(d_a * 2 + d_b + i)
+            .copy_from_slice(rlwe.get_row_slice(1));
+    });
+
+    // RLWE'(-sm)
+    let ss_decomposer = pbs_info
+        .scheme_switch_decomposer()
+        .expect("Parameters do not support circuit bootstrapping");
+    let ss_key = pbs_key
+        .scheme_switch_key()
+        .expect("Server key does not support circuit bootstrapping");
+    let d_ss = ss_decomposer.decomposition_count().0;
+    let ss_key = RlweKskRef::new(ss_key.as_ref().as_ref(), d_ss);
+    let mut decomp_a = M::zeros(d_ss, ring_size);
+    let gadget_a = rlwe_rgsw_decomposer.a().gadget_vector();
+    gadget_a.iter().enumerate().for_each(|(i, beta)| {
+        let rlwe = rlwe_beta_m(beta);
+
+        // RLWE(a s^2) = decomp<a> \cdot RLWE'(s^2)
+        decompose_r(rlwe.get_row_slice(0), decomp_a.as_mut(), ss_decomposer);
+        decomp_a
+            .iter_rows_mut()
+            .for_each(|r| ntt_op.forward(r.as_mut()));
+        let mut rlwe_out = M::zeros(2, ring_size);
+        poly_fma_routine(
+            rlwe_out.get_row_mut(0),
+            decomp_a.as_ref(),
+            ss_key.ksk_part_a(),
+            mod_op,
+        );
+        poly_fma_routine(
+            rlwe_out.get_row_mut(1),
+            decomp_a.as_ref(),
+            ss_key.ksk_part_b(),
+            mod_op,
+        );
+
+        // RLWE(-s \beta m) = RLWE(a s^2) + (b, 0), since a s^2 - b s = -s(b - as)
+        let mut b_eval = rlwe.get_row_slice(1).to_vec();
+        ntt_op.forward(b_eval.as_mut());
+        mod_op.elwise_add_mut(rlwe_out.get_row_mut(0), b_eval.as_ref());
+
+        rgsw_out
+            .get_row_mut(i)
+            .copy_from_slice(rlwe_out.get_row_slice(0));
+        rgsw_out
+            .get_row_mut(d_a + i)
+            .copy_from_slice(rlwe_out.get_row_slice(1));
+    });
+
+    rgsw_out
+}
+
+/// CMUX tree lookup
+///
+/// Returns RLWE(p(X) X^{-step * l}), where `rgsw_cts` are RGSW ciphertexts (in
+/// evaluation domain) of bits of index `i = l + h * 2^{r}` in little endian,
+/// `p = polys[h]` and `r = rgsw_cts.len() - log(polys.len())`. Hence constant
+/// term of the output (and terms upto `step`) are the coefficients of
+/// `polys[h]` at `step * l` (and the next `step - 1` terms).
+///
+/// Polynomial `polys[h]` is selected with a tree of CMUXs over bits of `h`
+/// (horizontal packing) and rotated by X^{-step * 2^j} for each bit j of `l`
+/// (vertical packing). Hence only `rgsw_cts.len()` RLWE x RGSW products are on
+/// the path of the output.
+pub(crate) fn cmux_tree_lookup<M: MatrixMut + MatrixEntity, P: PbsInfo<M = M>>(
+    pbs_info: &P,
+    polys: &[M::R],
+    rgsw_cts: &[M],
+    step: usize,
+    scratch_matrix: &mut M,
+) -> M
+where
+    <M as Matrix>::R: RowMut,
+    M::MatElement: Copy + Zero,
+    P::RlweModOp: VectorOps<Element = M::MatElement>,
+{
+    let ring_size = pbs_info.rlwe_n();
+    let mod_op = pbs_info.modop_rlweq();
+    let rlwe_rgsw_decomposer = pbs_info.rlwe_rgsw_decomposer();
+    let d_a = rlwe_rgsw_decomposer.a().decomposition_count().0;
+    let d_b = rlwe_rgsw_decomposer.b().decomposition_count().0;
+
+    assert!(polys.len().is_power_of_two());
+    let log_polys = polys.len().trailing_zeros() as usize;
+    assert!(rgsw_cts.len() >= log_polys);
+    let (rotation_bits, selection_bits) = rgsw_cts.split_at(rgsw_cts.len() - log_polys);
+    assert!(step << rotation_bits.len() <= ring_size);
+
+    let mut scratch_matrix = RuntimeScratchMutRef::new(scratch_matrix.as_mut());
+    // CMUX(b, c0, c1) = c0 + RGSW(b) x (c1 - c0)
+    let mut cmux = |rgsw: &M, c0: &M, mut c1: M, is_trivial: bool| {
+        izip!(c1.iter_rows_mut(), c0.iter_rows())
+            .for_each(|(c1_i, c0_i)| mod_op.elwise_sub_mut(c1_i.as_mut(), c0_i.as_ref()));
+        rlwe_by_rgsw(
+            &mut RlweCiphertextMutRef::new(c1.as_mut()),
+            &RgswCiphertextRef::new(rgsw.as_ref(), d_a, d_b),
+            &mut scratch_matrix,
+            rlwe_rgsw_decomposer,
+            pbs_info.nttop_rlweq(),
+            mod_op,
+            is_trivial,
+        );
+        izip!(c1.iter_rows_mut(), c0.iter_rows())
+            .for_each(|(c1_i, c0_i)| mod_op.elwise_add_mut(c1_i.as_mut(), c0_i.as_ref()));
+        c1
+    };
+
+    // Trivial RLWE(p) for each polynomial p
+    let mut cts = polys
+        .iter()
+        .map(|p| {
+            let mut ct = M::zeros(2, ring_size);
+            ct.get_row_mut(1).copy_from_slice(p.as_ref());
+            ct
+        })
+        .collect_vec();
+    let mut is_trivial = true;
+
+    // Horizontal packing: select polys[h]
+    for rgsw in selection_bits {
+        cts = cts
+            .into_iter()
+            .tuples()
+            .map(|(c0, c1)| cmux(rgsw, &c0, c1, is_trivial))
+            .collect_vec();
+        is_trivial = false;
+    }
+    let mut ct = cts.pop().unwrap();
+
+    // Vertical packing: rotate by X^{-step * l}, where X^{-k} = -X^{N-k}
+    for (j, rgsw) in rotation_bits.iter().enumerate() {
+        let mut rotated = M::zeros(2, ring_size);
+        izip!(rotated.iter_rows_mut(), ct.iter_rows()).for_each(|(to_ri, from_ri)| {
+            monomial_mul(
+                from_ri.as_ref(),
+                to_ri.as_mut(),
+                ring_size - (step << j),
+                false,
+                ring_size,
+                mod_op,
+            )
+        });
+        ct = cmux(rgsw, &ct, rotated, is_trivial);
+        is_trivial = false;
+    }
+
+    ct
+}
+
+/// Evaluates trace Tr(m) = \sum_{k \in Z^*_{2N}} m(X^k) on RLWE(m) inplace.
+/// Trace of m(X) is N times the constant term of m(X).
+///
+/// Z^*_{2N} = {1, -g} x <g> where g has order N/2. Hence trace equals
+/// (1 + \tau_{-g}) \prod_{i=0}^{log(N/2)-1} (1 + \tau_{g^{2^i}}), where
+/// \tau_{k} is automorphism X -> X^k. \tau_{g^{2^i}} is evaluated as
+/// composition of autos with available keys for g^{j}, j <= w.
+fn trace<
+    M: MatrixMut + MatrixEntity,
+    MShoup: WithShoupRepr<M = M>,
+    P: PbsInfo<M = M>,
+    K: PbsKey<AutoKey = MShoup>,
+>(
+    pbs_info: &P,
+    rlwe: &mut M,
+    pbs_key: &K,
+    scratch_matrix: &mut M,
+) where
+    <M as Matrix>::R: RowMut,
+    M::MatElement: Copy + Zero,
+    P::RlweModOp: VectorOps<Element = M::MatElement>,
+{
+    let ring_size = pbs_info.rlwe_n();
+    let mod_op = pbs_info.modop_rlweq();
+    let auto_decomposer = pbs_info.auto_decomposer();
+    let d_auto = auto_decomposer.decomposition_count().0;
+    let mut scratch_matrix = RuntimeScratchMutRef::new(scratch_matrix.as_mut());
+
+    let mut auto = |rlwe: &mut M, k: usize| {
+        let (auto_map_index, auto_map_sign) = pbs_info.rlwe_auto_map(k);
+        let auto_key = pbs_key.galois_key_for_auto(k);
+        rlwe_auto_shoup(
+            &mut RlweCiphertextMutRef::new(rlwe.as_mut()),
+            &RlweKskRef::new(auto_key.as_ref().as_ref(), d_auto),
+            &RlweKskRef::new(auto_key.shoup_repr().as_ref(), d_auto),
+            &mut scratch_matrix,
+            auto_map_index,
+            auto_map_sign,
+            mod_op,
+            pbs_info.nttop_rlweq(),
+            auto_decomposer,
+            false,
+        );
+    };
+
+    let mut add_auto = |rlwe: &mut M, dlogs: &[usize]| {
+        let mut tmp = M::zeros(2, ring_size);
+        izip!(tmp.iter_rows_mut(), rlwe.iter_rows())
+            .for_each(|(to_ri, from_ri)| to_ri.as_mut().copy_from_slice(from_ri.as_ref()));
+        dlogs.iter().for_each(|k| auto(&mut tmp, *k));
+        izip!(rlwe.iter_rows_mut(), tmp.iter_rows())
+            .for_each(|(to_ri, from_ri)| mod_op.elwise_add_mut(to_ri.as_mut(), from_ri.as_ref()));
+    };
+
+    // (1 + \tau_{g^{2^i}})
+    let w = pbs_info.w();
+    let mut exp = 1;
+    while exp < ring_size / 2 {
+        let dlogs = (0..exp)
+            .step_by(w)
+            .map(|j| std::cmp::min(w, exp - j))
+            .collect_vec();
+        add_auto(rlwe, &dlogs);
+        exp <<= 1;
+    }
+
+    // (1 + \tau_{-g})
+    add_auto(rlwe, &[0]);
 }
 
 /// LMKCY+ Blind rotation
@@ -413,7 +841,7 @@ fn blind_rotation<
     // println!("Auto count: {count}");
 }
 
-fn mod_switch_odd(v: f64, from_q: f64, to_q: f64) -> usize {
+pub(crate) fn mod_switch_odd(v: f64, from_q: f64, to_q: f64) -> usize {
     let odd_v = (((v * to_q) / (from_q)).floor()).to_usize().unwrap();
     //TODO(Jay): check correctness of this
     odd_v + ((odd_v & 1) ^ 1)
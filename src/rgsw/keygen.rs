@@ -429,6 +429,141 @@ pub(crate) fn seeded_auto_key_gen<
     );
 }
 
+/// Returns scheme switching key RLWE'(s^2(X))
+///
+/// Scheme switching key is key switching key that key-switches
+/// RLWE_{-s^2(X)}(m(X)) to RLWE_{s(X)}(m(X)). Circuit bootstrapping uses it
+/// to produce RLWE'(-s(X)m(X)) from RLWE'(m(X)).
+///
+/// - s: secret polynomial s(X)
+/// - gadget_vector: Gadget vector corresponding to decomposer used in scheme
+///   switch
+/// - p_rng: pseudo random generator used to generate `a` polynomials of key
+///   switching key RLWE ciphertexts
+#[cfg(test)]
+pub(crate) fn seeded_scheme_switch_key_gen<
+    Mmut: MatrixMut + MatrixEntity,
+    ModOp: ArithmeticOps<Element = Mmut::MatElement>
+        + VectorOps<Element = Mmut::MatElement>
+        + GetModulus<Element = Mmut::MatElement>,
+    NttOp: Ntt<Element = Mmut::MatElement>,
+    S,
+    R: RandomFillGaussianInModulus<[Mmut::MatElement], ModOp::M>,
+    PR: RandomFillUniformInModulus<[Mmut::MatElement], ModOp::M>,
+>(
+    ksk_out: &mut Mmut,
+    s: &[S],
+    gadget_vector: &[Mmut::MatElement],
+    mod_op: &ModOp,
+    ntt_op: &NttOp,
+    p_rng: &mut PR,
+    rng: &mut R,
+) where
+    <Mmut as Matrix>::R: RowMut,
+    Mmut::R: TryConvertFrom1<[S], ModOp::M>,
+{
+    let q = mod_op.modulus();
+
+    // s(X) -> s^2(X)
+    let mut s_sq = Mmut::R::try_convert_from(s, q);
+    ntt_op.forward(s_sq.as_mut());
+    s_sq.as_mut().iter_mut().for_each(|v| *v = mod_op.mul(v, v));
+    ntt_op.backward(s_sq.as_mut());
+    let s = Mmut::R::try_convert_from(s, q);
+
+    // Ksk from -s^2(X) to s(X)
+    seeded_rlwe_ksk_gen(ksk_out, s_sq, s, gadget_vector, mod_op, ntt_op, p_rng, rng);
+}
+
+/// Returns share of scheme switching key RLWE'(s^2(X)) of an interactive
+/// multi-party protocol encrypted using collective public key
+///
+/// Collective secret is s(X) = \sum s_i(X). Each party encrypts, for every
+/// \beta^k of the gadget vector, RLWE_{s}(\beta^k s_i(X)s(X)) as public key
+/// encryption of 0 minus \beta^k s_i(X) in part `a`. Sum of shares of all
+/// parties is RLWE'_{s}(s^2(X)).
+///
+/// Unlike the single party key, share cannot be seeded and `ksk_out` has `2d`
+/// rows: part `a` of `d` RLWE ciphertexts followed by their part `b`.
+///
+/// - s: secret polynomial s_i(X) of the party
+/// - public_key: collective public key
+/// - gadget_vector: Gadget vector corresponding to decomposer used in scheme
+///   switch
+#[cfg(feature = "interactive_mp")]
+pub(crate) fn public_key_encrypt_scheme_switch_key_share<
+    Mmut: MatrixMut + MatrixEntity,
+    M: Matrix<MatElement = Mmut::MatElement>,
+    ModOp: VectorOps<Element = Mmut::MatElement> + GetModulus<Element = Mmut::MatElement>,
+    NttOp: Ntt<Element = Mmut::MatElement>,
+    R: RandomFillGaussianInModulus<[Mmut::MatElement], ModOp::M>
+        + RandomFill<[u8]>
+        + RandomElementInModulus<usize, usize>,
+>(
+    ksk_out: &mut Mmut,
+    s: &[i32],
+    public_key: &M,
+    gadget_vector: &[Mmut::MatElement],
+    mod_op: &ModOp,
+    ntt_op: &NttOp,
+    rng: &mut R,
+) where
+    <Mmut as Matrix>::R: RowMut + RowEntity + TryConvertFrom1<[i32], ModOp::M>,
+    Mmut::MatElement: Copy,
+{
+    let ring_size = s.len();
+    let d = gadget_vector.len();
+    assert!(public_key.dimension() == (2, ring_size));
+    assert!(ksk_out.dimension() == (d * 2, ring_size));
+
+    let q = mod_op.modulus();
+
+    let mut pk_eval = Mmut::zeros(2, ring_size);
+    izip!(pk_eval.iter_rows_mut(), public_key.iter_rows()).for_each(|(to_i, from_i)| {
+        to_i.as_mut().copy_from_slice(from_i.as_ref());
+        ntt_op.forward(to_i.as_mut());
+    });
+    let p0 = pk_eval.get_row_slice(0);
+    let p1 = pk_eval.get_row_slice(1);
+
+    let s = Mmut::R::try_convert_from(s, q);
+
+    let (part_a, part_b) = ksk_out.split_at_row_mut(d);
+    izip!(part_a.iter_mut(), part_b.iter_mut(), gadget_vector.iter()).for_each(
+        |(ai, bi, beta_i)| {
+            // sample ephemeral secret u_i
+            let mut u = vec![0i32; ring_size];
+            fill_random_ternary_secret_with_hamming_weight(u.as_mut(), ring_size >> 1, rng);
+            let mut u_eval = Mmut::R::try_convert_from(u.as_ref(), &q);
+            ntt_op.forward(u_eval.as_mut());
+
+            let mut u_eval_copy = Mmut::R::zeros(ring_size);
+            u_eval_copy.as_mut().copy_from_slice(u_eval.as_ref());
+
+            // p0 * u
+            mod_op.elwise_mul_mut(u_eval.as_mut(), p0.as_ref());
+            // p1 * u
+            mod_op.elwise_mul_mut(u_eval_copy.as_mut(), p1.as_ref());
+            ntt_op.backward(u_eval.as_mut());
+            ntt_op.backward(u_eval_copy.as_mut());
+
+            // sample error
+            RandomFillGaussianInModulus::random_fill(rng, &q, ai.as_mut());
+            RandomFillGaussianInModulus::random_fill(rng, &q, bi.as_mut());
+
+            // a = p0*u+e0
+            mod_op.elwise_add_mut(ai.as_mut(), u_eval.as_ref());
+            // b = p1*u+e1
+            mod_op.elwise_add_mut(bi.as_mut(), u_eval_copy.as_ref());
+
+            // a = p0*u + e0 - \beta*s_i, hence b - as = e + \beta*s_i*s
+            // use u_eval as scratch
+            mod_op.elwise_scalar_mul(u_eval.as_mut(), s.as_ref(), beta_i);
+            mod_op.elwise_sub_mut(ai.as_mut(), u_eval.as_ref());
+        },
+    );
+}
+
 /// Returns seeded RLWE(m(X))
 ///
 /// RLWE(m(X)) = [a(X), b(X) = a(X)s(X) + e(X) + m(X)]
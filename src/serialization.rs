@@ -0,0 +1,719 @@
+//! Versioned binary encoding of keys, key shares, and ciphertexts
+//!
+//! Types exchanged between clients and the server implement `BinaryEncoding`.
+//! For example, a client sends its server key share with
+//!
+//! ```ignore
+//! let bytes = gen_server_key_share(user_id, total_users, &ck).to_bytes()?;
+//! ```
+//!
+//! and the server reads it back with `from_bytes`, which returns `DecodeError`
+//! for truncated, corrupted, or incompatible input instead of panicking.
+//!
+//! Every encoding starts with a header of magic bytes `PZ`, `FORMAT_VERSION`,
+//! a tag identifying the encoded type, and the `Fingerprint` of the protocol
+//! instance (parameters, common reference seed, and session id) the value was
+//! encoded in. `from_bytes` rejects values encoded in a different protocol
+//! instance than the one active on the current thread (refer to
+//! `fingerprint`), since mixing them up silently produces garbage or weakens
+//! security. Hence encoding or decoding any value returns
+//! `DecodeError::ParametersNotSet` (or `CommonReferenceSeedNotSet`) unless
+//! parameters and the common reference seed are set, or a session is entered.
+//!
+//! Polynomials are encoded with each coefficient packed into `log_q` bits,
+//! where `q` is the ciphertext modulus of the parameters the polynomial belongs
+//! to. Ciphertexts and decryption shares, which do not carry parameters, are
+//! packed into bits of their largest coefficient.
+//!
+//! With feature `serde`, the types also implement `serde::Serialize` and
+//! `serde::Deserialize` as bytes of the same encoding.
+
+use std::{collections::HashMap, fmt::Display};
+
+use crate::{
+    backend::Modulus,
+    bool::parameters::{BoolParameters, CiphertextModulus},
+    multi_party::DecryptionShare,
+    SizeInBitsWithLogModulus,
+};
+
+/// Version of the encoding written by `BinaryEncoding::to_bytes`.
+///
+/// Incremented on every change of the encoding of any type. Encodings with a
+/// different version are rejected with `DecodeError::UnsupportedVersion`.
+pub const FORMAT_VERSION: u8 = 1;
+
+const MAGIC: [u8; 2] = *b"PZ";
+
+/// Tags identifying encoded types
+///
+/// Tags are fixed across features, hence tags of key types that are not
+/// reachable with the enabled feature are unused.
+#[allow(dead_code)]
+pub(crate) mod tag {
+    pub(crate) const CLIENT_KEY: u8 = 1;
+    pub(crate) const COLLECTIVE_PUBLIC_KEY_SHARE: u8 = 2;
+    pub(crate) const PUBLIC_KEY: u8 = 3;
+    pub(crate) const INTERACTIVE_SERVER_KEY_SHARE: u8 = 4;
+    pub(crate) const INTERACTIVE_SERVER_KEY: u8 = 5;
+    pub(crate) const NON_INTERACTIVE_SERVER_KEY_SHARE: u8 = 6;
+    pub(crate) const NON_INTERACTIVE_SERVER_KEY: u8 = 7;
+    pub(crate) const SEEDED_BATCHED_FHE_INTEGER: u8 = 8;
+    pub(crate) const FHE_BOOL: u8 = 9;
+    pub(crate) const FHE_UINT: u8 = 10;
+    pub(crate) const FHE_INT: u8 = 11;
+    pub(crate) const DECRYPTION_SHARE: u8 = 12;
+    pub(crate) const DECRYPTION_SHARES: u8 = 13;
+}
+
+/// Fingerprint of a protocol instance, that is a hash of its parameters,
+/// common reference seed, and session id
+///
+/// Fingerprint is meant to catch accidental mix ups of protocol instances
+/// (for ex, a share generated with `NonInteractiveLTE2Party` sent to an
+/// instance with `NonInteractiveLTE8Party`). It is not a cryptographic hash.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Fingerprint(u64);
+
+impl Fingerprint {
+    pub(crate) fn new(
+        parameters: &BoolParameters<u64>,
+        seed: &[u8; 32],
+        session_id: u64,
+    ) -> Fingerprint {
+        let mut w = Writer::default();
+        parameters.encode(&mut w);
+        w.bytes(seed);
+        w.u64(session_id);
+        Fingerprint(hash_bytes(w.bytes))
+    }
+}
+
+/// Returns FNV-1a hash of `bytes`, which unlike `std::hash::DefaultHasher` is
+/// stable across releases and platforms. It is not a cryptographic hash.
+pub(crate) fn hash_bytes<I: IntoIterator<Item = u8>>(bytes: I) -> u64 {
+    bytes.into_iter().fold(0xcbf29ce484222325u64, |h, b| {
+        (h ^ b as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+impl Display for Fingerprint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:016x}", self.0)
+    }
+}
+
+/// Error of decoding bytes with `BinaryEncoding::from_bytes` (or of encoding
+/// a value with `BinaryEncoding::to_bytes` outside of a protocol instance)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DecodeError {
+    /// Neither a session is entered nor parameters are set on the current
+    /// thread, hence there's no fingerprint to encode or check
+    ParametersNotSet,
+    /// Neither a session is entered nor the process wide common reference
+    /// seed is set, hence there's no fingerprint to encode or check
+    CommonReferenceSeedNotSet,
+    /// Bytes do not start with the magic bytes of the encoding
+    InvalidMagic,
+    /// Bytes were encoded with a different version of the encoding
+    UnsupportedVersion(u8),
+    /// Bytes encode a different type
+    UnexpectedType { expected: u8, found: u8 },
+    /// Value was encoded in a protocol instance with different parameters,
+    /// common reference seed, or session id than the active one
+    FingerprintMismatch {
+        expected: Fingerprint,
+        found: Fingerprint,
+    },
+    /// Bytes end before the encoded value
+    UnexpectedEnd,
+    /// Bytes continue after the encoded value
+    TrailingBytes(usize),
+    /// Encoded value is malformed
+    Invalid(String),
+}
+
+impl Display for DecodeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DecodeError::ParametersNotSet => write!(
+                f,
+                "parameters must be set or a session entered before encoding or decoding"
+            ),
+            DecodeError::CommonReferenceSeedNotSet => write!(
+                f,
+                "common reference seed must be set or a session entered before encoding or \
+                 decoding"
+            ),
+            DecodeError::InvalidMagic => write!(f, "not a phantom-zone encoding"),
+            DecodeError::UnsupportedVersion(v) => write!(
+                f,
+                "encoding version {v} is not supported (expected {FORMAT_VERSION})"
+            ),
+            DecodeError::UnexpectedType { expected, found } => {
+                write!(
+                    f,
+                    "expected encoding of type {expected}, found type {found}"
+                )
+            }
+            DecodeError::FingerprintMismatch { expected, found } => write!(
+                f,
+                "value was encoded with different parameters, common reference seed, or session \
+                 id (fingerprint {found}, expected {expected})"
+            ),
+            DecodeError::UnexpectedEnd => write!(f, "unexpected end of bytes"),
+            DecodeError::TrailingBytes(n) => write!(f, "{n} trailing bytes"),
+            DecodeError::Invalid(message) => write!(f, "{message}"),
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+/// Types with a versioned binary encoding that can be exchanged between
+/// clients and the server
+pub trait BinaryEncoding: Sized {
+    fn to_bytes(&self) -> Result<Vec<u8>, DecodeError>;
+    fn from_bytes(bytes: &[u8]) -> Result<Self, DecodeError>;
+}
+
+/// Encodes `Self` without the header
+pub(crate) trait Encode {
+    fn encode(&self, w: &mut Writer);
+}
+
+/// Decodes `Self` encoded with `Encode`
+pub(crate) trait Decode: Sized {
+    fn decode(r: &mut Reader) -> Result<Self, DecodeError>;
+}
+
+/// Returns fingerprint of the active protocol instance (refer to
+/// `crate::bool::fingerprint`)
+fn fingerprint() -> Result<Fingerprint, DecodeError> {
+    crate::bool::try_fingerprint().map_err(|e| match e {
+        crate::Error::CommonReferenceSeedNotSet => DecodeError::CommonReferenceSeedNotSet,
+        _ => DecodeError::ParametersNotSet,
+    })
+}
+
+pub(crate) fn to_bytes<T: Encode>(value: &T, tag: u8) -> Result<Vec<u8>, DecodeError> {
+    let mut w = Writer::default();
+    w.bytes(&MAGIC);
+    w.u8(FORMAT_VERSION);
+    w.u8(tag);
+    w.u64(fingerprint()?.0);
+    value.encode(&mut w);
+    Ok(w.bytes)
+}
+
+pub(crate) fn from_bytes<T: Decode>(bytes: &[u8], tag: u8) -> Result<T, DecodeError> {
+    let mut r = Reader { bytes };
+    if r.take(2)? != MAGIC {
+        return Err(DecodeError::InvalidMagic);
+    }
+    let version = r.u8()?;
+    if version != FORMAT_VERSION {
+        return Err(DecodeError::UnsupportedVersion(version));
+    }
+    let found = r.u8()?;
+    if found != tag {
+        return Err(DecodeError::UnexpectedType {
+            expected: tag,
+            found,
+        });
+    }
+    let expected = fingerprint()?;
+    let found = Fingerprint(r.u64()?);
+    if found != expected {
+        return Err(DecodeError::FingerprintMismatch { expected, found });
+    }
+    let value = T::decode(&mut r)?;
+    if !r.bytes.is_empty() {
+        return Err(DecodeError::TrailingBytes(r.bytes.len()));
+    }
+    Ok(value)
+}
+
+/// Returns no. of bits of the largest value in `values`, which is at least 1
+/// if `values` is not empty (refer to `Writer::packed`)
+pub(crate) fn bits_of_largest(values: &[u64]) -> usize {
+    values
+        .iter()
+        .max()
+        .map_or(0, |v| (u64::BITS - v.leading_zeros()).max(1) as usize)
+}
+
+/// Appends encodings to a byte vector. Integers are little endian.
+#[derive(Default)]
+pub(crate) struct Writer {
+    bytes: Vec<u8>,
+}
+
+impl Writer {
+    pub(crate) fn bytes(&mut self, v: &[u8]) {
+        self.bytes.extend_from_slice(v);
+    }
+
+    pub(crate) fn u8(&mut self, v: u8) {
+        self.bytes.push(v);
+    }
+
+    pub(crate) fn bool(&mut self, v: bool) {
+        self.u8(v as u8);
+    }
+
+    pub(crate) fn u64(&mut self, v: u64) {
+        self.bytes(&v.to_le_bytes());
+    }
+
+    pub(crate) fn usize(&mut self, v: usize) {
+        self.u64(v as u64);
+    }
+
+    /// Writes length of `values` followed by each value packed into `log_q`
+    /// bits
+    ///
+    /// Panics if any value does not fit in `log_q` bits, or if `log_q` is 0
+    /// and `values` is not empty, since the length of values packed into 0 bits
+    /// would not be bounded by the no. of bytes
+    pub(crate) fn packed(&mut self, values: &Vec<u64>, log_q: usize) {
+        assert!(log_q <= 64 && bits_of_largest(values) <= log_q);
+        assert!(log_q > 0 || values.is_empty());
+        self.usize(values.len());
+        self.u8(log_q as u8);
+        self.bytes.reserve(values.size(log_q).div_ceil(8));
+
+        let mut acc = 0u128;
+        let mut acc_bits = 0;
+        for v in values {
+            acc |= (*v as u128) << acc_bits;
+            acc_bits += log_q;
+            while acc_bits >= 8 {
+                self.bytes.push(acc as u8);
+                acc >>= 8;
+                acc_bits -= 8;
+            }
+        }
+        if acc_bits > 0 {
+            self.bytes.push(acc as u8);
+        }
+    }
+
+    /// Writes dimensions of matrix `m` followed by its rows packed into `log_q`
+    /// bits. Rows of `m` must have equal lengths.
+    pub(crate) fn packed_matrix(&mut self, m: &Vec<Vec<u64>>, log_q: usize) {
+        let cols = m.first().map_or(0, |r| r.len());
+        assert!(m.iter().all(|r| r.len() == cols));
+        self.usize(m.len());
+        self.bytes.reserve(m.size(log_q).div_ceil(8));
+        m.iter().for_each(|r| self.packed(r, log_q));
+    }
+
+    /// Writes `map` with keys in increasing order, so that equal maps have
+    /// equal encodings
+    pub(crate) fn map<V>(&mut self, map: &HashMap<usize, V>, mut f: impl FnMut(&mut Self, &V)) {
+        let mut keys = map.keys().copied().collect::<Vec<_>>();
+        keys.sort_unstable();
+        self.usize(keys.len());
+        keys.iter().for_each(|k| {
+            self.usize(*k);
+            f(self, &map[k]);
+        });
+    }
+
+    pub(crate) fn seq<V>(&mut self, values: &[V], mut f: impl FnMut(&mut Self, &V)) {
+        self.usize(values.len());
+        values.iter().for_each(|v| f(self, v));
+    }
+}
+
+/// Reads encodings written by `Writer`
+pub(crate) struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    pub(crate) fn take(&mut self, n: usize) -> Result<&'a [u8], DecodeError> {
+        if self.bytes.len() < n {
+            return Err(DecodeError::UnexpectedEnd);
+        }
+        let (out, rest) = self.bytes.split_at(n);
+        self.bytes = rest;
+        Ok(out)
+    }
+
+    pub(crate) fn u8(&mut self) -> Result<u8, DecodeError> {
+        Ok(self.take(1)?[0])
+    }
+
+    pub(crate) fn bool(&mut self) -> Result<bool, DecodeError> {
+        match self.u8()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            v => Err(DecodeError::Invalid(format!("invalid bool {v}"))),
+        }
+    }
+
+    pub(crate) fn u64(&mut self) -> Result<u64, DecodeError> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    pub(crate) fn usize(&mut self) -> Result<usize, DecodeError> {
+        let v = self.u64()?;
+        usize::try_from(v).map_err(|_| DecodeError::Invalid(format!("length {v} too large")))
+    }
+
+    /// Reads length of a sequence whose every element takes at least
+    /// `min_bytes` bytes. Returns error if remaining bytes are too few, hence
+    /// corrupted lengths never cause large allocations.
+    pub(crate) fn len(&mut self, min_bytes: usize) -> Result<usize, DecodeError> {
+        let len = self.usize()?;
+        if len.saturating_mul(min_bytes) > self.bytes.len() {
+            return Err(DecodeError::UnexpectedEnd);
+        }
+        Ok(len)
+    }
+
+    /// Reads values written by `Writer::packed`
+    pub(crate) fn packed(&mut self) -> Result<Vec<u64>, DecodeError> {
+        let len = self.usize()?;
+        let log_q = self.u8()? as usize;
+        if log_q > 64 || (log_q == 0 && len > 0) {
+            return Err(DecodeError::Invalid(format!("invalid bit width {log_q}")));
+        }
+        let packed_bytes = len
+            .checked_mul(log_q)
+            .map(|bits| bits.div_ceil(8))
+            .ok_or(DecodeError::UnexpectedEnd)?;
+        let packed = self.take(packed_bytes)?;
+
+        let mask = if log_q == 64 {
+            u64::MAX
+        } else {
+            (1u64 << log_q) - 1
+        };
+        let mut values = Vec::with_capacity(len);
+        let mut bytes = packed.iter();
+        let mut acc = 0u128;
+        let mut acc_bits = 0;
+        for _ in 0..len {
+            while acc_bits < log_q {
+                acc |= (*bytes.next().unwrap() as u128) << acc_bits;
+                acc_bits += 8;
+            }
+            values.push(acc as u64 & mask);
+            acc >>= log_q;
+            acc_bits -= log_q;
+        }
+        Ok(values)
+    }
+
+    /// Reads values written by `Writer::packed` and checks that there are
+    /// `len` values in [0, q)
+    pub(crate) fn packed_mod(
+        &mut self,
+        len: usize,
+        q: &CiphertextModulus<u64>,
+    ) -> Result<Vec<u64>, DecodeError> {
+        let values = self.packed()?;
+        if values.len() != len {
+            return Err(DecodeError::Invalid(format!(
+                "expected {len} coefficients, found {}",
+                values.len()
+            )));
+        }
+        let max = q.largest_unsigned_value();
+        if values.iter().any(|v| *v > max) {
+            return Err(DecodeError::Invalid(
+                "coefficient exceeds ciphertext modulus".to_string(),
+            ));
+        }
+        Ok(values)
+    }
+
+    /// Reads matrix written by `Writer::packed_matrix` and checks that its
+    /// rows have `cols` values in [0, q)
+    pub(crate) fn packed_matrix_mod(
+        &mut self,
+        cols: usize,
+        q: &CiphertextModulus<u64>,
+    ) -> Result<Vec<Vec<u64>>, DecodeError> {
+        let rows = self.len(9)?;
+        (0..rows).map(|_| self.packed_mod(cols, q)).collect()
+    }
+
+    /// Reads map written by `Writer::map`
+    pub(crate) fn map<V>(
+        &mut self,
+        mut f: impl FnMut(&mut Self) -> Result<V, DecodeError>,
+    ) -> Result<HashMap<usize, V>, DecodeError> {
+        let len = self.len(8)?;
+        let mut map = HashMap::with_capacity(len);
+        for _ in 0..len {
+            let k = self.usize()?;
+            if map.insert(k, f(self)?).is_some() {
+                return Err(DecodeError::Invalid(format!("duplicate key {k}")));
+            }
+        }
+        Ok(map)
+    }
+
+    /// Reads sequence written by `Writer::seq`
+    pub(crate) fn seq<V>(
+        &mut self,
+        mut f: impl FnMut(&mut Self) -> Result<V, DecodeError>,
+    ) -> Result<Vec<V>, DecodeError> {
+        let len = self.len(1)?;
+        (0..len).map(|_| f(self)).collect()
+    }
+}
+
+/// Implements `BinaryEncoding` for type that implements `Encode` and
+/// `Decode`, and with feature `serde` `serde::Serialize` and
+/// `serde::Deserialize` as bytes of the encoding. `@binary` implements only
+/// `BinaryEncoding`, for types that serde already implements.
+macro_rules! impl_binary_encoding {
+    (@binary $tag:expr, impl[$($generics:tt)*] $ty:ty) => {
+        impl<$($generics)*> $crate::serialization::BinaryEncoding for $ty {
+            fn to_bytes(&self) -> Result<Vec<u8>, $crate::serialization::DecodeError> {
+                $crate::serialization::to_bytes(self, $tag)
+            }
+
+            fn from_bytes(
+                bytes: &[u8],
+            ) -> Result<Self, $crate::serialization::DecodeError> {
+                $crate::serialization::from_bytes(bytes, $tag)
+            }
+        }
+    };
+    ($tag:expr, impl[$($generics:tt)*] $ty:ty) => {
+        $crate::serialization::impl_binary_encoding!(@binary $tag, impl[$($generics)*] $ty);
+
+        #[cfg(feature = "serde")]
+        impl<$($generics)*> serde::Serialize for $ty {
+            fn serialize<Ser: serde::Serializer>(
+                &self,
+                serializer: Ser,
+            ) -> Result<Ser::Ok, Ser::Error> {
+                let bytes = $crate::serialization::BinaryEncoding::to_bytes(self)
+                    .map_err(<Ser::Error as serde::ser::Error>::custom)?;
+                serializer.serialize_bytes(&bytes)
+            }
+        }
+
+        #[cfg(feature = "serde")]
+        impl<'de, $($generics)*> serde::Deserialize<'de> for $ty {
+            fn deserialize<De: serde::Deserializer<'de>>(
+                deserializer: De,
+            ) -> Result<Self, De::Error> {
+                let bytes = deserializer.deserialize_byte_buf($crate::serialization::BytesVisitor)?;
+                $crate::serialization::BinaryEncoding::from_bytes(&bytes)
+                    .map_err(<De::Error as serde::de::Error>::custom)
+            }
+        }
+    };
+}
+pub(crate) use impl_binary_encoding;
+
+/// Deserializes bytes from formats that support bytes natively and from
+/// formats that represent bytes as sequences of integers (for ex, JSON)
+#[cfg(feature = "serde")]
+pub(crate) struct BytesVisitor;
+
+#[cfg(feature = "serde")]
+impl<'de> serde::de::Visitor<'de> for BytesVisitor {
+    type Value = Vec<u8>;
+
+    fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "bytes")
+    }
+
+    fn visit_bytes<E: serde::de::Error>(self, v: &[u8]) -> Result<Vec<u8>, E> {
+        Ok(v.to_vec())
+    }
+
+    fn visit_byte_buf<E: serde::de::Error>(self, v: Vec<u8>) -> Result<Vec<u8>, E> {
+        Ok(v)
+    }
+
+    fn visit_seq<A: serde::de::SeqAccess<'de>>(self, mut seq: A) -> Result<Vec<u8>, A::Error> {
+        let mut bytes = Vec::with_capacity(seq.size_hint().unwrap_or(0).min(1 << 20));
+        while let Some(b) = seq.next_element()? {
+            bytes.push(b);
+        }
+        Ok(bytes)
+    }
+}
+
+/// Decryption share of `FheBool` (and `FheShortUint`)
+impl Encode for DecryptionShare {
+    fn encode(&self, w: &mut Writer) {
+        w.u64(self.0);
+    }
+}
+
+impl Decode for DecryptionShare {
+    fn decode(r: &mut Reader) -> Result<Self, DecodeError> {
+        r.u64().map(DecryptionShare)
+    }
+}
+
+impl_binary_encoding!(tag::DECRYPTION_SHARE, impl[] DecryptionShare);
+
+/// Decryption shares of `FheUint8` (and other Fhe integers)
+impl Encode for Vec<DecryptionShare> {
+    fn encode(&self, w: &mut Writer) {
+        let values = self.iter().map(|s| s.0).collect::<Vec<_>>();
+        w.packed(&values, bits_of_largest(&values));
+    }
+}
+
+impl Decode for Vec<DecryptionShare> {
+    fn decode(r: &mut Reader) -> Result<Self, DecodeError> {
+        Ok(r.packed()?.into_iter().map(DecryptionShare).collect())
+    }
+}
+
+// `Vec<T>` implements serde traits for all `T: Serialize`, hence only binary
+// encoding
+impl_binary_encoding!(@binary tag::DECRYPTION_SHARES, impl[] Vec<DecryptionShare>);
+
+#[cfg(test)]
+mod tests {
+    use rand::{thread_rng, Rng};
+
+    use super::*;
+
+    /// Returns new session with seed `seed` and id `id`
+    fn session(seed: [u8; 32], id: u64) -> crate::Session {
+        #[cfg(feature = "interactive_mp")]
+        let select = crate::ParameterSelector::InteractiveLTE2Party;
+        #[cfg(feature = "non_interactive_mp")]
+        let select = crate::ParameterSelector::NonInteractiveLTE2Party;
+        crate::Session::with_id(select, seed, id)
+    }
+
+    fn shares(values: &[u64]) -> Vec<DecryptionShare> {
+        values.iter().copied().map(DecryptionShare).collect()
+    }
+
+    #[test]
+    fn packing_round_trips() {
+        for log_q in [0, 1, 7, 15, 54, 63, 64] {
+            // only empty values are packed into 0 bits
+            for len in [0, 1, 3, 100]
+                .into_iter()
+                .filter(|len| log_q > 0 || *len == 0)
+            {
+                let values = (0..len)
+                    .map(|_| thread_rng().gen::<u64>() >> (64 - log_q))
+                    .collect::<Vec<_>>();
+                let mut w = Writer::default();
+                w.packed(&values, log_q);
+                assert_eq!(w.bytes.len(), 9 + (len * log_q).div_ceil(8));
+
+                let mut r = Reader { bytes: &w.bytes };
+                assert_eq!(r.packed().unwrap(), values);
+                assert!(r.bytes.is_empty());
+            }
+        }
+        assert_eq!(bits_of_largest(&[0, 0]), 1);
+        assert_eq!(bits_of_largest(&[]), 0);
+    }
+
+    #[test]
+    fn rejects_malformed_bytes() {
+        let _guard = session([0; 32], 0).enter();
+        let share = shares(&[1, 2, 3]).to_bytes().unwrap();
+        assert_eq!(
+            Vec::<DecryptionShare>::from_bytes(&share),
+            Ok(shares(&[1, 2, 3]))
+        );
+
+        assert_eq!(
+            Vec::<DecryptionShare>::from_bytes(&share[..share.len() - 1]),
+            Err(DecodeError::UnexpectedEnd)
+        );
+        assert_eq!(
+            Vec::<DecryptionShare>::from_bytes(&[share.as_slice(), &[0]].concat()),
+            Err(DecodeError::TrailingBytes(1))
+        );
+        assert_eq!(
+            DecryptionShare::from_bytes(&share),
+            Err(DecodeError::UnexpectedType {
+                expected: tag::DECRYPTION_SHARE,
+                found: tag::DECRYPTION_SHARES
+            })
+        );
+
+        let mut other_version = share.clone();
+        other_version[2] = FORMAT_VERSION + 1;
+        assert_eq!(
+            Vec::<DecryptionShare>::from_bytes(&other_version),
+            Err(DecodeError::UnsupportedVersion(FORMAT_VERSION + 1))
+        );
+        assert_eq!(
+            Vec::<DecryptionShare>::from_bytes(b"XX"),
+            Err(DecodeError::InvalidMagic)
+        );
+
+        // decode errors are the source of `Error::Decode`
+        let e = crate::Error::from(DecodeError::InvalidMagic);
+        assert_eq!(
+            std::error::Error::source(&e).map(|e| e.to_string()),
+            Some(DecodeError::InvalidMagic.to_string())
+        );
+
+        // length of the sequence larger than the remaining bytes
+        let mut w = Writer::default();
+        w.usize(usize::MAX);
+        let mut r = Reader { bytes: &w.bytes };
+        assert_eq!(r.seq(|r| r.u8()), Err(DecodeError::UnexpectedEnd));
+
+        // values packed into 0 bits, whose length is not bounded by the
+        // remaining bytes
+        let mut w = Writer::default();
+        w.usize(usize::MAX);
+        w.u8(0);
+        let mut r = Reader { bytes: &w.bytes };
+        assert_eq!(
+            r.packed(),
+            Err(DecodeError::Invalid("invalid bit width 0".to_string()))
+        );
+    }
+
+    #[test]
+    fn rejects_other_protocol_instances() {
+        let share = {
+            let _guard = session([0; 32], 0).enter();
+            shares(&[1, 2, 3]).to_bytes().unwrap()
+        };
+
+        for other in [session([1; 32], 0), session([0; 32], 1)] {
+            let _guard = other.enter();
+            assert_eq!(
+                Vec::<DecryptionShare>::from_bytes(&share),
+                Err(DecodeError::FingerprintMismatch {
+                    expected: other.fingerprint(),
+                    found: session([0; 32], 0).fingerprint(),
+                })
+            );
+        }
+
+        // parameters are not set on the current thread
+        assert_eq!(
+            Vec::<DecryptionShare>::from_bytes(&share),
+            Err(DecodeError::ParametersNotSet)
+        );
+        assert_eq!(
+            shares(&[1, 2, 3]).to_bytes(),
+            Err(DecodeError::ParametersNotSet)
+        );
+
+        let _guard = session([0; 32], 0).enter();
+        assert_eq!(
+            Vec::<DecryptionShare>::from_bytes(&share),
+            Ok(shares(&[1, 2, 3]))
+        );
+    }
+}
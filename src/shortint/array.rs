@@ -0,0 +1,326 @@
+//! Arrays and 2D grids of Fhe values indexed by encrypted `FheUint8`s.
+//!
+//! Reads and writes at encrypted indices are oblivious, that is every element
+//! is touched irrespective of the index. Hence their cost grows linearly with
+//! no. of elements.
+
+use itertools::izip;
+
+use crate::{
+    bool::{impl_bool_frontend::FheBool, Backend, BooleanGates},
+    utils::WithLocal,
+};
+
+use super::{
+    enc_dec::{FheInt, FheUint},
+    ops::arbitrary_bit_decoder,
+    Select,
+};
+
+/// Fhe types that can be stored in `FheArray` and `FheGrid`, that is
+/// `FheBool`, `FheUint`s, and `FheInt`s.
+///
+/// The trait is sealed, hence it can not be implemented outside the crate.
+pub trait ArrayElement<C>: sealed::Element<C> {}
+
+impl<C, T: sealed::Element<C>> ArrayElement<C> for T {}
+
+mod sealed {
+    use super::*;
+
+    /// Reads and writes at encrypted indices. Private to the crate, since
+    /// they are evaluated with crate private `Backend`.
+    pub trait Element<C>: Sized {
+        /// Returns `elements[index]`, or 0 if `index` is out of bounds
+        fn get(elements: &[Self], index: &FheUint<C, 8>) -> Self;
+
+        /// Sets `elements[index]` to `value`, unless `index` is out of bounds
+        fn set(elements: &mut [Self], index: &FheUint<C, 8>, value: &Self);
+
+        /// Sets cell `(x, y)` of `width x height` grid `cells` to `value`,
+        /// unless `(x, y)` is out of bounds
+        fn set_cell(
+            cells: &mut [Self],
+            width: usize,
+            height: usize,
+            x: &FheUint<C, 8>,
+            y: &FheUint<C, 8>,
+            value: &Self,
+        );
+    }
+
+    impl<C: Backend, T: ZeroElement<C>> Element<C> for T {
+        fn get(elements: &[Self], index: &FheUint<C, 8>) -> Self {
+            oblivious_get(elements, index)
+        }
+
+        fn set(elements: &mut [Self], index: &FheUint<C, 8>, value: &Self) {
+            let flags = decode(index, elements.len());
+            izip!(elements.iter_mut(), flags.iter()).for_each(|(element, flag)| {
+                *element = value.mux(element, flag);
+            });
+        }
+
+        fn set_cell(
+            cells: &mut [Self],
+            width: usize,
+            height: usize,
+            x: &FheUint<C, 8>,
+            y: &FheUint<C, 8>,
+            value: &Self,
+        ) {
+            let columns = decode(x, width);
+            let rows = decode(y, height);
+            izip!(
+                cells.iter_mut(),
+                rows.iter()
+                    .flat_map(|r| columns.iter().map(move |c| (r, c)))
+            )
+            .for_each(|(cell, (row, column))| {
+                *cell = value.mux(cell, &(row & column));
+            });
+        }
+    }
+}
+
+/// Element types of `FheArray` and `FheGrid` evaluated with `Backend`
+trait ZeroElement<C: Backend>: Clone + Select<FheBool<C>> {
+    /// Returns trivial encryption of 0 (false for `FheBool`)
+    fn zero() -> Self;
+}
+
+impl<C: Backend> ZeroElement<C> for FheBool<C> {
+    fn zero() -> Self {
+        FheBool::trivial(false)
+    }
+}
+
+impl<C: Backend, const BITS: usize> ZeroElement<C> for FheUint<C, BITS> {
+    fn zero() -> Self {
+        C::Evaluator::with_local(|e| FheUint {
+            data: vec![e.trivial(false); BITS],
+        })
+    }
+}
+
+impl<C: Backend, const BITS: usize> ZeroElement<C> for FheInt<C, BITS> {
+    fn zero() -> Self {
+        C::Evaluator::with_local(|e| FheInt {
+            data: vec![e.trivial(false); BITS],
+        })
+    }
+}
+
+/// Returns `elements[index]`, or 0 if `index` is out of bounds
+///
+/// Elements are selected with a mux tree over bits of `index` from LSB to MSB.
+/// Remaining bits of `index` (if `elements` has at most 128 elements) are
+/// checked with a single mux.
+fn oblivious_get<C: Backend, T: ZeroElement<C>>(elements: &[T], index: &FheUint<C, 8>) -> T {
+    let bits = index.bits();
+    let mut bits = bits.iter();
+    let mut level = elements.to_vec();
+    while level.len() > 1 {
+        let bit = bits.next().unwrap();
+        level = level
+            .chunks(2)
+            .map(|pair| match pair {
+                [low, high] => high.mux(low, bit),
+                [low] => T::zero().mux(low, bit),
+                _ => unreachable!(),
+            })
+            .collect();
+    }
+
+    let Some(element) = level.pop() else {
+        return T::zero();
+    };
+    // `index` is out of bounds if any of the remaining bits is set
+    match bits.cloned().reduce(|a, b| &a | &b) {
+        Some(out_of_bounds) => T::zero().mux(&element, &out_of_bounds),
+        None => element,
+    }
+}
+
+/// Returns flags `index == i` for all `i < len`
+fn decode<C: Backend>(index: &FheUint<C, 8>, len: usize) -> Vec<FheBool<C>> {
+    C::with_evaluator(|e, key| {
+        arbitrary_bit_decoder(e, index.data(), len, key)
+            .into_iter()
+            .map(|data| FheBool { data })
+            .collect()
+    })
+}
+
+/// Array of at most 256 Fhe values (`FheBool`, `FheUint8`, ...) that can be
+/// read and written at encrypted `FheUint8` indices
+///
+/// `get` requires `len - 1` muxes of the element type (plus at most 8 if
+/// `len` is not a power of 2). `set` requires fewer than `2 * len + 8` ANDs
+/// to compare `index` with every index of the array and a mux per element.
+#[derive(Clone)]
+pub struct FheArray<T> {
+    elements: Vec<T>,
+}
+
+impl<T> FheArray<T> {
+    /// Panics if `elements` has more than 256 elements
+    pub fn new(elements: Vec<T>) -> FheArray<T> {
+        assert!(
+            elements.len() <= 256,
+            "Expected at most 256 elements, got {}",
+            elements.len()
+        );
+        FheArray { elements }
+    }
+
+    pub fn len(&self) -> usize {
+        self.elements.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.elements.is_empty()
+    }
+
+    /// Returns elements of the array
+    pub fn elements(&self) -> &[T] {
+        &self.elements
+    }
+
+    pub fn into_elements(self) -> Vec<T> {
+        self.elements
+    }
+
+    /// Returns element at `index`. Returns trivial encryption of 0 (or
+    /// false) if `index` is out of bounds.
+    pub fn get<C>(&self, index: &FheUint<C, 8>) -> T
+    where
+        T: ArrayElement<C>,
+    {
+        T::get(&self.elements, index)
+    }
+
+    /// Sets element at `index` to `value`. Array is left unchanged if `index`
+    /// is out of bounds.
+    pub fn set<C>(&mut self, index: &FheUint<C, 8>, value: &T)
+    where
+        T: ArrayElement<C>,
+    {
+        T::set(&mut self.elements, index, value)
+    }
+}
+
+/// 2D grid of Fhe values with at most 256 columns and 256 rows that can be
+/// read and written at encrypted coordinates `(x, y)`, where `x` is the column
+/// and `y` is the row
+///
+/// Cells are stored in row major order, that is cell `(x, y)` is at index `y *
+/// width + x` of `cells`.
+#[derive(Clone)]
+pub struct FheGrid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T> FheGrid<T> {
+    /// Panics if `width` or `height` exceeds 256 or if `cells` does not have
+    /// `width * height` cells
+    pub fn new(width: usize, height: usize, cells: Vec<T>) -> FheGrid<T> {
+        assert!(
+            width <= 256 && height <= 256,
+            "Expected at most 256 columns and rows, got {width}x{height}"
+        );
+        assert!(
+            cells.len() == width * height,
+            "Expected {} cells, got {}",
+            width * height,
+            cells.len()
+        );
+        FheGrid {
+            width,
+            height,
+            cells,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Returns cells in row major order
+    pub fn cells(&self) -> &[T] {
+        &self.cells
+    }
+
+    pub fn into_cells(self) -> Vec<T> {
+        self.cells
+    }
+
+    /// Returns cell at plaintext coordinates `(x, y)`
+    pub fn cell(&self, x: usize, y: usize) -> &T {
+        assert!(x < self.width && y < self.height);
+        &self.cells[y * self.width + x]
+    }
+
+    /// Returns cell at `(x, y)`. Returns trivial encryption of 0 (or false) if
+    /// `(x, y)` is out of bounds.
+    ///
+    /// Cell is selected within every row with `x`, and then among selected
+    /// cells of all rows with `y`. Hence requires `width * height - 1` muxes
+    /// (plus at most `8 * (height + 1)` if width or height is not a power of
+    /// 2).
+    pub fn get<C>(&self, x: &FheUint<C, 8>, y: &FheUint<C, 8>) -> T
+    where
+        T: ArrayElement<C>,
+    {
+        // grid without columns has no cells, hence every `(x, y)` is out of
+        // bounds
+        let column = match self.width {
+            0 => vec![],
+            width => self
+                .cells
+                .chunks(width)
+                .map(|row| T::get(row, x))
+                .collect::<Vec<_>>(),
+        };
+        T::get(&column, y)
+    }
+
+    /// Sets cell at `(x, y)` to `value`. Grid is left unchanged if `(x, y)` is
+    /// out of bounds.
+    ///
+    /// Flags indicating column `x` and row `y` are evaluated once and
+    /// combined with an AND per cell. Hence requires `width * height` ANDs
+    /// and muxes (plus fewer than `2 * (width + height) + 16` ANDs).
+    pub fn set<C>(&mut self, x: &FheUint<C, 8>, y: &FheUint<C, 8>, value: &T)
+    where
+        T: ArrayElement<C>,
+    {
+        T::set_cell(&mut self.cells, self.width, self.height, x, y, value)
+    }
+
+    /// Returns cells at each of `coordinates`. Refer to `FheGrid::get`.
+    pub fn get_many<C>(&self, coordinates: &[(FheUint<C, 8>, FheUint<C, 8>)]) -> Vec<T>
+    where
+        T: ArrayElement<C>,
+    {
+        coordinates.iter().map(|(x, y)| self.get(x, y)).collect()
+    }
+
+    /// Sets cell at each `(x, y, value)` of `updates` in order. Hence if
+    /// multiple updates write to the same cell, the cell is set to value of
+    /// the last one. Refer to `FheGrid::set`.
+    pub fn set_many<C>(&mut self, updates: &[(FheUint<C, 8>, FheUint<C, 8>, T)])
+    where
+        T: ArrayElement<C>,
+    {
+        updates
+            .iter()
+            .for_each(|(x, y, value)| self.set(x, y, value));
+    }
+}
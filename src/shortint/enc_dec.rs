@@ -1,24 +1,129 @@
+use std::marker::PhantomData;
+
 use itertools::Itertools;
+use num_traits::{PrimInt, Signed, Unsigned, WrappingAdd, WrappingMul, WrappingSub};
 
 use crate::{
     bool::BoolEvaluator,
     random::{DefaultSecureRng, RandomFillUniformInModulus},
     utils::WithLocal,
-    Decryptor, Encryptor, KeySwitchWithId, Matrix, MatrixEntity, MatrixMut, MultiPartyDecryptor,
-    RowMut, SampleExtractor,
+    Decryptor, Encryptor, Error, KeySwitchWithId, Matrix, MatrixEntity, MatrixMut,
+    MultiPartyDecryptor, RowMut, SampleExtractor,
 };
 
-/// Fhe UInt8
+/// Plaintext integers that can be encrypted as Fhe integers
+pub trait FhePlaintext: PrimInt + WrappingAdd + WrappingSub + WrappingMul {
+    /// Returns bits of `self` in little endian
+    fn to_bits(&self) -> Vec<bool> {
+        let bits = Self::zero().count_zeros() as usize;
+        (0..bits)
+            .map(|i| ((*self >> i) & Self::one()) == Self::one())
+            .collect_vec()
+    }
+
+    /// Returns integer with bits set to little endian `bits`
+    ///
+    /// For signed integers `bits` are interpreted as two's complement
+    fn from_bits(bits: &[bool]) -> Self {
+        assert!(bits.len() == Self::zero().count_zeros() as usize);
+        let mut out = Self::zero();
+        bits.iter().enumerate().for_each(|(index, bit)| {
+            if *bit {
+                out = out | (Self::one() << index);
+            }
+        });
+        out
+    }
+}
+
+impl FhePlaintext for u8 {}
+impl FhePlaintext for u16 {}
+impl FhePlaintext for u32 {}
+impl FhePlaintext for u64 {}
+impl FhePlaintext for i8 {}
+impl FhePlaintext for i16 {}
+impl FhePlaintext for i32 {}
+impl FhePlaintext for i64 {}
+
+/// Unsigned plaintext integers with `BITS` bits that can be encrypted as Fhe
+/// unsigned integers with `BITS` bits
+pub trait FheUintPlaintext<const BITS: usize>: FhePlaintext + Unsigned {}
+
+impl FheUintPlaintext<8> for u8 {}
+impl FheUintPlaintext<16> for u16 {}
+impl FheUintPlaintext<32> for u32 {}
+impl FheUintPlaintext<64> for u64 {}
+
+/// Signed plaintext integers with `BITS` bits that can be encrypted as Fhe
+/// signed integers with `BITS` bits
+pub trait FheIntPlaintext<const BITS: usize>: FhePlaintext + Signed {}
+
+impl FheIntPlaintext<8> for i8 {}
+impl FheIntPlaintext<16> for i16 {}
+impl FheIntPlaintext<32> for i32 {}
+impl FheIntPlaintext<64> for i64 {}
+
+/// Bit width of Fhe integers
+pub struct Bits<const BITS: usize>;
+
+/// Maps bit width of Fhe integers to their plaintext integer types
+///
+/// `FheUintPlaintext` and `FheIntPlaintext` map plaintext type to bit width.
+/// This is required for the other direction, that is when plaintext type needs
+/// to be inferred from the ciphertext (for ex, during decryption).
+pub trait BitsPlaintext {
+    type Unsigned;
+    type Signed;
+}
+
+impl BitsPlaintext for Bits<8> {
+    type Unsigned = u8;
+    type Signed = i8;
+}
+impl BitsPlaintext for Bits<16> {
+    type Unsigned = u16;
+    type Signed = i16;
+}
+impl BitsPlaintext for Bits<32> {
+    type Unsigned = u32;
+    type Signed = i32;
+}
+impl BitsPlaintext for Bits<64> {
+    type Unsigned = u64;
+    type Signed = i64;
+}
+
+/// Fhe unsigned integer with `BITS` bits
 ///
 /// Note that `Self.data` stores encryptions of bits in little endian (i.e least
-/// signficant bit stored at 0th index and most signficant bit stores at 7th
-/// index)
+/// signficant bit stored at 0th index and most signficant bit stores at
+/// `BITS-1`th index)
+#[derive(Clone)]
+pub struct FheUint<C, const BITS: usize> {
+    pub(super) data: Vec<C>,
+}
+
+impl<C, const BITS: usize> FheUint<C, BITS> {
+    pub(super) fn data(&self) -> &[C] {
+        &self.data
+    }
+
+    pub(super) fn data_mut(&mut self) -> &mut [C] {
+        &mut self.data
+    }
+}
+
+/// Fhe signed integer with `BITS` bits
+///
+/// Integer is stored in two's complement. Like `FheUint`, `Self.data` stores
+/// encryptions of bits in little endian. Hence, sign bit is stored at
+/// `BITS-1`th index.
 #[derive(Clone)]
-pub struct FheUint8<C> {
+pub struct FheInt<C, const BITS: usize> {
     pub(super) data: Vec<C>,
 }
 
-impl<C> FheUint8<C> {
+impl<C, const BITS: usize> FheInt<C, BITS> {
     pub(super) fn data(&self) -> &[C] {
         &self.data
     }
@@ -28,51 +133,48 @@ impl<C> FheUint8<C> {
     }
 }
 
-/// Stores a batch of Fhe Uint8 ciphertext as collection of unseeded RLWE
-/// ciphertexts always encrypted under the ideal RLWE secret `s` of the MPC
-/// protocol
+/// Stores a batch of Fhe integers with plaintext type `T` as collection of
+/// unseeded RLWE ciphertexts always encrypted under the ideal RLWE secret `s`
+/// of the MPC protocol
 ///
-/// To extract Fhe Uint8 ciphertext at `index` call `self.extract(index)`
-pub struct BatchedFheUint8<C> {
+/// To extract Fhe integer at `index` call `self.extract(index)`. If `T` is
+/// unsigned, extracted integer is `FheUint`, otherwise it is `FheInt`.
+pub struct BatchedFheInteger<C, T> {
     /// Vector of RLWE ciphertexts `C`
     data: Vec<C>,
-    /// Count of FheUint8s packed in vector of RLWE ciphertexts
+    /// Count of Fhe integers packed in vector of RLWE ciphertexts
     count: usize,
+    _phantom: PhantomData<T>,
 }
 
-impl<K, C> Encryptor<[u8], BatchedFheUint8<C>> for K
+impl<K, C, T> Encryptor<[T], BatchedFheInteger<C, T>> for K
 where
     K: Encryptor<[bool], Vec<C>>,
+    T: FhePlaintext,
 {
-    /// Encrypt a batch of uint8s packed in vector of RLWE ciphertexts
+    /// Encrypt a batch of integers packed in vector of RLWE ciphertexts
     ///
-    /// Uint8s can be extracted from `BatchedFheUint8` with `SampleExtractor`
-    fn encrypt(&self, m: &[u8]) -> BatchedFheUint8<C> {
-        let bool_m = m
-            .iter()
-            .flat_map(|v| {
-                (0..8)
-                    .into_iter()
-                    .map(|i| ((*v >> i) & 1) == 1)
-                    .collect_vec()
-            })
-            .collect_vec();
+    /// Integers can be extracted from `BatchedFheInteger` with
+    /// `SampleExtractor`
+    fn encrypt(&self, m: &[T]) -> BatchedFheInteger<C, T> {
+        let bool_m = m.iter().flat_map(|v| v.to_bits()).collect_vec();
         let cts = K::encrypt(&self, &bool_m);
-        BatchedFheUint8 {
+        BatchedFheInteger {
             data: cts,
             count: m.len(),
+            _phantom: PhantomData,
         }
     }
 }
 
-impl<M: MatrixEntity + MatrixMut<MatElement = u64>> From<&SeededBatchedFheUint8<M::R, [u8; 32]>>
-    for BatchedFheUint8<M>
+impl<M: MatrixEntity + MatrixMut<MatElement = u64>, T>
+    From<&SeededBatchedFheInteger<M::R, [u8; 32], T>> for BatchedFheInteger<M, T>
 where
     <M as Matrix>::R: RowMut,
 {
-    /// Unseeds collection of seeded RLWE ciphertext in SeededBatchedFheUint8
+    /// Unseeds collection of seeded RLWE ciphertext in SeededBatchedFheInteger
     /// and returns as `Self`
-    fn from(value: &SeededBatchedFheUint8<M::R, [u8; 32]>) -> Self {
+    fn from(value: &SeededBatchedFheInteger<M::R, [u8; 32], T>) -> Self {
         BoolEvaluator::with_local(|e| {
             let parameters = e.parameters();
             let ring_size = parameters.rlwe_n().0;
@@ -97,84 +199,135 @@ where
             Self {
                 data: rlwes,
                 count: value.count,
+                _phantom: PhantomData,
             }
         })
     }
 }
 
-impl<C, R> SampleExtractor<FheUint8<R>> for BatchedFheUint8<C>
+impl<C, R, T, const BITS: usize> SampleExtractor<FheUint<R, BITS>> for BatchedFheInteger<C, T>
 where
     C: SampleExtractor<R>,
+    T: FheUintPlaintext<BITS>,
 {
-    /// Extract Fhe Uint8 ciphertext at `index`
+    /// Extract Fhe unsigned integer ciphertext at `index`
     ///
-    /// `Self` stores batch of Fhe uint8 ciphertext as vector of RLWE
-    /// ciphertexts. Since Fhe uint8 ciphertext is collection of 8 bool
-    /// ciphertexts, Fhe uint8 ciphertext at index `i` is stored in coefficients
-    /// `i*8...(i+1)*8`. To extract Fhe uint8 at index `i`, sample extract bool
-    /// ciphertext at indices `[i*8, ..., (i+1)*8)`
-    fn extract_at(&self, index: usize) -> FheUint8<R> {
-        assert!(index < self.count);
-        BoolEvaluator::with_local(|e| {
-            let ring_size = e.parameters().rlwe_n().0;
-
-            let start_index = index * 8;
-            let end_index = (index + 1) * 8;
-            let data = (start_index..end_index)
-                .map(|i| {
-                    let rlwe_index = i / ring_size;
-                    let coeff_index = i % ring_size;
-                    self.data[rlwe_index].extract_at(coeff_index)
-                })
-                .collect_vec();
-            FheUint8 { data }
-        })
+    /// `Self` stores batch of Fhe unsigned integer ciphertexts as vector of
+    /// RLWE ciphertexts. Since Fhe unsigned integer ciphertext is collection of
+    /// `BITS` bool ciphertexts, Fhe unsigned integer ciphertext at index `i` is
+    /// stored in coefficients `i*BITS...(i+1)*BITS`. To extract Fhe unsigned
+    /// integer at index `i`, sample extract bool ciphertext at indices
+    /// `[i*BITS, ..., (i+1)*BITS)`
+    fn extract_at(&self, index: usize) -> FheUint<R, BITS> {
+        FheUint {
+            data: extract_bits_at(&self.data, self.count, index, BITS),
+        }
+    }
+
+    /// Extracts all Fhe unsigned integers packed in vector of RLWE ciphertexts
+    /// of `Self`
+    fn extract_all(&self) -> Vec<FheUint<R, BITS>> {
+        (0..self.count)
+            .map(|index| self.extract_at(index))
+            .collect_vec()
+    }
+
+    /// Extracts first `how_many` Fhe unsigned integers packed in vector of
+    /// RLWE ciphertexts of `Self`
+    fn extract_many(&self, how_many: usize) -> Vec<FheUint<R, BITS>> {
+        (0..how_many)
+            .map(|index| self.extract_at(index))
+            .collect_vec()
+    }
+}
+
+impl<C, R, T, const BITS: usize> SampleExtractor<FheInt<R, BITS>> for BatchedFheInteger<C, T>
+where
+    C: SampleExtractor<R>,
+    T: FheIntPlaintext<BITS>,
+{
+    /// Extract Fhe signed integer ciphertext at `index`
+    ///
+    /// Fhe signed integers are packed in the same way as Fhe unsigned integers.
+    /// Fhe signed integer at index `i` is stored in coefficients
+    /// `i*BITS...(i+1)*BITS`
+    fn extract_at(&self, index: usize) -> FheInt<R, BITS> {
+        FheInt {
+            data: extract_bits_at(&self.data, self.count, index, BITS),
+        }
     }
 
-    /// Extracts all FheUint8s packed in vector of RLWE ciphertexts of `Self`
-    fn extract_all(&self) -> Vec<FheUint8<R>> {
+    /// Extracts all Fhe signed integers packed in vector of RLWE ciphertexts
+    /// of `Self`
+    fn extract_all(&self) -> Vec<FheInt<R, BITS>> {
         (0..self.count)
             .map(|index| self.extract_at(index))
             .collect_vec()
     }
 
-    /// Extracts first `how_many` FheUint8s packed in vector of RLWE
+    /// Extracts first `how_many` Fhe signed integers packed in vector of RLWE
     /// ciphertexts of `Self`
-    fn extract_many(&self, how_many: usize) -> Vec<FheUint8<R>> {
+    fn extract_many(&self, how_many: usize) -> Vec<FheInt<R, BITS>> {
         (0..how_many)
             .map(|index| self.extract_at(index))
             .collect_vec()
     }
 }
 
-/// Stores a batch of FheUint8s packed in a collection unseeded RLWE ciphertexts
+/// Sample extracts bool ciphertexts of the `index`th integer with `bits` bits
+/// from batch of `count` integers packed in vector of RLWE ciphertexts `rlwes`
+fn extract_bits_at<C: SampleExtractor<R>, R>(
+    rlwes: &[C],
+    count: usize,
+    index: usize,
+    bits: usize,
+) -> Vec<R> {
+    assert!(index < count);
+    BoolEvaluator::with_local(|e| {
+        let ring_size = e.parameters().rlwe_n().0;
+
+        let start_index = index * bits;
+        let end_index = (index + 1) * bits;
+        (start_index..end_index)
+            .map(|i| {
+                let rlwe_index = i / ring_size;
+                let coeff_index = i % ring_size;
+                rlwes[rlwe_index].extract_at(coeff_index)
+            })
+            .collect_vec()
+    })
+}
+
+/// Stores a batch of Fhe integers packed in a collection unseeded RLWE
+/// ciphertexts
 ///
 /// `Self` stores unseeded RLWE ciphertexts encrypted under user's RLWE secret
-/// `u_j` and is different from `BatchFheUint8` which stores collection of RLWE
-/// ciphertexts under ideal RLWE secret `s` of the (non-interactive/interactive)
-/// MPC protocol.
+/// `u_j` and is different from `BatchedFheInteger` which stores collection of
+/// RLWE ciphertexts under ideal RLWE secret `s` of the
+/// (non-interactive/interactive) MPC protocol.
 ///
-/// To extract FheUint8s from `Self`'s collection of RLWE ciphertexts, first
-/// switch `Self` to `BatchFheUint8` with `key_switch(user_id)` where `user_id`
-/// is user's id. This key switches collection of RLWE ciphertexts from
-/// user's RLWE secret `u_j` to ideal RLWE secret `s` of the MPC protocol. Then
-/// proceed to use `SampleExtract` on `BatchFheUint8` (for ex, call
-/// `extract_at(0)` to extract FheUint8 stored at index 0)
-pub struct NonInteractiveBatchedFheUint8<C> {
+/// To extract Fhe integers from `Self`'s collection of RLWE ciphertexts, first
+/// switch `Self` to `BatchedFheInteger` with `key_switch(user_id)` where
+/// `user_id` is user's id. This key switches collection of RLWE ciphertexts
+/// from user's RLWE secret `u_j` to ideal RLWE secret `s` of the MPC protocol.
+/// Then proceed to use `SampleExtract` on `BatchedFheInteger` (for ex, call
+/// `extract_at(0)` to extract Fhe integer stored at index 0)
+pub struct NonInteractiveBatchedFheInteger<C, T> {
     /// Vector of RLWE ciphertexts `C`
     data: Vec<C>,
-    /// Count of FheUint8s packed in vector of RLWE ciphertexts
+    /// Count of Fhe integers packed in vector of RLWE ciphertexts
     count: usize,
+    _phantom: PhantomData<T>,
 }
 
-impl<M: MatrixEntity + MatrixMut<MatElement = u64>> From<&SeededBatchedFheUint8<M::R, [u8; 32]>>
-    for NonInteractiveBatchedFheUint8<M>
+impl<M: MatrixEntity + MatrixMut<MatElement = u64>, T>
+    From<&SeededBatchedFheInteger<M::R, [u8; 32], T>> for NonInteractiveBatchedFheInteger<M, T>
 where
     <M as Matrix>::R: RowMut,
 {
-    /// Unseeds collection of seeded RLWE ciphertext in SeededBatchedFheUint8
+    /// Unseeds collection of seeded RLWE ciphertext in SeededBatchedFheInteger
     /// and returns as `Self`
-    fn from(value: &SeededBatchedFheUint8<M::R, [u8; 32]>) -> Self {
+    fn from(value: &SeededBatchedFheInteger<M::R, [u8; 32], T>) -> Self {
         BoolEvaluator::with_local(|e| {
             let parameters = e.parameters();
             let ring_size = parameters.rlwe_n().0;
@@ -199,12 +352,13 @@ where
             Self {
                 data: rlwes,
                 count: value.count,
+                _phantom: PhantomData,
             }
         })
     }
 }
 
-impl<C> KeySwitchWithId<BatchedFheUint8<C>> for NonInteractiveBatchedFheUint8<C>
+impl<C, T> KeySwitchWithId<BatchedFheInteger<C, T>> for NonInteractiveBatchedFheInteger<C, T>
 where
     C: KeySwitchWithId<C>,
 {
@@ -212,20 +366,34 @@ where
     /// RLWE secret `u_j` to ideal RLWE secret `s` of the MPC protocol.
     ///
     /// - user_id: user id of user `j`
-    fn key_switch(&self, user_id: usize) -> BatchedFheUint8<C> {
+    fn key_switch(&self, user_id: usize) -> BatchedFheInteger<C, T> {
         let data = self
             .data
             .iter()
             .map(|c| c.key_switch(user_id))
             .collect_vec();
-        BatchedFheUint8 {
+        BatchedFheInteger {
             data,
             count: self.count,
+            _phantom: PhantomData,
         }
     }
+
+    fn try_key_switch(&self, user_id: usize) -> Result<BatchedFheInteger<C, T>, Error> {
+        let data = self
+            .data
+            .iter()
+            .map(|c| c.try_key_switch(user_id))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(BatchedFheInteger {
+            data,
+            count: self.count,
+            _phantom: PhantomData,
+        })
+    }
 }
 
-pub struct SeededBatchedFheUint8<C, S> {
+pub struct SeededBatchedFheInteger<C, S, T> {
     /// Vector of Seeded RLWE ciphertexts `C`.
     ///
     /// If RLWE(m) = [a, b] s.t. m + e = b - as, `a` can be seeded and seeded
@@ -233,34 +401,34 @@ pub struct SeededBatchedFheUint8<C, S> {
     data: Vec<C>,
     /// Seed for the ciphertexts
     seed: S,
-    /// Count of FheUint8s packed in vector of RLWE ciphertexts
+    /// Count of Fhe integers packed in vector of RLWE ciphertexts
     count: usize,
+    _phantom: PhantomData<T>,
 }
 
-impl<K, C, S> Encryptor<[u8], SeededBatchedFheUint8<C, S>> for K
+impl<K, C, S, T> Encryptor<[T], SeededBatchedFheInteger<C, S, T>> for K
 where
     K: Encryptor<[bool], (Vec<C>, S)>,
+    T: FhePlaintext,
 {
-    /// Encrypt a slice of u8s of arbitray length packed into collection of
-    /// seeded RLWE ciphertexts and return `SeededBatchedFheUint8`
-    fn encrypt(&self, m: &[u8]) -> SeededBatchedFheUint8<C, S> {
-        // convert vector of u8s to vector bools
-        let bool_m = m
-            .iter()
-            .flat_map(|v| (0..8).into_iter().map(|i| (((*v) >> i) & 1) == 1))
-            .collect_vec();
+    /// Encrypt a slice of integers of arbitray length packed into collection
+    /// of seeded RLWE ciphertexts and return `SeededBatchedFheInteger`
+    fn encrypt(&self, m: &[T]) -> SeededBatchedFheInteger<C, S, T> {
+        // convert vector of integers to vector bools
+        let bool_m = m.iter().flat_map(|v| v.to_bits()).collect_vec();
         let (cts, seed) = K::encrypt(&self, &bool_m);
-        SeededBatchedFheUint8 {
+        SeededBatchedFheInteger {
             data: cts,
             seed,
             count: m.len(),
+            _phantom: PhantomData,
         }
     }
 }
 
-impl<C, S> SeededBatchedFheUint8<C, S> {
+impl<C, S, T> SeededBatchedFheInteger<C, S, T> {
     /// Unseed collection of seeded RLWE ciphertexts of `Self` and returns
-    /// `NonInteractiveBatchedFheUint8` with collection of unseeded RLWE
+    /// `NonInteractiveBatchedFheInteger` with collection of unseeded RLWE
     /// ciphertexts.
     ///
     /// In non-interactive MPC setting, RLWE ciphertexts are encrypted under
@@ -268,43 +436,46 @@ impl<C, S> SeededBatchedFheUint8<C, S> {
     /// ideal RLWE secret `s` of the MPC protocol before use.
     ///
     /// Note that we don't provide `unseed` API from `Self` to
-    /// `BatchedFheUint8`. This is because:
+    /// `BatchedFheInteger`. This is because:
     ///
     /// - In non-interactive setting (1) client encrypts private inputs using
-    ///   their secret `u_j` as `SeededBatchedFheUint8` and sends it to the
-    ///   server. (2) Server unseeds `SeededBatchedFheUint8` into
-    ///   `NonInteractiveBatchedFheUint8` indicating that private inputs are
+    ///   their secret `u_j` as `SeededBatchedFheInteger` and sends it to the
+    ///   server. (2) Server unseeds `SeededBatchedFheInteger` into
+    ///   `NonInteractiveBatchedFheInteger` indicating that private inputs are
     ///   still encrypted under user's RLWE secret `u_j`. (3) Server key
-    ///   switches `NonInteractiveBatchedFheUint8` from user's RLWE secret `u_j`
-    ///   to ideal RLWE secret `s` and outputs `BatchedFheUint8`. (4)
-    ///   `BatchedFheUint8` always stores RLWE secret under ideal RLWE secret of
-    ///   the protocol. Hence, it is safe to extract FheUint8s. Server proceeds
-    ///   to extract necessary FheUint8s.
+    ///   switches `NonInteractiveBatchedFheInteger` from user's RLWE secret
+    ///   `u_j` to ideal RLWE secret `s` and outputs `BatchedFheInteger`. (4)
+    ///   `BatchedFheInteger` always stores RLWE secret under ideal RLWE secret
+    ///   of the protocol. Hence, it is safe to extract Fhe integers. Server
+    ///   proceeds to extract necessary Fhe integers.
     ///
     /// - In interactive setting (1) client always encrypts private inputs using
     ///   public key corresponding to ideal RLWE secret `s` of the protocol and
-    ///   produces `BatchedFheUint8`. (2) Given `BatchedFheUint8` stores
+    ///   produces `BatchedFheInteger`. (2) Given `BatchedFheInteger` stores
     ///   collection of RLWE ciphertext under ideal RLWE secret `s`, server can
-    ///   directly extract necessary FheUint8s to use.
+    ///   directly extract necessary Fhe integers to use.
     ///
-    /// Thus, there's no need to go directly from `Self` to `BatchedFheUint8`.
-    pub fn unseed<M>(&self) -> NonInteractiveBatchedFheUint8<M>
+    /// Thus, there's no need to go directly from `Self` to `BatchedFheInteger`.
+    pub fn unseed<M>(&self) -> NonInteractiveBatchedFheInteger<M, T>
     where
-        NonInteractiveBatchedFheUint8<M>: for<'a> From<&'a SeededBatchedFheUint8<C, S>>,
+        NonInteractiveBatchedFheInteger<M, T>: for<'a> From<&'a SeededBatchedFheInteger<C, S, T>>,
         M: Matrix<R = C>,
     {
-        NonInteractiveBatchedFheUint8::from(self)
+        NonInteractiveBatchedFheInteger::from(self)
     }
 }
 
-impl<C, K> MultiPartyDecryptor<u8, FheUint8<C>> for K
+impl<C, K, const BITS: usize>
+    MultiPartyDecryptor<<Bits<BITS> as BitsPlaintext>::Unsigned, FheUint<C, BITS>> for K
 where
     K: MultiPartyDecryptor<bool, C>,
     <Self as MultiPartyDecryptor<bool, C>>::DecryptionShare: Clone,
+    Bits<BITS>: BitsPlaintext,
+    <Bits<BITS> as BitsPlaintext>::Unsigned: FheUintPlaintext<BITS>,
 {
     type DecryptionShare = Vec<<Self as MultiPartyDecryptor<bool, C>>::DecryptionShare>;
-    fn gen_decryption_share(&self, c: &FheUint8<C>) -> Self::DecryptionShare {
-        assert!(c.data().len() == 8);
+    fn gen_decryption_share(&self, c: &FheUint<C, BITS>) -> Self::DecryptionShare {
+        assert!(c.data().len() == BITS);
         c.data()
             .iter()
             .map(|bit_c| {
@@ -315,56 +486,313 @@ where
             .collect_vec()
     }
 
-    fn aggregate_decryption_shares(&self, c: &FheUint8<C>, shares: &[Self::DecryptionShare]) -> u8 {
-        let mut out = 0u8;
+    fn try_gen_decryption_share(
+        &self,
+        c: &FheUint<C, BITS>,
+    ) -> Result<Self::DecryptionShare, Error> {
+        try_gen_bit_decryption_shares::<_, _, BITS>(self, c.data())
+    }
+
+    fn aggregate_decryption_shares(
+        &self,
+        c: &FheUint<C, BITS>,
+        shares: &[Self::DecryptionShare],
+    ) -> <Bits<BITS> as BitsPlaintext>::Unsigned {
+        let bits = (0..BITS)
+            .map(|i| {
+                // Collect bit i^th decryption share of each party
+                let bit_i_decryption_shares = shares.iter().map(|s| s[i].clone()).collect_vec();
+                MultiPartyDecryptor::<bool, C>::aggregate_decryption_shares(
+                    self,
+                    &c.data()[i],
+                    &bit_i_decryption_shares,
+                )
+            })
+            .collect_vec();
 
-        (0..8).into_iter().for_each(|i| {
+        FhePlaintext::from_bits(&bits)
+    }
+
+    fn try_aggregate_decryption_shares(
+        &self,
+        c: &FheUint<C, BITS>,
+        shares: &[Self::DecryptionShare],
+    ) -> Result<<Bits<BITS> as BitsPlaintext>::Unsigned, Error> {
+        Ok(FhePlaintext::from_bits(
+            &try_aggregate_bit_decryption_shares::<_, _, BITS>(self, c.data(), shares)?,
+        ))
+    }
+}
+
+/// Returns decryption shares of bit ciphertexts `bits`. Returns `Error` if
+/// there are not `BITS` bit ciphertexts, or if share of any bit cannot be
+/// generated.
+fn try_gen_bit_decryption_shares<C, K, const BITS: usize>(
+    key: &K,
+    bits: &[C],
+) -> Result<Vec<K::DecryptionShare>, Error>
+where
+    K: MultiPartyDecryptor<bool, C>,
+{
+    if bits.len() != BITS {
+        return Err(Error::InvalidCiphertext);
+    }
+    bits.iter()
+        .map(|bit_c| key.try_gen_decryption_share(bit_c))
+        .collect()
+}
+
+/// Returns bits decrypted from decryption shares `shares` of each party for
+/// bit ciphertexts `bits`. Returns `Error` if there are not `BITS` bit
+/// ciphertexts, or `BITS` decryption shares per party, or if any bit fails to
+/// decrypt.
+fn try_aggregate_bit_decryption_shares<C, K, const BITS: usize>(
+    key: &K,
+    bits: &[C],
+    shares: &[Vec<K::DecryptionShare>],
+) -> Result<Vec<bool>, Error>
+where
+    K: MultiPartyDecryptor<bool, C>,
+    K::DecryptionShare: Clone,
+{
+    if bits.len() != BITS {
+        return Err(Error::InvalidCiphertext);
+    }
+    if let Some(index) = shares.iter().position(|s| s.len() != BITS) {
+        return Err(Error::InvalidDecryptionShare { index });
+    }
+    bits.iter()
+        .enumerate()
+        .map(|(i, bit_c)| {
             // Collect bit i^th decryption share of each party
             let bit_i_decryption_shares = shares.iter().map(|s| s[i].clone()).collect_vec();
-            let bit_i = MultiPartyDecryptor::<bool, C>::aggregate_decryption_shares(
-                self,
-                &c.data()[i],
-                &bit_i_decryption_shares,
-            );
-
-            if bit_i {
-                out += 1 << i;
-            }
-        });
+            key.try_aggregate_decryption_shares(bit_c, &bit_i_decryption_shares)
+        })
+        .collect()
+}
 
-        out
+impl<C, K, T, const BITS: usize> Encryptor<T, FheUint<C, BITS>> for K
+where
+    K: Encryptor<bool, C>,
+    T: FheUintPlaintext<BITS>,
+{
+    fn encrypt(&self, m: &T) -> FheUint<C, BITS> {
+        let cts = m
+            .to_bits()
+            .iter()
+            .map(|bit| K::encrypt(self, bit))
+            .collect_vec();
+        FheUint { data: cts }
     }
 }
 
-impl<C, K> Encryptor<u8, FheUint8<C>> for K
+impl<K, C, const BITS: usize> Decryptor<<Bits<BITS> as BitsPlaintext>::Unsigned, FheUint<C, BITS>>
+    for K
 where
-    K: Encryptor<bool, C>,
+    K: Decryptor<bool, C>,
+    Bits<BITS>: BitsPlaintext,
+    <Bits<BITS> as BitsPlaintext>::Unsigned: FheUintPlaintext<BITS>,
+{
+    fn decrypt(&self, c: &FheUint<C, BITS>) -> <Bits<BITS> as BitsPlaintext>::Unsigned {
+        assert!(c.data.len() == BITS);
+        let bits = c
+            .data()
+            .iter()
+            .map(|bit_c| K::decrypt(self, bit_c))
+            .collect_vec();
+        FhePlaintext::from_bits(&bits)
+    }
+}
+
+impl<C, K, const BITS: usize>
+    MultiPartyDecryptor<<Bits<BITS> as BitsPlaintext>::Signed, FheInt<C, BITS>> for K
+where
+    K: MultiPartyDecryptor<bool, C>,
+    <Self as MultiPartyDecryptor<bool, C>>::DecryptionShare: Clone,
+    Bits<BITS>: BitsPlaintext,
+    <Bits<BITS> as BitsPlaintext>::Signed: FheIntPlaintext<BITS>,
 {
-    fn encrypt(&self, m: &u8) -> FheUint8<C> {
-        let cts = (0..8)
-            .into_iter()
+    type DecryptionShare = Vec<<Self as MultiPartyDecryptor<bool, C>>::DecryptionShare>;
+    fn gen_decryption_share(&self, c: &FheInt<C, BITS>) -> Self::DecryptionShare {
+        assert!(c.data().len() == BITS);
+        c.data()
+            .iter()
+            .map(|bit_c| MultiPartyDecryptor::<bool, C>::gen_decryption_share(self, bit_c))
+            .collect_vec()
+    }
+
+    fn try_gen_decryption_share(
+        &self,
+        c: &FheInt<C, BITS>,
+    ) -> Result<Self::DecryptionShare, Error> {
+        try_gen_bit_decryption_shares::<_, _, BITS>(self, c.data())
+    }
+
+    fn aggregate_decryption_shares(
+        &self,
+        c: &FheInt<C, BITS>,
+        shares: &[Self::DecryptionShare],
+    ) -> <Bits<BITS> as BitsPlaintext>::Signed {
+        let bits = (0..BITS)
             .map(|i| {
-                let bit = ((m >> i) & 1) == 1;
-                K::encrypt(self, &bit)
+                // Collect bit i^th decryption share of each party
+                let bit_i_decryption_shares = shares.iter().map(|s| s[i].clone()).collect_vec();
+                MultiPartyDecryptor::<bool, C>::aggregate_decryption_shares(
+                    self,
+                    &c.data()[i],
+                    &bit_i_decryption_shares,
+                )
             })
             .collect_vec();
-        FheUint8 { data: cts }
+
+        FhePlaintext::from_bits(&bits)
+    }
+
+    fn try_aggregate_decryption_shares(
+        &self,
+        c: &FheInt<C, BITS>,
+        shares: &[Self::DecryptionShare],
+    ) -> Result<<Bits<BITS> as BitsPlaintext>::Signed, Error> {
+        Ok(FhePlaintext::from_bits(
+            &try_aggregate_bit_decryption_shares::<_, _, BITS>(self, c.data(), shares)?,
+        ))
     }
 }
 
-impl<K, C> Decryptor<u8, FheUint8<C>> for K
+impl<C, K, T, const BITS: usize> Encryptor<T, FheInt<C, BITS>> for K
+where
+    K: Encryptor<bool, C>,
+    T: FheIntPlaintext<BITS>,
+{
+    fn encrypt(&self, m: &T) -> FheInt<C, BITS> {
+        let cts = m
+            .to_bits()
+            .iter()
+            .map(|bit| K::encrypt(self, bit))
+            .collect_vec();
+        FheInt { data: cts }
+    }
+}
+
+impl<K, C, const BITS: usize> Decryptor<<Bits<BITS> as BitsPlaintext>::Signed, FheInt<C, BITS>>
+    for K
 where
     K: Decryptor<bool, C>,
+    Bits<BITS>: BitsPlaintext,
+    <Bits<BITS> as BitsPlaintext>::Signed: FheIntPlaintext<BITS>,
 {
-    fn decrypt(&self, c: &FheUint8<C>) -> u8 {
-        assert!(c.data.len() == 8);
-        let mut out = 0u8;
-        c.data().iter().enumerate().for_each(|(index, bit_c)| {
-            let bool = K::decrypt(self, bit_c);
-            if bool {
-                out += 1 << index;
+    fn decrypt(&self, c: &FheInt<C, BITS>) -> <Bits<BITS> as BitsPlaintext>::Signed {
+        assert!(c.data.len() == BITS);
+        let bits = c
+            .data()
+            .iter()
+            .map(|bit_c| K::decrypt(self, bit_c))
+            .collect_vec();
+        FhePlaintext::from_bits(&bits)
+    }
+}
+
+mod impl_serialization {
+    use crate::serialization::{
+        bits_of_largest, impl_binary_encoding, tag, Decode, DecodeError, Encode, Reader, Writer,
+    };
+
+    use super::*;
+
+    /// Encodes ciphertexts of bits of Fhe integers
+    fn encode_bits(w: &mut Writer, data: &[Vec<u64>]) {
+        w.seq(data, |w, c| w.packed(c, bits_of_largest(c)));
+    }
+
+    fn decode_bits<const BITS: usize>(r: &mut Reader) -> Result<Vec<Vec<u64>>, DecodeError> {
+        let data = r.seq(|r| r.packed())?;
+        if data.len() != BITS {
+            return Err(DecodeError::Invalid(format!(
+                "expected {BITS} bits, found {}",
+                data.len()
+            )));
+        }
+        if data
+            .iter()
+            .any(|c| c.is_empty() || c.len() != data[0].len())
+        {
+            return Err(DecodeError::Invalid("invalid ciphertext".to_string()));
+        }
+        Ok(data)
+    }
+
+    impl<const BITS: usize> Encode for FheUint<Vec<u64>, BITS> {
+        fn encode(&self, w: &mut Writer) {
+            encode_bits(w, &self.data);
+        }
+    }
+
+    impl<const BITS: usize> Decode for FheUint<Vec<u64>, BITS> {
+        fn decode(r: &mut Reader) -> Result<Self, DecodeError> {
+            Ok(FheUint {
+                data: decode_bits::<BITS>(r)?,
+            })
+        }
+    }
+
+    impl_binary_encoding!(tag::FHE_UINT, impl[const BITS: usize] FheUint<Vec<u64>, BITS>);
+
+    impl<const BITS: usize> Encode for FheInt<Vec<u64>, BITS> {
+        fn encode(&self, w: &mut Writer) {
+            encode_bits(w, &self.data);
+        }
+    }
+
+    impl<const BITS: usize> Decode for FheInt<Vec<u64>, BITS> {
+        fn decode(r: &mut Reader) -> Result<Self, DecodeError> {
+            Ok(FheInt {
+                data: decode_bits::<BITS>(r)?,
+            })
+        }
+    }
+
+    impl_binary_encoding!(tag::FHE_INT, impl[const BITS: usize] FheInt<Vec<u64>, BITS>);
+
+    /// Encodes bit width of `T` along with the ciphertexts, so that a batch is
+    /// never decoded as a batch of integers of different type
+    impl<T: FhePlaintext> Encode for SeededBatchedFheInteger<Vec<u64>, [u8; 32], T> {
+        fn encode(&self, w: &mut Writer) {
+            w.u8(T::zero().count_zeros() as u8);
+            w.bytes(&self.seed);
+            w.usize(self.count);
+            encode_bits(w, &self.data);
+        }
+    }
+
+    impl<T: FhePlaintext> Decode for SeededBatchedFheInteger<Vec<u64>, [u8; 32], T> {
+        fn decode(r: &mut Reader) -> Result<Self, DecodeError> {
+            let bits = T::zero().count_zeros() as usize;
+            let found = r.u8()? as usize;
+            if found != bits {
+                return Err(DecodeError::Invalid(format!(
+                    "expected batch of {bits} bit integers, found {found} bit integers"
+                )));
             }
-        });
-        out
+            let seed = r.take(32)?.try_into().unwrap();
+            let count = r.usize()?;
+            let data = r.seq(|r| r.packed())?;
+            let slots = data.iter().map(|c| c.len()).sum::<usize>();
+            if count.checked_mul(bits).is_none_or(|b| b > slots) {
+                return Err(DecodeError::Invalid(format!(
+                    "{count} integers do not fit in the ciphertexts"
+                )));
+            }
+            Ok(SeededBatchedFheInteger {
+                data,
+                seed,
+                count,
+                _phantom: PhantomData,
+            })
+        }
     }
+
+    impl_binary_encoding!(
+        tag::SEEDED_BATCHED_FHE_INTEGER,
+        impl[T: FhePlaintext] SeededBatchedFheInteger<Vec<u64>, [u8; 32], T>
+    );
 }
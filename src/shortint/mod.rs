@@ -1,58 +1,234 @@
+mod array;
 mod enc_dec;
-mod ops;
+pub(crate) mod ops;
+mod traits;
 
-pub type FheUint8 = enc_dec::FheUint8<Vec<u64>>;
+pub use array::{ArrayElement, FheArray, FheGrid};
+pub use enc_dec::{Bits, BitsPlaintext, FheIntPlaintext, FhePlaintext, FheUintPlaintext};
+pub use traits::{Compare, FheBoolOps, FheIntegerOps, Select};
 
-use std::cell::RefCell;
+/// Fhe unsigned integer with `BITS` bits
+pub type FheUint<const BITS: usize> = enc_dec::FheUint<Vec<u64>, BITS>;
+pub type FheUint8 = FheUint<8>;
+pub type FheUint16 = FheUint<16>;
+pub type FheUint32 = FheUint<32>;
+pub type FheUint64 = FheUint<64>;
 
-use crate::bool::{BoolEvaluator, BooleanGates, FheBool, RuntimeServerKey};
+/// Fhe signed integer with `BITS` bits
+pub type FheInt<const BITS: usize> = enc_dec::FheInt<Vec<u64>, BITS>;
+pub type FheInt8 = FheInt<8>;
+pub type FheInt16 = FheInt<16>;
+pub type FheInt32 = FheInt<32>;
+pub type FheInt64 = FheInt<64>;
+
+/// Plaintext simulation of `FheUint<BITS>` evaluated with `MockBoolEvaluator`
+pub type MockFheUint<const BITS: usize> = enc_dec::FheUint<bool, BITS>;
+pub type MockFheUint8 = MockFheUint<8>;
+pub type MockFheUint16 = MockFheUint<16>;
+pub type MockFheUint32 = MockFheUint<32>;
+pub type MockFheUint64 = MockFheUint<64>;
+
+/// Plaintext simulation of `FheInt<BITS>` evaluated with `MockBoolEvaluator`
+pub type MockFheInt<const BITS: usize> = enc_dec::FheInt<bool, BITS>;
+pub type MockFheInt8 = MockFheInt<8>;
+pub type MockFheInt16 = MockFheInt<16>;
+pub type MockFheInt32 = MockFheInt<32>;
+pub type MockFheInt64 = MockFheInt<64>;
+
+/// `FheUint<BITS>` recorded in a circuit instead of evaluated. Refer to
+/// `Circuit::record`.
+pub type LazyFheUint<const BITS: usize> = enc_dec::FheUint<Wire, BITS>;
+pub type LazyFheUint8 = LazyFheUint<8>;
+pub type LazyFheUint16 = LazyFheUint<16>;
+pub type LazyFheUint32 = LazyFheUint<32>;
+pub type LazyFheUint64 = LazyFheUint<64>;
+
+/// `FheInt<BITS>` recorded in a circuit instead of evaluated. Refer to
+/// `Circuit::record`.
+pub type LazyFheInt<const BITS: usize> = enc_dec::FheInt<Wire, BITS>;
+pub type LazyFheInt8 = LazyFheInt<8>;
+pub type LazyFheInt16 = LazyFheInt<16>;
+pub type LazyFheInt32 = LazyFheInt<32>;
+pub type LazyFheInt64 = LazyFheInt<64>;
+
+use std::sync::Mutex;
+
+use crate::{
+    bool::{
+        impl_bool_frontend, with_error_flags, Backend, BooleanGates, FheBool, LazyFheBool,
+        MockFheBool,
+    },
+    circuit::Wire,
+};
+
+/// Error flags of a session, shared by all threads that entered the session
+///
+/// Threads that have not entered any session use error flags of their own.
+#[derive(Default)]
+pub(crate) struct ErrorFlags {
+    div_zero: Mutex<Option<FheBool>>,
+    mock_div_zero: Mutex<Option<MockFheBool>>,
+    lazy_div_zero: Mutex<Option<LazyFheBool>>,
+}
 
 thread_local! {
-     static DIV_ZERO_ERROR: RefCell<Option<FheBool>> = RefCell::new(None);
+    /// Error flags of the current thread used outside sessions
+    pub(crate) static ERROR_FLAGS: ErrorFlags = ErrorFlags::default();
 }
 
 /// Returns Boolean ciphertext indicating whether last division was attempeted
 /// with decnomiantor set to 0.
 pub fn div_zero_error_flag() -> Option<FheBool> {
-    DIV_ZERO_ERROR.with_borrow(|c| c.clone())
+    with_error_flags(|f| f.div_zero.lock().unwrap().clone())
+}
+
+/// Returns Div by Zero error flag set by divisions of Mock types. Refer to
+/// `div_zero_error_flag`.
+pub fn mock_div_zero_error_flag() -> Option<MockFheBool> {
+    with_error_flags(|f| f.mock_div_zero.lock().unwrap().clone())
+}
+
+/// Returns Div by Zero error flag set by divisions of Lazy types in the
+/// circuit being recorded. Refer to `div_zero_error_flag`.
+///
+/// Flag is a wire of the circuit, hence it must be added to outputs of the
+/// circuit to be evaluated.
+pub fn lazy_div_zero_error_flag() -> Option<LazyFheBool> {
+    with_error_flags(|f| f.lazy_div_zero.lock().unwrap().clone())
 }
 
 /// Reset all error flags
 ///
-/// Error flags are thread local. When running multiple circuits in sequence
-/// within a single program you must prevent error flags set during the
-/// execution of previous circuit to affect error flags set during execution of
-/// the next circuit. To do so call `reset_error_flags()`.
+/// Error flags belong to the session entered on the current thread and are
+/// shared by all threads that entered the session (outside a session, they
+/// are thread local). When running multiple circuits in sequence within a
+/// single session you must prevent error flags set during the execution of
+/// previous circuit to affect error flags set during execution of the next
+/// circuit. To do so call `reset_error_flags()`.
 pub fn reset_error_flags() {
-    DIV_ZERO_ERROR.with_borrow_mut(|c| *c = None);
+    with_error_flags(|f| {
+        *f.div_zero.lock().unwrap() = None;
+        *f.mock_div_zero.lock().unwrap() = None;
+        *f.lazy_div_zero.lock().unwrap() = None;
+    })
+}
+
+/// Div by Zero error flag of backend `Self` in the active session
+pub(crate) trait DivZeroErrorFlag: Backend {
+    fn with_div_zero_error_flag<F: FnOnce(&mut Option<impl_bool_frontend::FheBool<Self>>)>(func: F);
+}
+
+impl DivZeroErrorFlag for Vec<u64> {
+    fn with_div_zero_error_flag<F: FnOnce(&mut Option<FheBool>)>(func: F) {
+        with_error_flags(|f| func(&mut f.div_zero.lock().unwrap()))
+    }
+}
+
+impl DivZeroErrorFlag for bool {
+    fn with_div_zero_error_flag<F: FnOnce(&mut Option<MockFheBool>)>(func: F) {
+        with_error_flags(|f| func(&mut f.mock_div_zero.lock().unwrap()))
+    }
+}
+
+impl DivZeroErrorFlag for Wire {
+    fn with_div_zero_error_flag<F: FnOnce(&mut Option<LazyFheBool>)>(func: F) {
+        with_error_flags(|f| func(&mut f.lazy_div_zero.lock().unwrap()))
+    }
+}
+
+/// Takes Div by Zero error flag of backend `C` set in the active session
+pub(crate) fn take_div_zero_error_flag<C: DivZeroErrorFlag>(
+) -> Option<impl_bool_frontend::FheBool<C>> {
+    let mut flag = None;
+    C::with_div_zero_error_flag(|c| flag = c.take());
+    flag
+}
+
+/// Sets Div by Zero error flag of backend `C` in the active session to `flag`
+/// OR the current flag
+pub(crate) fn merge_div_zero_error_flag<C: DivZeroErrorFlag>(flag: impl_bool_frontend::FheBool<C>) {
+    // flag stays locked while OR is evaluated, hence concurrent divisions in the
+    // session are not lost
+    C::with_div_zero_error_flag(|before| match before {
+        Some(before) => {
+            C::with_evaluator(|e, key| e.or_inplace(before.data_mut(), flag.data(), key))
+        }
+        None => *before = Some(flag),
+    })
 }
 
 mod frontend {
     use super::ops::{
-        arbitrary_bit_adder, arbitrary_bit_division_for_quotient_and_rem, arbitrary_bit_subtractor,
-        eight_bit_mul, is_zero,
+        arbitrary_bit_adder, arbitrary_bit_division_for_quotient_and_rem, arbitrary_bit_mul,
+        arbitrary_bit_subtractor, is_zero,
     };
-    use crate::utils::{Global, WithLocal};
+    use super::{enc_dec::FheInt, enc_dec::FheUint};
+    use crate::bool::impl_bool_frontend::FheBool;
+    use crate::utils::WithLocal;
 
     use super::*;
 
     /// Set Div by Zero flag after each divison. Div by zero flag is set to true
     /// if either 1 of the division executed in circuit evaluation has
     /// denominator set to 0.
-    fn set_div_by_zero_flag(denominator: &FheUint8) {
-        {
-            BoolEvaluator::with_local_mut(|e| {
-                let key = RuntimeServerKey::global();
-                let is_zero = is_zero(e, denominator.data(), key);
-                DIV_ZERO_ERROR.with_borrow_mut(|before_is_zero| {
-                    if before_is_zero.is_none() {
-                        *before_is_zero = Some(FheBool { data: is_zero });
-                    } else {
-                        e.or_inplace(before_is_zero.as_mut().unwrap().data_mut(), &is_zero, key);
-                    }
-                });
+    fn set_div_by_zero_flag<C: DivZeroErrorFlag>(denominator: &[C]) {
+        let is_zero = C::with_evaluator(|e, key| is_zero(e, denominator, key));
+        merge_div_zero_error_flag(FheBool { data: is_zero });
+    }
+
+    impl<C: Backend, const BITS: usize> FheUint<C, BITS> {
+        /// Returns trivial encryption of `m`.
+        ///
+        /// Trivial encryption is noiseless and does not require any secret key.
+        /// Hence it can be created by the server to use constants in the
+        /// circuit. Note that trivial encryption does not hide `m`.
+        pub fn trivial<T: FheUintPlaintext<BITS>>(m: T) -> FheUint<C, BITS> {
+            C::Evaluator::with_local(|e| FheUint {
+                data: m.to_bits().into_iter().map(|v| e.trivial(v)).collect(),
             })
         }
+
+        /// Returns bits of `self` in little endian
+        pub fn bits(&self) -> Vec<FheBool<C>> {
+            self.data()
+                .iter()
+                .map(|c| FheBool { data: c.clone() })
+                .collect()
+        }
+
+        /// Returns integer with bits set to little endian `bits`
+        pub fn from_bits(bits: &[FheBool<C>]) -> FheUint<C, BITS> {
+            assert!(bits.len() == BITS, "Expected {BITS} bits");
+            FheUint {
+                data: bits.iter().map(|b| b.data().clone()).collect(),
+            }
+        }
+    }
+
+    impl<C: Backend, const BITS: usize> FheInt<C, BITS> {
+        /// Returns trivial encryption of `m`. Refer to `FheUint::trivial`.
+        pub fn trivial<T: FheIntPlaintext<BITS>>(m: T) -> FheInt<C, BITS> {
+            C::Evaluator::with_local(|e| FheInt {
+                data: m.to_bits().into_iter().map(|v| e.trivial(v)).collect(),
+            })
+        }
+
+        /// Returns bits of `self` in little endian
+        pub fn bits(&self) -> Vec<FheBool<C>> {
+            self.data()
+                .iter()
+                .map(|c| FheBool { data: c.clone() })
+                .collect()
+        }
+
+        /// Returns integer with bits set to little endian `bits` (in two's
+        /// complement)
+        pub fn from_bits(bits: &[FheBool<C>]) -> FheInt<C, BITS> {
+            assert!(bits.len() == BITS, "Expected {BITS} bits");
+            FheInt {
+                data: bits.iter().map(|b| b.data().clone()).collect(),
+            }
+        }
     }
 
     mod arithetic {
@@ -60,90 +236,83 @@ mod frontend {
         use super::*;
         use std::ops::{Add, AddAssign, Div, Mul, Rem, Sub};
 
-        impl AddAssign<&FheUint8> for FheUint8 {
-            fn add_assign(&mut self, rhs: &FheUint8) {
-                BoolEvaluator::with_local_mut_mut(&mut |e| {
-                    let key = RuntimeServerKey::global();
+        impl<C: Backend, const BITS: usize> AddAssign<&FheUint<C, BITS>> for FheUint<C, BITS> {
+            fn add_assign(&mut self, rhs: &FheUint<C, BITS>) {
+                C::with_evaluator(|e, key| {
                     arbitrary_bit_adder(e, self.data_mut(), rhs.data(), false, key);
                 });
             }
         }
 
-        impl Add<&FheUint8> for &FheUint8 {
-            type Output = FheUint8;
-            fn add(self, rhs: &FheUint8) -> Self::Output {
+        impl<C: Backend, const BITS: usize> Add<&FheUint<C, BITS>> for &FheUint<C, BITS> {
+            type Output = FheUint<C, BITS>;
+            fn add(self, rhs: &FheUint<C, BITS>) -> Self::Output {
                 let mut a = self.clone();
                 a += rhs;
                 a
             }
         }
 
-        impl Sub<&FheUint8> for &FheUint8 {
-            type Output = FheUint8;
-            fn sub(self, rhs: &FheUint8) -> Self::Output {
-                BoolEvaluator::with_local_mut(|e| {
-                    let key = RuntimeServerKey::global();
+        impl<C: Backend, const BITS: usize> Sub<&FheUint<C, BITS>> for &FheUint<C, BITS> {
+            type Output = FheUint<C, BITS>;
+            fn sub(self, rhs: &FheUint<C, BITS>) -> Self::Output {
+                C::with_evaluator(|e, key| {
                     let (out, _, _) = arbitrary_bit_subtractor(e, self.data(), rhs.data(), key);
-                    FheUint8 { data: out }
+                    FheUint { data: out }
                 })
             }
         }
 
-        impl Mul<&FheUint8> for &FheUint8 {
-            type Output = FheUint8;
-            fn mul(self, rhs: &FheUint8) -> Self::Output {
-                BoolEvaluator::with_local_mut(|e| {
-                    let key = RuntimeServerKey::global();
-                    let out = eight_bit_mul(e, self.data(), rhs.data(), key);
-                    FheUint8 { data: out }
+        impl<C: Backend, const BITS: usize> Mul<&FheUint<C, BITS>> for &FheUint<C, BITS> {
+            type Output = FheUint<C, BITS>;
+            fn mul(self, rhs: &FheUint<C, BITS>) -> Self::Output {
+                C::with_evaluator(|e, key| {
+                    let out = arbitrary_bit_mul(e, self.data(), rhs.data(), key);
+                    FheUint { data: out }
                 })
             }
         }
 
-        impl Div<&FheUint8> for &FheUint8 {
-            type Output = FheUint8;
-            fn div(self, rhs: &FheUint8) -> Self::Output {
+        impl<C: DivZeroErrorFlag, const BITS: usize> Div<&FheUint<C, BITS>> for &FheUint<C, BITS> {
+            type Output = FheUint<C, BITS>;
+            fn div(self, rhs: &FheUint<C, BITS>) -> Self::Output {
                 // set div by 0 error flag
-                set_div_by_zero_flag(rhs);
-
-                BoolEvaluator::with_local_mut(|e| {
-                    let key = RuntimeServerKey::global();
+                set_div_by_zero_flag(rhs.data());
 
+                C::with_evaluator(|e, key| {
                     let (quotient, _) = arbitrary_bit_division_for_quotient_and_rem(
                         e,
                         self.data(),
                         rhs.data(),
                         key,
                     );
-                    FheUint8 { data: quotient }
+                    FheUint { data: quotient }
                 })
             }
         }
 
-        impl Rem<&FheUint8> for &FheUint8 {
-            type Output = FheUint8;
-            fn rem(self, rhs: &FheUint8) -> Self::Output {
-                BoolEvaluator::with_local_mut(|e| {
-                    let key = RuntimeServerKey::global();
+        impl<C: Backend, const BITS: usize> Rem<&FheUint<C, BITS>> for &FheUint<C, BITS> {
+            type Output = FheUint<C, BITS>;
+            fn rem(self, rhs: &FheUint<C, BITS>) -> Self::Output {
+                C::with_evaluator(|e, key| {
                     let (_, remainder) = arbitrary_bit_division_for_quotient_and_rem(
                         e,
                         self.data(),
                         rhs.data(),
                         key,
                     );
-                    FheUint8 { data: remainder }
+                    FheUint { data: remainder }
                 })
             }
         }
 
-        impl FheUint8 {
+        impl<C: Backend, const BITS: usize> FheUint<C, BITS> {
             /// Calculates `Self += rhs` and returns `overflow`
             ///
             /// `overflow` is set to `True` if `Self += rhs` overflowed,
             /// otherwise it is set to `False`
-            pub fn overflowing_add_assign(&mut self, rhs: &FheUint8) -> FheBool {
-                BoolEvaluator::with_local_mut_mut(&mut |e| {
-                    let key = RuntimeServerKey::global();
+            pub fn overflowing_add_assign(&mut self, rhs: &FheUint<C, BITS>) -> FheBool<C> {
+                C::with_evaluator(|e, key| {
                     let (overflow, _) =
                         arbitrary_bit_adder(e, self.data_mut(), rhs.data(), false, key);
                     FheBool { data: overflow }
@@ -154,10 +323,9 @@ mod frontend {
             ///
             /// `overflow` is set to `True` if `Self + rhs` overflowed,
             /// otherwise it is set to `False`
-            pub fn overflowing_add(self, rhs: &FheUint8) -> (FheUint8, FheBool) {
-                BoolEvaluator::with_local_mut(|e| {
+            pub fn overflowing_add(self, rhs: &FheUint<C, BITS>) -> (FheUint<C, BITS>, FheBool<C>) {
+                C::with_evaluator(|e, key| {
                     let mut lhs = self.clone();
-                    let key = RuntimeServerKey::global();
                     let (overflow, _) =
                         arbitrary_bit_adder(e, lhs.data_mut(), rhs.data(), false, key);
                     (lhs, FheBool { data: overflow })
@@ -168,36 +336,39 @@ mod frontend {
             ///
             /// `overflow` is set to `True` if `Self - rhs` overflowed,
             /// otherwise it is set to `False`
-            pub fn overflowing_sub(&self, rhs: &FheUint8) -> (FheUint8, FheBool) {
-                BoolEvaluator::with_local_mut(|e| {
-                    let key = RuntimeServerKey::global();
+            pub fn overflowing_sub(
+                &self,
+                rhs: &FheUint<C, BITS>,
+            ) -> (FheUint<C, BITS>, FheBool<C>) {
+                C::with_evaluator(|e, key| {
                     let (out, mut overflow, _) =
                         arbitrary_bit_subtractor(e, self.data(), rhs.data(), key);
                     e.not_inplace(&mut overflow);
-                    (FheUint8 { data: out }, FheBool { data: overflow })
+                    (FheUint { data: out }, FheBool { data: overflow })
                 })
             }
 
             /// Returns (quotient, remainder) s.t. self = rhs x quotient +
             /// remainder.
             ///
-            /// If rhs is 0, then quotient = 255, remainder = self, and Div by
+            /// If rhs is 0, then quotient = 2^BITS - 1, remainder = self, and Div by
             /// Zero error flag (accessible via `div_zero_error_flag`) is set to
             /// `True`
-            pub fn div_rem(&self, rhs: &FheUint8) -> (FheUint8, FheUint8) {
+            pub fn div_rem(&self, rhs: &FheUint<C, BITS>) -> (FheUint<C, BITS>, FheUint<C, BITS>)
+            where
+                C: DivZeroErrorFlag,
+            {
                 // set div by 0 error flag
-                set_div_by_zero_flag(rhs);
-
-                BoolEvaluator::with_local_mut(|e| {
-                    let key = RuntimeServerKey::global();
+                set_div_by_zero_flag(rhs.data());
 
+                C::with_evaluator(|e, key| {
                     let (quotient, remainder) = arbitrary_bit_division_for_quotient_and_rem(
                         e,
                         self.data(),
                         rhs.data(),
                         key,
                     );
-                    (FheUint8 { data: quotient }, FheUint8 { data: remainder })
+                    (FheUint { data: quotient }, FheUint { data: remainder })
                 })
             }
         }
@@ -210,20 +381,18 @@ mod frontend {
 
         use super::*;
 
-        impl FheUint8 {
+        impl<C: Backend, const BITS: usize> FheUint<C, BITS> {
             /// Returns `FheBool` indicating `Self == other`
-            pub fn eq(&self, other: &FheUint8) -> FheBool {
-                BoolEvaluator::with_local_mut(|e| {
-                    let key = RuntimeServerKey::global();
+            pub fn eq(&self, other: &FheUint<C, BITS>) -> FheBool<C> {
+                C::with_evaluator(|e, key| {
                     let out = arbitrary_bit_equality(e, self.data(), other.data(), key);
                     FheBool { data: out }
                 })
             }
 
             /// Returns `FheBool` indicating `Self != other`
-            pub fn neq(&self, other: &FheUint8) -> FheBool {
-                BoolEvaluator::with_local_mut(|e| {
-                    let key = RuntimeServerKey::global();
+            pub fn neq(&self, other: &FheUint<C, BITS>) -> FheBool<C> {
+                C::with_evaluator(|e, key| {
                     let mut is_equal = arbitrary_bit_equality(e, self.data(), other.data(), key);
                     e.not_inplace(&mut is_equal);
                     FheBool { data: is_equal }
@@ -231,27 +400,24 @@ mod frontend {
             }
 
             /// Returns `FheBool` indicating `Self < other`
-            pub fn lt(&self, other: &FheUint8) -> FheBool {
-                BoolEvaluator::with_local_mut(|e| {
-                    let key = RuntimeServerKey::global();
+            pub fn lt(&self, other: &FheUint<C, BITS>) -> FheBool<C> {
+                C::with_evaluator(|e, key| {
                     let out = arbitrary_bit_comparator(e, other.data(), self.data(), key);
                     FheBool { data: out }
                 })
             }
 
             /// Returns `FheBool` indicating `Self > other`
-            pub fn gt(&self, other: &FheUint8) -> FheBool {
-                BoolEvaluator::with_local_mut(|e| {
-                    let key = RuntimeServerKey::global();
+            pub fn gt(&self, other: &FheUint<C, BITS>) -> FheBool<C> {
+                C::with_evaluator(|e, key| {
                     let out = arbitrary_bit_comparator(e, self.data(), other.data(), key);
                     FheBool { data: out }
                 })
             }
 
             /// Returns `FheBool` indicating `Self <= other`
-            pub fn le(&self, other: &FheUint8) -> FheBool {
-                BoolEvaluator::with_local_mut(|e| {
-                    let key = RuntimeServerKey::global();
+            pub fn le(&self, other: &FheUint<C, BITS>) -> FheBool<C> {
+                C::with_evaluator(|e, key| {
                     let mut a_greater_b =
                         arbitrary_bit_comparator(e, self.data(), other.data(), key);
                     e.not_inplace(&mut a_greater_b);
@@ -260,9 +426,8 @@ mod frontend {
             }
 
             /// Returns `FheBool` indicating `Self >= other`
-            pub fn ge(&self, other: &FheUint8) -> FheBool {
-                BoolEvaluator::with_local_mut(|e| {
-                    let key = RuntimeServerKey::global();
+            pub fn ge(&self, other: &FheUint<C, BITS>) -> FheBool<C> {
+                C::with_evaluator(|e, key| {
                     let mut a_less_b = arbitrary_bit_comparator(e, other.data(), self.data(), key);
                     e.not_inplace(&mut a_less_b);
                     FheBool { data: a_less_b }
@@ -270,22 +435,680 @@ mod frontend {
             }
 
             /// Returns `Self` if `selector = True` else returns `other`
-            pub fn mux(&self, other: &FheUint8, selector: &FheBool) -> FheUint8 {
-                BoolEvaluator::with_local_mut(|e| {
-                    let key = RuntimeServerKey::global();
+            pub fn mux(&self, other: &FheUint<C, BITS>, selector: &FheBool<C>) -> FheUint<C, BITS> {
+                C::with_evaluator(|e, key| {
+                    let out = arbitrary_bit_mux(e, selector.data(), self.data(), other.data(), key);
+                    FheUint { data: out }
+                })
+            }
+
+            /// Returns max(`Self`, `other`)
+            pub fn max(&self, other: &FheUint<C, BITS>) -> FheUint<C, BITS> {
+                let self_gt = self.gt(other);
+                self.mux(other, &self_gt)
+            }
+
+            /// Returns min(`Self`, `other`)
+            pub fn min(&self, other: &FheUint<C, BITS>) -> FheUint<C, BITS> {
+                let self_lt = self.lt(other);
+                self.mux(other, &self_lt)
+            }
+        }
+    }
+
+    mod bitwise {
+        use itertools::izip;
+        use std::ops::{
+            BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Not, Shl, Shr,
+        };
+
+        use crate::shortint::ops::{
+            arbitrary_bit_rotate_left, arbitrary_bit_rotate_left_by_plain,
+            arbitrary_bit_rotate_right, arbitrary_bit_rotate_right_by_plain, arbitrary_bit_shl,
+            arbitrary_bit_shl_by_plain, arbitrary_bit_shr, arbitrary_bit_shr_by_plain,
+        };
+
+        use super::*;
+
+        impl<C: Backend, const BITS: usize> BitAndAssign<&FheUint<C, BITS>> for FheUint<C, BITS> {
+            fn bitand_assign(&mut self, rhs: &FheUint<C, BITS>) {
+                C::with_evaluator(|e, key| {
+                    izip!(self.data_mut().iter_mut(), rhs.data().iter())
+                        .for_each(|(a, b)| e.and_inplace(a, b, key));
+                });
+            }
+        }
+
+        impl<C: Backend, const BITS: usize> BitAnd<&FheUint<C, BITS>> for &FheUint<C, BITS> {
+            type Output = FheUint<C, BITS>;
+            fn bitand(self, rhs: &FheUint<C, BITS>) -> Self::Output {
+                let mut a = self.clone();
+                a &= rhs;
+                a
+            }
+        }
+
+        impl<C: Backend, const BITS: usize> BitOrAssign<&FheUint<C, BITS>> for FheUint<C, BITS> {
+            fn bitor_assign(&mut self, rhs: &FheUint<C, BITS>) {
+                C::with_evaluator(|e, key| {
+                    izip!(self.data_mut().iter_mut(), rhs.data().iter())
+                        .for_each(|(a, b)| e.or_inplace(a, b, key));
+                });
+            }
+        }
+
+        impl<C: Backend, const BITS: usize> BitOr<&FheUint<C, BITS>> for &FheUint<C, BITS> {
+            type Output = FheUint<C, BITS>;
+            fn bitor(self, rhs: &FheUint<C, BITS>) -> Self::Output {
+                let mut a = self.clone();
+                a |= rhs;
+                a
+            }
+        }
+
+        impl<C: Backend, const BITS: usize> BitXorAssign<&FheUint<C, BITS>> for FheUint<C, BITS> {
+            fn bitxor_assign(&mut self, rhs: &FheUint<C, BITS>) {
+                C::with_evaluator(|e, key| {
+                    izip!(self.data_mut().iter_mut(), rhs.data().iter())
+                        .for_each(|(a, b)| e.xor_inplace(a, b, key));
+                });
+            }
+        }
+
+        impl<C: Backend, const BITS: usize> BitXor<&FheUint<C, BITS>> for &FheUint<C, BITS> {
+            type Output = FheUint<C, BITS>;
+            fn bitxor(self, rhs: &FheUint<C, BITS>) -> Self::Output {
+                let mut a = self.clone();
+                a ^= rhs;
+                a
+            }
+        }
+
+        impl<C: Backend, const BITS: usize> Not for &FheUint<C, BITS> {
+            type Output = FheUint<C, BITS>;
+            fn not(self) -> Self::Output {
+                C::Evaluator::with_local(|e| FheUint {
+                    data: self.data().iter().map(|c| e.not(c)).collect(),
+                })
+            }
+        }
+
+        impl<C: Backend, const BITS: usize> Shl<u32> for &FheUint<C, BITS> {
+            type Output = FheUint<C, BITS>;
+            /// Shift by plaintext amount is free. Panics if `rhs` >= `BITS`
+            fn shl(self, rhs: u32) -> Self::Output {
+                assert!((rhs as usize) < BITS, "attempt to shift left with overflow");
+                C::Evaluator::with_local(|e| FheUint {
+                    data: arbitrary_bit_shl_by_plain(e, self.data(), rhs as usize),
+                })
+            }
+        }
+
+        impl<C: Backend, const BITS: usize> Shr<u32> for &FheUint<C, BITS> {
+            type Output = FheUint<C, BITS>;
+            /// Shift by plaintext amount is free. Panics if `rhs` >= `BITS`
+            fn shr(self, rhs: u32) -> Self::Output {
+                assert!(
+                    (rhs as usize) < BITS,
+                    "attempt to shift right with overflow"
+                );
+                C::Evaluator::with_local(|e| FheUint {
+                    data: arbitrary_bit_shr_by_plain(e, self.data(), rhs as usize),
+                })
+            }
+        }
+
+        impl<C: Backend, const BITS: usize> Shl<&FheUint<C, BITS>> for &FheUint<C, BITS> {
+            type Output = FheUint<C, BITS>;
+            /// Shift amount is reduced modulo `BITS`, same as `wrapping_shl`
+            fn shl(self, rhs: &FheUint<C, BITS>) -> Self::Output {
+                C::with_evaluator(|e, key| FheUint {
+                    data: arbitrary_bit_shl(e, self.data(), rhs.data(), key),
+                })
+            }
+        }
+
+        impl<C: Backend, const BITS: usize> Shr<&FheUint<C, BITS>> for &FheUint<C, BITS> {
+            type Output = FheUint<C, BITS>;
+            /// Shift amount is reduced modulo `BITS`, same as `wrapping_shr`
+            fn shr(self, rhs: &FheUint<C, BITS>) -> Self::Output {
+                C::with_evaluator(|e, key| FheUint {
+                    data: arbitrary_bit_shr(e, self.data(), rhs.data(), key),
+                })
+            }
+        }
+
+        impl<C: Backend, const BITS: usize> FheUint<C, BITS> {
+            /// Returns `Self` rotated left by plaintext amount `n`. Rotation by
+            /// plaintext amount is free.
+            pub fn rotate_left(&self, n: u32) -> FheUint<C, BITS> {
+                FheUint {
+                    data: arbitrary_bit_rotate_left_by_plain(self.data(), n as usize),
+                }
+            }
+
+            /// Returns `Self` rotated right by plaintext amount `n`. Rotation
+            /// by plaintext amount is free.
+            pub fn rotate_right(&self, n: u32) -> FheUint<C, BITS> {
+                FheUint {
+                    data: arbitrary_bit_rotate_right_by_plain(self.data(), n as usize),
+                }
+            }
+
+            /// Returns `Self` rotated left by encrypted amount `n`
+            pub fn rotate_left_by(&self, n: &FheUint<C, BITS>) -> FheUint<C, BITS> {
+                C::with_evaluator(|e, key| FheUint {
+                    data: arbitrary_bit_rotate_left(e, self.data(), n.data(), key),
+                })
+            }
+
+            /// Returns `Self` rotated right by encrypted amount `n`
+            pub fn rotate_right_by(&self, n: &FheUint<C, BITS>) -> FheUint<C, BITS> {
+                C::with_evaluator(|e, key| FheUint {
+                    data: arbitrary_bit_rotate_right(e, self.data(), n.data(), key),
+                })
+            }
+        }
+    }
+
+    mod scalar {
+        use itertools::{izip, Itertools};
+        use std::ops::{Add, AddAssign, BitAnd, BitOr, BitXor, Div, Mul, Rem, Sub};
+
+        use crate::shortint::ops::{
+            and_plain, arbitrary_bit_adder_plain, arbitrary_bit_comparator_plain,
+            arbitrary_bit_division_plain_for_quotient_and_rem, arbitrary_bit_equality_plain,
+            arbitrary_bit_lookup, arbitrary_bit_mul_plain, arbitrary_bit_subtractor_plain,
+            or_plain, xor_plain,
+        };
+
+        use super::*;
+
+        impl<C: Backend, T: FheUintPlaintext<BITS>, const BITS: usize> AddAssign<T> for FheUint<C, BITS> {
+            fn add_assign(&mut self, rhs: T) {
+                C::with_evaluator(|e, key| {
+                    arbitrary_bit_adder_plain(e, self.data_mut(), &rhs.to_bits(), false, key);
+                });
+            }
+        }
+
+        impl<C: Backend, T: FheUintPlaintext<BITS>, const BITS: usize> Add<T> for &FheUint<C, BITS> {
+            type Output = FheUint<C, BITS>;
+            fn add(self, rhs: T) -> Self::Output {
+                let mut a = self.clone();
+                a += rhs;
+                a
+            }
+        }
+
+        impl<C: Backend, T: FheUintPlaintext<BITS>, const BITS: usize> Sub<T> for &FheUint<C, BITS> {
+            type Output = FheUint<C, BITS>;
+            fn sub(self, rhs: T) -> Self::Output {
+                C::with_evaluator(|e, key| {
+                    let (out, _) =
+                        arbitrary_bit_subtractor_plain(e, self.data(), &rhs.to_bits(), key);
+                    FheUint { data: out }
+                })
+            }
+        }
+
+        impl<C: Backend, T: FheUintPlaintext<BITS>, const BITS: usize> Mul<T> for &FheUint<C, BITS> {
+            type Output = FheUint<C, BITS>;
+            fn mul(self, rhs: T) -> Self::Output {
+                C::with_evaluator(|e, key| {
+                    let out = arbitrary_bit_mul_plain(e, self.data(), &rhs.to_bits(), key);
+                    FheUint { data: out }
+                })
+            }
+        }
+
+        impl<C: DivZeroErrorFlag, T: FheUintPlaintext<BITS>, const BITS: usize> Div<T>
+            for &FheUint<C, BITS>
+        {
+            type Output = FheUint<C, BITS>;
+            fn div(self, rhs: T) -> Self::Output {
+                let (quotient, _) = self.div_rem_const(rhs);
+                quotient
+            }
+        }
+
+        impl<C: DivZeroErrorFlag, T: FheUintPlaintext<BITS>, const BITS: usize> Rem<T>
+            for &FheUint<C, BITS>
+        {
+            type Output = FheUint<C, BITS>;
+            fn rem(self, rhs: T) -> Self::Output {
+                let (_, remainder) = self.div_rem_const(rhs);
+                remainder
+            }
+        }
+
+        impl<C: Backend, T: FheUintPlaintext<BITS>, const BITS: usize> BitAnd<T> for &FheUint<C, BITS> {
+            type Output = FheUint<C, BITS>;
+            fn bitand(self, rhs: T) -> Self::Output {
+                C::Evaluator::with_local(|e| FheUint {
+                    data: izip!(self.data().iter(), rhs.to_bits().iter())
+                        .map(|(a, b)| and_plain(e, a, *b))
+                        .collect(),
+                })
+            }
+        }
+
+        impl<C: Backend, T: FheUintPlaintext<BITS>, const BITS: usize> BitOr<T> for &FheUint<C, BITS> {
+            type Output = FheUint<C, BITS>;
+            fn bitor(self, rhs: T) -> Self::Output {
+                C::Evaluator::with_local(|e| FheUint {
+                    data: izip!(self.data().iter(), rhs.to_bits().iter())
+                        .map(|(a, b)| or_plain(e, a, *b))
+                        .collect(),
+                })
+            }
+        }
+
+        impl<C: Backend, T: FheUintPlaintext<BITS>, const BITS: usize> BitXor<T> for &FheUint<C, BITS> {
+            type Output = FheUint<C, BITS>;
+            fn bitxor(self, rhs: T) -> Self::Output {
+                C::Evaluator::with_local(|e| FheUint {
+                    data: izip!(self.data().iter(), rhs.to_bits().iter())
+                        .map(|(a, b)| xor_plain(e, a, *b))
+                        .collect(),
+                })
+            }
+        }
+
+        impl<C: Backend, const BITS: usize> FheUint<C, BITS> {
+            /// Returns (Self + rhs, overflow) for plaintext `rhs`.
+            ///
+            /// `overflow` is set to `True` if `Self + rhs` overflowed,
+            /// otherwise it is set to `False`
+            pub fn overflowing_add_const<T: FheUintPlaintext<BITS>>(
+                &self,
+                rhs: T,
+            ) -> (FheUint<C, BITS>, FheBool<C>) {
+                C::with_evaluator(|e, key| {
+                    let mut out = self.data().to_vec();
+                    let (overflow, _) =
+                        arbitrary_bit_adder_plain(e, &mut out, &rhs.to_bits(), false, key);
+                    (
+                        FheUint { data: out },
+                        FheBool {
+                            data: overflow.into_ciphertext(e),
+                        },
+                    )
+                })
+            }
+
+            /// Returns (Self - rhs, overflow) for plaintext `rhs`.
+            ///
+            /// `overflow` is set to `True` if `Self - rhs` overflowed,
+            /// otherwise it is set to `False`
+            pub fn overflowing_sub_const<T: FheUintPlaintext<BITS>>(
+                &self,
+                rhs: T,
+            ) -> (FheUint<C, BITS>, FheBool<C>) {
+                C::with_evaluator(|e, key| {
+                    let (out, no_overflow) =
+                        arbitrary_bit_subtractor_plain(e, self.data(), &rhs.to_bits(), key);
+                    let mut overflow = no_overflow.into_ciphertext(e);
+                    e.not_inplace(&mut overflow);
+                    (FheUint { data: out }, FheBool { data: overflow })
+                })
+            }
+
+            /// Returns (quotient, remainder) s.t. self = rhs x quotient +
+            /// remainder for plaintext `rhs`.
+            ///
+            /// If rhs is 0, then quotient = 2^BITS - 1, remainder = self, and Div by
+            /// Zero error flag is set to `True`. Refer to `div_rem`.
+            pub fn div_rem_const<T: FheUintPlaintext<BITS>>(
+                &self,
+                rhs: T,
+            ) -> (FheUint<C, BITS>, FheUint<C, BITS>)
+            where
+                C: DivZeroErrorFlag,
+            {
+                if rhs == T::zero() {
+                    let (flag, quotient) = C::Evaluator::with_local(|e| {
+                        (
+                            e.trivial(true),
+                            (0..BITS).map(|_| e.trivial(true)).collect(),
+                        )
+                    });
+                    merge_div_zero_error_flag(FheBool { data: flag });
+                    return (FheUint { data: quotient }, self.clone());
+                }
+
+                C::with_evaluator(|e, key| {
+                    let (quotient, remainder) = arbitrary_bit_division_plain_for_quotient_and_rem(
+                        e,
+                        self.data(),
+                        &rhs.to_bits(),
+                        key,
+                    );
+                    (FheUint { data: quotient }, FheUint { data: remainder })
+                })
+            }
+
+            /// Returns `FheBool` indicating `Self == other` for plaintext
+            /// `other`
+            pub fn eq_const<T: FheUintPlaintext<BITS>>(&self, other: T) -> FheBool<C> {
+                C::with_evaluator(|e, key| {
+                    let out = arbitrary_bit_equality_plain(e, self.data(), &other.to_bits(), key);
+                    FheBool { data: out }
+                })
+            }
+
+            /// Returns `FheBool` indicating `Self != other` for plaintext
+            /// `other`
+            pub fn neq_const<T: FheUintPlaintext<BITS>>(&self, other: T) -> FheBool<C> {
+                !&self.eq_const(other)
+            }
+
+            /// Returns `FheBool` indicating `Self > other` for plaintext
+            /// `other`
+            pub fn gt_const<T: FheUintPlaintext<BITS>>(&self, other: T) -> FheBool<C> {
+                C::with_evaluator(|e, key| {
+                    let out = arbitrary_bit_comparator_plain(e, self.data(), &other.to_bits(), key);
+                    FheBool { data: out }
+                })
+            }
+
+            /// Returns `FheBool` indicating `Self <= other` for plaintext
+            /// `other`
+            pub fn le_const<T: FheUintPlaintext<BITS>>(&self, other: T) -> FheBool<C> {
+                !&self.gt_const(other)
+            }
+
+            /// Returns `FheBool` indicating `Self >= other` for plaintext
+            /// `other`
+            pub fn ge_const<T: FheUintPlaintext<BITS>>(&self, other: T) -> FheBool<C> {
+                if other == T::zero() {
+                    C::Evaluator::with_local(|e| FheBool {
+                        data: e.trivial(true),
+                    })
+                } else {
+                    // Self >= other <=> Self > other - 1
+                    self.gt_const(other - T::one())
+                }
+            }
+
+            /// Returns `FheBool` indicating `Self < other` for plaintext
+            /// `other`
+            pub fn lt_const<T: FheUintPlaintext<BITS>>(&self, other: T) -> FheBool<C> {
+                !&self.ge_const(other)
+            }
+        }
+
+        impl<C: Backend> FheUint<C, 8> {
+            /// Returns `table[Self]` for plaintext `table`. Refer to
+            /// `FheUint8::select_from`.
+            pub fn lookup(&self, table: &[u8; 256]) -> FheUint<C, 8> {
+                FheUint::select_from(table, self)
+            }
+
+            /// Returns `table[index]` for plaintext `table` of at most 256
+            /// entries. Returns 0 if `index` is out of bounds of `table`.
+            ///
+            /// Lookup is evaluated with a mux tree per output bit. Muxes with
+            /// plaintext inputs are folded and sub-trees of equal sub-tables
+            /// are shared, hence the cost depends on contents of `table` and
+            /// is much lower than a chain of `mux`es over all the entries.
+            ///
+            /// Panics if `table` has more than 256 entries
+            pub fn select_from(table: &[u8], index: &FheUint<C, 8>) -> FheUint<C, 8> {
+                assert!(
+                    table.len() <= 256,
+                    "Expected at most 256 entries, got {}",
+                    table.len()
+                );
+                let table = table.iter().map(|v| v.to_bits()).collect_vec();
+                C::with_evaluator(|e, key| FheUint {
+                    data: arbitrary_bit_lookup(e, index.data(), &table, 8, key),
+                })
+            }
+        }
+    }
+
+    mod signed {
+        use std::ops::{Add, AddAssign, Div, Mul, Neg, Rem, Sub};
+
+        use crate::shortint::ops::{
+            arbitrary_bit_conditional_negation, arbitrary_bit_equality, arbitrary_bit_mux,
+            arbitrary_bit_negation, arbitrary_signed_bit_comparator,
+            arbitrary_signed_bit_division_for_quotient_and_rem,
+        };
+
+        use super::*;
+
+        impl<C: Backend, const BITS: usize> AddAssign<&FheInt<C, BITS>> for FheInt<C, BITS> {
+            fn add_assign(&mut self, rhs: &FheInt<C, BITS>) {
+                C::with_evaluator(|e, key| {
+                    arbitrary_bit_adder(e, self.data_mut(), rhs.data(), false, key);
+                });
+            }
+        }
+
+        impl<C: Backend, const BITS: usize> Add<&FheInt<C, BITS>> for &FheInt<C, BITS> {
+            type Output = FheInt<C, BITS>;
+            fn add(self, rhs: &FheInt<C, BITS>) -> Self::Output {
+                let mut a = self.clone();
+                a += rhs;
+                a
+            }
+        }
+
+        impl<C: Backend, const BITS: usize> Sub<&FheInt<C, BITS>> for &FheInt<C, BITS> {
+            type Output = FheInt<C, BITS>;
+            fn sub(self, rhs: &FheInt<C, BITS>) -> Self::Output {
+                C::with_evaluator(|e, key| {
+                    let (out, _, _) = arbitrary_bit_subtractor(e, self.data(), rhs.data(), key);
+                    FheInt { data: out }
+                })
+            }
+        }
+
+        impl<C: Backend, const BITS: usize> Mul<&FheInt<C, BITS>> for &FheInt<C, BITS> {
+            type Output = FheInt<C, BITS>;
+            fn mul(self, rhs: &FheInt<C, BITS>) -> Self::Output {
+                // Lower `BITS` bits of the product are same for signed and unsigned
+                // integers in two's complement
+                C::with_evaluator(|e, key| {
+                    let out = arbitrary_bit_mul(e, self.data(), rhs.data(), key);
+                    FheInt { data: out }
+                })
+            }
+        }
+
+        impl<C: DivZeroErrorFlag, const BITS: usize> Div<&FheInt<C, BITS>> for &FheInt<C, BITS> {
+            type Output = FheInt<C, BITS>;
+            fn div(self, rhs: &FheInt<C, BITS>) -> Self::Output {
+                let (quotient, _) = self.div_rem(rhs);
+                quotient
+            }
+        }
+
+        impl<C: DivZeroErrorFlag, const BITS: usize> Rem<&FheInt<C, BITS>> for &FheInt<C, BITS> {
+            type Output = FheInt<C, BITS>;
+            fn rem(self, rhs: &FheInt<C, BITS>) -> Self::Output {
+                let (_, remainder) = self.div_rem(rhs);
+                remainder
+            }
+        }
+
+        impl<C: Backend, const BITS: usize> Neg for &FheInt<C, BITS> {
+            type Output = FheInt<C, BITS>;
+            fn neg(self) -> Self::Output {
+                C::with_evaluator(|e, key| {
+                    let out = arbitrary_bit_negation(e, self.data(), key);
+                    FheInt { data: out }
+                })
+            }
+        }
+
+        impl<C: Backend, const BITS: usize> FheInt<C, BITS> {
+            /// Calculates `Self += rhs` and returns `overflow`
+            ///
+            /// `overflow` is set to `True` if `Self += rhs` overflowed,
+            /// otherwise it is set to `False`
+            pub fn overflowing_add_assign(&mut self, rhs: &FheInt<C, BITS>) -> FheBool<C> {
+                C::with_evaluator(|e, key| {
+                    let (carry_last, carry_last_last) =
+                        arbitrary_bit_adder(e, self.data_mut(), rhs.data(), false, key);
+                    // Signed addition overflows if and only if carry into the sign bit
+                    // differs from the carry out of the sign bit
+                    FheBool {
+                        data: e.xor(&carry_last, &carry_last_last, key),
+                    }
+                })
+            }
+
+            /// Returns (Self + rhs, overflow).
+            ///
+            /// `overflow` is set to `True` if `Self + rhs` overflowed,
+            /// otherwise it is set to `False`
+            pub fn overflowing_add(&self, rhs: &FheInt<C, BITS>) -> (FheInt<C, BITS>, FheBool<C>) {
+                let mut lhs = self.clone();
+                let overflow = lhs.overflowing_add_assign(rhs);
+                (lhs, overflow)
+            }
+
+            /// Returns (Self - rhs, overflow).
+            ///
+            /// `overflow` is set to `True` if `Self - rhs` overflowed,
+            /// otherwise it is set to `False`
+            pub fn overflowing_sub(&self, rhs: &FheInt<C, BITS>) -> (FheInt<C, BITS>, FheBool<C>) {
+                C::with_evaluator(|e, key| {
+                    let (out, carry_last, carry_last_last) =
+                        arbitrary_bit_subtractor(e, self.data(), rhs.data(), key);
+                    (
+                        FheInt { data: out },
+                        FheBool {
+                            data: e.xor(&carry_last, &carry_last_last, key),
+                        },
+                    )
+                })
+            }
+
+            /// Returns (quotient, remainder) s.t. self = rhs x quotient +
+            /// remainder.
+            ///
+            /// Quotient is rounded towards zero and remainder has the same
+            /// sign as `self`, same as division of Rust's signed integers. If
+            /// `self` is the minimum value and `rhs` is -1, then quotient
+            /// wraps around to the minimum value and remainder is 0.
+            ///
+            /// If rhs is 0, then quotient = -1 if `self` >= 0 otherwise
+            /// quotient = 1, remainder = self, and Div by Zero error flag
+            /// (accessible via `div_zero_error_flag`) is set to `True`
+            pub fn div_rem(&self, rhs: &FheInt<C, BITS>) -> (FheInt<C, BITS>, FheInt<C, BITS>)
+            where
+                C: DivZeroErrorFlag,
+            {
+                // set div by 0 error flag
+                set_div_by_zero_flag(rhs.data());
+
+                C::with_evaluator(|e, key| {
+                    let (quotient, remainder) = arbitrary_signed_bit_division_for_quotient_and_rem(
+                        e,
+                        self.data(),
+                        rhs.data(),
+                        key,
+                    );
+                    (FheInt { data: quotient }, FheInt { data: remainder })
+                })
+            }
+
+            /// Returns absolute value of `Self`
+            ///
+            /// Absolute value of the minimum value wraps around to the minimum
+            /// value
+            pub fn abs(&self) -> FheInt<C, BITS> {
+                C::with_evaluator(|e, key| {
+                    let out = arbitrary_bit_conditional_negation(
+                        e,
+                        self.data(),
+                        &self.data()[BITS - 1],
+                        key,
+                    );
+                    FheInt { data: out }
+                })
+            }
+
+            /// Returns `FheBool` indicating `Self < 0`
+            pub fn is_negative(&self) -> FheBool<C> {
+                FheBool {
+                    data: self.data()[BITS - 1].clone(),
+                }
+            }
+
+            /// Returns `FheBool` indicating `Self == other`
+            pub fn eq(&self, other: &FheInt<C, BITS>) -> FheBool<C> {
+                C::with_evaluator(|e, key| {
+                    let out = arbitrary_bit_equality(e, self.data(), other.data(), key);
+                    FheBool { data: out }
+                })
+            }
+
+            /// Returns `FheBool` indicating `Self != other`
+            pub fn neq(&self, other: &FheInt<C, BITS>) -> FheBool<C> {
+                C::with_evaluator(|e, key| {
+                    let mut is_equal = arbitrary_bit_equality(e, self.data(), other.data(), key);
+                    e.not_inplace(&mut is_equal);
+                    FheBool { data: is_equal }
+                })
+            }
+
+            /// Returns `FheBool` indicating `Self < other`
+            pub fn lt(&self, other: &FheInt<C, BITS>) -> FheBool<C> {
+                C::with_evaluator(|e, key| {
+                    let out = arbitrary_signed_bit_comparator(e, other.data(), self.data(), key);
+                    FheBool { data: out }
+                })
+            }
+
+            /// Returns `FheBool` indicating `Self > other`
+            pub fn gt(&self, other: &FheInt<C, BITS>) -> FheBool<C> {
+                C::with_evaluator(|e, key| {
+                    let out = arbitrary_signed_bit_comparator(e, self.data(), other.data(), key);
+                    FheBool { data: out }
+                })
+            }
+
+            /// Returns `FheBool` indicating `Self <= other`
+            pub fn le(&self, other: &FheInt<C, BITS>) -> FheBool<C> {
+                C::with_evaluator(|e, key| {
+                    let mut a_greater_b =
+                        arbitrary_signed_bit_comparator(e, self.data(), other.data(), key);
+                    e.not_inplace(&mut a_greater_b);
+                    FheBool { data: a_greater_b }
+                })
+            }
+
+            /// Returns `FheBool` indicating `Self >= other`
+            pub fn ge(&self, other: &FheInt<C, BITS>) -> FheBool<C> {
+                C::with_evaluator(|e, key| {
+                    let mut a_less_b =
+                        arbitrary_signed_bit_comparator(e, other.data(), self.data(), key);
+                    e.not_inplace(&mut a_less_b);
+                    FheBool { data: a_less_b }
+                })
+            }
+
+            /// Returns `Self` if `selector = True` else returns `other`
+            pub fn mux(&self, other: &FheInt<C, BITS>, selector: &FheBool<C>) -> FheInt<C, BITS> {
+                C::with_evaluator(|e, key| {
                     let out = arbitrary_bit_mux(e, selector.data(), self.data(), other.data(), key);
-                    FheUint8 { data: out }
+                    FheInt { data: out }
                 })
             }
 
             /// Returns max(`Self`, `other`)
-            pub fn max(&self, other: &FheUint8) -> FheUint8 {
+            pub fn max(&self, other: &FheInt<C, BITS>) -> FheInt<C, BITS> {
                 let self_gt = self.gt(other);
                 self.mux(other, &self_gt)
             }
 
             /// Returns min(`Self`, `other`)
-            pub fn min(&self, other: &FheUint8) -> FheUint8 {
+            pub fn min(&self, other: &FheInt<C, BITS>) -> FheInt<C, BITS> {
                 let self_lt = self.lt(other);
                 self.mux(other, &self_lt)
             }
@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use itertools::{izip, Itertools};
 
 use crate::bool::BooleanGates;
@@ -8,9 +10,7 @@ pub(super) fn half_adder<E: BooleanGates>(
     b: &E::Ciphertext,
     key: &E::Key,
 ) -> E::Ciphertext {
-    let carry = evaluator.and(a, b, key);
-    evaluator.xor_inplace(a, b, key);
-    carry
+    evaluator.half_adder(a, b, key)
 }
 
 pub(super) fn full_adder_plain_carry_in<E: BooleanGates>(
@@ -20,27 +20,18 @@ pub(super) fn full_adder_plain_carry_in<E: BooleanGates>(
     carry_in: bool,
     key: &E::Key,
 ) -> E::Ciphertext {
-    let mut a_and_b = evaluator.and(a, b, key);
-    evaluator.xor_inplace(a, b, key); //a = a ^ b
-    if carry_in {
-        // a_and_b = A & B | ((A^B) & C_in={True})
-        evaluator.or_inplace(&mut a_and_b, &a, key);
-    } else {
-        // a_and_b = A & B | ((A^B) & C_in={False})
-        // a_and_b = A & B
-        // noop
-    }
-
-    // In xor if a input is 0, output equals the firt variable. If input is 1 then
-    // output equals !(first variable)
+    // carry = majority(A, B, C_in), that is A | B if C_in = True, otherwise A & B
     if carry_in {
-        // (A^B)^1 = !(A^B)
+        // A | B = !(!A & !B) and (A^B)^1 = !(A^B) = !(!A ^ !B). Hence both are
+        // negated outputs of half adder of !A and !B.
         evaluator.not_inplace(a);
+        let mut carry = evaluator.half_adder(a, &evaluator.not(b), key);
+        evaluator.not_inplace(&mut carry);
+        evaluator.not_inplace(a);
+        carry
     } else {
-        // (A^B)^0
-        // no-op
+        evaluator.half_adder(a, b, key)
     }
-    a_and_b
 }
 
 pub(super) fn full_adder<E: BooleanGates>(
@@ -50,12 +41,8 @@ pub(super) fn full_adder<E: BooleanGates>(
     carry_in: &E::Ciphertext,
     key: &E::Key,
 ) -> E::Ciphertext {
-    let mut a_and_b = evaluator.and(a, b, key);
-    evaluator.xor_inplace(a, b, key); //a = a ^ b
-    let a_xor_b_and_c = evaluator.and(&a, carry_in, key);
-    evaluator.or_inplace(&mut a_and_b, &a_xor_b_and_c, key); // a_and_b = A & B | ((A^B) & C_in)
-    evaluator.xor_inplace(a, &carry_in, key);
-    a_and_b
+    // carry = A & B | ((A^B) & C_in) = majority(A, B, C_in)
+    evaluator.full_adder(a, b, carry_in, key)
 }
 
 pub(super) fn arbitrary_bit_adder<E: BooleanGates>(
@@ -104,20 +91,100 @@ where
     return (neg_b, carry_last, carry_last_last);
 }
 
+/// Returns two's complement negation of `a`, that is `!a + 1`
+pub(super) fn arbitrary_bit_negation<E: BooleanGates>(
+    evaluator: &mut E,
+    a: &[E::Ciphertext],
+    key: &E::Key,
+) -> Vec<E::Ciphertext>
+where
+    E::Ciphertext: Clone,
+{
+    let n = a.len();
+    let mut out = a.iter().map(|v| evaluator.not(v)).collect_vec();
+
+    // Add 1. LSB is flipped and carry equals to LSB of `!a`
+    let mut carry = out[0].clone();
+    evaluator.not_inplace(&mut out[0]);
+    for o in out.iter_mut().take(n - 1).skip(1) {
+        carry = half_adder(evaluator, o, &carry, key);
+    }
+    evaluator.xor_inplace(&mut out[n - 1], &carry, key);
+
+    out
+}
+
+/// Returns `-a` if `negate` is True, otherwise returns `a`
+///
+/// Uses the identity `(a ^ negate) + negate` where each bit of `a` is XORed
+/// with `negate` and `negate` is added as carry in
+pub(super) fn arbitrary_bit_conditional_negation<E: BooleanGates>(
+    evaluator: &mut E,
+    a: &[E::Ciphertext],
+    negate: &E::Ciphertext,
+    key: &E::Key,
+) -> Vec<E::Ciphertext>
+where
+    E::Ciphertext: Clone,
+{
+    let n = a.len();
+    let mut out = a
+        .iter()
+        .map(|v| evaluator.xor(v, negate, key))
+        .collect_vec();
+
+    let mut carry = negate.clone();
+    for o in out.iter_mut().take(n - 1) {
+        carry = half_adder(evaluator, o, &carry, key);
+    }
+    evaluator.xor_inplace(&mut out[n - 1], &carry, key);
+
+    out
+}
+
+/// Returns (quotient, remainder) of signed division `a / b`
+///
+/// Quotient is rounded towards zero and remainder has the same sign as `a`,
+/// same as Rust's signed integer division. Division is performed on absolute
+/// values of `a` and `b` and signs of quotient and remainder are corrected
+/// afterwards.
+pub(super) fn arbitrary_signed_bit_division_for_quotient_and_rem<E: BooleanGates>(
+    evaluator: &mut E,
+    a: &[E::Ciphertext],
+    b: &[E::Ciphertext],
+    key: &E::Key,
+) -> (Vec<E::Ciphertext>, Vec<E::Ciphertext>)
+where
+    E::Ciphertext: Clone,
+{
+    assert!(a.len() == b.len());
+    let n = a.len();
+
+    let a_is_negative = &a[n - 1];
+    let b_is_negative = &b[n - 1];
+
+    let abs_a = arbitrary_bit_conditional_negation(evaluator, a, a_is_negative, key);
+    let abs_b = arbitrary_bit_conditional_negation(evaluator, b, b_is_negative, key);
+
+    let (quotient, remainder) =
+        arbitrary_bit_division_for_quotient_and_rem(evaluator, &abs_a, &abs_b, key);
+
+    let quotient_is_negative = evaluator.xor(a_is_negative, b_is_negative, key);
+    let quotient =
+        arbitrary_bit_conditional_negation(evaluator, &quotient, &quotient_is_negative, key);
+    let remainder = arbitrary_bit_conditional_negation(evaluator, &remainder, a_is_negative, key);
+
+    (quotient, remainder)
+}
+
 pub(super) fn bit_mux<E: BooleanGates>(
     evaluator: &mut E,
-    selector: E::Ciphertext,
+    selector: &E::Ciphertext,
     if_true: &E::Ciphertext,
     if_false: &E::Ciphertext,
     key: &E::Key,
 ) -> E::Ciphertext {
-    // (s&a) | ((1-s)^b)
-    let not_selector = evaluator.not(&selector);
-
-    let mut s_and_a = evaluator.and(&selector, if_true, key);
-    let s_and_b = evaluator.and(&not_selector, if_false, key);
-    evaluator.or(&mut s_and_a, &s_and_b, key);
-    s_and_a
+    evaluator.mux(selector, if_true, if_false, key)
 }
 
 pub(super) fn arbitrary_bit_mux<E: BooleanGates>(
@@ -127,31 +194,333 @@ pub(super) fn arbitrary_bit_mux<E: BooleanGates>(
     if_false: &[E::Ciphertext],
     key: &E::Key,
 ) -> Vec<E::Ciphertext> {
-    // (s&a) | ((1-s)^b)
-    let not_selector = evaluator.not(&selector);
-
     izip!(if_true.iter(), if_false.iter())
-        .map(|(a, b)| {
-            let mut s_and_a = evaluator.and(&selector, a, key);
-            let s_and_b = evaluator.and(&not_selector, b, key);
-            evaluator.or_inplace(&mut s_and_a, &s_and_b, key);
-            s_and_a
+        .map(|(a, b)| evaluator.mux(selector, a, b, key))
+        .collect()
+}
+
+/// Returns `table[index]` for plaintext `table` with entries of `out_bits`
+/// bits in little endian. Entries missing from `table` (i.e. at indices >=
+/// `table.len()`) are 0.
+///
+/// Every output bit is evaluated with a mux tree over bits of the table
+/// entries that selects with index bits from LSB to MSB. Muxes with plaintext
+/// inputs are folded (refer to `mux_bits`), hence the first level of the tree
+/// is free. Sub-trees are evaluated once per distinct sub-table, up to
+/// negation, and shared across levels and output bits.
+///
+/// If the evaluator supports circuit bootstrapping, the lookup is instead
+/// evaluated with a single CMUX tree over circuit bootstrapped index bits
+/// (refer to `BooleanGates::cmux_tree_lookup`).
+pub(crate) fn arbitrary_bit_lookup<E: BooleanGates>(
+    evaluator: &mut E,
+    index: &[E::Ciphertext],
+    table: &[Vec<bool>],
+    out_bits: usize,
+    key: &E::Key,
+) -> Vec<E::Ciphertext>
+where
+    E::Ciphertext: Clone,
+{
+    assert!(
+        index.len() < usize::BITS as usize && table.len() <= 1 << index.len(),
+        "Table with {} entries cannot be indexed with {} bits",
+        table.len(),
+        index.len()
+    );
+    assert!(table.iter().all(|entry| entry.len() == out_bits));
+
+    if let Some(out) = evaluator.cmux_tree_lookup(index, table, out_bits, key) {
+        return out;
+    }
+
+    let mut sub_trees = HashMap::new();
+    (0..out_bits)
+        .map(|i| {
+            let bits = (0..1usize << index.len())
+                .map(|j| table.get(j).is_some_and(|entry| entry[i]))
+                .collect_vec();
+            lookup_tree(evaluator, index, &bits, &mut sub_trees, key).into_ciphertext(evaluator)
         })
         .collect()
 }
 
-pub(super) fn eight_bit_mul<E: BooleanGates>(
+/// Returns `bits[index]` where `bits.len()` is 2^{index.len()}.
+///
+/// `sub_trees` caches ciphertexts of sub-tables starting with false. Sub-table
+/// starting with true is the negation of its complement.
+fn lookup_tree<E: BooleanGates>(
+    evaluator: &mut E,
+    index: &[E::Ciphertext],
+    bits: &[bool],
+    sub_trees: &mut HashMap<Vec<bool>, E::Ciphertext>,
+    key: &E::Key,
+) -> Bit<E::Ciphertext>
+where
+    E::Ciphertext: Clone,
+{
+    if bits.iter().all(|b| *b == bits[0]) {
+        return Bit::Plain(bits[0]);
+    }
+    if bits[0] {
+        let complement = bits.iter().map(|b| !b).collect_vec();
+        return match lookup_tree(evaluator, index, &complement, sub_trees, key) {
+            Bit::Plain(v) => Bit::Plain(!v),
+            Bit::Encrypted(c) => Bit::Encrypted(evaluator.not(&c)),
+        };
+    }
+    if let Some(c) = sub_trees.get(bits) {
+        return Bit::Encrypted(c.clone());
+    }
+
+    let (low, high) = bits.split_at(bits.len() / 2);
+    let selector = &index[bits.len().trailing_zeros() as usize - 1];
+    let out = if low == high {
+        lookup_tree(evaluator, index, low, sub_trees, key)
+    } else if izip!(low.iter(), high.iter()).all(|(l, h)| l != h) {
+        // high is negation of low, hence mux reduces to XOR
+        match lookup_tree(evaluator, index, low, sub_trees, key) {
+            Bit::Plain(v) => Bit::Encrypted(xor_plain(evaluator, selector, v)),
+            Bit::Encrypted(c) => Bit::Encrypted(evaluator.xor(selector, &c, key)),
+        }
+    } else {
+        let high = lookup_tree(evaluator, index, high, sub_trees, key);
+        let low = lookup_tree(evaluator, index, low, sub_trees, key);
+        mux_bits(evaluator, selector, high, low, key)
+    };
+
+    if let Bit::Encrypted(c) = &out {
+        sub_trees.insert(bits.to_vec(), c.clone());
+    }
+    out
+}
+
+/// Returns `if_true` if `selector` is true, otherwise returns `if_false`.
+///
+/// Requires 3 bootstraps if both inputs are encrypted, otherwise at most 1.
+fn mux_bits<E: BooleanGates>(
+    evaluator: &mut E,
+    selector: &E::Ciphertext,
+    if_true: Bit<E::Ciphertext>,
+    if_false: Bit<E::Ciphertext>,
+    key: &E::Key,
+) -> Bit<E::Ciphertext>
+where
+    E::Ciphertext: Clone,
+{
+    let out = match (if_true, if_false) {
+        (Bit::Plain(t), Bit::Plain(f)) if t == f => return Bit::Plain(t),
+        (Bit::Plain(t), Bit::Plain(_)) => xor_plain(evaluator, selector, !t),
+        (Bit::Encrypted(t), Bit::Plain(false)) => evaluator.and(selector, &t, key),
+        (Bit::Encrypted(t), Bit::Plain(true)) => evaluator.orny(selector, &t, key),
+        (Bit::Plain(false), Bit::Encrypted(f)) => evaluator.andny(selector, &f, key),
+        (Bit::Plain(true), Bit::Encrypted(f)) => evaluator.or(selector, &f, key),
+        (Bit::Encrypted(t), Bit::Encrypted(f)) => evaluator.mux(selector, &t, &f, key),
+    };
+    Bit::Encrypted(out)
+}
+
+/// Returns flags `index == i` for every `i < len`, that is one-hot encoding of
+/// `index` truncated to `len` entries.
+///
+/// Flags are evaluated from MSB to LSB of `index`, each level extending flags
+/// of prefixes of the previous level by a single AND. Hence decoding requires
+/// fewer than `2 * len + index.len()` bootstraps, instead of `index.len() - 1`
+/// per flag with `arbitrary_bit_equality`.
+pub(super) fn arbitrary_bit_decoder<E: BooleanGates>(
+    evaluator: &mut E,
+    index: &[E::Ciphertext],
+    len: usize,
+    key: &E::Key,
+) -> Vec<E::Ciphertext>
+where
+    E::Ciphertext: Clone,
+{
+    assert!(
+        index.len() < usize::BITS as usize && len <= 1 << index.len(),
+        "{len} entries cannot be indexed with {} bits",
+        index.len()
+    );
+
+    // flags[j] indicates whether bits of `index` processed so far equal `j`
+    let mut flags = vec![Bit::Plain(true)];
+    for (k, bit) in index.iter().enumerate().rev() {
+        flags = (0..len.div_ceil(1 << k))
+            .map(|j| match (&flags[j >> 1], j & 1 == 1) {
+                (Bit::Plain(false), _) => Bit::Plain(false),
+                (Bit::Plain(true), true) => Bit::Encrypted(bit.clone()),
+                (Bit::Plain(true), false) => Bit::Encrypted(evaluator.not(bit)),
+                (Bit::Encrypted(f), true) => Bit::Encrypted(evaluator.and(f, bit, key)),
+                (Bit::Encrypted(f), false) => Bit::Encrypted(evaluator.andny(bit, f, key)),
+            })
+            .collect();
+    }
+    flags
+        .into_iter()
+        .map(|f| f.into_ciphertext(evaluator))
+        .collect()
+}
+
+/// Returns `a` shifted left by `shift` bits. Vacated bits are set to 0.
+///
+/// Shift by plaintext amount only rewires the bits and does not require any
+/// bootstrapping.
+pub(super) fn arbitrary_bit_shl_by_plain<E: BooleanGates>(
+    evaluator: &E,
+    a: &[E::Ciphertext],
+    shift: usize,
+) -> Vec<E::Ciphertext>
+where
+    E::Ciphertext: Clone,
+{
+    (0..a.len())
+        .map(|i| {
+            if i >= shift {
+                a[i - shift].clone()
+            } else {
+                evaluator.trivial(false)
+            }
+        })
+        .collect()
+}
+
+/// Returns `a` shifted right (logical) by `shift` bits. Vacated bits are set
+/// to 0.
+pub(super) fn arbitrary_bit_shr_by_plain<E: BooleanGates>(
+    evaluator: &E,
+    a: &[E::Ciphertext],
+    shift: usize,
+) -> Vec<E::Ciphertext>
+where
+    E::Ciphertext: Clone,
+{
+    (0..a.len())
+        .map(|i| {
+            if i + shift < a.len() {
+                a[i + shift].clone()
+            } else {
+                evaluator.trivial(false)
+            }
+        })
+        .collect()
+}
+
+/// Returns `a` rotated left by `shift` bits
+pub(super) fn arbitrary_bit_rotate_left_by_plain<C: Clone>(a: &[C], shift: usize) -> Vec<C> {
+    let n = a.len();
+    let shift = shift % n;
+    (0..n).map(|i| a[(i + n - shift) % n].clone()).collect()
+}
+
+/// Returns `a` rotated right by `shift` bits
+pub(super) fn arbitrary_bit_rotate_right_by_plain<C: Clone>(a: &[C], shift: usize) -> Vec<C> {
+    let n = a.len();
+    (0..n).map(|i| a[(i + shift) % n].clone()).collect()
+}
+
+/// Barrel shifter. Applies `shift_by_plain` to `a` with encrypted shift
+/// amount `shift`.
+///
+/// At stage k, `a` shifted by 2^k is selected if k^th bit of `shift` is set.
+/// Only the lower log2(a.len()) bits of `shift` are used, that is the shift
+/// amount is reduced modulo a.len(). Hence a.len() must be a power of 2.
+fn barrel_shifter<E: BooleanGates, F>(
+    evaluator: &mut E,
+    a: &[E::Ciphertext],
+    shift: &[E::Ciphertext],
+    key: &E::Key,
+    shift_by_plain: F,
+) -> Vec<E::Ciphertext>
+where
+    E::Ciphertext: Clone,
+    F: Fn(&E, &[E::Ciphertext], usize) -> Vec<E::Ciphertext>,
+{
+    let n = a.len();
+    assert!(n.is_power_of_two());
+    let stages = n.trailing_zeros() as usize;
+    assert!(shift.len() >= stages);
+
+    let mut out = a.to_vec();
+    for (k, shift_k) in shift.iter().take(stages).enumerate() {
+        let shifted = shift_by_plain(evaluator, &out, 1 << k);
+        out = arbitrary_bit_mux(evaluator, shift_k, &shifted, &out, key);
+    }
+    out
+}
+
+/// Returns `a` shifted left by encrypted amount `shift` modulo a.len()
+pub(super) fn arbitrary_bit_shl<E: BooleanGates>(
+    evaluator: &mut E,
+    a: &[E::Ciphertext],
+    shift: &[E::Ciphertext],
+    key: &E::Key,
+) -> Vec<E::Ciphertext>
+where
+    E::Ciphertext: Clone,
+{
+    barrel_shifter(evaluator, a, shift, key, |e, v, s| {
+        arbitrary_bit_shl_by_plain(e, v, s)
+    })
+}
+
+/// Returns `a` shifted right (logical) by encrypted amount `shift` modulo
+/// a.len()
+pub(super) fn arbitrary_bit_shr<E: BooleanGates>(
+    evaluator: &mut E,
+    a: &[E::Ciphertext],
+    shift: &[E::Ciphertext],
+    key: &E::Key,
+) -> Vec<E::Ciphertext>
+where
+    E::Ciphertext: Clone,
+{
+    barrel_shifter(evaluator, a, shift, key, |e, v, s| {
+        arbitrary_bit_shr_by_plain(e, v, s)
+    })
+}
+
+/// Returns `a` rotated left by encrypted amount `shift`
+pub(super) fn arbitrary_bit_rotate_left<E: BooleanGates>(
+    evaluator: &mut E,
+    a: &[E::Ciphertext],
+    shift: &[E::Ciphertext],
+    key: &E::Key,
+) -> Vec<E::Ciphertext>
+where
+    E::Ciphertext: Clone,
+{
+    barrel_shifter(evaluator, a, shift, key, |_, v, s| {
+        arbitrary_bit_rotate_left_by_plain(v, s)
+    })
+}
+
+/// Returns `a` rotated right by encrypted amount `shift`
+pub(super) fn arbitrary_bit_rotate_right<E: BooleanGates>(
+    evaluator: &mut E,
+    a: &[E::Ciphertext],
+    shift: &[E::Ciphertext],
+    key: &E::Key,
+) -> Vec<E::Ciphertext>
+where
+    E::Ciphertext: Clone,
+{
+    barrel_shifter(evaluator, a, shift, key, |_, v, s| {
+        arbitrary_bit_rotate_right_by_plain(v, s)
+    })
+}
+
+pub(super) fn arbitrary_bit_mul<E: BooleanGates>(
     evaluator: &mut E,
     a: &[E::Ciphertext],
     b: &[E::Ciphertext],
     key: &E::Key,
 ) -> Vec<E::Ciphertext> {
-    assert!(a.len() == 8);
-    assert!(b.len() == 8);
-    let mut carries = Vec::with_capacity(7);
-    let mut out = Vec::with_capacity(8);
+    assert!(a.len() == b.len());
+    let n = a.len();
+    let mut carries = Vec::with_capacity(n.saturating_sub(1));
+    let mut out = Vec::with_capacity(n);
 
-    for i in (0..8) {
+    for i in 0..n {
         if i == 0 {
             let s = evaluator.and(&a[0], &b[0], key);
             out.push(s);
@@ -180,7 +549,7 @@ pub(super) fn eight_bit_mul<E: BooleanGates>(
 
             out.push(sum)
         }
-        debug_assert!(carries.len() <= 7);
+        debug_assert!(carries.len() < n);
     }
 
     out
@@ -232,19 +601,14 @@ where
         }
         for j in i + 1..n {
             // All I care about are the carries
-            evaluator.and_inplace(&mut carry, &neg_b[j], key);
+            evaluator.andyn_inplace(&mut carry, &b[j], key);
         }
 
-        let not_carry = evaluator.not(&carry);
         // Choose `remainder` if subtraction has overflown (i.e. carry = false).
         // Otherwise choose `subtractor`.
-        //
-        // mux k^a | !(k)^b, where k is the selector.
-        izip!(remainder.iter_mut(), subtract.iter_mut()).for_each(|(r, s)| {
+        izip!(remainder.iter_mut(), subtract.iter()).for_each(|(r, s)| {
             // choose `s` when carry is true, otherwise choose r
-            evaluator.and_inplace(s, &carry, key);
-            evaluator.and_inplace(r, &not_carry, key);
-            evaluator.or_inplace(r, s, key);
+            *r = evaluator.mux(&carry, s, r, key);
         });
 
         // Set i^th MSB of quotient to 1 if carry = true, otherwise set it to 0.
@@ -286,71 +650,361 @@ pub(super) fn arbitrary_bit_equality<E: BooleanGates>(
     return out;
 }
 
-/// Comparator handle computes comparator result 2ns MSB onwards. It is
-/// separated because comparator subroutine for signed and unsgind integers
-/// differs only for 1st MSB and is common second MSB onwards
-fn _comparator_handler_from_second_msb<E: BooleanGates>(
+/// Returns `a > b`, where `a` and `b` are signed if `signed` is true
+///
+/// `a > b` iff `a + !b` overflows. Hence starting from LSB, `a[..i+1] >
+/// b[..i+1]` equals carry out of `a_i + !b_i + (a[..i] > b[..i])`, that is
+/// `majority(a_i, !b_i, a[..i] > b[..i])`, which requires 2 bootstraps per bit.
+/// Signed integer comparison is same as unsigned integer with MSB flipped.
+fn _comparator<E: BooleanGates>(
     evaluator: &mut E,
     a: &[E::Ciphertext],
     b: &[E::Ciphertext],
-    mut comp: E::Ciphertext,
-    mut casc: E::Ciphertext,
+    signed: bool,
     key: &E::Key,
 ) -> E::Ciphertext {
+    assert!(a.len() == b.len());
     let n = a.len();
+    let msb_flipped = |i: usize| signed && i == n - 1;
 
-    // handle MSB - 1
-    let mut tmp = evaluator.not(&b[n - 2]);
-    evaluator.and_inplace(&mut tmp, &a[n - 2], key);
-    evaluator.and_inplace(&mut tmp, &casc, key);
-    evaluator.or_inplace(&mut comp, &tmp, key);
-
-    for i in 2..n {
-        // calculate cascading bit
-        let tmp_casc = evaluator.xnor(&a[n - i], &b[n - i], key);
-        evaluator.and_inplace(&mut casc, &tmp_casc, key);
-
-        // calculate computate bit
-        let mut tmp = evaluator.not(&b[n - 1 - i]);
-        evaluator.and_inplace(&mut tmp, &a[n - 1 - i], key);
-        evaluator.and_inplace(&mut tmp, &casc, key);
-        evaluator.or_inplace(&mut comp, &tmp, key);
+    let mut a_greater_b = if msb_flipped(0) {
+        evaluator.andny(&a[0], &b[0], key)
+    } else {
+        evaluator.andyn(&a[0], &b[0], key)
+    };
+    for i in 1..n {
+        a_greater_b = if msb_flipped(i) {
+            let not_a = evaluator.not(&a[i]);
+            evaluator.majority(&not_a, &b[i], &a_greater_b, key)
+        } else {
+            let not_b = evaluator.not(&b[i]);
+            evaluator.majority(&a[i], &not_b, &a_greater_b, key)
+        };
     }
-
-    return comp;
+    a_greater_b
 }
 
-/// Signed integer comparison is same as unsigned integer with MSB flipped.
+/// Returns `a > b` for signed `a` and `b`
 pub(super) fn arbitrary_signed_bit_comparator<E: BooleanGates>(
     evaluator: &mut E,
     a: &[E::Ciphertext],
     b: &[E::Ciphertext],
     key: &E::Key,
 ) -> E::Ciphertext {
+    _comparator(evaluator, a, b, true, key)
+}
+
+/// Returns `a > b` for unsigned `a` and `b`
+pub(super) fn arbitrary_bit_comparator<E: BooleanGates>(
+    evaluator: &mut E,
+    a: &[E::Ciphertext],
+    b: &[E::Ciphertext],
+    key: &E::Key,
+) -> E::Ciphertext {
+    _comparator(evaluator, a, b, false, key)
+}
+
+/// Bit of an operand that is either known in plaintext or encrypted.
+///
+/// Gates with at least one plaintext input are folded to either a constant or
+/// a free operation (copy or NOT) on the other input, hence only gates with
+/// both inputs encrypted require bootstrapping.
+#[derive(Clone)]
+pub(super) enum Bit<C> {
+    Plain(bool),
+    Encrypted(C),
+}
+
+impl<C> Bit<C> {
+    /// Returns ciphertext of the bit. Plaintext bit is trivially encrypted.
+    pub(super) fn into_ciphertext<E: BooleanGates<Ciphertext = C>>(self, evaluator: &E) -> C {
+        match self {
+            Bit::Plain(v) => evaluator.trivial(v),
+            Bit::Encrypted(c) => c,
+        }
+    }
+}
+
+/// Returns `a & b` for plaintext `b`
+pub(super) fn and_plain<E: BooleanGates>(evaluator: &E, a: &E::Ciphertext, b: bool) -> E::Ciphertext
+where
+    E::Ciphertext: Clone,
+{
+    if b {
+        a.clone()
+    } else {
+        evaluator.trivial(false)
+    }
+}
+
+/// Returns `a | b` for plaintext `b`
+pub(super) fn or_plain<E: BooleanGates>(evaluator: &E, a: &E::Ciphertext, b: bool) -> E::Ciphertext
+where
+    E::Ciphertext: Clone,
+{
+    if b {
+        evaluator.trivial(true)
+    } else {
+        a.clone()
+    }
+}
+
+/// Returns `a ^ b` for plaintext `b`
+pub(super) fn xor_plain<E: BooleanGates>(evaluator: &E, a: &E::Ciphertext, b: bool) -> E::Ciphertext
+where
+    E::Ciphertext: Clone,
+{
+    if b {
+        evaluator.not(a)
+    } else {
+        a.clone()
+    }
+}
+
+/// Full adder with plaintext `b`. Sets `a = a ^ b ^ carry_in` and returns the
+/// carry out.
+///
+/// Requires 2 bootstraps if `carry_in` is encrypted, otherwise none.
+fn full_adder_plain_b<E: BooleanGates>(
+    evaluator: &mut E,
+    a: &mut E::Ciphertext,
+    b: bool,
+    carry_in: Bit<E::Ciphertext>,
+    key: &E::Key,
+) -> Bit<E::Ciphertext>
+where
+    E::Ciphertext: Clone,
+{
+    match carry_in {
+        Bit::Plain(c) => {
+            // carry = A&b | (A^b)&c = majority(A, b, c)
+            let carry = if b && c {
+                Bit::Plain(true)
+            } else if b || c {
+                Bit::Encrypted(a.clone())
+            } else {
+                Bit::Plain(false)
+            };
+            if b ^ c {
+                evaluator.not_inplace(a);
+            }
+            carry
+        }
+        Bit::Encrypted(c) => {
+            if b {
+                // carry = A | C, sum = !(A ^ C)
+                Bit::Encrypted(full_adder_plain_carry_in(evaluator, a, &c, true, key))
+            } else {
+                // carry = A & C, sum = A ^ C
+                Bit::Encrypted(evaluator.half_adder(a, &c, key))
+            }
+        }
+    }
+}
+
+/// Sets `a = a + b + carry_in` for plaintext `b` and returns (carry_last,
+/// carry_last_last)
+pub(super) fn arbitrary_bit_adder_plain<E: BooleanGates>(
+    evaluator: &mut E,
+    a: &mut [E::Ciphertext],
+    b: &[bool],
+    carry_in: bool,
+    key: &E::Key,
+) -> (Bit<E::Ciphertext>, Bit<E::Ciphertext>)
+where
+    E::Ciphertext: Clone,
+{
+    assert!(a.len() == b.len());
+
+    let mut carry_last_last = Bit::Plain(carry_in);
+    let mut carry = Bit::Plain(carry_in);
+    izip!(a.iter_mut(), b.iter()).for_each(|(a_bit, b_bit)| {
+        carry_last_last = carry.clone();
+        carry = full_adder_plain_b(evaluator, a_bit, *b_bit, carry.clone(), key);
+    });
+
+    (carry, carry_last_last)
+}
+
+/// Returns (a - b, carry_last) for plaintext `b`. `carry_last` is set to
+/// `False` if the subtraction underflowed, otherwise to `True`.
+pub(super) fn arbitrary_bit_subtractor_plain<E: BooleanGates>(
+    evaluator: &mut E,
+    a: &[E::Ciphertext],
+    b: &[bool],
+    key: &E::Key,
+) -> (Vec<E::Ciphertext>, Bit<E::Ciphertext>)
+where
+    E::Ciphertext: Clone,
+{
+    // a - b = a + !b + 1
+    let neg_b = b.iter().map(|v| !v).collect_vec();
+    let mut out = a.to_vec();
+    let (carry_last, _) = arbitrary_bit_adder_plain(evaluator, &mut out, &neg_b, true, key);
+    (out, carry_last)
+}
+
+/// Returns `a * b` for plaintext `b`.
+///
+/// Computes the product with shift-and-add where `a` shifted by `i` is added
+/// only if i^th bit of `b` is set.
+pub(super) fn arbitrary_bit_mul_plain<E: BooleanGates>(
+    evaluator: &mut E,
+    a: &[E::Ciphertext],
+    b: &[bool],
+    key: &E::Key,
+) -> Vec<E::Ciphertext>
+where
+    E::Ciphertext: Clone,
+{
     assert!(a.len() == b.len());
     let n = a.len();
 
-    // handle MSB
-    let mut comp = evaluator.not(&a[n - 1]);
-    evaluator.and_inplace(&mut comp, &b[n - 1], key); // comp
-    let casc = evaluator.xnor(&a[n - 1], &b[n - 1], key); // casc
+    let mut set_bits = b.iter().enumerate().filter(|(_, v)| **v).map(|(i, _)| i);
+    let mut out = match set_bits.next() {
+        Some(i) => arbitrary_bit_shl_by_plain(evaluator, a, i),
+        None => return (0..n).map(|_| evaluator.trivial(false)).collect(),
+    };
+
+    for i in set_bits {
+        // Bits below i^th bit of `out` are unaffected by the addition of `a << i`.
+        // Carry out of MSB is discarded.
+        let mut carry: Option<E::Ciphertext> = None;
+        for (j, a_bit) in a.iter().take(n - i).enumerate() {
+            let out_bit = &mut out[i + j];
+            let is_last = i + j == n - 1;
+            carry = match carry {
+                None if is_last => {
+                    evaluator.xor_inplace(out_bit, a_bit, key);
+                    None
+                }
+                None => Some(half_adder(evaluator, out_bit, a_bit, key)),
+                Some(c) if is_last => {
+                    evaluator.xor_inplace(out_bit, a_bit, key);
+                    evaluator.xor_inplace(out_bit, &c, key);
+                    None
+                }
+                Some(c) => Some(full_adder(evaluator, out_bit, a_bit, &c, key)),
+            };
+        }
+    }
 
-    return _comparator_handler_from_second_msb(evaluator, a, b, comp, casc, key);
+    out
 }
 
-pub(super) fn arbitrary_bit_comparator<E: BooleanGates>(
+/// Returns (quotient, remainder) of `a / b` for plaintext non-zero `b`.
+///
+/// Same as `arbitrary_bit_division_for_quotient_and_rem` but steps at which
+/// the divisor is known to be greater than the partial remainder are skipped.
+pub(super) fn arbitrary_bit_division_plain_for_quotient_and_rem<E: BooleanGates>(
     evaluator: &mut E,
     a: &[E::Ciphertext],
-    b: &[E::Ciphertext],
+    b: &[bool],
     key: &E::Key,
-) -> E::Ciphertext {
+) -> (Vec<E::Ciphertext>, Vec<E::Ciphertext>)
+where
+    E::Ciphertext: Clone,
+{
     assert!(a.len() == b.len());
+    assert!(b.iter().any(|v| *v), "attempt to divide by zero");
     let n = a.len();
+    let b_msb = b.iter().rposition(|v| *v).unwrap();
 
-    // handle MSB
-    let mut comp = evaluator.not(&b[n - 1]);
-    evaluator.and_inplace(&mut comp, &a[n - 1], key);
-    let casc = evaluator.xnor(&a[n - 1], &b[n - 1], key);
+    // Remainder and quotient are stored in Big-endian as in
+    // `arbitrary_bit_division_for_quotient_and_rem`
+    let mut remainder = vec![];
+    let mut quotient = vec![];
+    for i in 0..n {
+        // left shift
+        remainder.push(a[n - 1 - i].clone());
 
-    return _comparator_handler_from_second_msb(evaluator, a, b, comp, casc, key);
+        // At i^th iteration remainder has i+1 bits. If divisor has more bits than
+        // the remainder then divisor > remainder.
+        if b_msb > i {
+            quotient.push(Bit::Plain(false));
+            continue;
+        }
+
+        let mut subtract = remainder.clone();
+        let mut carry = Bit::Plain(true);
+        for j in 0..i + 1 {
+            carry = full_adder_plain_b(evaluator, &mut subtract[i - j], !b[j], carry, key);
+        }
+
+        match &carry {
+            Bit::Plain(true) => remainder = subtract,
+            Bit::Plain(false) => {}
+            Bit::Encrypted(carry) => {
+                izip!(remainder.iter_mut(), subtract.iter()).for_each(|(r, s)| {
+                    // choose `s` when carry is true, otherwise choose r
+                    *r = evaluator.mux(carry, s, r, key);
+                });
+            }
+        }
+        quotient.push(carry);
+    }
+
+    remainder.reverse();
+    quotient.reverse();
+
+    let quotient = quotient
+        .into_iter()
+        .map(|v| v.into_ciphertext(evaluator))
+        .collect_vec();
+    (quotient, remainder)
+}
+
+/// Returns `a == b` for plaintext `b`
+pub(super) fn arbitrary_bit_equality_plain<E: BooleanGates>(
+    evaluator: &mut E,
+    a: &[E::Ciphertext],
+    b: &[bool],
+    key: &E::Key,
+) -> E::Ciphertext
+where
+    E::Ciphertext: Clone,
+{
+    assert!(a.len() == b.len());
+    // a_i == b_i is a_i if b_i = 1, otherwise !a_i
+    let mut out = xor_plain(evaluator, &a[0], !b[0]);
+    izip!(a.iter(), b.iter())
+        .skip(1)
+        .for_each(|(a_bit, b_bit)| {
+            let e = xor_plain(evaluator, a_bit, !b_bit);
+            evaluator.and_inplace(&mut out, &e, key);
+        });
+    out
+}
+
+/// Returns `a > b` for plaintext `b`
+///
+/// Starting from LSB, `a[..i+1] > b[..i+1]` equals `a_i | (a[..i] > b[..i])`
+/// if `b_i = 0`, otherwise `a_i & (a[..i] > b[..i])`. Hence requires atmost 1
+/// bootstrap per bit.
+pub(super) fn arbitrary_bit_comparator_plain<E: BooleanGates>(
+    evaluator: &mut E,
+    a: &[E::Ciphertext],
+    b: &[bool],
+    key: &E::Key,
+) -> E::Ciphertext
+where
+    E::Ciphertext: Clone,
+{
+    assert!(a.len() == b.len());
+    let mut a_greater_b = Bit::Plain(false);
+    izip!(a.iter(), b.iter()).for_each(|(a_bit, b_bit)| {
+        a_greater_b = match (a_greater_b.clone(), *b_bit) {
+            (Bit::Plain(false), false) | (Bit::Plain(true), true) => Bit::Encrypted(a_bit.clone()),
+            (Bit::Plain(v), _) => Bit::Plain(v),
+            (Bit::Encrypted(mut c), false) => {
+                evaluator.or_inplace(&mut c, a_bit, key);
+                Bit::Encrypted(c)
+            }
+            (Bit::Encrypted(mut c), true) => {
+                evaluator.and_inplace(&mut c, a_bit, key);
+                Bit::Encrypted(c)
+            }
+        };
+    });
+    a_greater_b.into_ciphertext(evaluator)
 }
@@ -0,0 +1,307 @@
+//! Traits common to plaintext and encrypted types.
+//!
+//! Application logic written against these traits can be tested on plaintext
+//! types (`bool`, `u8`, `i8`, ...) and then run, unchanged, on encrypted types
+//! (`FheBool`, `FheUint8`, `FheInt8`, ...). For example
+//!
+//! ```ignore
+//! fn is_near<T: FheIntegerOps>(a: &T, b: &T, threshold: &T) -> T::Bool {
+//!     let diff = a.wrapping_sub(b);
+//!     diff.wrapping_mul(&diff).fhe_le(threshold)
+//! }
+//! ```
+//!
+//! Methods are prefixed with `fhe_` where their names would otherwise clash
+//! with methods of `PartialEq`, `PartialOrd`, or `Ord`, which plaintext types
+//! also implement.
+//!
+//! Arithmetic on plaintext types wraps around on overflow and division by zero
+//! follows the same convention as encrypted types (refer to `div_rem`), so
+//! that both produce identical outputs.
+
+use num_traits::{WrappingAdd, WrappingMul, WrappingSub};
+
+use crate::bool::{impl_bool_frontend::FheBool, Backend};
+
+use super::{
+    enc_dec::{FheInt, FheUint},
+    ops::bit_mux,
+    DivZeroErrorFlag, FhePlaintext,
+};
+
+/// Boolean operations common to `bool` and `FheBool`
+pub trait FheBoolOps: Clone {
+    fn fhe_and(&self, other: &Self) -> Self;
+    fn fhe_or(&self, other: &Self) -> Self;
+    fn fhe_xor(&self, other: &Self) -> Self;
+    fn fhe_not(&self) -> Self;
+}
+
+/// Select between two values using a (encrypted) boolean selector `B`
+pub trait Select<B: FheBoolOps>: Sized {
+    /// Returns `self` if `selector` is true, otherwise returns `other`
+    fn mux(&self, other: &Self, selector: &B) -> Self;
+}
+
+/// Comparisons that return a (encrypted) boolean
+pub trait Compare {
+    type Bool: FheBoolOps;
+
+    fn fhe_eq(&self, other: &Self) -> Self::Bool;
+    fn fhe_neq(&self, other: &Self) -> Self::Bool;
+    fn fhe_lt(&self, other: &Self) -> Self::Bool;
+    fn fhe_gt(&self, other: &Self) -> Self::Bool;
+    fn fhe_le(&self, other: &Self) -> Self::Bool;
+    fn fhe_ge(&self, other: &Self) -> Self::Bool;
+}
+
+/// Integer operations common to plaintext and encrypted integers
+pub trait FheIntegerOps: Clone + Compare + Select<<Self as Compare>::Bool> {
+    fn wrapping_add(&self, rhs: &Self) -> Self;
+    fn wrapping_sub(&self, rhs: &Self) -> Self;
+    fn wrapping_mul(&self, rhs: &Self) -> Self;
+
+    /// Returns (quotient, remainder)
+    ///
+    /// If `rhs` is 0, then quotient has all bits set (i.e. MAX for unsigned
+    /// and -1 for signed integers) if `self` >= 0, otherwise quotient is 1.
+    /// Remainder equals `self`.
+    fn div_rem(&self, rhs: &Self) -> (Self, Self);
+
+    /// Returns `max(self, other)`
+    fn fhe_max(&self, other: &Self) -> Self {
+        self.mux(other, &self.fhe_gt(other))
+    }
+
+    /// Returns `min(self, other)`
+    fn fhe_min(&self, other: &Self) -> Self {
+        self.mux(other, &self.fhe_lt(other))
+    }
+}
+
+impl FheBoolOps for bool {
+    fn fhe_and(&self, other: &Self) -> Self {
+        *self & *other
+    }
+
+    fn fhe_or(&self, other: &Self) -> Self {
+        *self | *other
+    }
+
+    fn fhe_xor(&self, other: &Self) -> Self {
+        *self ^ *other
+    }
+
+    fn fhe_not(&self) -> Self {
+        !*self
+    }
+}
+
+impl Select<bool> for bool {
+    fn mux(&self, other: &Self, selector: &bool) -> Self {
+        if *selector {
+            *self
+        } else {
+            *other
+        }
+    }
+}
+
+impl<C: Backend> FheBoolOps for FheBool<C> {
+    fn fhe_and(&self, other: &Self) -> Self {
+        self & other
+    }
+
+    fn fhe_or(&self, other: &Self) -> Self {
+        self | other
+    }
+
+    fn fhe_xor(&self, other: &Self) -> Self {
+        self ^ other
+    }
+
+    fn fhe_not(&self) -> Self {
+        !self
+    }
+}
+
+impl<C: Backend> Select<FheBool<C>> for FheBool<C> {
+    fn mux(&self, other: &Self, selector: &FheBool<C>) -> Self {
+        C::with_evaluator(|e, key| FheBool {
+            data: bit_mux(e, selector.data(), self.data(), other.data(), key),
+        })
+    }
+}
+
+impl<T: FhePlaintext> Select<bool> for T {
+    fn mux(&self, other: &Self, selector: &bool) -> Self {
+        if *selector {
+            *self
+        } else {
+            *other
+        }
+    }
+}
+
+impl<T: FhePlaintext> Compare for T {
+    type Bool = bool;
+
+    fn fhe_eq(&self, other: &Self) -> bool {
+        self == other
+    }
+
+    fn fhe_neq(&self, other: &Self) -> bool {
+        self != other
+    }
+
+    fn fhe_lt(&self, other: &Self) -> bool {
+        self < other
+    }
+
+    fn fhe_gt(&self, other: &Self) -> bool {
+        self > other
+    }
+
+    fn fhe_le(&self, other: &Self) -> bool {
+        self <= other
+    }
+
+    fn fhe_ge(&self, other: &Self) -> bool {
+        self >= other
+    }
+}
+
+impl<T: FhePlaintext> FheIntegerOps for T {
+    fn wrapping_add(&self, rhs: &Self) -> Self {
+        WrappingAdd::wrapping_add(self, rhs)
+    }
+
+    fn wrapping_sub(&self, rhs: &Self) -> Self {
+        WrappingSub::wrapping_sub(self, rhs)
+    }
+
+    fn wrapping_mul(&self, rhs: &Self) -> Self {
+        WrappingMul::wrapping_mul(self, rhs)
+    }
+
+    fn div_rem(&self, rhs: &Self) -> (Self, Self) {
+        if rhs.is_zero() {
+            let quotient = if *self >= T::zero() {
+                !T::zero()
+            } else {
+                T::one()
+            };
+            (quotient, *self)
+        } else {
+            match self.checked_div(rhs) {
+                Some(q) => (q, *self % *rhs),
+                // MIN / -1 wraps around to MIN
+                None => (*self, T::zero()),
+            }
+        }
+    }
+}
+
+impl<C: Backend, const BITS: usize> Select<FheBool<C>> for FheUint<C, BITS> {
+    fn mux(&self, other: &Self, selector: &FheBool<C>) -> Self {
+        FheUint::mux(self, other, selector)
+    }
+}
+
+impl<C: Backend, const BITS: usize> Compare for FheUint<C, BITS> {
+    type Bool = FheBool<C>;
+
+    fn fhe_eq(&self, other: &Self) -> FheBool<C> {
+        FheUint::eq(self, other)
+    }
+
+    fn fhe_neq(&self, other: &Self) -> FheBool<C> {
+        FheUint::neq(self, other)
+    }
+
+    fn fhe_lt(&self, other: &Self) -> FheBool<C> {
+        FheUint::lt(self, other)
+    }
+
+    fn fhe_gt(&self, other: &Self) -> FheBool<C> {
+        FheUint::gt(self, other)
+    }
+
+    fn fhe_le(&self, other: &Self) -> FheBool<C> {
+        FheUint::le(self, other)
+    }
+
+    fn fhe_ge(&self, other: &Self) -> FheBool<C> {
+        FheUint::ge(self, other)
+    }
+}
+
+impl<C: DivZeroErrorFlag, const BITS: usize> FheIntegerOps for FheUint<C, BITS> {
+    fn wrapping_add(&self, rhs: &Self) -> Self {
+        self + rhs
+    }
+
+    fn wrapping_sub(&self, rhs: &Self) -> Self {
+        self - rhs
+    }
+
+    fn wrapping_mul(&self, rhs: &Self) -> Self {
+        self * rhs
+    }
+
+    fn div_rem(&self, rhs: &Self) -> (Self, Self) {
+        FheUint::div_rem(self, rhs)
+    }
+}
+
+impl<C: Backend, const BITS: usize> Select<FheBool<C>> for FheInt<C, BITS> {
+    fn mux(&self, other: &Self, selector: &FheBool<C>) -> Self {
+        FheInt::mux(self, other, selector)
+    }
+}
+
+impl<C: Backend, const BITS: usize> Compare for FheInt<C, BITS> {
+    type Bool = FheBool<C>;
+
+    fn fhe_eq(&self, other: &Self) -> FheBool<C> {
+        FheInt::eq(self, other)
+    }
+
+    fn fhe_neq(&self, other: &Self) -> FheBool<C> {
+        FheInt::neq(self, other)
+    }
+
+    fn fhe_lt(&self, other: &Self) -> FheBool<C> {
+        FheInt::lt(self, other)
+    }
+
+    fn fhe_gt(&self, other: &Self) -> FheBool<C> {
+        FheInt::gt(self, other)
+    }
+
+    fn fhe_le(&self, other: &Self) -> FheBool<C> {
+        FheInt::le(self, other)
+    }
+
+    fn fhe_ge(&self, other: &Self) -> FheBool<C> {
+        FheInt::ge(self, other)
+    }
+}
+
+impl<C: DivZeroErrorFlag, const BITS: usize> FheIntegerOps for FheInt<C, BITS> {
+    fn wrapping_add(&self, rhs: &Self) -> Self {
+        self + rhs
+    }
+
+    fn wrapping_sub(&self, rhs: &Self) -> Self {
+        self - rhs
+    }
+
+    fn wrapping_mul(&self, rhs: &Self) -> Self {
+        self * rhs
+    }
+
+    fn div_rem(&self, rhs: &Self) -> (Self, Self) {
+        FheInt::div_rem(self, rhs)
+    }
+}
@@ -0,0 +1,219 @@
+//! Gate counts and bootstrap latency of evaluated circuits
+//!
+//! Every boolean gate evaluated on the current thread, with Fhe types or with
+//! Mock types, is recorded in a thread local report. Gates evaluated with
+//! `BoolEvaluator` additionally record wall-clock time of each bootstrap. For
+//! example
+//!
+//! ```ignore
+//! phantom_zone::stats::reset();
+//! let c = &a + &b;
+//! let report = phantom_zone::stats::take();
+//! println!("{report}");
+//! ```
+//!
+//! Since gate counts are independent of parameters, cost of a circuit under
+//! different parameter sets can be estimated by evaluating it once with Mock
+//! types and scaling `GateCounts::bootstraps` with the bootstrap latency of
+//! each parameter set (refer to `Stats::estimated_time`).
+
+use std::{cell::RefCell, fmt::Display, time::Duration, time::Instant};
+
+thread_local! {
+    static STATS: RefCell<Stats> = RefCell::new(Stats::default());
+}
+
+/// No. of gates evaluated per gate type
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct GateCounts {
+    pub and: usize,
+    pub nand: usize,
+    pub or: usize,
+    pub nor: usize,
+    pub xor: usize,
+    pub xnor: usize,
+    pub andny: usize,
+    pub andyn: usize,
+    pub orny: usize,
+    pub oryn: usize,
+    pub majority: usize,
+    pub mux: usize,
+    /// Half adders, that is XOR and AND of the same inputs
+    pub half_adder: usize,
+    /// Full adders, that is XOR and majority of the same inputs
+    pub full_adder: usize,
+    /// Lookup tables of `FheShortUint`s, including conversions from and to
+    /// `FheBool`
+    pub lut: usize,
+    pub not: usize,
+    /// Circuit bootstraps of index bits of table lookups evaluated with CMUX
+    /// trees. Circuit bootstraps are not timed.
+    pub circuit_bootstrap: usize,
+    /// Blind rotations executed by circuit bootstraps. A circuit bootstrap
+    /// executes a blind rotation per element of the two RLWE x RGSW gadget
+    /// vectors, hence the count depends on parameters.
+    pub circuit_bootstrap_blind_rotations: usize,
+}
+
+impl GateCounts {
+    /// Returns no. of bootstraps the gates require when evaluated with
+    /// `BoolEvaluator`. NOT does not require a bootstrap, majority and half
+    /// adder require 2, MUX and full adder require 3, and all other gates
+    /// require 1. Each blind rotation of circuit bootstraps counts as 1.
+    pub fn bootstraps(&self) -> usize {
+        self.and
+            + self.nand
+            + self.or
+            + self.nor
+            + self.xor
+            + self.xnor
+            + self.andny
+            + self.andyn
+            + self.orny
+            + self.oryn
+            + 2 * self.majority
+            + 3 * self.mux
+            + 2 * self.half_adder
+            + 3 * self.full_adder
+            + self.lut
+            + self.circuit_bootstrap_blind_rotations
+    }
+}
+
+/// Report of gates evaluated on the current thread
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Stats {
+    /// Gates evaluated, with Fhe or Mock types
+    pub gates: GateCounts,
+    /// No. of bootstraps executed. Gates of Mock types do not execute
+    /// bootstraps.
+    pub executed_bootstraps: usize,
+    /// Total wall-clock time of executed bootstraps
+    pub bootstrap_time: Duration,
+}
+
+impl Stats {
+    /// Returns average wall-clock time per executed bootstrap. Returns None if
+    /// no bootstrap was executed.
+    pub fn mean_bootstrap_time(&self) -> Option<Duration> {
+        if self.executed_bootstraps == 0 {
+            None
+        } else {
+            Some(self.bootstrap_time / self.executed_bootstraps as u32)
+        }
+    }
+
+    /// Returns estimated time to evaluate the gates if a single bootstrap takes
+    /// `per_bootstrap`
+    pub fn estimated_time(&self, per_bootstrap: Duration) -> Duration {
+        per_bootstrap * self.gates.bootstraps() as u32
+    }
+}
+
+impl Display for Stats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let g = &self.gates;
+        writeln!(
+            f,
+            "Gates: AND={} NAND={} OR={} NOR={} XOR={} XNOR={} ANDNY={} ANDYN={} ORNY={} \
+             ORYN={} MAJORITY={} MUX={} HALF_ADDER={} FULL_ADDER={} LUT={} NOT={} CBS={} ({} blind \
+             rotations)",
+            g.and,
+            g.nand,
+            g.or,
+            g.nor,
+            g.xor,
+            g.xnor,
+            g.andny,
+            g.andyn,
+            g.orny,
+            g.oryn,
+            g.majority,
+            g.mux,
+            g.half_adder,
+            g.full_adder,
+            g.lut,
+            g.not,
+            g.circuit_bootstrap,
+            g.circuit_bootstrap_blind_rotations
+        )?;
+        writeln!(f, "Bootstraps required: {}", g.bootstraps())?;
+        write!(
+            f,
+            "Bootstraps executed: {} in {:?}",
+            self.executed_bootstraps, self.bootstrap_time
+        )?;
+        if let Some(mean) = self.mean_bootstrap_time() {
+            write!(f, " ({:?} per bootstrap)", mean)?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns report of gates evaluated on the current thread since the last
+/// `take` or `reset`, and resets the report
+pub fn take() -> Stats {
+    STATS.with_borrow_mut(std::mem::take)
+}
+
+/// Returns report of gates evaluated on the current thread since the last
+/// `take` or `reset`
+pub fn peek() -> Stats {
+    STATS.with_borrow(|s| s.clone())
+}
+
+/// Resets report of the current thread
+pub fn reset() {
+    STATS.with_borrow_mut(|s| *s = Stats::default());
+}
+
+/// Adds `other`, for example report of a worker thread, to report of the
+/// current thread
+pub(crate) fn merge(other: Stats) {
+    STATS.with_borrow_mut(|s| {
+        let (g, o) = (&mut s.gates, &other.gates);
+        g.and += o.and;
+        g.nand += o.nand;
+        g.or += o.or;
+        g.nor += o.nor;
+        g.xor += o.xor;
+        g.xnor += o.xnor;
+        g.andny += o.andny;
+        g.andyn += o.andyn;
+        g.orny += o.orny;
+        g.oryn += o.oryn;
+        g.majority += o.majority;
+        g.mux += o.mux;
+        g.half_adder += o.half_adder;
+        g.full_adder += o.full_adder;
+        g.lut += o.lut;
+        g.not += o.not;
+        g.circuit_bootstrap += o.circuit_bootstrap;
+        g.circuit_bootstrap_blind_rotations += o.circuit_bootstrap_blind_rotations;
+        s.executed_bootstraps += other.executed_bootstraps;
+        s.bootstrap_time += other.bootstrap_time;
+    });
+}
+
+/// Records evaluation of a gate that does not execute a bootstrap. `count`
+/// increments the gate's counter.
+pub(crate) fn record_gate<C: FnOnce(&mut GateCounts)>(count: C) {
+    STATS.with_borrow_mut(|s| count(&mut s.gates));
+}
+
+/// Records evaluation of a gate that executes bootstrap `f`. `count`
+/// increments the gate's counter.
+pub(crate) fn record_bootstrap<C: FnOnce(&mut GateCounts), R, F: FnOnce() -> R>(
+    count: C,
+    f: F,
+) -> R {
+    let now = Instant::now();
+    let out = f();
+    let elapsed = now.elapsed();
+    STATS.with_borrow_mut(|s| {
+        count(&mut s.gates);
+        s.executed_bootstraps += 1;
+        s.bootstrap_time += elapsed;
+    });
+    out
+}
@@ -18,6 +18,8 @@ pub trait WithLocal {
     where
         F: Fn(&mut Self) -> R;
 
+    // Only used by non-interactive multi-party key generation
+    #[cfg_attr(not(feature = "non_interactive_mp"), allow(dead_code))]
     fn with_local_mut_mut<F, R>(func: &mut F) -> R
     where
         F: FnMut(&mut Self) -> R;